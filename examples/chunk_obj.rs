@@ -0,0 +1,32 @@
+// examples/chunk_obj.rs
+
+// builds one chunk through the library, no window or gpu involved, and writes it to chunk.obj:
+//     cargo run --example chunk_obj -- [seed] [resolution] [face] [u] [v]
+
+use std::fmt::Write;
+use voxanet::common::{ChunkKey, PlanetData, DEFAULT_RESOLUTION, DEFAULT_SEED};
+use voxanet::gen::MeshGen;
+
+fn main() {
+    let args: Vec<u32> = std::env::args().skip(1).filter_map(|a| a.parse().ok()).collect();
+    let arg = |i: usize, default: u32| args.get(i).copied().unwrap_or(default);
+    let planet = PlanetData::with_seed(arg(1, DEFAULT_RESOLUTION), arg(0, DEFAULT_SEED));
+    let key = ChunkKey { face: arg(2, 0) as u8, u_idx: arg(3, 0), v_idx: arg(4, 0) };
+    let (verts, inds) = MeshGen::build_chunk(key, &planet);
+
+    let mut obj = String::new();
+    for v in &verts {
+        let _ = writeln!(obj, "v {} {} {} {} {} {}", v.pos[0], v.pos[1], v.pos[2], v.color[0], v.color[1], v.color[2]);
+    }
+    for v in &verts {
+        let _ = writeln!(obj, "vn {} {} {}", v.normal[0], v.normal[1], v.normal[2]);
+    }
+    for tri in inds.chunks_exact(3) {
+        let (a, b, c) = (tri[0] + 1, tri[1] + 1, tri[2] + 1);
+        let _ = writeln!(obj, "f {a}//{a} {b}//{b} {c}//{c}");
+    }
+    match std::fs::write("chunk.obj", obj) {
+        Ok(()) => println!("chunk.obj: {} vertices, {} triangles", verts.len(), inds.len() / 3),
+        Err(e) => println!("chunk.obj: {}", e),
+    }
+}
@@ -0,0 +1,76 @@
+//alias.rs
+
+// console shorthands. `/alias name "command; command"` makes /name run those commands in turn, with $1, $2, ...
+// filled in from its own arguments and $* standing for all of them. kept next to the binary in aliases.cfg, one
+// `name = commands` line each in the same format as client.cfg, and changed live with /alias.
+
+use std::path::Path;
+
+// --- CONSTANTS ---
+pub const ALIAS_CONFIG: &str = "aliases.cfg";
+
+#[derive(Default)]
+pub struct Aliases {
+    // name with its slash, and the commands it stands for
+    list: Vec<(String, String)>,
+}
+
+impl Aliases {
+    pub fn load(path: &Path) -> Self {
+        let mut aliases = Self::default();
+        let Ok(text) = std::fs::read_to_string(path) else { return aliases };
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() { continue; }
+            match line.split_once('=') {
+                Some((name, body)) if !name.trim().is_empty() => aliases.set(name.trim(), Some(body.trim())),
+                _ => println!("{}:{}: ignoring '{}'", path.display(), i + 1, line),
+            }
+        }
+        aliases
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let text: String = self.list.iter().map(|(name, body)| format!("{} = {}\n", name.trim_start_matches('/'), body)).collect();
+        std::fs::write(path, text)
+    }
+
+    // with or without the slash
+    pub fn get(&self, name: &str) -> Option<&str> {
+        let name = slashed(name);
+        self.list.iter().find(|(n, _)| *n == name).map(|(_, body)| body.as_str())
+    }
+
+    // `name` runs `body` from now on. None removes it
+    pub fn set(&mut self, name: &str, body: Option<&str>) {
+        let name = slashed(name);
+        self.list.retain(|(n, _)| *n != name);
+        if let Some(body) = body {
+            self.list.push((name, body.to_string()));
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.list.iter().map(|(name, body)| (name.as_str(), body.as_str()))
+    }
+
+    // the command lines `body` runs when called with `args`. arguments it doesn't use are dropped, and ones it
+    // uses but wasn't given are left empty
+    pub fn expand(body: &str, args: &[&str]) -> Vec<String> {
+        body.split(';')
+            .map(|command| {
+                // highest first, so $12 isn't read as $1 followed by 2
+                let mut line = command.replace("$*", &args.join(" "));
+                for n in (1..=9.max(args.len())).rev() {
+                    line = line.replace(&format!("${}", n), args.get(n - 1).unwrap_or(&""));
+                }
+                line.trim().to_string()
+            })
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+}
+
+fn slashed(name: &str) -> String {
+    format!("/{}", name.trim_start_matches('/'))
+}
@@ -0,0 +1,213 @@
+//bindings.rs
+
+// which key does what. kept next to the binary in bindings.cfg, one `key = action` line per key in the same
+// format as client.cfg, and changed live with /bind. a key does one thing, an action can have several keys
+// or none (`none = action`).
+// the console key, shift, the hotbar digits and [ ] (brush size) aren't bindable, so a bad file can't lock
+// anyone out.
+
+use std::path::Path;
+use winit::keyboard::KeyCode;
+
+// --- CONSTANTS ---
+pub const BINDINGS_CONFIG: &str = "bindings.cfg";
+
+// the keys an action can be bound to
+const BINDABLE: &[KeyCode] = &[
+    KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF, KeyCode::KeyG,
+    KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL, KeyCode::KeyM, KeyCode::KeyN,
+    KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR, KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU,
+    KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX, KeyCode::KeyY, KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::Space, KeyCode::Tab, KeyCode::CapsLock,
+    KeyCode::ControlLeft, KeyCode::ControlRight, KeyCode::AltLeft, KeyCode::AltRight,
+    KeyCode::ArrowUp, KeyCode::ArrowDown, KeyCode::ArrowLeft, KeyCode::ArrowRight,
+    KeyCode::Insert, KeyCode::Delete, KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown,
+    KeyCode::Minus, KeyCode::Equal, KeyCode::Backslash,
+    KeyCode::Semicolon, KeyCode::Quote, KeyCode::Comma, KeyCode::Period, KeyCode::Slash,
+    KeyCode::Numpad0, KeyCode::Numpad1, KeyCode::Numpad2, KeyCode::Numpad3, KeyCode::Numpad4,
+    KeyCode::Numpad5, KeyCode::Numpad6, KeyCode::Numpad7, KeyCode::Numpad8, KeyCode::Numpad9,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Forward,
+    Left,
+    Back,
+    Right,
+    Jump,
+    Sprint,
+    Fly,
+    Camera,
+    Glide,
+    Editor,
+    Select,
+    PrefabMenu,
+    Rotate,
+    Blueprint,
+    Ride,
+    Grapple,
+    Help,
+    Wireframe,
+    Collisions,
+    FreezeCulling,
+}
+
+impl Action {
+    pub const ALL: [Action; 20] = [
+        Action::Forward, Action::Left, Action::Back, Action::Right, Action::Jump, Action::Sprint,
+        Action::Fly, Action::Camera, Action::Glide, Action::Editor, Action::Select, Action::PrefabMenu,
+        Action::Rotate, Action::Blueprint, Action::Ride, Action::Grapple, Action::Help,
+        Action::Wireframe, Action::Collisions, Action::FreezeCulling,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Forward => "forward",
+            Action::Left => "left",
+            Action::Back => "back",
+            Action::Right => "right",
+            Action::Jump => "jump",
+            Action::Sprint => "sprint",
+            Action::Fly => "fly",
+            Action::Camera => "camera",
+            Action::Glide => "glide",
+            Action::Editor => "editor",
+            Action::Select => "select",
+            Action::PrefabMenu => "prefab_menu",
+            Action::Rotate => "rotate",
+            Action::Blueprint => "blueprint",
+            Action::Ride => "ride",
+            Action::Grapple => "grapple",
+            Action::Help => "help",
+            Action::Wireframe => "wireframe",
+            Action::Collisions => "collisions",
+            Action::FreezeCulling => "freeze_culling",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.name() == s)
+    }
+
+    fn default_key(self) -> KeyCode {
+        match self {
+            Action::Forward => KeyCode::KeyW,
+            Action::Left => KeyCode::KeyA,
+            Action::Back => KeyCode::KeyS,
+            Action::Right => KeyCode::KeyD,
+            Action::Jump => KeyCode::Space,
+            Action::Sprint => KeyCode::ControlLeft,
+            Action::Fly => KeyCode::KeyF,
+            Action::Camera => KeyCode::KeyK,
+            Action::Glide => KeyCode::KeyH,
+            Action::Editor => KeyCode::KeyG,
+            Action::Select => KeyCode::KeyB,
+            Action::PrefabMenu => KeyCode::Tab,
+            Action::Rotate => KeyCode::KeyR,
+            Action::Blueprint => KeyCode::KeyV,
+            Action::Ride => KeyCode::KeyE,
+            Action::Grapple => KeyCode::KeyQ,
+            Action::Help => KeyCode::F1,
+            Action::Wireframe => KeyCode::KeyP,
+            Action::Collisions => KeyCode::KeyO,
+            Action::FreezeCulling => KeyCode::Quote,
+        }
+    }
+}
+
+// winit's name without the Key / Digit in front: W, 0, Space, ControlLeft
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    name.strip_prefix("Key").or_else(|| name.strip_prefix("Digit")).unwrap_or(&name).to_string()
+}
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    BINDABLE.iter().copied().find(|&k| key_name(k).eq_ignore_ascii_case(name))
+}
+
+pub struct Bindings {
+    keys: Vec<(KeyCode, Action)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self { keys: Action::ALL.into_iter().map(|a| (a.default_key(), a)).collect() }
+    }
+}
+
+impl Bindings {
+    // a missing file is the defaults. actions the file never mentions (added since it was written) get their
+    // default key, unless the file gave that key to something else
+    pub fn load(path: &Path) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else { return Self::default() };
+        let mut bindings = Self { keys: Vec::new() };
+        let mut unbound = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() { continue; }
+            let parsed = line.split_once('=').and_then(|(key, action)| {
+                let action = Action::parse(action.trim())?;
+                match key.trim() {
+                    "none" => Some((None, action)),
+                    key => Some((Some(parse_key(key)?), action)),
+                }
+            });
+            match parsed {
+                Some((Some(key), action)) => bindings.bind(key, Some(action)),
+                Some((None, action)) => unbound.push(action),
+                None => println!("{}:{}: ignoring '{}'", path.display(), i + 1, line),
+            }
+        }
+        for action in Action::ALL {
+            if unbound.contains(&action) { continue; }
+            if bindings.keys(action).is_empty() && bindings.action(action.default_key()).is_none() {
+                bindings.bind(action.default_key(), Some(action));
+            }
+        }
+        bindings
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut text = String::new();
+        for action in Action::ALL {
+            let keys = self.keys(action);
+            if keys.is_empty() {
+                text.push_str(&format!("none = {}\n", action.name()));
+            }
+            for key in keys {
+                text.push_str(&format!("{} = {}\n", key_name(key), action.name()));
+            }
+        }
+        std::fs::write(path, text)
+    }
+
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.keys.iter().find(|(k, _)| *k == key).map(|(_, a)| *a)
+    }
+
+    pub fn keys(&self, action: Action) -> Vec<KeyCode> {
+        self.keys.iter().filter(|(_, a)| *a == action).map(|(k, _)| *k).collect()
+    }
+
+    // `key` does `action` from now on, whatever it did before. None leaves it doing nothing
+    pub fn bind(&mut self, key: KeyCode, action: Option<Action>) {
+        self.keys.retain(|(k, _)| *k != key);
+        if let Some(action) = action {
+            self.keys.push((key, action));
+        }
+    }
+
+    // the keys of `actions` for the F1 overlay, like "W A S D". an action without a key shows as -
+    pub fn describe(&self, actions: &[Action]) -> String {
+        actions.iter()
+            .map(|&a| {
+                let keys: Vec<String> = self.keys(a).into_iter().map(key_name).collect();
+                if keys.is_empty() { "-".to_string() } else { keys.join("/") }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
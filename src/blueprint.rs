@@ -0,0 +1,75 @@
+//blueprint.rs
+
+// a prefab pinned in the world as a build guide. every cell of its box is compared with the world:
+// blocks the prefab has but the world doesn't are missing, blocks the world has where the prefab is air, or
+// that aren't the block the prefab has there, are wrong.
+
+use std::collections::HashMap;
+use crate::common::{BlockId, BlockType, PlanetData};
+use crate::schematic::Schematic;
+
+// --- CONSTANTS ---
+// seconds between comparisons, so edits from other players show up too without hooking every edit
+const REFRESH_INTERVAL: f32 = 0.25;
+
+pub struct Blueprint {
+    pub name: String,
+    pub schematic: Schematic,
+    pub origin: BlockId,
+    pub missing: Vec<BlockId>,
+    pub wrong: Vec<BlockId>,
+    // prefab blocks that fit on the face
+    total: usize,
+    timer: f32,
+}
+
+impl Blueprint {
+    pub fn new(name: String, schematic: Schematic, origin: BlockId, planet: &PlanetData) -> Self {
+        let mut blueprint = Self { name, schematic, origin, missing: Vec::new(), wrong: Vec::new(), total: 0, timer: 0.0 };
+        blueprint.refresh(planet);
+        blueprint
+    }
+
+    // returns true when the comparison was redone and the overlay should be rebuilt
+    pub fn update(&mut self, dt: f32, planet: &PlanetData) -> bool {
+        self.timer += dt;
+        if self.timer < REFRESH_INTERVAL { return false; }
+        self.timer = 0.0;
+        self.refresh(planet);
+        true
+    }
+
+    fn refresh(&mut self, planet: &PlanetData) {
+        let res = planet.resolution;
+        let wanted: HashMap<[u32; 3], BlockType> = self.schematic.solid.iter().copied().collect();
+        self.missing.clear();
+        self.wrong.clear();
+        self.total = 0;
+
+        let [w, d, h] = self.schematic.size;
+        for l in 0..h {
+            for v in 0..d {
+                for u in 0..w {
+                    let Some(id) = Schematic::cell_id(self.origin, [u, v, l], res) else { continue };
+                    let want = wanted.get(&[u, v, l]).copied();
+                    if want.is_some() { self.total += 1; }
+                    match (want, planet.exists(id)) {
+                        (Some(_), false) => self.missing.push(id),
+                        (None, true) => self.wrong.push(id),
+                        (Some(block), true) if planet.block_type(id) != block => self.wrong.push(id),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.wrong.is_empty()
+    }
+
+    // (blocks in place, blocks in the prefab)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.total - self.missing.len(), self.total)
+    }
+}
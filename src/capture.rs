@@ -0,0 +1,83 @@
+//capture.rs
+
+// rendered frames going out to disk as png files. the renderer reads a frame back from the gpu,
+// this compresses and writes it on a thread of its own so the game doesn't hitch while it does.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+
+// a frame read back from the gpu, 8 bit rgba rows top to bottom
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+// writes `frame` to `path` in the background, a failure comes back through `errors`
+pub fn save_png(path: PathBuf, frame: Frame, errors: Sender<String>) {
+    std::thread::spawn(move || {
+        if let Err(e) = fs::write(&path, encode_png(&frame)) {
+            let _ = errors.send(format!("{}: {}", path.display(), e));
+        }
+    });
+}
+
+pub fn encode_png(frame: &Frame) -> Vec<u8> {
+    // every row starts with filter type 0, the rest is left to zlib
+    let row = frame.width as usize * 4;
+    let mut raw = Vec::with_capacity((row + 1) * frame.height as usize);
+    for line in frame.rgba.chunks_exact(row) {
+        raw.push(0);
+        raw.extend_from_slice(line);
+    }
+    let mut zlib = ZlibEncoder::new(Vec::new(), Compression::fast());
+    let data = zlib.write_all(&raw).and_then(|_| zlib.finish()).unwrap_or_default();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&frame.width.to_be_bytes());
+    header.extend_from_slice(&frame.height.to_be_bytes());
+    // 8 bits per channel, rgba, deflate, no filtering extras, not interlaced
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    put_chunk(&mut out, b"IHDR", &header);
+    put_chunk(&mut out, b"IDAT", &data);
+    put_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn put_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for b in bytes {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+// seconds since the epoch, captures are named after it
+pub fn unix_stamp() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// a fresh numbered directory under `root` for one run of captures
+pub fn new_session_dir(root: &str) -> io::Result<PathBuf> {
+    let dir = PathBuf::from(root).join(unix_stamp().to_string());
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
@@ -0,0 +1,30 @@
+//clipboard.rs
+
+// the system clipboard, for the console: ctrl+c copies the log lines picked with the mouse and ctrl+v pastes into
+// the input line. it's opened on first use and kept open, since on x11 whoever copied serves the text until
+// something else is copied, so dropping it would take the copy with it.
+
+pub struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self { inner: None }
+    }
+
+    pub fn copy(&mut self, text: String) -> Result<(), arboard::Error> {
+        self.open()?.set_text(text)
+    }
+
+    pub fn paste(&mut self) -> Result<String, arboard::Error> {
+        self.open()?.get_text()
+    }
+
+    fn open(&mut self) -> Result<&mut arboard::Clipboard, arboard::Error> {
+        if self.inner.is_none() {
+            self.inner = Some(arboard::Clipboard::new()?);
+        }
+        Ok(self.inner.as_mut().unwrap())
+    }
+}
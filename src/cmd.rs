@@ -1,4 +1,211 @@
+use std::sync::mpsc::Sender;
+use std::sync::RwLock;
+use crate::common::{BlockId, BlockType, PlanetData};
 use crate::entity::Player;
+use crate::world::{Autosave, Backups, World, WorldSnapshot};
+use crate::i18n::{self, tr};
+use crate::settings::{Settings, CLIENT_CONFIG};
+use crate::bindings::{self, Action, Bindings, BINDINGS_CONFIG};
+use crate::alias::{Aliases, ALIAS_CONFIG};
+use crate::prefab::Prefabs;
+use crate::debug_view::DebugCamera;
+use crate::renderer::RenderMode;
+use crate::monitor::Monitors;
+use crate::sign::MAX_SIGN_TEXT;
+use crate::teleporter;
+use crate::marker::{Marker, MarkerEdit, Markers, Visibility};
+use crate::timelapse::{LapseCamera, TimeLapse};
+use crate::panorama::Panorama;
+use crate::recorder::{RecordFormat, Recorder, DEFAULT_RECORD_FPS, MAX_RECORD_FPS};
+use crate::gen::CoordSystem;
+use crate::music::{Music, AUDIO_OUTPUT, MUSIC_DIR, MUSIC_OPTIONS};
+use crate::stats::{self, Stats};
+use crate::mob::{MobKind, Mobs, MAX_SPAWN};
+use crate::plugin::Plugins;
+use crate::health::{self, DamageSource};
+use crate::explosion::{self, MAX_BOOM_RADIUS};
+
+// pixels between console lines. the newest log line sits this far above the input line, which is as far again
+// above the bottom of the panel
+pub const CONSOLE_LINE_HEIGHT: f32 = 20.0;
+
+// command files for /exec live here, and the client runs AUTOEXEC (next to client.cfg) once at startup
+pub const SCRIPT_DIR: &str = "scripts";
+pub const AUTOEXEC: &str = "autoexec.cfg";
+// scripts and aliases running scripts and aliases, so one that runs itself gives up instead of overflowing the stack
+const MAX_NESTING: usize = 8;
+
+// who is running a command. ordered, so a higher level can run everything below it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Permission {
+    Player,
+    Moderator,
+    Operator,
+    // local console, server stdin and rcon
+    Owner,
+}
+
+impl Permission {
+    pub fn from_level(level: u8) -> Self {
+        match level {
+            0 => Permission::Player,
+            1 => Permission::Moderator,
+            2 => Permission::Operator,
+            _ => Permission::Owner,
+        }
+    }
+}
+
+// runs a command: the console to answer on, the line split on whitespace (command first) and the game
+pub type Handler = fn(&mut Console, &[&str], &mut CommandContext);
+
+#[derive(Clone, Copy)]
+pub struct Command {
+    // the first word is the command, the rest is shown by `help` and the F1 overlay
+    pub usage: &'static str,
+    // language key of the one-line summary
+    pub summary: &'static str,
+    pub permission: Permission,
+    pub run: Handler,
+}
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        self.usage.split_whitespace().next().unwrap_or(self.usage)
+    }
+}
+
+// the console's own commands, in the order `help` lists them
+const BUILTIN: &[Command] = &[
+    Command { usage: "help", summary: "cmd.summary.help", permission: Permission::Player, run: Console::run_help },
+    Command { usage: "/debug_mode set true", summary: "cmd.summary.debug_mode", permission: Permission::Player, run: Console::run_debug_mode },
+    Command { usage: "/move_speed set {value}", summary: "cmd.summary.move_speed", permission: Permission::Player, run: Console::run_move_speed },
+    Command { usage: "/jump_force set {value}", summary: "cmd.summary.jump_force", permission: Permission::Player, run: Console::run_jump_force },
+    Command { usage: "/autosave_interval set {seconds}", summary: "cmd.summary.autosave_interval", permission: Permission::Operator, run: Console::run_autosave_interval },
+    Command { usage: "/spectator [on/off]", summary: "cmd.summary.spectator", permission: Permission::Moderator, run: Console::run_spectator },
+    Command { usage: "/language [code]", summary: "cmd.summary.language", permission: Permission::Player, run: Console::run_language },
+    Command { usage: "/option [name] [value]", summary: "cmd.summary.option", permission: Permission::Player, run: Console::run_option },
+    Command { usage: "/bind [key] [action|none]", summary: "cmd.summary.bind", permission: Permission::Player, run: Console::run_bind },
+    Command { usage: "/alias [name] [\"command; command $1\"|none]", summary: "cmd.summary.alias", permission: Permission::Player, run: Console::run_alias },
+    Command { usage: "/prefab {save|delete} {name} | list", summary: "cmd.summary.prefab", permission: Permission::Player, run: Console::run_prefab },
+    Command { usage: "/renderdistance [chunks|auto|lod {factor}]", summary: "cmd.summary.renderdistance", permission: Permission::Player, run: Console::run_renderdistance },
+    Command { usage: "/debugview {sun|map|off}", summary: "cmd.summary.debugview", permission: Permission::Player, run: Console::run_debugview },
+    Command { usage: "/rendermode {normal|normals|ao|light|lod|depth|overdraw}", summary: "cmd.summary.rendermode", permission: Permission::Player, run: Console::run_rendermode },
+    Command { usage: "/fog [density {value}|color {rrggbb}|off]", summary: "cmd.summary.fog", permission: Permission::Player, run: Console::run_fog },
+    Command { usage: "/monitor {camera|screen {n}|clear}", summary: "cmd.summary.monitor", permission: Permission::Player, run: Console::run_monitor },
+    Command { usage: "/sign [text]", summary: "cmd.summary.sign", permission: Permission::Player, run: Console::run_sign },
+    Command { usage: "/teleporter link <channel> | unlink | list", summary: "cmd.summary.teleporter", permission: Permission::Player, run: Console::run_teleporter },
+    Command { usage: "/timelapse start <minutes> [orbit] | stop", summary: "cmd.summary.timelapse", permission: Permission::Player, run: Console::run_timelapse },
+    Command { usage: "/record start [png|ffmpeg] [fps] | stop", summary: "cmd.summary.record", permission: Permission::Player, run: Console::run_record },
+    Command { usage: "/panorama", summary: "cmd.summary.panorama", permission: Permission::Player, run: Console::run_panorama },
+    Command { usage: "/marker add <name> [public|private] | remove <name> [owner] | share <name> {public|private} | list", summary: "cmd.summary.marker", permission: Permission::Player, run: Console::run_marker },
+    Command { usage: "/music [on|off|next|volume {0-1}]", summary: "cmd.summary.music", permission: Permission::Player, run: Console::run_music },
+    Command { usage: "/stats me", summary: "cmd.summary.stats", permission: Permission::Player, run: Console::run_stats },
+    Command { usage: "/spawn {cube|ball|bird|bike|villager|lurker} [count] | clear", summary: "cmd.summary.spawn", permission: Permission::Operator, run: Console::run_spawn },
+    Command { usage: "/boom <radius>", summary: "cmd.summary.boom", permission: Permission::Operator, run: Console::run_boom },
+    Command { usage: "/backup [list]", summary: "cmd.summary.backup", permission: Permission::Moderator, run: Console::run_backup },
+    Command { usage: "/rollback {timestamp|latest}", summary: "cmd.summary.rollback", permission: Permission::Owner, run: Console::run_rollback },
+    Command { usage: "/save [name]", summary: "cmd.summary.save", permission: Permission::Moderator, run: Console::run_save },
+    Command { usage: "/load [name]", summary: "cmd.summary.load", permission: Permission::Owner, run: Console::run_load },
+    Command { usage: "/server {command}", summary: "cmd.summary.server", permission: Permission::Player, run: Console::run_server },
+    Command { usage: "/stop", summary: "cmd.summary.stop", permission: Permission::Owner, run: Console::run_stop },
+    Command { usage: "/exec <file>", summary: "cmd.summary.exec", permission: Permission::Operator, run: Console::run_exec },
+];
+
+// commands other modules added at startup, listed after BUILTIN
+static REGISTERED: RwLock<Vec<Command>> = RwLock::new(Vec::new());
+
+// adds a command for every console from now on. false, and nothing added, if the name is taken
+pub fn register(command: Command) -> bool {
+    if is_command(command.name()) { return false; }
+    REGISTERED.write().unwrap().push(command);
+    true
+}
+
+// a plugin's command, answered through Plugins::run_command. op-only like anything else that might
+// edit the world
+pub fn register_command(usage: &'static str, summary: &'static str) -> bool {
+    register(Command { usage, summary, permission: Permission::Operator, run: Console::run_plugin })
+}
+
+// BUILTIN and then the registered ones
+pub fn commands() -> Vec<Command> {
+    BUILTIN.iter().copied().chain(REGISTERED.read().unwrap().iter().copied()).collect()
+}
+
+pub fn find(name: &str) -> Option<Command> {
+    commands().into_iter().find(|c| c.name() == name)
+}
+
+// whether `name` (with its slash) is already taken
+pub fn is_command(name: &str) -> bool {
+    find(name).is_some()
+}
+
+// everything a console command is allowed to touch
+pub struct CommandContext<'a> {
+    pub player: &'a mut Player,
+    pub planet: &'a mut PlanetData,
+    pub autosave: &'a mut Autosave,
+    pub permission: Permission,
+    // client preferences, None on a dedicated server
+    pub settings: Option<&'a mut Settings>,
+    // what the keys do, None on a dedicated server
+    pub bindings: Option<&'a mut Bindings>,
+    // console shorthands, None on a dedicated server
+    pub aliases: Option<&'a mut Aliases>,
+    // saved prefabs and the current selection, None on a dedicated server
+    pub prefabs: Option<&'a mut Prefabs>,
+    // security cameras and their screens, None on a dedicated server
+    pub monitors: Option<&'a mut Monitors>,
+    // background music state, None on a dedicated server
+    pub music: Option<&'a mut Music>,
+    // what the player has done in this world, None on a dedicated server
+    pub stats: Option<&'a mut Stats>,
+    // test entities, None on a dedicated server
+    pub mobs: Option<&'a mut Mobs>,
+    // map markers this client knows about, None on a dedicated server
+    pub markers: Option<&'a mut Markers>,
+    // the running time-lapse, None on a dedicated server
+    pub timelapse: Option<&'a mut Option<TimeLapse>>,
+    // the running recording, None on a dedicated server
+    pub recorder: Option<&'a mut Option<Recorder>>,
+    // the panorama being taken, None on a dedicated server
+    pub panorama: Option<&'a mut Option<Panorama>>,
+    // block the player is looking at, None on a dedicated server
+    pub target: Option<BlockId>,
+    // loaded plugins, for the commands they answer. None on a dedicated server
+    pub plugins: Option<&'a mut Plugins>,
+    // this client joined a server, so the planet here is the server's and not saved locally
+    pub online: bool,
+    // the player is flying (Controller::fly_mode), which spares them damage like a spectator
+    pub fly_mode: bool,
+
+    // set by commands that swap out the planet, so the caller can rebuild meshes
+    pub world_reloaded: bool,
+    // set by /stop, the caller saves and shuts down
+    pub quit: bool,
+    // set by /server, a command line the caller should send to the server it's connected to
+    pub forward: Option<String>,
+    // set by /debugview, the camera the caller should open the debug window with. Some(None) closes it
+    pub debug_view: Option<Option<DebugCamera>>,
+    // render mode to switch the main view to
+    pub render_mode: Option<RenderMode>,
+    // set by /sign, text the caller should write on the block (and send to the server). empty takes the sign down
+    pub sign: Option<(BlockId, String)>,
+    // set by /teleporter, the channel the caller should put on the block (and send to the server). empty takes it off
+    pub teleporter: Option<(BlockId, String)>,
+    // set by /marker, the change the caller should make (offline) or ask the server for
+    pub marker: Option<MarkerEdit>,
+    // set by /boom, the blocks it cleared, for the caller to remesh and send to the server
+    pub blasted: Vec<(BlockId, BlockType)>,
+}
+
+impl<'a> CommandContext<'a> {
+    pub fn new(player: &'a mut Player, planet: &'a mut PlanetData, autosave: &'a mut Autosave, permission: Permission) -> Self {
+        Self { player, planet, autosave, permission, settings: None, bindings: None, aliases: None, prefabs: None, monitors: None, music: None, stats: None, mobs: None, markers: None, timelapse: None, recorder: None, panorama: None, target: None, plugins: None, online: false, fly_mode: false, world_reloaded: false, quit: false, forward: None, debug_view: None, render_mode: None, sign: None, teleporter: None, marker: None, blasted: Vec::new() }
+    }
+}
 
 pub struct Console {
     pub is_open: bool,
@@ -8,6 +215,18 @@ pub struct Console {
     
    
     history_capacity: usize,
+    // lines entered this session, oldest first, that up and down page through
+    inputs: Vec<String>,
+    // which of them is in the input line while paging, and what was typed before paging started
+    recall: Option<usize>,
+    draft: String,
+    // remote listeners (rcon sessions) that get a copy of every log line
+    taps: Vec<Sender<String>>,
+    // log lines picked with the mouse for copying: the one the drag started on and the one it's on now
+    selection: Option<(usize, usize)>,
+    selecting: bool,
+    // scripts and aliases being run, one inside the other
+    nesting: usize,
 }
 
 impl Console {
@@ -18,6 +237,13 @@ impl Console {
             history: Vec::new(),
             height_fraction: 0.0,
             history_capacity: 50,
+            inputs: Vec::new(),
+            recall: None,
+            draft: String::new(),
+            taps: Vec::new(),
+            selection: None,
+            selecting: false,
+            nesting: 0,
         }
     }
 
@@ -26,112 +252,1026 @@ impl Console {
         if self.is_open {
             
             self.input_buffer.clear();
+            self.recall = None;
+        } else {
+            self.selection = None;
+            self.selecting = false;
         }
     }
 
     pub fn log(&mut self, text: &str, color: [f32; 3]) {
         // print to actual terminal
         println!("{}", text);
+        self.taps.retain(|tap| tap.send(text.to_string()).is_ok());
         
         if self.history.len() >= self.history_capacity {
             self.history.remove(0);
+            // the selected lines moved up one, and are gone if the first of them was the one dropped
+            self.selection = self.selection.and_then(|(a, b)| Some((a.checked_sub(1)?, b.checked_sub(1)?)));
         }
         self.history.push((text.to_string(), color));
     }
 
+    pub fn add_tap(&mut self, tap: Sender<String>) {
+        self.taps.push(tap);
+    }
+
     pub fn handle_char(&mut self, c: char) {
         if !self.is_open { return; }
         // filter control characters
         if !c.is_control() {
             self.input_buffer.push(c);
+            self.recall = None;
+        }
+    }
+
+    // text from the clipboard. line breaks become spaces, the input is one line
+    pub fn paste(&mut self, text: &str) {
+        for c in text.trim_end().chars() {
+            self.handle_char(if c == '\n' || c == '\r' { ' ' } else { c });
         }
     }
 
     pub fn handle_backspace(&mut self) {
         if !self.is_open { return; }
         self.input_buffer.pop();
+        self.recall = None;
     }
 
-    pub fn submit(&mut self, player: &mut Player) {
+    // the line entered before the one showing, stopping at the first
+    pub fn history_up(&mut self) {
+        let i = match self.recall {
+            Some(i) => i.saturating_sub(1),
+            None if self.inputs.is_empty() => return,
+            None => {
+                self.draft = self.input_buffer.clone();
+                self.inputs.len() - 1
+            },
+        };
+        self.recall = Some(i);
+        self.input_buffer = self.inputs[i].clone();
+    }
+
+    // the line entered after the one showing, and past the newest whatever was being typed
+    pub fn history_down(&mut self) {
+        let Some(i) = self.recall else { return };
+        if i + 1 < self.inputs.len() {
+            self.recall = Some(i + 1);
+            self.input_buffer = self.inputs[i + 1].clone();
+        } else {
+            self.recall = None;
+            self.input_buffer = std::mem::take(&mut self.draft);
+        }
+    }
+
+    // tab: finishes the command name being typed as far as every command starting with it agrees, and lists
+    // them when that's not far enough to tell them apart
+    pub fn complete(&mut self) {
+        if self.input_buffer.is_empty() || self.input_buffer.contains(char::is_whitespace) { return; }
+        let mut names: Vec<&str> = commands().iter()
+            .map(Command::name)
+            .filter(|name| name.starts_with(self.input_buffer.as_str()))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        let Some(first) = names.first() else { return };
+        if names.len() == 1 {
+            self.input_buffer = format!("{} ", first);
+        } else {
+            let common = names.iter().fold(first.len(), |len, name| {
+                first.bytes().zip(name.bytes()).take(len).take_while(|(a, b)| a == b).count()
+            });
+            if common > self.input_buffer.len() {
+                self.input_buffer = first[..common].to_string();
+            } else {
+                self.log(&names.join("  "), [0.8, 0.8, 0.8]);
+            }
+        }
+        self.recall = None;
+    }
+
+    pub fn submit(&mut self, ctx: &mut CommandContext) {
         if self.input_buffer.is_empty() { return; }
         
         let cmd = self.input_buffer.clone();
         self.log(&format!("> {}", cmd), [1.0, 1.0, 1.0]); // log
+        if self.inputs.last() != Some(&cmd) {
+            if self.inputs.len() >= self.history_capacity { self.inputs.remove(0); }
+            self.inputs.push(cmd.clone());
+        }
+        self.recall = None;
         
-        self.process_command(&cmd, player);
+        self.process_command(&cmd, ctx);
         self.input_buffer.clear();
     }
 
-    fn process_command(&mut self, cmd_line: &str, player: &mut Player) {
-        let parts: Vec<&str> = cmd_line.trim().split_whitespace().collect();
-        if parts.is_empty() { return; }
+    // runs a file of commands, one per line, each echoed and checked like a typed one. `#` starts a comment and
+    // `$name = value` sets a variable that `$name` stands for in the lines after it. stops early if a line
+    // asks to quit. returns the commands run
+    pub fn exec_file(&mut self, path: &std::path::Path, ctx: &mut CommandContext) -> std::io::Result<usize> {
+        let text = std::fs::read_to_string(path)?;
+        if self.nesting >= MAX_NESTING {
+            self.log(&tr!("cmd.too_deep", MAX_NESTING), [1.0, 0.0, 0.0]);
+            return Ok(0);
+        }
+        self.nesting += 1;
+        let mut vars: Vec<(String, String)> = Vec::new();
+        let mut run = 0;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() { continue; }
+            if let Some((name, value)) = line.strip_prefix('$').and_then(|l| l.split_once('=')) {
+                let (name, value) = (format!("${}", name.trim()), substitute(value.trim(), &vars));
+                vars.retain(|(n, _)| *n != name);
+                vars.push((name, value));
+                // longest first, so $ab isn't read as $a followed by b
+                vars.sort_by_key(|(n, _)| std::cmp::Reverse(n.len()));
+                continue;
+            }
+            let line = substitute(line, &vars);
+            self.log(&format!("> {}", line), [0.8, 0.8, 0.8]);
+            self.process_command(&line, ctx);
+            run += 1;
+            if ctx.quit { break; }
+        }
+        self.nesting -= 1;
+        Ok(run)
+    }
+
+    fn process_command(&mut self, cmd_line: &str, ctx: &mut CommandContext) {
+        let parts: Vec<&str> = cmd_line.split_whitespace().collect();
+        let Some(&name) = parts.first() else { return };
+        let Some(command) = find(name) else {
+            match ctx.aliases.as_deref().and_then(|aliases| aliases.get(name)) {
+                Some(body) => self.run_expanded(Aliases::expand(body, &parts[1..]), ctx),
+                None => self.log(&tr!("cmd.unknown", name), [1.0, 0.0, 0.0]),
+            }
+            return;
+        };
+        if ctx.permission < command.permission {
+            self.log(&tr!("cmd.no_permission", name, format!("{:?}", command.permission)), [1.0, 0.0, 0.0]);
+            return;
+        }
+        (command.run)(self, &parts, ctx);
+    }
+
+    // an alias's commands, one after the other like a script's
+    fn run_expanded(&mut self, lines: Vec<String>, ctx: &mut CommandContext) {
+        if self.nesting >= MAX_NESTING {
+            self.log(&tr!("cmd.too_deep", MAX_NESTING), [1.0, 0.0, 0.0]);
+            return;
+        }
+        self.nesting += 1;
+        for line in lines {
+            self.process_command(&line, ctx);
+            if ctx.quit { break; }
+        }
+        self.nesting -= 1;
+    }
+
+    // --- COMMANDS ---
+    fn run_help(&mut self, _parts: &[&str], _ctx: &mut CommandContext) {
+        self.log(&tr!("cmd.help.title"), [0.0, 1.0, 1.0]);
+        for command in commands() {
+            self.log(&format!("  {}", command.usage), [0.8, 0.8, 0.8]);
+        }
+    }
+
+    fn run_move_speed(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        self.handle_property_command(parts, "move_speed", &mut ctx.player.move_speed);
+    }
+
+    fn run_jump_force(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        self.handle_property_command(parts, "jump_force", &mut ctx.player.jump_force);
+    }
+
+    fn run_autosave_interval(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        // the autosave stays off while connected, see main
+        if !self.offline_only(ctx) { return; }
+        self.handle_property_command(parts, "autosave_interval", &mut ctx.autosave.interval);
+        ctx.autosave.interval = ctx.autosave.interval.max(0.0);
+    }
+
+    fn run_debug_mode(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        if parts.len() < 3 || parts[1] != "set" {
+            self.log(&tr!("cmd.debug_mode.usage"), [1.0, 0.5, 0.0]);
+            return;
+        }
+        match parts[2] {
+            "true" => { ctx.player.debug_mode = true; self.log(&tr!("cmd.debug_mode.on"), [0.0, 1.0, 0.0]); },
+            "false" => { ctx.player.debug_mode = false; self.log(&tr!("cmd.debug_mode.off"), [1.0, 0.0, 0.0]); },
+            _ => self.log(&tr!("cmd.expected_bool"), [1.0, 0.0, 0.0]),
+        }
+    }
+
+    fn run_spectator(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        ctx.player.spectator = match parts.get(1) {
+            Some(&"on") => true,
+            Some(&"off") => false,
+            _ => !ctx.player.spectator,
+        };
+        ctx.player.velocity = glam::Vec3::ZERO;
+        if ctx.player.spectator {
+            self.log(&tr!("cmd.spectator.on"), [0.0, 1.0, 0.0]);
+        } else {
+            self.log(&tr!("cmd.spectator.off"), [1.0, 0.0, 0.0]);
+        }
+    }
 
-        let command = parts[0];
+    fn run_language(&mut self, parts: &[&str], _ctx: &mut CommandContext) {
+        let Some(code) = parts.get(1) else {
+            self.log(&tr!("cmd.language.current", i18n::current_language()), [0.0, 1.0, 0.0]);
+            self.log(&tr!("cmd.language.available", i18n::available_languages().join(", ")), [0.8, 0.8, 0.8]);
+            return;
+        };
+        match i18n::set_language(code) {
+            Ok(()) => self.log(&tr!("cmd.language.set", code), [0.0, 1.0, 0.0]),
+            Err(e) => self.log(&tr!("cmd.language.failed", code, e), [1.0, 0.0, 0.0]),
+        }
+    }
 
-        match command {
-            "/move_speed" => {
-                self.handle_property_command(parts, "move_speed", &mut player.move_speed);
+    fn run_option(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(settings) = ctx.settings.as_deref_mut() else {
+            self.log(&tr!("cmd.option.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        if !AUDIO_OUTPUT && parts.get(1).is_some_and(|key| MUSIC_OPTIONS.contains(key)) {
+            self.log(&tr!("cmd.music.no_output"), [1.0, 0.5, 0.0]);
+            return;
+        }
+        match (parts.get(1), parts.get(2)) {
+            (None, _) => {
+                // nothing plays, so there's nothing for these to change
+                for key in Settings::KEYS.iter().filter(|k| AUDIO_OUTPUT || !MUSIC_OPTIONS.contains(k)) {
+                    self.log(&format!("  {} = {}", key, settings.get(key).unwrap_or_default()), [0.8, 0.8, 0.8]);
+                }
             },
-            "/jump_force" => {
-                self.handle_property_command(parts, "jump_force", &mut player.jump_force);
+            (Some(key), None) => match settings.get(key) {
+                Some(value) => self.log(&format!("{} = {}", key, value), [0.0, 1.0, 0.0]),
+                None => self.log(&tr!("cmd.option.unknown", key), [1.0, 0.0, 0.0]),
             },
-            
-            "/debug_mode" => {
-                 if parts.len() < 3 || parts[1] != "set" {
-                    self.log("Usage: /debug_mode set [true/false]", [1.0, 0.5, 0.0]);
+            (Some(key), Some(value)) => {
+                if !settings.set(key, value) {
+                    self.log(&tr!("cmd.option.invalid", key, value), [1.0, 0.0, 0.0]);
                     return;
                 }
-                match parts[2] {
-                    "true" => { player.debug_mode = true; self.log("Debug Mode: ON", [0.0, 1.0, 0.0]); },
-                    "false" => { player.debug_mode = false; self.log("Debug Mode: OFF", [1.0, 0.0, 0.0]); },
-                    _ => self.log("Value must be true or false", [1.0, 0.0, 0.0]),
+                self.log(&format!("{} = {}", key, settings.get(key).unwrap_or_default()), [0.0, 1.0, 0.0]);
+                if let Err(e) = settings.save(std::path::Path::new(CLIENT_CONFIG)) {
+                    self.log(&tr!("cmd.option.save_failed", e), [1.0, 0.0, 0.0]);
+                }
+            },
+        }
+    }
+
+    fn run_bind(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(bindings) = ctx.bindings.as_deref_mut() else {
+            self.log(&tr!("cmd.option.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        let Some(name) = parts.get(1) else {
+            for action in Action::ALL {
+                self.log(&format!("  {} = {}", action.name(), bindings.describe(&[action])), [0.8, 0.8, 0.8]);
+            }
+            return;
+        };
+        let Some(key) = bindings::parse_key(name) else {
+            self.log(&tr!("cmd.bind.unknown_key", name), [1.0, 0.0, 0.0]);
+            return;
+        };
+        let action = match parts.get(2).copied() {
+            None => {
+                let action = bindings.action(key).map_or("none", |a| a.name());
+                self.log(&format!("{} = {}", bindings::key_name(key), action), [0.0, 1.0, 0.0]);
+                return;
+            },
+            Some("none") => None,
+            Some(action) => match Action::parse(action) {
+                Some(action) => Some(action),
+                None => {
+                    let names: Vec<&str> = Action::ALL.iter().map(|a| a.name()).collect();
+                    self.log(&tr!("cmd.bind.unknown_action", action, names.join(", ")), [1.0, 0.0, 0.0]);
+                    return;
+                },
+            },
+        };
+        bindings.bind(key, action);
+        self.log(&format!("{} = {}", bindings::key_name(key), action.map_or("none", |a| a.name())), [0.0, 1.0, 0.0]);
+        if let Err(e) = bindings.save(std::path::Path::new(BINDINGS_CONFIG)) {
+            self.log(&tr!("cmd.bind.save_failed", e), [1.0, 0.0, 0.0]);
+        }
+    }
+
+    fn run_alias(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(aliases) = ctx.aliases.as_deref_mut() else {
+            self.log(&tr!("cmd.option.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        let Some(name) = parts.get(1) else {
+            for (name, body) in aliases.iter() {
+                self.log(&format!("  {} = {}", name, body), [0.8, 0.8, 0.8]);
+            }
+            return;
+        };
+        // the commands may have been quoted to keep them together, they're one string either way
+        let body = parts[2..].join(" ");
+        let body = body.trim_matches('"').trim();
+        match body {
+            "" => {
+                match aliases.get(name) {
+                    Some(body) => self.log(&format!("{} = {}", name, body), [0.0, 1.0, 0.0]),
+                    None => self.log(&tr!("cmd.alias.unknown", name), [1.0, 0.0, 0.0]),
                 }
+                return;
+            },
+            "none" => aliases.set(name, None),
+            _ if is_command(&format!("/{}", name.trim_start_matches('/'))) => {
+                self.log(&tr!("cmd.alias.taken", name), [1.0, 0.0, 0.0]);
+                return;
             },
-         
-            "help" => {
-                self.log("Available Commands:", [0.0, 1.0, 1.0]);
-                self.log("  /debug_mode set true", [0.8, 0.8, 0.8]); 
-                self.log("  /move_speed set {value}", [0.8, 0.8, 0.8]);
-                self.log("  /jump_force set {value}", [0.8, 0.8, 0.8]);
+            _ => aliases.set(name, Some(body)),
+        }
+        self.log(&format!("{} = {}", name, aliases.get(name).unwrap_or("none")), [0.0, 1.0, 0.0]);
+        if let Err(e) = aliases.save(std::path::Path::new(ALIAS_CONFIG)) {
+            self.log(&tr!("cmd.alias.save_failed", e), [1.0, 0.0, 0.0]);
+        }
+    }
+
+    fn run_renderdistance(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(settings) = ctx.settings.as_deref_mut() else {
+            self.log(&tr!("cmd.option.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        let ok = match (parts.get(1).copied(), parts.get(2)) {
+            (None, _) => {
+                let mode = if settings.auto_render_distance { tr!("cmd.renderdistance.auto") } else { tr!("cmd.renderdistance.manual") };
+                self.log(&tr!("cmd.renderdistance.current", settings.render_distance, settings.lod_detail, mode), [0.0, 1.0, 0.0]);
+                return;
             },
+            (Some("auto"), _) => settings.set("auto_render_distance", "true"),
+            (Some("lod"), Some(factor)) => settings.set("lod_detail", factor) && settings.set("auto_render_distance", "false"),
+            (Some(chunks), None) => settings.set("render_distance", chunks) && settings.set("auto_render_distance", "false"),
+            _ => false,
+        };
+        if !ok {
+            self.log(&tr!("cmd.renderdistance.usage"), [1.0, 0.5, 0.0]);
+            return;
+        }
+        if settings.auto_render_distance {
+            self.log(&tr!("cmd.renderdistance.auto_on"), [0.0, 1.0, 0.0]);
+        } else {
+            self.log(&tr!("cmd.renderdistance.set", settings.render_distance, settings.lod_detail), [0.0, 1.0, 0.0]);
+        }
+        if let Err(e) = settings.save(std::path::Path::new(CLIENT_CONFIG)) {
+            self.log(&tr!("cmd.option.save_failed", e), [1.0, 0.0, 0.0]);
+        }
+    }
+
+    fn run_debugview(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        // only the client has windows, and it always passes its settings
+        if ctx.settings.is_none() {
+            self.log(&tr!("cmd.debugview.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        }
+        match parts.get(1).copied() {
+            Some("off") => ctx.debug_view = Some(None),
+            Some(name) if DebugCamera::parse(name).is_some() => ctx.debug_view = Some(DebugCamera::parse(name)),
+            _ => self.log(&tr!("cmd.debugview.usage"), [1.0, 0.5, 0.0]),
+        }
+    }
+
+    fn run_rendermode(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        if ctx.settings.is_none() {
+            self.log(&tr!("cmd.rendermode.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        }
+        match parts.get(1).and_then(|name| RenderMode::parse(name)) {
+            Some(mode) if parts.len() == 2 => {
+                ctx.render_mode = Some(mode);
+                self.log(&tr!("cmd.rendermode.set", mode.name()), [0.0, 1.0, 0.0]);
+            },
+            _ => self.log(&tr!("cmd.rendermode.usage"), [1.0, 0.5, 0.0]),
+        }
+    }
+
+    // the fog options by shorter names: `/fog density 0.003` is `/option fog_density 0.003`
+    fn run_fog(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        match (parts.get(1).copied(), parts.get(2).copied()) {
+            (None, _) => {
+                self.run_option(&["/option", "fog_density"], ctx);
+                self.run_option(&["/option", "fog_color"], ctx);
+            },
+            (Some("off"), None) => self.run_option(&["/option", "fog_density", "0"], ctx),
+            (Some(what @ ("density" | "color")), value) if parts.len() <= 3 => {
+                let key = format!("fog_{}", what);
+                let args: Vec<&str> = ["/option", key.as_str()].into_iter().chain(value).collect();
+                self.run_option(&args, ctx);
+            },
+            _ => self.log(&tr!("cmd.fog.usage"), [1.0, 0.5, 0.0]),
+        }
+    }
+
+    fn run_monitor(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(monitors) = ctx.monitors.as_deref_mut() else {
+            self.log(&tr!("cmd.monitor.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        let result = match (parts.get(1).copied(), parts.get(2).map(|n| n.parse::<usize>())) {
+            (Some("camera"), None) => monitors.add_camera(ctx.player).map(|n| tr!("cmd.monitor.camera_added", n)),
+            (Some("screen"), Some(Ok(n))) => monitors.add_screen(n, ctx.player).map(|_| tr!("cmd.monitor.screen_added", n)),
+            (Some("clear"), None) => {
+                monitors.clear();
+                Ok(tr!("cmd.monitor.cleared"))
+            },
+            _ => Err(tr!("cmd.monitor.usage")),
+        };
+        match result {
+            Ok(text) => self.log(&text, [0.0, 1.0, 0.0]),
+            Err(e) => self.log(&e, [1.0, 0.5, 0.0]),
+        }
+    }
+
+    fn run_spawn(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(mobs) = ctx.mobs.as_deref_mut() else {
+            self.log(&tr!("cmd.spawn.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        if parts.get(1) == Some(&"clear") {
+            let n = mobs.len();
+            mobs.clear();
+            self.log(&tr!("cmd.spawn.cleared", n), [0.0, 1.0, 0.0]);
+            return;
+        }
+        let kind = parts.get(1).and_then(|s| MobKind::parse(s));
+        let count = parts.get(2).map_or(Ok(1), |n| n.parse::<usize>());
+        let (Some(kind), Ok(count)) = (kind, count) else {
+            self.log(&tr!("cmd.spawn.usage"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        let Some(id) = ctx.target else {
+            self.log(&tr!("cmd.spawn.no_target"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        match mobs.spawn(kind, count.clamp(1, MAX_SPAWN), id, ctx.planet) {
+            Ok(n) => self.log(&tr!("cmd.spawn.done", n, kind.name(), mobs.len()), [0.0, 1.0, 0.0]),
+            Err(e) => self.log(&e, [1.0, 0.5, 0.0]),
+        }
+    }
+
+    fn run_boom(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        // a blast is thousands of edits the server would have to take one by one from a client
+        if !self.offline_only(ctx) { return; }
+        let radius = match parts.get(1).map(|r| r.parse::<f32>()) {
+            Some(Ok(r)) if r > 0.0 && parts.len() == 2 => r.min(MAX_BOOM_RADIUS),
             _ => {
-                self.log(&format!("Unknown command: {}", command), [1.0, 0.0, 0.0]);
+                self.log(&tr!("cmd.boom.usage", MAX_BOOM_RADIUS), [1.0, 0.5, 0.0]);
+                return;
+            },
+        };
+        let Some(id) = ctx.target else {
+            self.log(&tr!("cmd.boom.no_target"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        let center = CoordSystem::get_block_center(id.face, id.u, id.v, id.layer, ctx.planet.resolution);
+        ctx.blasted = explosion::blast(ctx.planet, center, radius);
+        if !ctx.player.spectator && !ctx.fly_mode {
+            ctx.player.health.hurt(DamageSource::Explosion, health::explosion_damage(center.distance(ctx.player.position), radius));
+        }
+        self.log(&tr!("cmd.boom.done", ctx.blasted.len(), radius), [0.0, 1.0, 0.0]);
+    }
+
+    fn run_sign(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(id) = ctx.target.filter(|id| ctx.planet.exists(*id)) else {
+            self.log(&tr!("cmd.sign.no_target"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        let text = parts[1..].join(" ");
+        if text.chars().count() > MAX_SIGN_TEXT {
+            self.log(&tr!("cmd.sign.too_long", MAX_SIGN_TEXT), [1.0, 0.5, 0.0]);
+            return;
+        }
+        if text.is_empty() && ctx.planet.entity(id).is_none() {
+            self.log(&tr!("cmd.sign.usage"), [1.0, 0.5, 0.0]);
+            return;
+        }
+        self.log(&if text.is_empty() { tr!("cmd.sign.removed") } else { tr!("cmd.sign.written") }, [0.0, 1.0, 0.0]);
+        ctx.sign = Some((id, text));
+    }
+
+    fn run_timelapse(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(timelapse) = ctx.timelapse.as_deref_mut() else {
+            self.log(&tr!("cmd.timelapse.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        match (parts.get(1).copied(), parts.get(2).map(|m| m.parse::<f32>()), parts.get(3).copied()) {
+            (None, _, _) => match timelapse.as_ref() {
+                Some(lapse) => self.log(&tr!("cmd.timelapse.status", lapse.frames, lapse.interval / 60.0, lapse.dir.display()), [0.0, 1.0, 0.0]),
+                None => self.log(&tr!("cmd.timelapse.off"), [1.0, 1.0, 0.0]),
+            },
+            (Some("start"), Some(Ok(minutes)), orbit) if minutes > 0.0 && matches!(orbit, None | Some("orbit")) => {
+                let camera = if orbit.is_some() {
+                    // around the block under the cursor, or the player when there's none
+                    let res = ctx.planet.resolution;
+                    let center = ctx.target.map_or(ctx.player.position, |id| CoordSystem::get_block_center(id.face, id.u, id.v, id.layer, res));
+                    LapseCamera::orbit(ctx.player, center)
+                } else {
+                    LapseCamera::fixed(ctx.player)
+                };
+                match TimeLapse::start(minutes, camera) {
+                    Ok(lapse) => {
+                        self.log(&tr!("cmd.timelapse.started", minutes, lapse.dir.display()), [0.0, 1.0, 0.0]);
+                        *timelapse = Some(lapse);
+                    },
+                    Err(e) => self.log(&tr!("cmd.timelapse.failed", e), [1.0, 0.0, 0.0]),
+                }
+            },
+            (Some("stop"), None, None) => match timelapse.take() {
+                Some(lapse) => self.log(&tr!("cmd.timelapse.stopped", lapse.frames, lapse.dir.display()), [0.0, 1.0, 0.0]),
+                None => self.log(&tr!("cmd.timelapse.off"), [1.0, 1.0, 0.0]),
+            },
+            _ => self.log(&tr!("cmd.timelapse.usage"), [1.0, 0.5, 0.0]),
+        }
+    }
+
+    fn run_record(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(recorder) = ctx.recorder.as_deref_mut() else {
+            self.log(&tr!("cmd.record.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        match parts.get(1).copied() {
+            None => match recorder.as_ref() {
+                Some(rec) => self.log(&tr!("cmd.record.status", rec.format.name(), rec.elapsed.floor(), rec.frames, rec.fps, rec.dropped, rec.dir.display()), [0.0, 1.0, 0.0]),
+                None => self.log(&tr!("cmd.record.off"), [1.0, 1.0, 0.0]),
+            },
+            Some("start") if parts.len() <= 4 => {
+                if recorder.is_some() {
+                    self.log(&tr!("cmd.record.running"), [1.0, 1.0, 0.0]);
+                    return;
+                }
+                // format and rate in either order, both optional
+                let (mut format, mut fps) = (RecordFormat::Png, DEFAULT_RECORD_FPS);
+                for arg in &parts[2..] {
+                    match (RecordFormat::parse(arg), arg.parse::<u32>()) {
+                        (Some(f), _) => format = f,
+                        (None, Ok(n)) if (1..=MAX_RECORD_FPS).contains(&n) => fps = n,
+                        _ => {
+                            self.log(&tr!("cmd.record.usage", MAX_RECORD_FPS), [1.0, 0.5, 0.0]);
+                            return;
+                        },
+                    }
+                }
+                match Recorder::start(format, fps) {
+                    Ok(rec) => {
+                        self.log(&tr!("cmd.record.started", format.name(), fps, rec.dir.display()), [0.0, 1.0, 0.0]);
+                        *recorder = Some(rec);
+                    },
+                    Err(e) => self.log(&tr!("cmd.record.failed", e), [1.0, 0.0, 0.0]),
+                }
+            },
+            Some("stop") if parts.len() == 2 => match recorder.take() {
+                Some(mut rec) => match rec.finish() {
+                    Ok(()) => self.log(&tr!("cmd.record.stopped", rec.frames, rec.dropped, rec.dir.display()), [0.0, 1.0, 0.0]),
+                    Err(e) => self.log(&tr!("record.failed", e), [1.0, 0.0, 0.0]),
+                },
+                None => self.log(&tr!("cmd.record.off"), [1.0, 1.0, 0.0]),
+            },
+            _ => self.log(&tr!("cmd.record.usage", MAX_RECORD_FPS), [1.0, 0.5, 0.0]),
+        }
+    }
+
+    fn run_panorama(&mut self, _parts: &[&str], ctx: &mut CommandContext) {
+        let Some(panorama) = ctx.panorama.as_deref_mut() else {
+            self.log(&tr!("cmd.panorama.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        if panorama.is_some() {
+            self.log(&tr!("cmd.panorama.busy"), [1.0, 1.0, 0.0]);
+            return;
+        }
+        *panorama = Some(Panorama::start(ctx.player));
+        self.log(&tr!("cmd.panorama.started"), [0.0, 1.0, 0.0]);
+    }
+
+    fn run_marker(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(markers) = ctx.markers.as_deref() else {
+            self.log(&tr!("cmd.marker.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        let me = markers.me.clone();
+        let edit = match (parts.get(1).copied(), parts.get(2).copied(), parts.get(3).copied()) {
+            (Some("list"), None, None) => {
+                let eye = ctx.player.position;
+                let mut visible: Vec<&Marker> = markers.visible_to(&me).collect();
+                if visible.is_empty() {
+                    self.log(&tr!("cmd.marker.none"), [1.0, 1.0, 0.0]);
+                }
+                visible.sort_by(|a, b| a.position.distance(eye).total_cmp(&b.position.distance(eye)));
+                for m in visible {
+                    let line = tr!("cmd.marker.entry", m.name, m.owner, m.visibility.name(), m.position.distance(eye).round());
+                    self.log(&line, [1.0, 1.0, 1.0]);
+                }
+                return;
+            },
+            (Some("add"), Some(name), visibility) => {
+                let Some(visibility) = visibility.map_or(Some(Visibility::Private), Visibility::parse) else {
+                    self.log(&tr!("cmd.marker.usage"), [1.0, 0.5, 0.0]);
+                    return;
+                };
+                if let Err(e) = markers.check_set(&me, name) {
+                    self.log(&e, [1.0, 0.0, 0.0]);
+                    return;
+                }
+                self.log(&tr!("cmd.marker.added", name, visibility.name()), [0.0, 1.0, 0.0]);
+                MarkerEdit::Set(Marker { owner: me, name: name.to_string(), position: ctx.player.position, visibility })
+            },
+            (Some("share"), Some(name), Some(visibility)) => {
+                let (Some(visibility), Some(marker)) = (Visibility::parse(visibility), markers.get(&me, name)) else {
+                    self.log(&tr!("cmd.marker.not_found", name), [1.0, 0.5, 0.0]);
+                    return;
+                };
+                self.log(&tr!("cmd.marker.shared", name, visibility.name()), [0.0, 1.0, 0.0]);
+                MarkerEdit::Set(Marker { visibility, ..marker.clone() })
+            },
+            (Some("remove"), Some(name), owner) => {
+                let owner = owner.map_or(me, str::to_string);
+                if markers.get(&owner, name).is_none() {
+                    self.log(&tr!("cmd.marker.not_found", name), [1.0, 0.5, 0.0]);
+                    return;
+                }
+                self.log(&tr!("cmd.marker.removed", name), [0.0, 1.0, 0.0]);
+                MarkerEdit::Remove { owner, name: name.to_string() }
+            },
+            _ => {
+                self.log(&tr!("cmd.marker.usage"), [1.0, 0.5, 0.0]);
+                return;
+            },
+        };
+        ctx.marker = Some(edit);
+    }
+
+    fn run_teleporter(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        if parts.get(1) == Some(&"list") {
+            let channels = teleporter::channels(ctx.planet);
+            if channels.is_empty() {
+                self.log(&tr!("cmd.teleporter.none"), [1.0, 1.0, 0.0]);
             }
+            for (channel, ends) in channels {
+                let state = if ends.len() >= 2 { tr!("cmd.teleporter.linked") } else { tr!("cmd.teleporter.waiting") };
+                self.log(&format!("  {}: {}", channel, state), [1.0, 1.0, 1.0]);
+            }
+            return;
+        }
+        let Some(id) = ctx.target.filter(|id| ctx.planet.exists(*id)) else {
+            self.log(&tr!("cmd.teleporter.no_target"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        match (parts.get(1).copied(), parts.get(2)) {
+            (Some("link"), Some(channel)) => {
+                if let Err(e) = teleporter::check_link(ctx.planet, id, channel) {
+                    self.log(&e, [1.0, 0.0, 0.0]);
+                    return;
+                }
+                let linked = teleporter::channels(ctx.planet).get(*channel).is_some_and(|ends| ends.iter().any(|end| *end != id));
+                let msg = if linked { tr!("cmd.teleporter.linked_to", channel) } else { tr!("cmd.teleporter.first_end", channel) };
+                self.log(&msg, [0.0, 1.0, 0.0]);
+                ctx.teleporter = Some((id, channel.to_string()));
+            },
+            (Some("unlink"), None) if teleporter::channel(ctx.planet, id).is_some() => {
+                self.log(&tr!("cmd.teleporter.removed"), [0.0, 1.0, 0.0]);
+                ctx.teleporter = Some((id, String::new()));
+            },
+            (Some("unlink"), None) => self.log(&tr!("cmd.teleporter.not_teleporter"), [1.0, 0.5, 0.0]),
+            _ => self.log(&tr!("cmd.teleporter.usage"), [1.0, 0.5, 0.0]),
         }
     }
 
-    fn handle_property_command(&mut self, parts: Vec<&str>, name: &str, property: &mut f32) {
+    fn run_music(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let (Some(music), Some(settings)) = (ctx.music.as_deref_mut(), ctx.settings.as_deref_mut()) else {
+            self.log(&tr!("cmd.music.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        if !AUDIO_OUTPUT {
+            self.log(&tr!("cmd.music.no_output"), [1.0, 0.5, 0.0]);
+            return;
+        }
+        let ok = match (parts.get(1).copied(), parts.get(2)) {
+            (None, _) => {
+                let state = if settings.music { tr!("cmd.music.on") } else { tr!("cmd.music.off") };
+                self.log(&tr!("cmd.music.status", music.context.name(), state, settings.music_volume), [0.0, 1.0, 0.0]);
+                if music.track_count() == 0 {
+                    self.log(&tr!("cmd.music.no_tracks", MUSIC_DIR), [1.0, 0.5, 0.0]);
+                }
+                let volume = if settings.music { settings.music_volume } else { 0.0 };
+                let voices: Vec<String> = music.voices(volume).map(|(path, gain)| format!("  {} ({:.2})", path.display(), gain)).collect();
+                for line in voices {
+                    self.log(&line, [0.8, 0.8, 0.8]);
+                }
+                return;
+            },
+            (Some("next"), None) => {
+                music.next();
+                true
+            },
+            (Some("on"), None) => settings.set("music", "true"),
+            (Some("off"), None) => settings.set("music", "false"),
+            (Some("volume"), Some(v)) => settings.set("music_volume", v),
+            _ => false,
+        };
+        if !ok {
+            self.log(&tr!("cmd.music.usage"), [1.0, 0.5, 0.0]);
+            return;
+        }
+        match music.now_playing() {
+            Some(path) => self.log(&tr!("cmd.music.playing", path.display(), settings.music_volume), [0.0, 1.0, 0.0]),
+            None => self.log(&tr!("cmd.music.no_tracks", MUSIC_DIR), [1.0, 0.5, 0.0]),
+        }
+        if let Err(e) = settings.save(std::path::Path::new(CLIENT_CONFIG)) {
+            self.log(&tr!("cmd.option.save_failed", e), [1.0, 0.0, 0.0]);
+        }
+    }
+
+    fn run_stats(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(stats) = ctx.stats.as_deref() else {
+            self.log(&tr!("cmd.stats.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        if parts.get(1) != Some(&"me") {
+            self.log(&tr!("cmd.stats.usage"), [1.0, 0.5, 0.0]);
+            return;
+        }
+        let by_kind = |counts: &std::collections::BTreeMap<String, u32>| {
+            counts.iter().map(|(kind, n)| format!("{} {}", n, kind)).collect::<Vec<_>>().join(", ")
+        };
+        let deepest = stats.deepest.map_or_else(|| "-".to_string(), |layer| layer.to_string());
+        let lines = [
+            tr!("cmd.stats.title"),
+            tr!("cmd.stats.played", stats::format_duration(stats.played)),
+            tr!("cmd.stats.mined", stats.total_mined(), by_kind(&stats.mined)),
+            tr!("cmd.stats.placed", stats.total_placed(), by_kind(&stats.placed)),
+            tr!("cmd.stats.distance", format!("{:.2}", stats.walked / 1000.0), format!("{:.2}", stats.flown / 1000.0)),
+            tr!("cmd.stats.deepest", deepest),
+        ];
+        for line in lines {
+            self.log(&line, [0.8, 0.8, 0.8]);
+        }
+    }
+
+    fn run_prefab(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(prefabs) = ctx.prefabs.as_deref_mut() else {
+            self.log(&tr!("cmd.prefab.unavailable"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        match (parts.get(1).copied(), parts.get(2)) {
+            (Some("list"), _) => {
+                if prefabs.library.is_empty() {
+                    self.log(&tr!("cmd.prefab.none"), [1.0, 0.5, 0.0]);
+                }
+                for p in &prefabs.library {
+                    let [w, d, h] = p.schematic.size;
+                    self.log(&format!("  {} ({}x{}x{}, {})", p.name, w, d, h, p.schematic.solid.len()), [0.8, 0.8, 0.8]);
+                }
+            },
+            (Some("save"), Some(name)) => match prefabs.save_selection(name, ctx.planet) {
+                Ok(n) => self.log(&tr!("cmd.prefab.saved", name, n), [0.0, 1.0, 0.0]),
+                Err(e) => self.log(&tr!("cmd.prefab.save_failed", e), [1.0, 0.0, 0.0]),
+            },
+            (Some("delete"), Some(name)) => match prefabs.delete(name) {
+                Ok(()) => self.log(&tr!("cmd.prefab.deleted", name), [0.0, 1.0, 0.0]),
+                Err(e) => self.log(&tr!("cmd.prefab.delete_failed", name, e), [1.0, 0.0, 0.0]),
+            },
+            _ => self.log(&tr!("cmd.prefab.usage"), [1.0, 0.5, 0.0]),
+        }
+    }
+
+    // false, and says so, while connected: the planet is the server's then, saving it would mix it into the local
+    // world and loading one would leave the client out of step with the server
+    fn offline_only(&mut self, ctx: &CommandContext) -> bool {
+        if ctx.online {
+            self.log(&tr!("cmd.offline_only", tr!("cmd.server.usage")), [1.0, 0.5, 0.0]);
+        }
+        !ctx.online
+    }
+
+    fn run_backup(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        if !self.offline_only(ctx) { return; }
+        if parts.get(1) == Some(&"list") {
+            let stamps = Backups::list(&ctx.autosave.dir);
+            if stamps.is_empty() {
+                self.log(&tr!("cmd.backup.none"), [1.0, 0.5, 0.0]);
+            }
+            for ts in stamps {
+                self.log(&format!("  {}", ts), [0.8, 0.8, 0.8]);
+            }
+            return;
+        }
+        match Backups::create(&ctx.autosave.dir, ctx.planet, ctx.player) {
+            Ok(ts) => self.log(&tr!("cmd.backup.created", ts), [0.0, 1.0, 0.0]),
+            Err(e) => self.log(&tr!("cmd.backup.failed", e), [1.0, 0.0, 0.0]),
+        }
+    }
+
+    fn run_rollback(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        if !self.offline_only(ctx) { return; }
+        let stamps = Backups::list(&ctx.autosave.dir);
+        let target = match parts.get(1) {
+            Some(&"latest") => stamps.last().cloned(),
+            Some(s) => s.parse::<u64>().ok(),
+            None => {
+                self.log(&tr!("cmd.rollback.usage"), [1.0, 0.5, 0.0]);
+                return;
+            }
+        };
+        let Some(ts) = target.filter(|ts| stamps.contains(ts)) else {
+            self.log(&tr!("cmd.rollback.no_such_backup"), [1.0, 0.0, 0.0]);
+            return;
+        };
+        match Backups::restore(&ctx.autosave.dir, ts) {
+            Ok((planet, state)) => {
+                *ctx.planet = planet;
+                state.apply(ctx.player);
+                ctx.world_reloaded = true;
+                self.log(&tr!("cmd.rollback.done", ts), [0.0, 1.0, 0.0]);
+            },
+            Err(e) => self.log(&tr!("cmd.rollback.failed", e), [1.0, 0.0, 0.0]),
+        }
+    }
+
+    fn run_save(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        if !self.offline_only(ctx) { return; }
+        let result = match parts.get(1) {
+            // straight into the live world, everything the autosave hasn't got to yet
+            None => ctx.autosave.save_blocking(ctx.planet, ctx.player),
+            Some(name) => {
+                let Some(dir) = World::named_dir(&ctx.autosave.dir, name) else {
+                    self.log(&tr!("cmd.save.bad_name", name), [1.0, 0.0, 0.0]);
+                    return;
+                };
+                WorldSnapshot::full(ctx.planet, ctx.player).write(&dir)
+            }
+        };
+        match result {
+            Ok(n) => self.log(&tr!("cmd.save.done", n), [0.0, 1.0, 0.0]),
+            Err(e) => self.log(&tr!("cmd.save.failed", e), [1.0, 0.0, 0.0]),
+        }
+    }
+
+    fn run_load(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        if !self.offline_only(ctx) { return; }
+        let dir = match parts.get(1) {
+            Some(name) => match World::named_dir(&ctx.autosave.dir, name) {
+                Some(dir) => dir,
+                None => {
+                    self.log(&tr!("cmd.save.bad_name", name), [1.0, 0.0, 0.0]);
+                    return;
+                }
+            },
+            None => ctx.autosave.dir.clone(),
+        };
+        if !World::exists(&dir) {
+            self.log(&tr!("cmd.load.no_such_save", dir.display()), [1.0, 0.0, 0.0]);
+            return;
+        }
+        match World::load(&dir) {
+            Ok((mut planet, state)) => {
+                // the live world on disk is the one carried on with, so a named save replaces it
+                planet.needs_full_save = dir != ctx.autosave.dir;
+                *ctx.planet = planet;
+                state.apply(ctx.player);
+                ctx.world_reloaded = true;
+                self.log(&tr!("cmd.load.done", dir.display()), [0.0, 1.0, 0.0]);
+            },
+            Err(e) => self.log(&tr!("cmd.load.failed", e), [1.0, 0.0, 0.0]),
+        }
+    }
+
+    fn run_server(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        if parts.len() < 2 {
+            self.log(&tr!("cmd.server.usage"), [1.0, 0.5, 0.0]);
+            return;
+        }
+        ctx.forward = Some(parts[1..].join(" "));
+    }
+
+    fn run_stop(&mut self, _parts: &[&str], ctx: &mut CommandContext) {
+        self.log(&tr!("cmd.stop"), [1.0, 0.5, 0.0]);
+        ctx.quit = true;
+    }
+
+    fn run_exec(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        let Some(name) = parts.get(1) else {
+            self.log(&tr!("cmd.exec.usage"), [1.0, 0.5, 0.0]);
+            return;
+        };
+        // only files in SCRIPT_DIR, so a remote operator can't have any file on the machine read out
+        if name.contains(['/', '\\']) || name.contains("..") {
+            self.log(&tr!("cmd.exec.bad_name", name), [1.0, 0.0, 0.0]);
+            return;
+        }
+        let mut path = std::path::Path::new(SCRIPT_DIR).join(name);
+        if path.extension().is_none() { path.set_extension("cfg"); }
+        match self.exec_file(&path, ctx) {
+            Ok(n) => self.log(&tr!("cmd.exec.done", path.display(), n), [0.0, 1.0, 0.0]),
+            Err(e) => self.log(&tr!("cmd.exec.failed", path.display(), e), [1.0, 0.0, 0.0]),
+        }
+    }
+
+    fn run_plugin(&mut self, parts: &[&str], ctx: &mut CommandContext) {
+        if let Some(plugins) = ctx.plugins.take() {
+            let handled = plugins.run_command(parts, ctx, self);
+            ctx.plugins = Some(plugins);
+            if handled { return; }
+        }
+        self.log(&tr!("cmd.unknown", parts[0]), [1.0, 0.0, 0.0]);
+    }
+
+    fn handle_property_command(&mut self, parts: &[&str], name: &str, property: &mut f32) {
         if parts.len() < 2 {
-            self.log(&format!("Usage: /{} [set/get]", name), [1.0, 0.5, 0.0]);
+            self.log(&tr!("cmd.property.usage", name), [1.0, 0.5, 0.0]);
             return;
         }
 
         match parts[1] {
             "get" => {
-                self.log(&format!("{} is currently: {:.2}", name, property), [0.0, 1.0, 0.0]);
+                self.log(&tr!("cmd.property.get", name, format!("{:.2}", property)), [0.0, 1.0, 0.0]);
             },
             "set" => {
                 if parts.len() < 3 {
-                    self.log(&format!("Usage: /{} set <value>", name), [1.0, 0.5, 0.0]);
+                    self.log(&tr!("cmd.property.set_usage", name), [1.0, 0.5, 0.0]);
                     return;
                 }
                 match parts[2].parse::<f32>() {
                     Ok(val) => {
                         *property = val;
-                        self.log(&format!("{} set to {:.2}", name, val), [0.0, 1.0, 0.0]);
+                        self.log(&tr!("cmd.property.set", name, format!("{:.2}", val)), [0.0, 1.0, 0.0]);
                     },
                     Err(_) => {
-                        self.log("Invalid number format.", [1.0, 0.0, 0.0]);
+                        self.log(&tr!("cmd.invalid_number"), [1.0, 0.0, 0.0]);
                     }
                 }
             },
             _ => {
-                self.log(&format!("Unknown operation '{}'. Use set or get.", parts[1]), [1.0, 0.5, 0.0]);
+                self.log(&tr!("cmd.property.unknown_op", parts[1]), [1.0, 0.5, 0.0]);
             }
         }
     }
 
-    pub fn update_animation(&mut self, dt: f32) {
+    // --- SELECTION ---
+
+    // pixel height of the panel in a window `window_height` tall, as far as it's slid open
+    pub fn pixel_height(&self, window_height: f32) -> f32 {
+        window_height / 2.0 * self.height_fraction
+    }
+
+    // top of log line `i`, oldest first, in pixels. lines above 0 have scrolled off the panel
+    pub fn line_top(&self, i: usize, window_height: f32) -> f32 {
+        let from_bottom = (self.history.len() - 1 - i) as f32;
+        self.pixel_height(window_height) - CONSOLE_LINE_HEIGHT * 2.0 - from_bottom * CONSOLE_LINE_HEIGHT
+    }
+
+    // the log line at pixel row `y`, if there's one showing there
+    pub fn line_at(&self, y: f32, window_height: f32) -> Option<usize> {
+        (0..self.history.len()).find(|&i| {
+            let top = self.line_top(i, window_height);
+            top >= 0.0 && (top..top + CONSOLE_LINE_HEIGHT).contains(&y)
+        })
+    }
+
+    // a press on `line` starts picking lines from there. a press off the log drops the selection
+    pub fn select_from(&mut self, line: Option<usize>) {
+        self.selection = line.map(|l| (l, l));
+        self.selecting = line.is_some();
+    }
+
+    // the drag moved over `line`
+    pub fn select_to(&mut self, line: Option<usize>) {
+        if let (Some((start, _)), Some(line), true) = (self.selection, line, self.selecting) {
+            self.selection = Some((start, line));
+        }
+    }
+
+    pub fn finish_selection(&mut self) {
+        self.selecting = false;
+    }
+
+    pub fn is_selecting(&self) -> bool {
+        self.selecting
+    }
+
+    // first and last selected log line
+    pub fn selected_lines(&self) -> Option<(usize, usize)> {
+        self.selection.map(|(a, b)| (a.min(b), a.max(b)))
+    }
+
+    pub fn selected_text(&self) -> Option<String> {
+        let (first, last) = self.selected_lines()?;
+        Some(self.history[first..=last].iter().map(|(text, _)| text.as_str()).collect::<Vec<_>>().join("\n"))
+    }
+
+    // reduce motion snaps the console open or shut instead of sliding
+    pub fn update_animation(&mut self, dt: f32, reduce_motion: bool) {
+        if reduce_motion {
+            self.height_fraction = if self.is_open { 1.0 } else { 0.0 };
+            return;
+        }
         let speed = 5.0;
         if self.is_open {
             self.height_fraction = (self.height_fraction + dt * speed).min(1.0);
@@ -139,4 +1279,9 @@ impl Console {
             self.height_fraction = (self.height_fraction - dt * speed).max(0.0);
         }
     }
-}
\ No newline at end of file
+}
+
+// `line` with each of `vars` filled in, in order
+fn substitute(line: &str, vars: &[(String, String)]) -> String {
+    vars.iter().fold(line.to_string(), |line, (name, value)| line.replace(name.as_str(), value))
+}
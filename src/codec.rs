@@ -0,0 +1,148 @@
+//codec.rs
+
+// little-endian byte helpers shared by the save format and the network protocol
+
+use std::collections::HashMap;
+use std::io;
+use glam::{Vec3, Quat};
+use crate::common::{BlockEntity, BlockId, BlockType};
+
+pub fn put_u32(out: &mut Vec<u8>, v: u32) { out.extend_from_slice(&v.to_le_bytes()); }
+pub fn put_f32(out: &mut Vec<u8>, v: f32) { out.extend_from_slice(&v.to_le_bytes()); }
+
+pub fn put_vec3(out: &mut Vec<u8>, v: Vec3) {
+    for f in v.to_array() { put_f32(out, f); }
+}
+
+pub fn put_quat(out: &mut Vec<u8>, q: Quat) {
+    for f in q.to_array() { put_f32(out, f); }
+}
+
+pub fn put_str(out: &mut Vec<u8>, s: &str) {
+    put_u32(out, s.len() as u32);
+    out.extend_from_slice(s.as_bytes());
+}
+
+pub fn put_bytes(out: &mut Vec<u8>, b: &[u8]) {
+    put_u32(out, b.len() as u32);
+    out.extend_from_slice(b);
+}
+
+pub fn put_block_id(out: &mut Vec<u8>, id: BlockId) {
+    out.push(id.face);
+    put_u32(out, id.layer);
+    put_u32(out, id.u);
+    put_u32(out, id.v);
+}
+
+pub fn put_block_entity(out: &mut Vec<u8>, entity: &BlockEntity) {
+    match entity {
+        BlockEntity::Sign { text, facing } => {
+            out.push(0);
+            put_str(out, text);
+            out.push(*facing);
+        }
+        BlockEntity::Teleporter { channel } => {
+            out.push(1);
+            put_str(out, channel);
+        }
+    }
+}
+
+pub fn put_block_entities(out: &mut Vec<u8>, entities: &HashMap<BlockId, BlockEntity>) {
+    put_u32(out, entities.len() as u32);
+    for (id, entity) in entities {
+        put_block_id(out, *id);
+        put_block_entity(out, entity);
+    }
+}
+
+pub fn put_block_types(out: &mut Vec<u8>, types: &HashMap<BlockId, BlockType>) {
+    put_u32(out, types.len() as u32);
+    for (id, block) in types {
+        put_block_id(out, *id);
+        out.push(block.index());
+    }
+}
+
+pub fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self { Self { data, pos: 0 } }
+
+    pub fn bytes(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.pos + n > self.data.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "data is truncated"));
+        }
+        let slice = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    pub fn u8(&mut self) -> io::Result<u8> { Ok(self.bytes(1)?[0]) }
+    pub fn u32(&mut self) -> io::Result<u32> { Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap())) }
+    pub fn f32(&mut self) -> io::Result<f32> { Ok(f32::from_le_bytes(self.bytes(4)?.try_into().unwrap())) }
+
+    pub fn vec3(&mut self) -> io::Result<Vec3> {
+        Ok(Vec3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    pub fn quat(&mut self) -> io::Result<Quat> {
+        Ok(Quat::from_xyzw(self.f32()?, self.f32()?, self.f32()?, self.f32()?).normalize())
+    }
+
+    pub fn str(&mut self) -> io::Result<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.bytes(len)?.to_vec()).map_err(|_| invalid("string is not utf-8"))
+    }
+
+    pub fn blob(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+    }
+
+    pub fn block_id(&mut self) -> io::Result<BlockId> {
+        Ok(BlockId { face: self.u8()?, layer: self.u32()?, u: self.u32()?, v: self.u32()? })
+    }
+
+    pub fn block_entity(&mut self) -> io::Result<BlockEntity> {
+        match self.u8()? {
+            0 => Ok(BlockEntity::Sign { text: self.str()?, facing: self.u8()? }),
+            1 => Ok(BlockEntity::Teleporter { channel: self.str()? }),
+            tag => Err(invalid(&format!("unknown block entity {}", tag))),
+        }
+    }
+
+    pub fn block_entities(&mut self) -> io::Result<HashMap<BlockId, BlockEntity>> {
+        let count = self.u32()?;
+        let mut entities = HashMap::new();
+        for _ in 0..count {
+            entities.insert(self.block_id()?, self.block_entity()?);
+        }
+        Ok(entities)
+    }
+
+    pub fn block_type(&mut self) -> io::Result<BlockType> {
+        Ok(BlockType::from_index(self.u8()?))
+    }
+
+    pub fn block_types(&mut self) -> io::Result<HashMap<BlockId, BlockType>> {
+        let count = self.u32()?;
+        let mut types = HashMap::new();
+        for _ in 0..count {
+            types.insert(self.block_id()?, self.block_type()?);
+        }
+        Ok(types)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
@@ -1,11 +1,21 @@
 //common.rs
 
 use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 use bytemuck::{Pod, Zeroable};
 use crate::noise::PlanetTerrain;
+use crate::lighting::LightCache;
 
 // --- CONSTANTS ---
 pub const CHUNK_SIZE: u32 = 32;
+pub const DEFAULT_SEED: u32 = 42;
+// size of a freshly generated world
+pub const DEFAULT_RESOLUTION: u32 = 49;
+
+// direction towards the sun, fixed for now
+pub fn sun_dir() -> glam::Vec3 {
+    glam::Vec3::new(0.5, 0.8, 0.4).normalize()
+}
 
 // --- DATA TYPES ---
 
@@ -32,16 +42,6 @@ pub struct Vertex {
     pub normal: [f32; 3],
 }
 
-pub struct ChunkMesh {
-    pub v_buf: wgpu::Buffer,
-    pub i_buf: wgpu::Buffer,
-    pub num_inds: u32,
-    pub num_verts: usize,
-    pub uniform_buf: wgpu::Buffer,
-    pub bind_group: wgpu::BindGroup,
-    pub center: glam::Vec3,
-    pub radius: f32,
-}
 
 
 
@@ -54,39 +54,182 @@ pub struct LodKey {
 }
 
 
-#[derive(Clone)] 
+// what a block is made of. the generated ground is rock in the core, grass on top and dirt in between,
+// placed blocks are whatever the player had picked. Custom ones are registered at startup, see register_block
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum BlockType {
+    Rock,
+    Dirt,
+    Grass,
+    Stone,
+    Sand,
+    Glass,
+    Wood,
+    Brick,
+    Custom(u8),
+}
+
+// a block type that isn't built in, added by a plugin
+#[derive(Clone, Copy, Debug)]
+pub struct BlockDef {
+    pub name: &'static str,
+    pub color: [f32; 3],
+    pub see_through: bool,
+}
+
+static CUSTOM_BLOCKS: RwLock<Vec<BlockDef>> = RwLock::new(Vec::new());
+
+// adds a block type after the built-in ones. None if the name is taken or every byte is in use
+pub fn register_block(def: BlockDef) -> Option<BlockType> {
+    if BlockType::parse(def.name).is_some() { return None; }
+    let mut blocks = CUSTOM_BLOCKS.write().unwrap();
+    let i = u8::try_from(blocks.len()).ok().filter(|&i| i <= u8::MAX - BlockType::ALL.len() as u8)?;
+    blocks.push(def);
+    Some(BlockType::Custom(i))
+}
+
+impl BlockType {
+    pub const ALL: [BlockType; 8] = [
+        BlockType::Rock, BlockType::Dirt, BlockType::Grass, BlockType::Stone,
+        BlockType::Sand, BlockType::Glass, BlockType::Wood, BlockType::Brick,
+    ];
+
+    // the built-in ones, then whatever has been registered
+    pub fn all() -> Vec<BlockType> {
+        let custom = CUSTOM_BLOCKS.read().unwrap().len() as u8;
+        Self::ALL.into_iter().chain((0..custom).map(BlockType::Custom)).collect()
+    }
+
+    fn def(self) -> Option<BlockDef> {
+        let BlockType::Custom(i) = self else { return None };
+        CUSTOM_BLOCKS.read().unwrap().get(i as usize).copied()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BlockType::Rock => "rock",
+            BlockType::Dirt => "dirt",
+            BlockType::Grass => "grass",
+            BlockType::Stone => "stone",
+            BlockType::Sand => "sand",
+            BlockType::Glass => "glass",
+            BlockType::Wood => "wood",
+            BlockType::Brick => "brick",
+            BlockType::Custom(_) => self.def().map_or("unknown", |d| d.name),
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::all().into_iter().find(|t| t.name() == s)
+    }
+
+    // the byte it's saved and sent as. custom types count on from the built-in ones, so a world keeps its
+    // blocks as long as the plugins register theirs in the same order
+    pub fn index(self) -> u8 {
+        match self {
+            BlockType::Custom(i) => Self::ALL.len() as u8 + i,
+            _ => Self::ALL.iter().position(|&t| t == self).unwrap_or(0) as u8,
+        }
+    }
+
+    // an index past the registered types stays a Custom one, so it survives a save without its plugin
+    pub fn from_index(i: u8) -> Self {
+        Self::ALL.get(i as usize).copied().unwrap_or(BlockType::Custom(i - Self::ALL.len() as u8))
+    }
+
+    pub fn color(self) -> [f32; 3] {
+        match self {
+            BlockType::Rock => [0.2, 0.2, 0.2],
+            BlockType::Dirt => [0.6, 0.4, 0.2],
+            BlockType::Grass => [0.1, 0.7, 0.1],
+            BlockType::Stone => [0.5, 0.5, 0.52],
+            BlockType::Sand => [0.85, 0.78, 0.5],
+            BlockType::Glass => [0.7, 0.85, 0.95],
+            BlockType::Wood => [0.45, 0.3, 0.15],
+            BlockType::Brick => [0.65, 0.25, 0.2],
+            BlockType::Custom(_) => self.def().map_or([0.8, 0.0, 0.8], |d| d.color),
+        }
+    }
+
+    // faces behind it still get drawn, and it doesn't shade what's under it
+    pub fn see_through(self) -> bool {
+        self == BlockType::Glass || self.def().is_some_and(|d| d.see_through)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct ChunkMods {
     pub mined: HashSet<BlockId>,
     pub placed: HashSet<BlockId>,
+    // extra data on blocks of this chunk, saved and sent along with the edits
+    pub entities: HashMap<BlockId, BlockEntity>,
+    // what the placed blocks are made of. one that's missing (saved before there were types) looks like the
+    // ground would there
+    pub types: HashMap<BlockId, BlockType>,
 }
 
 
 
 impl ChunkMods {
     pub fn new() -> Self {
-        Self { mined: HashSet::new(), placed: HashSet::new() }
+        Self::default()
+    }
+
+    // nothing left to save, the chunk file can go
+    pub fn is_empty(&self) -> bool {
+        self.mined.is_empty() && self.placed.is_empty() && self.entities.is_empty() && self.types.is_empty()
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum BlockEntity {
+    // text on one side of the block, see sign.rs for the sides
+    Sign { text: String, facing: u8 },
+    // one end of a teleporter pair, linked to the other block on the same channel, see teleporter.rs
+    Teleporter { channel: String },
+}
+
+// the world the simulation edits. anything reading it off the main thread (meshers, saves) reads a snapshot
+// from publish() instead. a copy is cheap: the terrain and each chunk's edits are shared between copies until
+// one of them writes to a chunk (Arc::make_mut), so a snapshot costs a map of pointers
 #[derive(Clone)] 
 pub struct PlanetData {
-    pub chunks: HashMap<ChunkKey, ChunkMods>, 
+    pub chunks: HashMap<ChunkKey, Arc<ChunkMods>>, 
     pub resolution: u32,
+    pub seed: u32,
     pub has_core: bool,
-    pub terrain: crate::noise::PlanetTerrain,
+    pub terrain: Arc<PlanetTerrain>,
+
+    // chunks edited since the last save
+    pub dirty_chunks: HashSet<ChunkKey>,
+    // set when every chunk on disk is stale (e.g. after a resize)
+    pub needs_full_save: bool,
+    // bumped whenever a block entity changes, so the renderer knows to rebuild signs
+    pub entity_revision: u32,
+    // sunlight worked out for meshing, see lighting.rs
+    pub light_cache: LightCache,
 }
 
 impl PlanetData {
     pub fn new(resolution: u32) -> Self {
+        Self::with_seed(resolution, DEFAULT_SEED)
+    }
+
+    pub fn with_seed(resolution: u32, seed: u32) -> Self {
         println!("Generating Terrain Noise Map for res {}...", resolution);
-        let terrain = PlanetTerrain::new(resolution); // calculate once
+        let terrain = Arc::new(PlanetTerrain::new(resolution, seed)); // calculate once
         println!("Terrain Generation Complete.");
         
         Self {
             chunks: HashMap::new(),
             resolution,
+            seed,
             has_core: true,
             terrain, // <--- Store it
+            dirty_chunks: HashSet::new(),
+            needs_full_save: false,
+            entity_revision: 0,
+            light_cache: LightCache::default(),
         }
     }
 
@@ -104,10 +247,18 @@ pub fn resize(&mut self, increase: bool) {
         
 
         self.chunks.clear();
+        self.dirty_chunks.clear();
+        self.light_cache.clear();
+        self.needs_full_save = true;
         
         // regenerate noise map for new resolution
         println!("Regenerating Terrain for new res {}...", self.resolution);
-        self.terrain = PlanetTerrain::new(self.resolution); 
+        self.terrain = Arc::new(PlanetTerrain::new(self.resolution, self.seed)); 
+    }
+
+    // the world as it is now, frozen, for readers on other threads. the simulation goes on editing self
+    pub fn publish(&self) -> Arc<PlanetData> {
+        Arc::new(self.clone())
     }
 
     fn get_chunk_key(id: BlockId) -> ChunkKey {
@@ -118,15 +269,20 @@ pub fn resize(&mut self, increase: bool) {
         }
     }
 
-    pub fn add_block(&mut self, id: BlockId) {
+    pub fn add_block(&mut self, id: BlockId, block: BlockType) {
+        let natural = self.natural_type(id);
         let key = Self::get_chunk_key(id);
-        let mods = self.chunks.entry(key).or_insert_with(ChunkMods::new);
+        self.dirty_chunks.insert(key);
+        let mods = Arc::make_mut(self.chunks.entry(key).or_default());
         
         if mods.mined.contains(&id) {
             mods.mined.remove(&id);
         } else {
             mods.placed.insert(id);
         }
+        // a mined block put back as it was is just ground again
+        if block == natural { mods.types.remove(&id); } else { mods.types.insert(id, block); }
+        self.light_cache.invalidate(id);
     }
 
 pub fn remove_block(&mut self, id: BlockId) {
@@ -136,7 +292,8 @@ pub fn remove_block(&mut self, id: BlockId) {
         }
         
         let key = Self::get_chunk_key(id);
-        let mods = self.chunks.entry(key).or_insert_with(ChunkMods::new);
+        self.dirty_chunks.insert(key);
+        let mods = Arc::make_mut(self.chunks.entry(key).or_default());
 
         if mods.placed.contains(&id) {
             mods.placed.remove(&id);
@@ -145,6 +302,79 @@ pub fn remove_block(&mut self, id: BlockId) {
                 mods.mined.insert(id);
             }
         }
+        mods.types.remove(&id);
+        self.light_cache.invalidate(id);
+        // whatever was attached to the block goes with it
+        if mods.entities.remove(&id).is_some() {
+            self.entity_revision += 1;
+        }
+    }
+
+    // takes out many blocks at once, copying each chunk's edits and marking it dirty once rather than per block.
+    // returns the ones that were there to remove and what they were
+    pub fn remove_blocks(&mut self, ids: impl IntoIterator<Item = BlockId>) -> Vec<(BlockId, BlockType)> {
+        let mut by_chunk: HashMap<ChunkKey, Vec<(BlockId, BlockType)>> = HashMap::new();
+        for id in ids {
+            if (self.has_core && id.layer < 6) || !self.exists(id) { continue; }
+            by_chunk.entry(Self::get_chunk_key(id)).or_default().push((id, self.block_type(id)));
+        }
+        let mut removed = Vec::new();
+        for (key, blocks) in by_chunk {
+            self.dirty_chunks.insert(key);
+            let mods = Arc::make_mut(self.chunks.entry(key).or_default());
+            for &(id, _) in &blocks {
+                if !mods.placed.remove(&id) && id.layer < self.resolution {
+                    mods.mined.insert(id);
+                }
+                mods.types.remove(&id);
+                if mods.entities.remove(&id).is_some() {
+                    self.entity_revision += 1;
+                }
+            }
+            for &(id, _) in &blocks {
+                self.light_cache.invalidate(id);
+            }
+            removed.extend(blocks);
+        }
+        removed
+    }
+
+    // what the block at `id` is made of, or would be if it were there
+    pub fn block_type(&self, id: BlockId) -> BlockType {
+        self.chunks.get(&Self::get_chunk_key(id))
+            .and_then(|mods| mods.types.get(&id))
+            .copied()
+            .unwrap_or_else(|| self.natural_type(id))
+    }
+
+    fn natural_type(&self, id: BlockId) -> BlockType {
+        if self.has_core && id.layer < 6 {
+            BlockType::Rock
+        } else if id.layer == self.terrain.get_height(id.face, id.u, id.v) {
+            BlockType::Grass
+        } else {
+            BlockType::Dirt
+        }
+    }
+
+    pub fn entity(&self, id: BlockId) -> Option<&BlockEntity> {
+        self.chunks.get(&Self::get_chunk_key(id))?.entities.get(&id)
+    }
+
+    // attaches `entity` to a block, None takes it off again
+    pub fn set_entity(&mut self, id: BlockId, entity: Option<BlockEntity>) {
+        let key = Self::get_chunk_key(id);
+        self.dirty_chunks.insert(key);
+        let mods = Arc::make_mut(self.chunks.entry(key).or_default());
+        match entity {
+            Some(entity) => { mods.entities.insert(id, entity); },
+            None => { mods.entities.remove(&id); },
+        }
+        self.entity_revision += 1;
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = (&BlockId, &BlockEntity)> {
+        self.chunks.values().flat_map(|mods| mods.entities.iter())
     }
     
     pub fn exists(&self, id: BlockId) -> bool {
@@ -164,8 +394,22 @@ pub fn remove_block(&mut self, id: BlockId) {
 }
 
 
+// --- DEPTH ---
+// the depth buffer is reverse-z: near is 1 and far 0. floats are densest around 0, so flipping puts that
+// precision out at range, where lod terrain, skirts and the planet guide would otherwise z-fight. every camera
+// projection goes through reverse_z; the shadow map is orthographic, so it keeps near at 0
+pub const NEAR_DEPTH: f32 = 1.0;
+pub const FAR_DEPTH: f32 = 0.0;
+
+pub fn reverse_z(proj: glam::Mat4) -> glam::Mat4 {
+    // depth' = w - depth
+    let flip = glam::Mat4::from_cols(glam::Vec4::X, glam::Vec4::Y, glam::Vec4::new(0.0, 0.0, -1.0, 0.0), glam::Vec4::new(0.0, 0.0, 1.0, 1.0));
+    flip * proj
+}
+
 // --- FRUSTUM CULLING HELPER ---
 
+#[derive(Clone, Copy)]
 pub struct Frustum {
     planes: [glam::Vec4; 6],
 }
@@ -182,8 +426,8 @@ impl Frustum {
             r3 - r0, // Right
             r3 + r1, // Bottom
             r3 - r1, // Top
-            r3 + r2, // Near
-            r3 - r2, // Far
+            r2,      // depth 0: far, or near for the shadow map
+            r3 - r2, // depth 1
         ];
 
         // normalize planes
@@ -206,4 +450,47 @@ impl Frustum {
         }
         true
     }
+
+    // the same for a box: its reach towards each plane is its extents projected onto the plane's normal
+    pub fn intersects_obb(&self, obb: &Obb) -> bool {
+        for plane in &self.planes {
+            let normal = plane.truncate();
+            let dist = normal.dot(obb.center) + plane.w;
+            let reach = obb.axes.iter().zip(obb.extent.to_array()).map(|(axis, e)| e * normal.dot(*axis).abs()).sum::<f32>();
+            if dist < -reach {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// a box turned to fit what it bounds: the middle, three unit axes and the half size along each
+#[derive(Clone, Copy, Debug)]
+pub struct Obb {
+    pub center: glam::Vec3,
+    pub axes: [glam::Vec3; 3],
+    pub extent: glam::Vec3,
+}
+
+impl Obb {
+    // the smallest box along `axes`, which have to be orthonormal, around `points`
+    pub fn fit(axes: [glam::Vec3; 3], points: impl IntoIterator<Item = glam::Vec3>) -> Self {
+        let (mut min, mut max) = (glam::Vec3::splat(f32::MAX), glam::Vec3::splat(f32::MIN));
+        for p in points {
+            let local = glam::Vec3::new(p.dot(axes[0]), p.dot(axes[1]), p.dot(axes[2]));
+            min = min.min(local);
+            max = max.max(local);
+        }
+        if min.x > max.x { return Self { center: glam::Vec3::ZERO, axes, extent: glam::Vec3::ZERO }; }
+        let mid = (min + max) * 0.5;
+        Self { center: axes[0] * mid.x + axes[1] * mid.y + axes[2] * mid.z, axes, extent: (max - min) * 0.5 }
+    }
+
+    pub fn corners(&self) -> [glam::Vec3; 8] {
+        std::array::from_fn(|i| {
+            let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+            self.center + self.axes[0] * self.extent.x * sign(1) + self.axes[1] * self.extent.y * sign(2) + self.axes[2] * self.extent.z * sign(4)
+        })
+    }
 }
\ No newline at end of file
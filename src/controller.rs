@@ -1,35 +1,270 @@
 //engine controller
 
-use glam::{Vec3, Mat4, Vec2};
+use std::ops::Range;
+use glam::{Vec3, Mat4, Vec2, Quat};
 use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
 use winit::keyboard::{PhysicalKey, KeyCode};
 use crate::common::*;
 use crate::gen::CoordSystem;
 use crate::entity::Player;
-use crate::physics::Physics;
+use crate::physics::{Physics, Rope};
+use crate::settings::Settings;
+use crate::shake::{self, CameraShake};
+use crate::health::{self, DamageSource};
+use crate::ecs::Entity;
+use crate::mob::Mobs;
+use crate::oxygen::{self, SUFFOCATION_DAMAGE};
+use crate::temperature::EXPOSURE_DAMAGE;
+use crate::touch::TouchControls;
+use crate::gamepad::{self, Gamepad, PadAction};
+use crate::vehicle::DriveInput;
+use crate::inventory::HOTBAR_SLOTS;
+use crate::bindings::{Action, Bindings};
+
+// every key the controller and main loop react to, listed in the F1 overlay with a language key for the action.
+// the rebindable ones show whatever bindings.cfg has them on
+pub const KEYBINDS: &[(Keys, &str)] = &[
+    (Keys::Bound(&[Action::Forward, Action::Left, Action::Back, Action::Right]), "key.move"),
+    (Keys::Bound(&[Action::Jump]), "key.jump"),
+    (Keys::Bound(&[Action::Sprint]), "key.sprint"),
+    (Keys::Bound(&[Action::Fly]), "key.fly"),
+    (Keys::Bound(&[Action::Camera]), "key.camera"),
+    (Keys::Fixed("Left click"), "key.mine"),
+    (Keys::Fixed("Right click"), "key.place"),
+    (Keys::Fixed("Mouse wheel"), "key.zoom"),
+    (Keys::Fixed("Shift + wheel"), "key.brush"),
+    (Keys::Fixed("1 - 9"), "key.block"),
+    (Keys::Fixed("Click + drag"), "key.paint"),
+    (Keys::Fixed("[ / ]"), "key.resize"),
+    (Keys::Fixed("`"), "key.console"),
+    (Keys::Fixed("Up/Down, Tab"), "key.console_input"),
+    (Keys::Fixed("Drag, Ctrl+C/V"), "key.console_clipboard"),
+    (Keys::Bound(&[Action::Editor]), "key.editor"),
+    (Keys::Bound(&[Action::Select]), "key.select"),
+    (Keys::Bound(&[Action::PrefabMenu]), "key.prefab_menu"),
+    (Keys::Bound(&[Action::Rotate]), "key.rotate"),
+    (Keys::Bound(&[Action::Blueprint]), "key.blueprint"),
+    (Keys::Bound(&[Action::Ride]), "key.ride"),
+    (Keys::Bound(&[Action::Grapple]), "key.grapple"),
+    (Keys::Bound(&[Action::Glide]), "key.glide"),
+    (Keys::Fixed("Touch"), "key.touch"),
+    (Keys::Fixed("Gamepad"), "key.gamepad"),
+    (Keys::Bound(&[Action::Help]), "key.help"),
+    (Keys::Fixed("Esc"), "key.settings"),
+    (Keys::Bound(&[Action::Wireframe]), "key.wireframe"),
+    (Keys::Bound(&[Action::Collisions]), "key.collisions"),
+    (Keys::Bound(&[Action::FreezeCulling]), "key.freeze_culling"),
+];
+
+pub enum Keys {
+    Fixed(&'static str),
+    Bound(&'static [Action]),
+}
+
+// widest square brush for orbit-mode editing, and for the top-down editor
+pub const MAX_BRUSH_SIZE: u32 = 9;
+pub const MAX_EDITOR_BRUSH: u32 = 33;
+
+// editor camera limits, heights in blocks above the surface
+const EDITOR_MIN_HEIGHT: f32 = 8.0;
+const EDITOR_MAX_HEIGHT: f32 = 1500.0;
+const EDITOR_START_HEIGHT: f32 = 60.0;
+// pan speed in screen heights per second, so panning feels the same at any zoom
+const EDITOR_PAN_SPEED: f32 = 0.8;
+
+// top-down "god mode" camera hovering over a point on the surface, with the face grid pointing up the screen
+pub struct EditorCam {
+    // unit direction from the planet center to the point under the camera
+    pub focus: Vec3,
+    pub height: f32,
+    // surface radius under the focus and the face grid axes there, refreshed every frame
+    ground: f32,
+    east: Vec3,
+    north: Vec3,
+    // view to go back to when leaving the editor
+    was_first_person: bool,
+}
+
+impl EditorCam {
+    fn new(focus: Vec3, was_first_person: bool) -> Self {
+        Self { focus, height: EDITOR_START_HEIGHT, ground: 0.0, east: Vec3::X, north: Vec3::Z, was_first_person }
+    }
+
+    fn refresh(&mut self, planet: &PlanetData) {
+        let res = planet.resolution;
+        let probe = self.focus * CoordSystem::get_layer_radius(res / 2, res);
+        let Some((id, _)) = CoordSystem::get_local_coords(probe, res) else { return };
+
+        self.ground = CoordSystem::get_layer_radius(planet.terrain.get_height(id.face, id.u, id.v), res);
+        let here = CoordSystem::get_direction(id.face, id.u, id.v, res);
+        let flat = |d: Vec3| (d - self.focus * d.dot(self.focus)).normalize_or_zero();
+        let east = flat(CoordSystem::get_direction(id.face, id.u + 1, id.v, res) - here);
+        let north = flat(CoordSystem::get_direction(id.face, id.u, id.v + 1, res) - here);
+        if east != Vec3::ZERO && north != Vec3::ZERO {
+            (self.east, self.north) = (east, north);
+        }
+    }
+
+    fn eye(&self) -> Vec3 {
+        self.focus * (self.ground + self.height)
+    }
+
+    pub fn ground_point(&self) -> Vec3 {
+        self.focus * self.ground
+    }
+}
+
+// --- CLIP PLANES ---
+// the main camera's near plane moves out with height above the ground, so orbit views keep their depth
+// precision while blocks at your feet don't clip, and its far plane sits just past the planet's horizon
+const MIN_NEAR: f32 = 0.1;
+const MAX_NEAR: f32 = 50.0;
+const NEAR_PER_ALTITUDE: f32 = 0.01;
+// past the horizon of the ground sphere, for mountains showing over it, as a share of its radius
+const FAR_MARGIN: f32 = 0.25;
+const MIN_FAR: f32 = 1000.0;
+
+// near and far plane for a camera at `eye`
+fn clip_planes(eye: Vec3, planet: &PlanetData) -> (f32, f32) {
+    let res = planet.resolution;
+    let probe = eye.normalize_or_zero() * CoordSystem::get_layer_radius(res / 2, res);
+    let ground = match CoordSystem::get_local_coords(probe, res) {
+        Some((id, _)) => CoordSystem::get_layer_radius(planet.terrain.get_height(id.face, id.u, id.v), res),
+        None => CoordSystem::get_layer_radius(res / 2, res),
+    };
+    let dist = eye.length();
+    let near = ((dist - ground) * NEAR_PER_ALTITUDE).clamp(MIN_NEAR, MAX_NEAR);
+    let horizon = (dist * dist - ground * ground).max(0.0).sqrt();
+    (near, (horizon + ground * FAR_MARGIN).max(MIN_FAR))
+}
+
+// u and v cells a brush covers on its face
+pub type BrushArea = (Range<u32>, Range<u32>);
+
+// chase camera limits: distance behind and height above a ridden vehicle at rest, extra distance
+// and fov per block/s of speed, and how quickly it catches up (per second)
+const CHASE_DISTANCE: f32 = 6.0;
+const CHASE_HEIGHT: f32 = 2.5;
+const CHASE_PULL: f32 = 0.1;
+const CHASE_FOV: (f32, f32) = (70.0, 95.0);
+const CHASE_FOV_PER_SPEED: f32 = 0.8;
+const CHASE_STIFFNESS: f32 = 6.0;
+// how far ahead of the vehicle it looks
+const CHASE_LOOK_AHEAD: f32 = 4.0;
+
+// third person camera trailing a ridden vehicle, lagging a little so speed and turns can be felt
+pub struct ChaseCam {
+    pub eye: Vec3,
+    target: Vec3,
+    fov: f32,
+}
+
+impl ChaseCam {
+    pub fn new(pos: Vec3, rotation: Quat) -> Self {
+        let (eye, target) = Self::goal(pos, rotation, 0.0);
+        Self { eye, target, fov: CHASE_FOV.0 }
+    }
+
+    fn goal(pos: Vec3, rotation: Quat, speed: f32) -> (Vec3, Vec3) {
+        let up = Physics::get_up_vector(pos);
+        let forward = rotation * Vec3::NEG_Z;
+        let eye = pos - forward * (CHASE_DISTANCE + speed * CHASE_PULL) + up * CHASE_HEIGHT;
+        (eye, pos + forward * CHASE_LOOK_AHEAD + up)
+    }
+
+    pub fn follow(&mut self, dt: f32, pos: Vec3, rotation: Quat, velocity: Vec3) {
+        let speed = velocity.length();
+        let (eye, target) = Self::goal(pos, rotation, speed);
+        let t = 1.0 - (-CHASE_STIFFNESS * dt).exp();
+        self.eye = self.eye.lerp(eye, t);
+        // the target stays locked on so the vehicle never drifts out of frame
+        self.target = target;
+        self.fov = (CHASE_FOV.0 + speed * CHASE_FOV_PER_SPEED).min(CHASE_FOV.1);
+    }
+}
+
+// what the aim lands on first, see Controller::pick
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Block(BlockId),
+    Mob(Entity),
+}
+
+impl Target {
+    pub fn block(self) -> Option<BlockId> {
+        match self {
+            Target::Block(id) => Some(id),
+            Target::Mob(_) => None,
+        }
+    }
+}
+
+// a click-drag edit in orbit mode. every block the cursor passes over gets the brush,
+// all on the layer the stroke started on so holding still doesn't stack or dig a shaft
+pub struct Stroke {
+    pub placed: bool,
+    pub layer: u32,
+    // (face, u, v) last painted, so each cell is only edited once while the cursor rests on it
+    last: Option<(u8, u32, u32)>,
+}
+
+// how far the grappling hook reaches
+const GRAPPLE_RANGE: f32 = 40.0;
+
+// acceleration never multiplies a single mouse event by more than this
+const MAX_MOUSE_GAIN: f32 = 4.0;
+
+// stereo look: how far the head turns from the body before the body follows, in radians
+const HEAD_YAW_LIMIT: f32 = 1.0;
+// share of the head's lead the body takes per second while walking
+const HEAD_RECENTER: f32 = 2.5;
 
 pub struct Controller {
     
     pub cam_dist: f32,
+    #[allow(dead_code)]
     pub cam_yaw: f32,
+    #[allow(dead_code)]
     pub cam_pitch: f32,
     
     // input State
     pub mouse_pos: Vec2,
     pub mouse_delta: (f32, f32),
+    // what actually turned the camera last frame, mouse smoothing blends towards the new delta from here
+    smoothed_delta: (f32, f32),
     pub is_orbiting: bool,
     pub is_wireframe: bool,
     pub show_collisions: bool,
     pub fly_mode: bool, 
     pub sprint: bool,
     pub freeze_culling: bool, 
+    pub show_help: bool,
     pub cursor_id: Option<BlockId>,
+    pub shake: CameraShake,
+    // orbit-mode editing
+    pub brush_size: u32,
+    pub stroke: Option<Stroke>,
+    shift: bool,
+    // hotbar slot placed blocks are taken from
+    pub slot: usize,
+    // near and far plane of the view, see clip_planes
+    clip: (f32, f32),
+    pub editor: Option<EditorCam>,
+    // set while riding a vehicle, the player stands still and this camera follows the vehicle
+    pub chase: Option<ChaseCam>,
+    pub touch: TouchControls,
+    pub gamepad: Gamepad,
+    pub bindings: Bindings,
+    // head orientation relative to the body. in stereo mode mouse and gamepad look turn it, see turn_head
+    pub head_pose: Quat,
+    head_yaw: f32,
 
     
     pub first_person: bool,
+    // first person field of view in degrees, from the settings
+    pub fov: f32,
     
-    
-    keys: [bool; 5], // W, A, S, D, Space
+    keys: [bool; 5], // forward, left, back, right, jump
 }
 
 impl Controller {
@@ -40,44 +275,171 @@ impl Controller {
             cam_pitch: 0.5,
             mouse_pos: Vec2::ZERO,
             mouse_delta: (0.0, 0.0),
+            smoothed_delta: (0.0, 0.0),
             is_orbiting: false,
             cursor_id: None, 
+            shake: CameraShake::new(),
+            brush_size: 1,
+            stroke: None,
+            shift: false,
+            slot: 0,
+            clip: (MIN_NEAR, MIN_FAR),
+            editor: None,
+            chase: None,
+            touch: TouchControls::new(),
+            gamepad: Gamepad::new(),
+            bindings: Bindings::default(),
+            head_pose: Quat::IDENTITY,
+            head_yaw: 0.0,
             is_wireframe: false,
             show_collisions: false,
             fly_mode: false,
             freeze_culling: false,
+            show_help: false,
             sprint: false,
             first_person: true,
+            fov: 80.0,
             keys: [false; 5],
         }
     }
 
-    pub fn update_player(&mut self, player: &mut Player, planet: &PlanetData, dt: f32) {
+    // stereo look: the mouse or stick turns the head, the body only takes what goes past HEAD_YAW_LIMIT.
+    // walking hands the head's lead over to the body bit by bit, so you end up going where you look.
+    // returns the horizontal delta left for the body
+    fn turn_head(&mut self, dx: f32, sens: f32, moving: bool, dt: f32) -> f32 {
+        if sens <= 0.0 { return dx; }
+        let wanted = self.head_yaw - dx * sens;
+        let mut yaw = wanted.clamp(-HEAD_YAW_LIMIT, HEAD_YAW_LIMIT);
+        if moving { yaw *= (-HEAD_RECENTER * dt).exp(); }
+        self.head_yaw = yaw;
+        self.head_pose = Quat::from_rotation_y(yaw);
+        // the body turns by whatever the head didn't, yaw_delta in Player::update is -dx * sens
+        -(wanted - yaw) / sens
+    }
+
+    pub fn update_player(&mut self, player: &mut Player, planet: &PlanetData, dt: f32, settings: &Settings) {
         
 
         // read inputs regardless of the view mode.
        
         
-        let mut input = Vec3::ZERO;
-        if self.keys[0] { input.z -= 1.0; } // W
-        if self.keys[1] { input.x -= 1.0; } // A
-        if self.keys[2] { input.z += 1.0; } // S
-        if self.keys[3] { input.x += 1.0; } // D
-        let jump = self.keys[4]; // space
+        let mut input = self.move_input();
+        let jump = self.keys[4] || self.gamepad.jump;
+
+        // smoothing is the share of last frame's motion kept after 1/60 s, so it feels the same at any frame rate
+        let keep = if dt > 0.0 { settings.mouse_smoothing.powf(dt * 60.0) } else { 0.0 };
+        self.smoothed_delta = (
+            self.smoothed_delta.0 * keep + self.mouse_delta.0 * (1.0 - keep),
+            self.smoothed_delta.1 * keep + self.mouse_delta.1 * (1.0 - keep),
+        );
+        let mut rotation_delta = if self.first_person { self.smoothed_delta } else { (0.0, 0.0) };
+        if settings.stereo && self.first_person && self.chase.is_none() && self.editor.is_none() {
+            rotation_delta.0 = self.turn_head(rotation_delta.0, player.mouse_sens, input.length() > 0.01, dt);
+        } else {
+            self.head_yaw = 0.0;
+            self.head_pose = Quat::IDENTITY;
+        }
 
-        let rotation_delta = if self.first_person { self.mouse_delta } else { (0.0, 0.0) };
+        // the editor camera takes the movement keys for panning, the player just stands there
+        if let Some(editor) = self.editor.as_mut() {
+            editor.refresh(planet);
+            let pan = (editor.east * input.x - editor.north * input.z) * editor.height * EDITOR_PAN_SPEED * dt;
+            editor.focus = (editor.focus * editor.ground.max(1.0) + pan).normalize();
+            input = Vec3::ZERO;
+        }
+        let jump = jump && self.editor.is_none();
+        // out of stamina: no sprinting or jumping until it's back
+        let tiring = settings.stamina && !player.spectator && !self.fly_mode && self.chase.is_none();
+        let rested = !tiring || player.stamina.can_exert();
+        let (jump, sprint) = (jump && rested, (self.sprint || self.gamepad.sprint) && rested);
 
         
 
-        player.update(dt, planet, input, jump, rotation_delta, self.fly_mode, self.sprint);
+        let up = Physics::get_up_vector(player.position);
+        let (was_grounded, fall_speed) = (player.grounded, -player.velocity.dot(up));
+
+        player.view_bobbing = settings.view_bobbing && !settings.reduce_motion;
+        // a rider is carried by the vehicle, see vehicle::seat
+        if self.chase.is_none() {
+            player.update(dt, planet, input, jump, rotation_delta, self.fly_mode, sprint);
+        }
+
+        // --- STAMINA ---
+        if tiring {
+            let moving = input.length() > 0.01;
+            let jumped = jump && was_grounded && !player.grounded;
+            player.stamina.update(dt, sprint && moving, jumped, !moving && player.grounded);
+        } else {
+            player.stamina.refill();
+        }
+
+        if !was_grounded && player.grounded && fall_speed > shake::LANDING_MIN_SPEED {
+            self.shake.add_trauma((fall_speed - shake::LANDING_MIN_SPEED) * shake::LANDING_TRAUMA);
+        }
+        let intensity = if settings.reduce_motion { 0.0 } else { settings.shake_intensity };
+        self.shake.update(dt, intensity);
+
+        // --- DAMAGE ---
+        let exposed = player.temperature.update(dt, player.position, planet);
+        if !player.spectator && !self.fly_mode {
+            if !was_grounded && player.grounded {
+                player.health.hurt(DamageSource::Fall, health::fall_damage(fall_speed));
+            }
+            if let Some(amount) = health::void_damage(player.position, planet.resolution) {
+                player.health.hurt(DamageSource::Void, amount);
+            }
+            if player.oxygen.update(dt, oxygen::has_air(player.position, planet.resolution)) {
+                player.health.hurt(DamageSource::Suffocation, SUFFOCATION_DAMAGE);
+            }
+            if exposed {
+                let source = if player.temperature.is_cold() { DamageSource::Cold } else { DamageSource::Heat };
+                player.health.hurt(source, EXPOSURE_DAMAGE);
+            }
+        } else {
+            player.oxygen.refill();
+        }
+        player.health.update(dt);
 
+        self.clip = clip_planes(self.get_camera_pos(player), planet);
         
         // reset delta after use
         self.mouse_delta = (0.0, 0.0);
     }
 
+    // wasd plus the touch and gamepad sticks, x is right and z is back
+    fn move_input(&self) -> Vec3 {
+        let mut input = Vec3::ZERO;
+        if self.keys[0] { input.z -= 1.0; } // forward
+        if self.keys[1] { input.x -= 1.0; } // left
+        if self.keys[2] { input.z += 1.0; } // back
+        if self.keys[3] { input.x += 1.0; } // right
+        let stick = self.touch.stick_input();
+        input.x += stick.x;
+        input.z += stick.y;
+        input.x += self.gamepad.stick.x;
+        input.z += self.gamepad.stick.y;
+        input
+    }
+
+    // the movement keys as vehicle controls: w/s throttle, a/d steer, sprint boosts and jump hops
+    pub fn drive_input(&self) -> DriveInput {
+        // the editor camera has the keys
+        if self.editor.is_some() { return DriveInput::default(); }
+        let input = self.move_input();
+        DriveInput { throttle: -input.z, steer: input.x, boost: self.sprint || self.gamepad.sprint, hop: self.keys[4] || self.gamepad.jump }
+    }
+
+    // where terrain detail is centred: the player, or the spot the editor camera looks at
+    pub fn view_center(&self, player: &Player) -> Vec3 {
+        self.editor.as_ref().map_or(player.position, |e| e.ground_point())
+    }
+
     pub fn get_camera_pos(&self, player: &Player) -> Vec3 {
-        if self.first_person {
+        if let Some(editor) = &self.editor {
+            editor.eye()
+        } else if let Some(chase) = &self.chase {
+            chase.eye
+        } else if self.first_person {
             // first person: Camera is at player position + eye height
             player.position + (Physics::get_up_vector(player.position) * 1.6)
         } else {
@@ -88,12 +450,36 @@ impl Controller {
     }
 
 
-    pub fn process_mouse_motion(&mut self, delta: (f64, f64)) {
+    pub fn process_mouse_motion(&mut self, delta: (f64, f64), settings: &Settings) {
         if self.first_person {
+            let (dx, dy) = (delta.0 as f32, delta.1 as f32);
+            // acceleration: fast flicks turn further than the same distance moved slowly
+            let gain = (1.0 + settings.mouse_acceleration * (dx * dx + dy * dy).sqrt()).min(MAX_MOUSE_GAIN);
+            let invert = if settings.invert_y { -1.0 } else { 1.0 };
+
             // accumulate raw mouse delta
-            self.mouse_delta.0 += delta.0 as f32;
-            self.mouse_delta.1 += delta.1 as f32;
+            self.mouse_delta.0 += dx * gain * settings.mouse_sensitivity_x;
+            self.mouse_delta.1 += dy * gain * settings.mouse_sensitivity_y * invert;
+        }
+    }
+
+
+    // polls the controller: the right stick turns like the mouse and the bumpers step through the hotbar.
+    // returns the mine and place presses for the main loop to carry out at the cursor
+    pub fn update_gamepad(&mut self, dt: f32, settings: &Settings) -> Vec<PadAction> {
+        let mut actions = self.gamepad.update(settings.gamepad_deadzone);
+        if self.first_person {
+            let invert = if settings.invert_y { -1.0 } else { 1.0 };
+            let look = self.gamepad.look * gamepad::LOOK_SPEED * settings.gamepad_sensitivity * dt;
+            self.mouse_delta.0 += look.x;
+            self.mouse_delta.1 += look.y * invert;
         }
+        actions.retain(|action| match action {
+            PadAction::NextSlot => { self.slot = (self.slot + 1) % HOTBAR_SLOTS; false },
+            PadAction::PrevSlot => { self.slot = (self.slot + HOTBAR_SLOTS - 1) % HOTBAR_SLOTS; false },
+            _ => true,
+        });
+        actions
     }
 
 
@@ -106,34 +492,63 @@ impl Controller {
                 self.mouse_pos = new_pos;
                 self.mouse_delta = (d.x, d.y);                
             }
-            WindowEvent::MouseInput { state, button, .. } => {
-                if *button == MouseButton::Middle {
-                    self.is_orbiting = *state == ElementState::Pressed;
-                }
+            WindowEvent::MouseInput { state, button, .. } if *button == MouseButton::Middle => {
+                self.is_orbiting = *state == ElementState::Pressed;
             }
-            WindowEvent::MouseWheel { delta, .. } => {
-                if !self.first_person {
-                    let y = match delta {
-                        MouseScrollDelta::LineDelta(_, y) => *y,
-                        MouseScrollDelta::PixelDelta(p) => p.y as f32 * 0.01,
-                    };
-                    
-                    self.cam_dist = (self.cam_dist - y * 50.0).clamp(10.0, 10000.0);
+            WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left | MouseButton::Right, .. } => {
+                self.stroke = None;
+            }
+            // in first person the wheel runs through the hotbar
+            WindowEvent::MouseWheel { delta, .. } if self.first_person => {
+                let y = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(p) => p.y as f32 * 0.01,
+                };
+                if y == 0.0 { return false; }
+                self.slot = (self.slot as i32 - y.signum() as i32).rem_euclid(HOTBAR_SLOTS as i32) as usize;
+                return true;
+            }
+            WindowEvent::MouseWheel { delta, .. } if !self.first_person => {
+                let y = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(p) => p.y as f32 * 0.01,
+                };
+                if self.shift {
+                    let step = y.signum() as i32;
+                    let max = if self.editor.is_some() { MAX_EDITOR_BRUSH } else { MAX_BRUSH_SIZE };
+                    self.brush_size = (self.brush_size as i32 + step).clamp(1, max as i32) as u32;
+                    return true;
+                }
+                if let Some(editor) = self.editor.as_mut() {
+                    editor.height = (editor.height * (1.0 - y * 0.1)).clamp(EDITOR_MIN_HEIGHT, EDITOR_MAX_HEIGHT);
                     return true;
                 }
+                
+                self.cam_dist = (self.cam_dist - y * 50.0).clamp(10.0, 10000.0);
+                return true;
             }
             WindowEvent::KeyboardInput { event, .. } => {
                 let pressed = event.state == ElementState::Pressed;
-                match event.physical_key {
-                    PhysicalKey::Code(KeyCode::KeyW) => self.keys[0] = pressed,
-                    PhysicalKey::Code(KeyCode::KeyA) => self.keys[1] = pressed,
-                    PhysicalKey::Code(KeyCode::KeyS) => self.keys[2] = pressed,
-                    PhysicalKey::Code(KeyCode::KeyD) => self.keys[3] = pressed,
-                    PhysicalKey::Code(KeyCode::Space) => self.keys[4] = pressed,
+                let PhysicalKey::Code(code) = event.physical_key else { return false };
+                match code {
+                    KeyCode::ShiftLeft | KeyCode::ShiftRight => self.shift = pressed,
+                    KeyCode::Digit1 | KeyCode::Digit2 | KeyCode::Digit3 | KeyCode::Digit4 | KeyCode::Digit5
+                        | KeyCode::Digit6 | KeyCode::Digit7 | KeyCode::Digit8 | KeyCode::Digit9 if pressed => {
+                        let digits = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9];
+                        if let Some(i) = digits.iter().position(|&d| d == code) { self.slot = i; }
+                    },
+                    _ => {}
+                }
+                match self.bindings.action(code) {
+                    Some(Action::Forward) => self.keys[0] = pressed,
+                    Some(Action::Left) => self.keys[1] = pressed,
+                    Some(Action::Back) => self.keys[2] = pressed,
+                    Some(Action::Right) => self.keys[3] = pressed,
+                    Some(Action::Jump) => self.keys[4] = pressed,
                    
-                    PhysicalKey::Code(KeyCode::ControlLeft) => self.sprint = pressed, 
+                    Some(Action::Sprint) => self.sprint = pressed, 
                     
-                    PhysicalKey::Code(KeyCode::KeyP) if pressed => { 
+                    Some(Action::Wireframe) if pressed => { 
                       
                         if _player.debug_mode {
                             self.is_wireframe = !self.is_wireframe; 
@@ -141,7 +556,7 @@ impl Controller {
                         return true; 
                     }
                    
-                    PhysicalKey::Code(KeyCode::KeyO) if pressed => {
+                    Some(Action::Collisions) if pressed => {
                         if _player.debug_mode {
                             self.show_collisions = !self.show_collisions;
                             println!("Show Collisions: {}", self.show_collisions);
@@ -149,14 +564,42 @@ impl Controller {
                         return true;
                     }
 
-                    PhysicalKey::Code(KeyCode::Quote) if pressed => {
+                    Some(Action::FreezeCulling) if pressed => {
                         if _player.debug_mode {
                             self.freeze_culling = !self.freeze_culling;
                         }
                         return true;
                     }
                     
-                    PhysicalKey::Code(KeyCode::KeyK) if pressed => {
+                    Some(Action::Help) if pressed => {
+                        self.show_help = !self.show_help;
+                        return true;
+                    }
+
+                    Some(Action::Editor) if pressed => {
+                        match self.editor.take() {
+                            Some(editor) => {
+                                self.first_person = editor.was_first_person;
+                                self.brush_size = self.brush_size.min(MAX_BRUSH_SIZE);
+                            }
+                            None => {
+                                // hover over whatever the cursor points at, or straight above the player
+                                let focus = match self.cursor_id {
+                                    Some(id) => CoordSystem::get_block_center(id.face, id.u, id.v, id.layer, _planet.resolution),
+                                    None => _player.position,
+                                };
+                                let mut editor = EditorCam::new(focus.try_normalize().unwrap_or(Vec3::Y), self.first_person);
+                                editor.refresh(_planet);
+                                self.editor = Some(editor);
+                                self.first_person = false;
+                                self.stroke = None;
+                            }
+                        }
+                        return true;
+                    }
+
+                    Some(Action::Camera) if pressed => {
+                        if self.editor.is_some() { return true; }
                         self.first_person = !self.first_person;
                      
                         
@@ -165,7 +608,15 @@ impl Controller {
                         return true;
                     }
                     
-                    PhysicalKey::Code(KeyCode::KeyF) if pressed => {
+                    // opening it in fly mode drops out of fly mode, to deorbit from high up
+                    Some(Action::Glide) if pressed && !event.repeat => {
+                        if self.editor.is_some() || self.chase.is_some() || _player.spectator { return true; }
+                        _player.gliding = !_player.gliding && !_player.grounded;
+                        if _player.gliding { self.fly_mode = false; }
+                        return true;
+                    }
+
+                    Some(Action::Fly) if pressed => {
                         if self.first_person {
                             self.fly_mode = !self.fly_mode;
                             println!("Fly Mode: {}", self.fly_mode);
@@ -180,16 +631,37 @@ impl Controller {
         false
     }
 
-pub fn get_matrix(&self, player: &Player, width: f32, height: f32) -> Mat4 {
+    // what gets rendered, camera shake included
+    pub fn get_matrix(&self, player: &Player, width: f32, height: f32) -> Mat4 {
+        let (proj, view) = self.proj_view(player, width, height);
+        proj * Mat4::from_quat(self.shake.rotation()) * view
+    }
+
+    // left and right eye for side by side stereo. each eye gets half the window and sits half the ipd off centre
+    pub fn eye_matrices(&self, player: &Player, width: f32, height: f32, ipd: f32) -> [Mat4; 2] {
+        let (proj, view) = self.proj_view(player, width / 2.0, height);
+        let view = Mat4::from_quat(self.shake.rotation()) * view;
+        [0.5, -0.5].map(|side| proj * Mat4::from_translation(Vec3::X * (side * ipd)) * view)
+    }
+
+    fn proj_view(&self, player: &Player, width: f32, height: f32) -> (Mat4, Mat4) {
 
         // use 45 degrees in Orbit mode for less distortion.
-        let fov_degrees: f32 = if self.first_person { 80.0 } else { 45.0 };
+        let fov_degrees: f32 = match &self.chase {
+            Some(chase) if self.editor.is_none() => chase.fov,
+            _ if self.first_person => self.fov,
+            _ => 45.0,
+        };
 
-        // far plane increased to 20,000 for massive zoom out
-        let proj = Mat4::perspective_rh(fov_degrees.to_radians(), width / height, 0.1, 20000.0);
+        let (near, far) = self.clip;
+        let proj = reverse_z(Mat4::perspective_rh(fov_degrees.to_radians(), width / height, near, far));
         
-        let view = if self.first_person {
-            player.get_view_matrix()
+        let view = if let Some(editor) = &self.editor {
+            Mat4::look_at_rh(editor.eye(), editor.ground_point(), editor.north)
+        } else if let Some(chase) = &self.chase {
+            Mat4::look_at_rh(chase.eye, chase.target, Physics::get_up_vector(chase.target))
+        } else if self.first_person {
+            Mat4::from_quat(self.head_pose.inverse()) * player.get_view_matrix()
         } else {
           
             let up = Physics::get_up_vector(player.position);
@@ -202,12 +674,49 @@ pub fn get_matrix(&self, player: &Player, width: f32, height: f32) -> Mat4 {
             Mat4::look_at_rh(cam_pos, target, player_forward)
         };
         
-        proj * view
+        (proj, view)
     }
 
-pub fn raycast(&self, player: &Player, planet: &PlanetData, width: f32, height: f32, place_mode: bool) -> Option<(BlockId, f32)> {
-        let mvp = self.get_matrix(player, width, height);
-        let inv = mvp.inverse();
+    // cells covered by the brush around a block, clipped to its face. first person always edits one block
+    pub fn brush_area(&self, center: BlockId, res: u32) -> BrushArea {
+        let size = if self.first_person { 1 } else { self.brush_size };
+        let half = (size - 1) / 2;
+        let span = |c: u32| {
+            let start = c.saturating_sub(half);
+            start..(start + size).min(res)
+        };
+        (span(center.u), span(center.v))
+    }
+
+    pub fn start_stroke(&mut self, placed: bool, layer: u32) {
+        self.stroke = Some(Stroke { placed, layer, last: None });
+    }
+
+    // blocks the active stroke should edit this frame: the brush under the cursor,
+    // skipping cells that already are what the stroke wants
+    pub fn stroke_targets(&mut self, planet: &PlanetData) -> Vec<BlockId> {
+        let (Some(stroke), Some(cursor)) = (self.stroke.as_ref(), self.cursor_id) else { return Vec::new() };
+        let cell = (cursor.face, cursor.u, cursor.v);
+        if stroke.last == Some(cell) { return Vec::new(); }
+        let (placed, layer) = (stroke.placed, stroke.layer);
+
+        let (us, vs) = self.brush_area(cursor, planet.resolution);
+        let mut targets = Vec::new();
+        for u in us {
+            for v in vs.clone() {
+                let id = BlockId { face: cursor.face, layer, u, v };
+                if planet.exists(id) != placed { targets.push(id); }
+            }
+        }
+        if let Some(stroke) = self.stroke.as_mut() { stroke.last = Some(cell); }
+        targets
+    }
+
+    // start and direction of the ray under the crosshair (first person) or the mouse
+    fn aim_ray(&self, player: &Player, width: f32, height: f32) -> (Vec3, Vec3) {
+        // aim with the steady camera, a shake shouldn't make the cursor jump between blocks
+        let (proj, view) = self.proj_view(player, width, height);
+        let inv = (proj * view).inverse();
         
         let (ndc_x, ndc_y) = if self.first_person {
             (0.0, 0.0) 
@@ -215,15 +724,54 @@ pub fn raycast(&self, player: &Player, planet: &PlanetData, width: f32, height:
             ((2.0 * self.mouse_pos.x / width) - 1.0, 1.0 - (2.0 * self.mouse_pos.y / height))
         };
 
-        let start = inv.project_point3(Vec3::new(ndc_x, ndc_y, 0.0));
-        let end = inv.project_point3(Vec3::new(ndc_x, ndc_y, 1.0));
-        let dir = (end - start).normalize();
+        let start = inv.project_point3(Vec3::new(ndc_x, ndc_y, NEAR_DEPTH));
+        let end = inv.project_point3(Vec3::new(ndc_x, ndc_y, FAR_DEPTH));
+        (start, (end - start).normalize())
+    }
+
+    // hooks the first block along the aim within reach, the rope as long as the distance to it
+    pub fn grapple(&self, player: &Player, planet: &PlanetData, width: f32, height: f32) -> Option<Rope> {
+        if self.editor.is_some() || self.chase.is_some() || player.spectator { return None; }
+        let (start, dir) = self.aim_ray(player, width, height);
+        let mut dist = 0.0;
+        while dist < GRAPPLE_RANGE {
+            let p = start + dir * dist;
+            if CoordSystem::pos_to_id(p, planet.resolution).is_some_and(|id| planet.exists(id)) {
+                return Some(Rope { anchor: p, length: p.distance(player.position) });
+            }
+            dist += 0.25;
+        }
+        None
+    }
+
+    // how far along the aim blocks and mobs can be picked
+    fn reach(&self) -> f32 {
+        match &self.editor {
+            Some(editor) => editor.height + 100.0,
+            None if self.first_person => 8.0,
+            None => self.cam_dist + 100.0,
+        }
+    }
+
+    // the first block or mob along the aim, whichever is closer, and how far
+    pub fn pick(&self, player: &Player, planet: &PlanetData, mobs: &Mobs, width: f32, height: f32) -> Option<(Target, f32)> {
+        if self.chase.is_some() && self.editor.is_none() { return None; }
+        let block = self.raycast(player, planet, width, height, false).map(|(id, dist)| (Target::Block(id), dist));
+        let (start, dir) = self.aim_ray(player, width, height);
+        let reach = block.map_or(self.reach(), |(_, dist)| dist);
+        mobs.raycast(start, dir, reach).map(|(i, dist)| (Target::Mob(i), dist)).or(block)
+    }
+
+pub fn raycast(&self, player: &Player, planet: &PlanetData, width: f32, height: f32, place_mode: bool) -> Option<(BlockId, f32)> {
+        // no building from the saddle
+        if self.chase.is_some() && self.editor.is_none() { return None; }
+        let (start, dir) = self.aim_ray(player, width, height);
 
         let mut dist = 0.0;
         let mut last_empty = None;
         
        
-        let reach = if self.first_person { 8.0 } else { self.cam_dist + 100.0 };
+        let reach = self.reach();
         // stop raycast if we hit the absolute math center (radius < 0.5)
         let min_radius = 0.5;
 
@@ -0,0 +1,71 @@
+//debug_view.rs
+
+// a second window looking at the world from another camera, for checking culling and shadow coverage
+// from the outside. it only draws what the main camera drew, so whatever is missing around the player was culled.
+// opened with /debugview sun|map, closed with /debugview off or the window's close button.
+
+use std::sync::Arc;
+use glam::{Mat4, Vec3};
+use crate::common::reverse_z;
+use winit::window::Window;
+use crate::entity::Player;
+use crate::physics::Physics;
+
+// --- CONSTANTS ---
+// half the width of the area the map camera shows, in blocks
+const MAP_EXTENT: f32 = 256.0;
+// map camera height above the player
+const MAP_HEIGHT: f32 = 1000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugCamera {
+    // the shadow map's own view, everything outside it gets no shadows
+    Sun,
+    // straight down on the player, the top of the window is where they face
+    Map,
+}
+
+impl DebugCamera {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "sun" => Some(DebugCamera::Sun),
+            "map" => Some(DebugCamera::Map),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DebugCamera::Sun => "sun",
+            DebugCamera::Map => "map",
+        }
+    }
+}
+
+pub struct DebugView {
+    pub window: Arc<Window>,
+    pub camera: DebugCamera,
+    pub surface: wgpu::Surface<'static>,
+    pub config: wgpu::SurfaceConfiguration,
+    pub depth: wgpu::TextureView,
+    pub global_buf: wgpu::Buffer,
+    pub global_bind: wgpu::BindGroup,
+}
+
+impl DebugView {
+    // the camera for this frame. the sun view is the matrix the shadow pass used
+    pub fn view_proj(&self, player: &Player, light_view_proj: Mat4) -> Mat4 {
+        match self.camera {
+            // the shadow map's matrix has near at 0, the window is drawn reverse-z like the main view
+            DebugCamera::Sun => reverse_z(light_view_proj),
+            DebugCamera::Map => {
+                let up = Physics::get_up_vector(player.position);
+                let forward = player.rotation * Vec3::NEG_Z;
+                let eye = player.position + up * MAP_HEIGHT;
+                let aspect = self.config.width as f32 / self.config.height.max(1) as f32;
+                let proj = reverse_z(Mat4::orthographic_rh(-MAP_EXTENT * aspect, MAP_EXTENT * aspect, -MAP_EXTENT, MAP_EXTENT, 1.0, MAP_HEIGHT * 2.0));
+                proj * Mat4::look_at_rh(eye, player.position, forward)
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//determinism.rs
+
+// --terrain-hash: generates a planet for a seed and resolution, hashes its height map (and with --meshes every
+// chunk mesh too) and prints the digests. run it before and after a change that touches generation, or on two
+// machines, and compare: the same digest means the same terrain, bit for bit.
+
+use crate::common::{ChunkKey, PlanetData, CHUNK_SIZE, DEFAULT_RESOLUTION, DEFAULT_SEED};
+use crate::gen::MeshGen;
+use crate::i18n::tr;
+
+// 64 bit fnv-1a, simple enough to reproduce anywhere
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(FNV_OFFSET)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ *b as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+// every height as little endian u16, face by face, row by row
+pub fn height_digest(planet: &PlanetData) -> u64 {
+    let mut hash = Fnv::new();
+    for h in planet.terrain.heights() {
+        hash.write(&h.to_le_bytes());
+    }
+    hash.0
+}
+
+// every chunk mesh in key order. build_chunk visits blocks in hash set order, so the triangles come out in
+// a different order every run: each triangle is hashed on its own (vertex floats as bits) and the triangle
+// hashes are summed, which compares every mesh as a set of triangles
+pub fn mesh_digest(planet: &PlanetData) -> u64 {
+    let mut hash = Fnv::new();
+    let per_side = planet.resolution.div_ceil(CHUNK_SIZE);
+    for face in 0..6 {
+        for v_idx in 0..per_side {
+            for u_idx in 0..per_side {
+                let (verts, inds) = MeshGen::build_chunk(ChunkKey { face, u_idx, v_idx }, planet);
+                let mut triangles = 0u64;
+                for tri in inds.chunks_exact(3) {
+                    let mut tri_hash = Fnv::new();
+                    for vert in tri.iter().map(|i| &verts[*i as usize]) {
+                        for f in vert.pos.iter().chain(&vert.color).chain(&vert.normal) {
+                            tri_hash.write(&f.to_bits().to_le_bytes());
+                        }
+                    }
+                    triangles = triangles.wrapping_add(tri_hash.0);
+                }
+                hash.write(&((inds.len() / 3) as u32).to_le_bytes());
+                hash.write(&triangles.to_le_bytes());
+            }
+        }
+    }
+    hash.0
+}
+
+// --terrain-hash [--seed N] [--resolution N] [--meshes]
+pub fn run(seed: Option<u32>, resolution: Option<u32>, meshes: bool) {
+    // the same bounds PlanetData::resize keeps to
+    let (seed, resolution) = (seed.unwrap_or(DEFAULT_SEED), resolution.unwrap_or(DEFAULT_RESOLUTION).clamp(8, 16384));
+    let planet = PlanetData::with_seed(resolution, seed);
+    println!("{}", tr!("determinism.heights", seed, resolution, format!("{:016x}", height_digest(&planet))));
+    if meshes {
+        println!("{}", tr!("determinism.meshes", format!("{:016x}", mesh_digest(&planet))));
+    }
+}
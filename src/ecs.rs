@@ -0,0 +1,137 @@
+//ecs.rs
+
+// a small entity-component store. an Entity is a slot index and a generation, so a handle kept past a despawn
+// never finds whatever reused the slot. each component type lives in a Components<T> indexed by slot, and
+// systems are plain functions over the storages they need: a new behaviour is a component and a system, not
+// another arm in a shared update. see mob.rs for the storages mobs are made of. the player isn't on it yet: it's
+// still the Player struct in entity.rs, whose fields the controller, renderer and commands read directly.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    // the slot, shared with whatever had it before
+    pub fn index(self) -> u32 {
+        self.index
+    }
+}
+
+#[derive(Default)]
+pub struct Entities {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    count: usize,
+}
+
+impl Entities {
+    pub fn spawn(&mut self) -> Entity {
+        self.count += 1;
+        match self.free.pop() {
+            Some(index) => {
+                self.alive[index as usize] = true;
+                Entity { index, generation: self.generations[index as usize] }
+            },
+            None => {
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity { index: self.generations.len() as u32 - 1, generation: 0 }
+            },
+        }
+    }
+
+    // false if it was already gone
+    pub fn despawn(&mut self, e: Entity) -> bool {
+        if !self.is_alive(e) { return false; }
+        let i = e.index as usize;
+        self.alive[i] = false;
+        self.generations[i] += 1;
+        self.free.push(e.index);
+        self.count -= 1;
+        true
+    }
+
+    pub fn is_alive(&self, e: Entity) -> bool {
+        self.alive.get(e.index as usize).copied().unwrap_or(false) && self.generations[e.index as usize] == e.generation
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.alive.iter().enumerate().filter(|(_, alive)| **alive)
+            .map(|(i, _)| Entity { index: i as u32, generation: self.generations[i] })
+    }
+
+    pub fn clear(&mut self) {
+        for e in self.iter().collect::<Vec<_>>() {
+            self.despawn(e);
+        }
+    }
+}
+
+// one component type for every entity that has it
+pub struct Components<T> {
+    slots: Vec<Option<(u32, T)>>,
+}
+
+impl<T> Default for Components<T> {
+    fn default() -> Self {
+        Self { slots: Vec::new() }
+    }
+}
+
+impl<T> Components<T> {
+    pub fn insert(&mut self, e: Entity, value: T) {
+        let i = e.index as usize;
+        if self.slots.len() <= i {
+            self.slots.resize_with(i + 1, || None);
+        }
+        self.slots[i] = Some((e.generation, value));
+    }
+
+    pub fn remove(&mut self, e: Entity) -> Option<T> {
+        let slot = self.slots.get_mut(e.index as usize)?;
+        if slot.as_ref().is_some_and(|(g, _)| *g == e.generation) { slot.take().map(|(_, v)| v) } else { None }
+    }
+
+    pub fn get(&self, e: Entity) -> Option<&T> {
+        match self.slots.get(e.index as usize)? {
+            Some((g, v)) if *g == e.generation => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, e: Entity) -> Option<&mut T> {
+        match self.slots.get_mut(e.index as usize)? {
+            Some((g, v)) if *g == e.generation => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, e: Entity) -> bool {
+        self.get(e).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.slots.iter().enumerate()
+            .filter_map(|(i, slot)| slot.as_ref().map(|(g, v)| (Entity { index: i as u32, generation: *g }, v)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.slots.iter_mut().enumerate()
+            .filter_map(|(i, slot)| slot.as_mut().map(|(g, v)| (Entity { index: i as u32, generation: *g }, v)))
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+}
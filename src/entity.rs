@@ -1,7 +1,30 @@
 use glam::{Vec3, Quat, Mat4};
-use crate::physics::Physics;
+use crate::physics::{Physics, Rope};
 use crate::common::PlanetData;
+use crate::health::Health;
+use crate::stamina::Stamina;
+use crate::oxygen::Oxygen;
+use crate::temperature::Temperature;
 
+// --- VIEW BOBBING ---
+// eye bob height and side sway at full walking speed, in blocks
+const BOB_HEIGHT: f32 = 0.05;
+const BOB_SWAY: f32 = 0.03;
+// footsteps per block walked, the eye dips once per step and sways once per two
+const STEPS_PER_BLOCK: f32 = 0.4;
+// deepest landing dip, and how much each unit of fall speed adds to it
+const MAX_LANDING_DIP: f32 = 0.35;
+const LANDING_DIP_PER_SPEED: f32 = 0.025;
+// how fast the dip springs back and the bob fades in or out, per second
+const DIP_RECOVERY: f32 = 8.0;
+const BOB_FADE: f32 = 6.0;
+
+// --- GRAPPLE ---
+// jump reels the rope in at this many blocks per second, down to MIN_ROPE
+const REEL_SPEED: f32 = 8.0;
+const MIN_ROPE: f32 = 2.0;
+
+// not an entity in ecs.rs yet, see there
 pub struct Player {
     // State
     pub position: Vec3,
@@ -10,6 +33,22 @@ pub struct Player {
     pub cam_pitch: f32, 
     pub grounded: bool,
     pub debug_mode: bool,
+    // flies through terrain, can't edit blocks and isn't shown to other players
+    pub spectator: bool,
+    // head bob and landing dip in first person, set from the options every frame
+    pub view_bobbing: bool,
+    // grappling hook line, Q shoots and releases it
+    pub rope: Option<Rope>,
+    // glider out, H opens it in the air and landing folds it
+    pub gliding: bool,
+    pub health: Health,
+    pub stamina: Stamina,
+    pub oxygen: Oxygen,
+    pub temperature: Temperature,
+    bob_phase: f32,
+    // 0 standing still, 1 walking at full speed
+    bob_amount: f32,
+    landing_dip: f32,
 
     // Configuration
     pub move_speed: f32, 
@@ -26,6 +65,17 @@ impl Player {
             cam_pitch: 0.0,
             grounded: false,
             debug_mode: false, 
+            spectator: false,
+            view_bobbing: true,
+            rope: None,
+            gliding: false,
+            health: Health::new(),
+            stamina: Stamina::new(),
+            oxygen: Oxygen::new(),
+            temperature: Temperature::new(),
+            bob_phase: 0.0,
+            bob_amount: 0.0,
+            landing_dip: 0.0,
             move_speed: 5.0,
             jump_force: 8.0,     
             mouse_sens: 0.002,   
@@ -36,12 +86,16 @@ impl Player {
         self.position = pos;
         self.velocity = Vec3::ZERO;
         self.grounded = false;
+        self.rope = None;
+        self.gliding = false;
         let up = Physics::get_up_vector(self.position);
         self.rotation = Quat::from_rotation_arc(Vec3::Y, up);
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn update(&mut self, dt: f32, planet: &PlanetData, input: Vec3, jump: bool, mouse_delta: (f32, f32), flying: bool, sprint: bool) {
         let up = Physics::get_up_vector(self.position);
+        let flying = flying || self.spectator;
         
         // --- ROTATION (YAW) ---
         if mouse_delta.0.abs() > 0.001 {
@@ -63,6 +117,10 @@ impl Player {
             self.move_speed
         };
         
+        if self.grounded || flying {
+            self.gliding = false;
+        }
+
         // --- MOVEMENT INPUT ---
         if flying {
             
@@ -75,6 +133,10 @@ impl Player {
             } else {                
                 self.velocity = Vec3::ZERO;
             }
+        } else if self.gliding {
+            // steered by looking, gravity is part of the glide
+            let nose = self.rotation * Quat::from_axis_angle(Vec3::X, self.cam_pitch) * Vec3::NEG_Z;
+            self.velocity = Physics::glide(self.velocity, nose, up, dt);
         } else {
             // walk
             if input.length() > 0.01 {
@@ -101,6 +163,14 @@ impl Player {
             }
         }
         
+        // --- GRAPPLE ---
+        if flying {
+            self.rope = None;
+        }
+        if let (Some(rope), true) = (self.rope.as_mut(), jump) {
+            rope.length = (rope.length - REEL_SPEED * dt).max(MIN_ROPE);
+        }
+
         // --- JUMP ---
         if jump && self.grounded && !flying {
             self.velocity += up * self.jump_force;
@@ -108,22 +178,40 @@ impl Player {
         }
         
         // --- GRAVITY ---
-        if !flying {
+        if !flying && !self.gliding {
             self.velocity -= up * Physics::GRAVITY * dt;
         }
         
+        let (was_grounded, fall_speed) = (self.grounded, -self.velocity.dot(up));
+
         // --- PHYSICS SOLVE ---
         let (new_pos, new_vel, grounded) = Physics::solve_movement(
             self.position, 
             self.velocity, 
             dt, 
             planet, 
-            flying
+            flying,
+            self.spectator,
+            self.rope.as_ref()
         );
         
         self.position = new_pos;
         self.velocity = new_vel;
         self.grounded = grounded;
+
+        // --- VIEW BOBBING ---
+        if !was_grounded && grounded && !flying {
+            self.landing_dip = (self.landing_dip + fall_speed * LANDING_DIP_PER_SPEED).min(MAX_LANDING_DIP);
+        }
+        self.landing_dip *= (-DIP_RECOVERY * dt).exp();
+
+        let horz_speed = (self.velocity - up * self.velocity.dot(up)).length();
+        let walking = grounded && !flying && horz_speed > 0.1;
+        let target = if walking { (horz_speed / self.move_speed.max(0.01)).min(1.0) } else { 0.0 };
+        self.bob_amount += (target - self.bob_amount) * (BOB_FADE * dt).min(1.0);
+        if walking {
+            self.bob_phase = (self.bob_phase + horz_speed * dt * STEPS_PER_BLOCK * std::f32::consts::PI) % std::f32::consts::TAU;
+        }
         
         // --- ALIGN TO SURFACE ---
         self.rotation = Physics::align_to_planet(self.rotation, up);
@@ -135,7 +223,12 @@ impl Player {
 
     pub fn get_view_matrix(&self) -> Mat4 {
         let up = Physics::get_up_vector(self.position);
-        let cam_pos = self.position + (up * Physics::EYE_HEIGHT); 
+        let mut cam_pos = self.position + (up * Physics::EYE_HEIGHT); 
+        if self.view_bobbing {
+            let right = self.rotation * Vec3::X;
+            cam_pos += up * (self.bob_phase.sin().abs() * BOB_HEIGHT * self.bob_amount - self.landing_dip);
+            cam_pos += right * (self.bob_phase.sin() * BOB_SWAY * self.bob_amount);
+        }
         
         let pitch_rot = Quat::from_axis_angle(Vec3::X, self.cam_pitch);
         let final_rot = self.rotation * pitch_rot;
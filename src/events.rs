@@ -0,0 +1,69 @@
+//events.rs
+
+// the event bus: gameplay code emits what happened (a block mined, the player moving, a chunk arriving) and
+// main hands each event to every Subscriber once a frame, instead of the code that mined the block knowing to
+// refresh the mesh, count it in the stats and tell the server. a new listener implements Subscriber and gets
+// a line in main's dispatch, the emitters don't change. /option log_events prints every event but moves.
+
+use glam::Vec3;
+use crate::common::{BlockId, BlockType, ChunkKey, PlanetData};
+use crate::i18n::tr;
+use crate::mob::MobKind;
+
+#[derive(Clone, Debug)]
+pub enum GameEvent {
+    // `remote` edits weren't the player's: they came from the server, or a plugin's weather. nothing to send
+    BlockMined { id: BlockId, block: BlockType, remote: bool },
+    BlockPlaced { id: BlockId, block: BlockType, remote: bool },
+    // once a frame while the player moves, however they moved. `flying` covers spectators too
+    PlayerMoved { from: Vec3, to: Vec3, flying: bool },
+    // a chunk's edits arrived from the server
+    ChunkLoaded(ChunkKey),
+    // /boom cleared these blocks in one go, the player's own. they're remeshed together rather than one by one
+    Blasted { mined: Vec<(BlockId, BlockType)> },
+    EntitySpawned { kind: MobKind, position: Vec3 },
+}
+
+impl GameEvent {
+    // blocks or chunks changed, what's been published of the world is out of date
+    pub fn edits_world(&self) -> bool {
+        matches!(self, GameEvent::BlockMined { .. } | GameEvent::BlockPlaced { .. } | GameEvent::ChunkLoaded(_) | GameEvent::Blasted { .. })
+    }
+
+    // one line for the console
+    pub fn describe(&self) -> String {
+        let block = |id: &BlockId| format!("{}:{},{},{}", id.face, id.u, id.v, id.layer);
+        match self {
+            GameEvent::BlockMined { id, block: kind, remote } => tr!("event.block_mined", kind.name(), block(id), remote),
+            GameEvent::BlockPlaced { id, block: kind, remote } => tr!("event.block_placed", kind.name(), block(id), remote),
+            GameEvent::PlayerMoved { from, to, flying } => tr!("event.player_moved", format!("{:.1}", from.distance(*to)), flying),
+            GameEvent::ChunkLoaded(key) => tr!("event.chunk_loaded", format!("{}:{},{}", key.face, key.u_idx, key.v_idx)),
+            GameEvent::Blasted { mined } => tr!("event.blasted", mined.len()),
+            GameEvent::EntitySpawned { kind, position } => tr!("event.entity_spawned", kind.name(), format!("{:.0}", position)),
+        }
+    }
+}
+
+pub trait Subscriber {
+    fn on_event(&mut self, event: &GameEvent, planet: &PlanetData);
+}
+
+#[derive(Default)]
+pub struct EventBus {
+    queue: Vec<GameEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn emit(&mut self, event: GameEvent) {
+        self.queue.push(event);
+    }
+
+    // everything emitted since the last call, oldest first
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.queue)
+    }
+}
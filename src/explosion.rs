@@ -0,0 +1,44 @@
+//explosion.rs
+
+// /boom: clears every block whose centre is within a radius of a point, all at once. the blocks are found by
+// stepping through the sphere's bounding box closer than the smallest block near the surface, asking
+// CoordSystem::pos_to_id what each point is in, and keeping the ones whose centre is inside. they come out of the
+// world through PlanetData::remove_blocks, which writes each chunk's edits once rather than block by block, and go
+// out as one GameEvent::Blasted, so the renderer queues the chunks they touched for a single remesh. a player
+// inside the radius is hurt too, less the further out they stand (health::explosion_damage).
+
+use std::collections::HashSet;
+use glam::Vec3;
+use crate::common::{BlockId, BlockType, PlanetData};
+use crate::gen::CoordSystem;
+
+// --- CONSTANTS ---
+pub const MAX_BOOM_RADIUS: f32 = 10.0;
+// distance between the points sampled for blocks. under the smallest block width near the surface, so none is missed
+const SAMPLE_STEP: f32 = 0.35;
+
+// every block within `radius` of `center` that's there to remove
+fn blast_ids(planet: &PlanetData, center: Vec3, radius: f32) -> HashSet<BlockId> {
+    let res = planet.resolution;
+    let steps = (radius / SAMPLE_STEP).ceil() as i32;
+    let mut ids = HashSet::new();
+    for x in -steps..=steps {
+        for y in -steps..=steps {
+            for z in -steps..=steps {
+                let offset = Vec3::new(x as f32, y as f32, z as f32) * SAMPLE_STEP;
+                if offset.length() > radius + SAMPLE_STEP { continue; }
+                if let Some(id) = CoordSystem::pos_to_id(center + offset, res) {
+                    ids.insert(id);
+                }
+            }
+        }
+    }
+    ids.retain(|id| CoordSystem::get_block_center(id.face, id.u, id.v, id.layer, res).distance(center) <= radius && planet.exists(*id));
+    ids
+}
+
+// clears the sphere, returning what was removed. the core stays, like it does for mining
+pub fn blast(planet: &mut PlanetData, center: Vec3, radius: f32) -> Vec<(BlockId, BlockType)> {
+    let ids = blast_ids(planet, center, radius.min(MAX_BOOM_RADIUS));
+    planet.remove_blocks(ids)
+}
@@ -0,0 +1,78 @@
+//frame_graph.rs
+
+// the passes a frame is drawn in. each pass names the resources it reads and writes, and the graph works out
+// the order: everything that writes a resource runs before what reads it, otherwise passes keep the order
+// they were added in. a pass that reads and writes the same thing draws over it, so it goes after the writers
+// added before it. a new pass (post-processing, another reflection) is one more add() saying what it needs.
+
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Resource {
+    ShadowMap,
+    // the security camera textures the screens show
+    MonitorTargets,
+    // the offscreen target timelapses and panoramas are taken from
+    Still,
+    ProbeCube,
+    // the window's surface and its depth buffer
+    Backbuffer,
+    DebugView,
+    // the copy of the finished frame a recording reads
+    FrameReadback,
+}
+
+pub type PassFn<C, F> = fn(&mut C, &mut wgpu::CommandEncoder, &F);
+
+struct Pass<C, F> {
+    name: &'static str,
+    reads: &'static [Resource],
+    writes: &'static [Resource],
+    run: PassFn<C, F>,
+}
+
+// C is what the passes draw with (the renderer), F what they share about the frame
+pub struct FrameGraph<C, F> {
+    passes: Vec<Pass<C, F>>,
+}
+
+impl<C, F> FrameGraph<C, F> {
+    pub fn new() -> Self {
+        Self { passes: Vec::new() }
+    }
+
+    pub fn add(mut self, name: &'static str, reads: &'static [Resource], writes: &'static [Resource], run: PassFn<C, F>) -> Self {
+        self.passes.push(Pass { name, reads, writes, run });
+        self
+    }
+
+    // whether pass `b` has to wait for pass `a`
+    fn depends(&self, b: usize, a: usize) -> bool {
+        let (pa, pb) = (&self.passes[a], &self.passes[b]);
+        pb.reads.iter().any(|r| pa.writes.contains(r) && (a < b || !pa.reads.contains(r)))
+    }
+
+    // pass indices in the order they run. a cycle is a mistake in how the passes were declared
+    fn schedule(&self) -> Vec<usize> {
+        let n = self.passes.len();
+        let mut done: HashSet<usize> = HashSet::new();
+        let mut order = Vec::with_capacity(n);
+        while order.len() < n {
+            let next = (0..n)
+                .find(|&b| !done.contains(&b) && (0..n).all(|a| a == b || done.contains(&a) || !self.depends(b, a)));
+            let Some(next) = next else {
+                let stuck: Vec<&str> = (0..n).filter(|i| !done.contains(i)).map(|i| self.passes[i].name).collect();
+                panic!("frame graph has a cycle between {}", stuck.join(", "));
+            };
+            done.insert(next);
+            order.push(next);
+        }
+        order
+    }
+
+    pub fn execute(&self, ctx: &mut C, enc: &mut wgpu::CommandEncoder, frame: &F) {
+        for i in self.schedule() {
+            (self.passes[i].run)(ctx, enc, frame);
+        }
+    }
+}
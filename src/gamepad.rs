@@ -0,0 +1,91 @@
+//gamepad.rs
+
+// xbox and playstation style controllers, read through gilrs: the left stick walks, the right stick looks,
+// south (A / cross) jumps, pressing the left stick sprints, the right trigger mines, the left trigger places
+// and the bumpers step through the hotbar. only built with the `gamepad` feature, since gilrs needs libudev
+// on linux. without it, or with no controller plugged in, nothing here ever reports anything.
+
+use glam::Vec2;
+
+// --- CONSTANTS ---
+// look speed at full tilt and sensitivity 1, in mouse counts per second
+pub const LOOK_SPEED: f32 = 600.0;
+
+// only gilrs ever makes one
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PadAction {
+    Mine,
+    Place,
+    NextSlot,
+    PrevSlot,
+}
+
+#[derive(Default)]
+pub struct Gamepad {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    // left stick with x right and y back, like the touch stick
+    pub stick: Vec2,
+    // right stick with y down, like mouse motion
+    pub look: Vec2,
+    pub jump: bool,
+    pub sprint: bool,
+}
+
+impl Gamepad {
+    pub fn new() -> Self {
+        #[cfg(feature = "gamepad")]
+        {
+            // no backend (no udev, a sandbox) is the same as no controller
+            Self { gilrs: gilrs::Gilrs::new().ok(), ..Default::default() }
+        }
+        #[cfg(not(feature = "gamepad"))]
+        Self::default()
+    }
+
+    // reads the first connected controller and returns the buttons pressed since the last call. sticks
+    // inside `deadzone` (0..1 of their travel) count as centred
+    pub fn update(&mut self, deadzone: f32) -> Vec<PadAction> {
+        #[cfg(feature = "gamepad")]
+        {
+            use gilrs::{Axis, Button, EventType};
+            let Some(gilrs) = self.gilrs.as_mut() else { return Vec::new() };
+            let mut actions = Vec::new();
+            while let Some(event) = gilrs.next_event() {
+                if let EventType::ButtonPressed(button, _) = event.event {
+                    match button {
+                        Button::RightTrigger2 => actions.push(PadAction::Mine),
+                        Button::LeftTrigger2 => actions.push(PadAction::Place),
+                        Button::RightTrigger => actions.push(PadAction::NextSlot),
+                        Button::LeftTrigger => actions.push(PadAction::PrevSlot),
+                        _ => {}
+                    }
+                }
+            }
+            let Some((_, pad)) = gilrs.gamepads().find(|(_, pad)| pad.is_connected()) else {
+                (self.stick, self.look, self.jump, self.sprint) = (Vec2::ZERO, Vec2::ZERO, false, false);
+                return actions;
+            };
+            // gilrs has y up on both sticks
+            self.stick = dead_zone(Vec2::new(pad.value(Axis::LeftStickX), -pad.value(Axis::LeftStickY)), deadzone);
+            self.look = dead_zone(Vec2::new(pad.value(Axis::RightStickX), -pad.value(Axis::RightStickY)), deadzone);
+            self.jump = pad.is_pressed(Button::South);
+            self.sprint = pad.is_pressed(Button::LeftThumb);
+            actions
+        }
+        #[cfg(not(feature = "gamepad"))]
+        {
+            let _ = deadzone;
+            Vec::new()
+        }
+    }
+}
+
+// radial, and rescaled so the stick still goes from 0 at the edge of the dead zone to 1 at full tilt
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+fn dead_zone(v: Vec2, deadzone: f32) -> Vec2 {
+    let len = v.length();
+    if len <= deadzone { return Vec2::ZERO; }
+    v / len * ((len - deadzone) / (1.0 - deadzone)).min(1.0)
+}
@@ -1,11 +1,25 @@
 //gen.rs
 
 use std::collections::HashSet;
-use glam::Vec3;
+use glam::{Vec2, Vec3};
 use crate::common::*;
+use crate::lighting::LightEngine;
 
 pub struct CoordSystem;
 
+// how far full-detail voxel chunks reach, in chunks, and a multiplier on how far each lod level reaches
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewDistance {
+    pub chunks: f32,
+    pub lod_detail: f32,
+}
+
+impl Default for ViewDistance {
+    fn default() -> Self {
+        Self { chunks: 12.0, lod_detail: 1.0 }
+    }
+}
+
 impl CoordSystem {
     // k = 0.85 balances the shape.
     const K: f64 = 0.85; 
@@ -35,7 +49,7 @@ impl CoordSystem {
         let fy = y.abs();
         let fz = z.abs();
 
-        const INVERSE_SQRT_2: f64 = 0.70710676908493042;
+        const INVERSE_SQRT_2: f64 = 0.707_106_769_084_930_4;
 
         if fy >= fx && fy >= fz {
             let a2 = x * x * 2.0;
@@ -160,6 +174,69 @@ impl CoordSystem {
         r as f32
     }
 
+    // bounding sphere of the shell between radii r_min and r_max over the square of `size` blocks at (u, v). the
+    // points farthest from its middle are the corners, at one radius or the other
+    pub fn patch_bounds(face: u8, u: u32, v: u32, size: u32, (r_min, r_max): (f32, f32), res: u32) -> (Vec3, f32) {
+        let (u1, v1) = ((u + size).min(res), (v + size).min(res));
+        let mid = Self::get_direction(face, (u + u1) / 2, (v + v1) / 2, res);
+        let center = mid * (r_min + r_max) * 0.5;
+        let radius = [(u, v), (u1, v), (u, v1), (u1, v1)].into_iter()
+            .map(|(cu, cv)| Self::get_direction(face, cu, cv, res))
+            .chain(std::iter::once(mid))
+            .flat_map(|dir| [dir * r_min, dir * r_max])
+            .map(|p| p.distance(center))
+            .fold(0.0, f32::max);
+        (center, radius)
+    }
+
+    // a frame for boxing the square of `size` blocks at (u, v): up through its middle, then along u and along v
+    pub fn patch_axes(face: u8, u: u32, v: u32, size: u32, res: u32) -> [Vec3; 3] {
+        let (u1, v1) = ((u + size).min(res), (v + size).min(res));
+        let (mu, mv) = ((u + u1) / 2, (v + v1) / 2);
+        let up = Self::get_direction(face, mu, mv, res);
+        let along = Self::get_direction(face, u1, mv, res) - Self::get_direction(face, u, mv, res);
+        let across = (along - up * along.dot(up)).normalize();
+        [up, across, up.cross(across)]
+    }
+
+    // the box of the same shell, along patch_axes(). much tighter than the sphere for a wide, thin patch, which
+    // the sphere has to reach round the corners of. the grid of points catches the bulge of the surface
+    pub fn patch_obb(face: u8, u: u32, v: u32, size: u32, (r_min, r_max): (f32, f32), res: u32) -> Obb {
+        let (u1, v1) = ((u + size).min(res), (v + size).min(res));
+        let points = [u, (u + u1) / 2, u1].into_iter()
+            .flat_map(|cu| [v, (v + v1) / 2, v1].map(|cv| Self::get_direction(face, cu, cv, res)))
+            .flat_map(|dir| [dir * r_min, dir * r_max]);
+        Obb::fit(Self::patch_axes(face, u, v, size, res), points)
+    }
+
+    // the same for a fractional layer, like a smoothed height
+    pub fn get_fractional_layer_radius(layer: f32, res: u32) -> f32 {
+        let s = res as f64 / 2.0;
+        (s * (Self::K * ((layer as f64 / s) - 1.0)).exp()) as f32
+    }
+
+    // the other way round: which layer, fractional, is `radius` from the centre
+    pub fn get_layer_at(radius: f32, res: u32) -> f32 {
+        let s = res as f64 / 2.0;
+        (s * (1.0 + (radius.max(f32::MIN_POSITIVE) as f64 / s).ln() / Self::K)) as f32
+    }
+
+    // distance from the camera at which a quadtree node of `size` blocks splits into its children.
+    // nodes of CHUNK_SIZE * 2 split into full voxel chunks, so that distance is the edge of the detail bubble
+    pub fn lod_split_distance(size: u32, res: u32, view: ViewDistance) -> f32 {
+        let h = res / 2;
+        let node_radius_world = (size as f32 * Self::get_layer_radius(h, res)) / res as f32;
+
+        let mut lod_factor = 4.0; 
+        if size <= CHUNK_SIZE * 8 { lod_factor = 5.0; }
+        if size <= CHUNK_SIZE * 4 { lod_factor = 7.0; }
+        if size <= CHUNK_SIZE * 2 { return node_radius_world * view.chunks; }
+
+        // a parent has to split at least as far out as its children, or a big chunk radius would be cut off by the lods
+        let min_factor = view.chunks * (CHUNK_SIZE * 2) as f32 / size as f32 + 1.0;
+        node_radius_world * (lod_factor * view.lod_detail).max(min_factor)
+    }
+
 pub fn get_direction(face: u8, u: u32, v: u32, res: u32) -> Vec3 {
         let rf = res as f64;
         
@@ -259,6 +336,13 @@ pub fn pos_to_id(pos: Vec3, res: u32) -> Option<BlockId> {
 
 pub struct MeshGen;
 
+// a lod sample spans about this many radians from where it's first drawn, some 5 pixels on a 1080p screen
+const LOD_SAMPLE_ANGLE: f32 = 0.005;
+// and a grid is coarsened while dropping samples moves the surface by less than this, about a pixel
+const LOD_ERROR_ANGLE: f32 = 0.001;
+const MIN_LOD_GRID: u32 = 8;
+const MAX_LOD_GRID: u32 = 128;
+
 impl MeshGen {
 
     fn add_mined_candidates(mods: &ChunkMods, candidates: &mut HashSet<BlockId>, res: u32) {
@@ -344,10 +428,8 @@ impl MeshGen {
 
         // generate Mesh
         for id in candidates {
-            if id.u >= u_start && id.u < u_end && id.v >= v_start && id.v < v_end {
-                if data.exists(id) {
-                    Self::add_voxel(id, data, &mut verts, &mut inds, &mut idx);
-                }
+            if id.u >= u_start && id.u < u_end && id.v >= v_start && id.v < v_end && data.exists(id) {
+                Self::add_voxel(id, data, &mut verts, &mut inds, &mut idx);
             }
         }
         (verts, inds)
@@ -453,13 +535,15 @@ impl MeshGen {
 
 
     // generates a simplified heightmap mesh for distant terrain
-    pub fn generate_lod_mesh(key: crate::common::LodKey, data: &PlanetData) -> (Vec<Vertex>, Vec<u32>) {
+    pub fn generate_lod_mesh(key: crate::common::LodKey, data: &PlanetData, view: ViewDistance) -> (Vec<Vertex>, Vec<u32>) {
         let mut verts = Vec::new();
         let mut inds = Vec::new();
         
       
-        let grid_res = 64; 
+        let grid_res = Self::lod_grid(key, data, view); 
         let row_len = grid_res + 1;
+        // heights come from the mip with a texel per sample, not whichever block a sample lands on
+        let level = (key.size / grid_res).trailing_zeros();
         
         // calculate global pos for any grid index (even outside this chunk)
         // this allows us to "peek" into neighbor chunks for perfect normals.
@@ -472,8 +556,8 @@ impl MeshGen {
              let abs_u = (key.x as i64 + step_u).clamp(0, data.resolution as i64) as u32;
              let abs_v = (key.y as i64 + step_v).clamp(0, data.resolution as i64) as u32;
              
-             let h = data.terrain.get_height(key.face, abs_u, abs_v);
-             CoordSystem::get_vertex_pos(key.face, abs_u, abs_v, h, data.resolution)
+             let h = data.terrain.smooth_height(level, key.face, abs_u, abs_v);
+             CoordSystem::get_direction(key.face, abs_u, abs_v, data.resolution) * CoordSystem::get_fractional_layer_radius(h, data.resolution)
         };
 
         // 1. Generate Vertices
@@ -584,16 +668,59 @@ impl MeshGen {
         (verts, inds)
     }
 
+    // --- LOD GRID ---
+    // samples per side of a lod's grid. as fine as it takes for one sample to span about LOD_SAMPLE_ANGLE seen from
+    // the nearest the node is ever drawn (its split distance), then halved for as long as the terrain is flat enough
+    // that the coarser grid stays within LOD_ERROR_ANGLE of it from there. always a power of two that divides the
+    // node, so samples land on whole blocks
+    fn lod_grid(key: crate::common::LodKey, data: &PlanetData, view: ViewDistance) -> u32 {
+        let res = data.resolution;
+        let nearest = CoordSystem::lod_split_distance(key.size, res, view);
+        let node_world = key.size as f32 * CoordSystem::get_layer_radius(res / 2, res) / res as f32;
+        let wanted = (node_world / (nearest * LOD_SAMPLE_ANGLE)).max(1.0) as u32;
+        let mut grid = wanted.next_power_of_two().clamp(MIN_LOD_GRID, MAX_LOD_GRID.min(key.size));
+
+        // heights are in layers, about a block each near the surface
+        let max_error = nearest * LOD_ERROR_ANGLE;
+        // flat all over, which the mip texel covering the node tells at once
+        let span = data.terrain.height_span(key.size.trailing_zeros(), key.face, key.x, key.y);
+        if ((span.max - span.min) as f32) <= max_error { return MIN_LOD_GRID; }
+        while grid > MIN_LOD_GRID {
+            let step = key.size / grid;
+            let h = |gx: u32, gy: u32| data.terrain.get_height(key.face, (key.x + gx * step).min(res), (key.y + gy * step).min(res)) as f32;
+            // every sample the half grid drops, against where the half grid's triangles put it. they're split
+            // corner to corner from top right to bottom left, like the indices below
+            let mut error = 0.0_f32;
+            for gy in 0..=grid {
+                for gx in 0..=grid {
+                    let coarse = match (gx % 2, gy % 2) {
+                        (0, 0) => continue,
+                        (1, 0) => (h(gx - 1, gy) + h(gx + 1, gy)) * 0.5,
+                        (0, _) => (h(gx, gy - 1) + h(gx, gy + 1)) * 0.5,
+                        _ => (h(gx + 1, gy - 1) + h(gx - 1, gy + 1)) * 0.5,
+                    };
+                    error = error.max((h(gx, gy) - coarse).abs());
+                }
+            }
+            if error > max_error { break; }
+            grid /= 2;
+        }
+        grid
+    }
+
 fn add_voxel(id: BlockId, data: &PlanetData, verts: &mut Vec<Vertex>, inds: &mut Vec<u32>, idx: &mut u32) {
         let res = data.resolution;
 
-        // neighbor existence check
+        let block = data.block_type(id);
+
+        // neighbor existence check. glass hides glass but nothing else
         let check = |d_face: u8, d_layer: i32, d_u: i32, d_v: i32| -> bool {
             let l = id.layer as i32 + d_layer;
             let u = id.u as i32 + d_u;
             let v = id.v as i32 + d_v;
             if l >= 0 && u >= 0 && u < res as i32 && v >= 0 && v < res as i32 {
-                return data.exists(BlockId { face: d_face, layer: l as u32, u: u as u32, v: v as u32 });
+                let n = BlockId { face: d_face, layer: l as u32, u: u as u32, v: v as u32 };
+                return data.exists(n) && (block.see_through() || !data.block_type(n).see_through());
             }
             l < 0 // Core is solid
         };
@@ -608,79 +735,66 @@ fn add_voxel(id: BlockId, data: &PlanetData, verts: &mut Vec<Vertex>, inds: &mut
 
         if has_top && has_btm && has_left && has_right && has_front && has_back { return; }
 
-        // --- LIGHTING CALCULATION ( this is simple, i will change this later)---
-        // we cast a short ray (8 blocks)
-        // if we hit nothing, we assume we are near the surface
-        // if we hit blocks, we darken
-
-        let mut sky_occlusion: f32 = 0.0; 
-        for i in 1..=8 {
-            if check(id.face, i, 0, 0) {
-                sky_occlusion += 1.0;
-            }
-        }
-        // 0.0 = full sky, 1.0 = buried
-
-        let mut light_val: f32 = 1.0; 
-        
-        for i in 1..=8 {
-            if check(id.face, i, 0, 0) {
-                light_val = 0.15; // Dark shadow immediately
-                break;
-            }
-        }
-
-        // boost light if it's the natural surface (Grass) to ensure terrain looks bright
-        let natural_h = data.terrain.get_height(id.face, id.u, id.v);
-        if id.layer >= natural_h { light_val = 1.0; }
-
-     
-        let is_core = data.has_core && id.layer < 6;
-        let is_grass = id.layer == natural_h;
-        
-        let mut base_color = if is_core { 
-            [0.2, 0.2, 0.2] // rock
-        } else if is_grass { 
-            [0.1, 0.7, 0.1] // grass
-        } else { 
-            [0.6, 0.4, 0.2] // dirt
-        };
-
-        // apply Skylight
-        base_color[0] *= light_val;
-        base_color[1] *= light_val;
-        base_color[2] *= light_val;
+        let base_color = block.color();
 
         // geometry Helpers
         let p = |u_off: u32, v_off: u32, l_off: u32| CoordSystem::get_vertex_pos(id.face, id.u + u_off, id.v + v_off, id.layer + l_off, res);
-        let i_bl = p(0,0,0); let i_br = p(1,0,0); let i_tl = p(0,1,0); let i_tr = p(1,1,0);
-        let o_bl = p(0,0,1); let o_br = p(1,0,1); let o_tl = p(0,1,1); let o_tr = p(1,1,1);
 
         let apply = |ao: f32| -> [f32; 3] { [base_color[0] * ao, base_color[1] * ao, base_color[2] * ao] };
 
-   
-        if !has_top {
-            
-            let n = |u, v| check(id.face, 1, u, v);
-            let ao_bl = Self::calculate_ao(n(-1, 0), n(0, -1), n(-1, -1));
-            let ao_br = Self::calculate_ao(n(1, 0),  n(0, -1), n(1, -1));
-            let ao_tr = Self::calculate_ao(n(1, 0),  n(0, 1),  n(1, 1));
-            let ao_tl = Self::calculate_ao(n(-1, 0), n(0, 1),  n(-1, 1));
-            Self::quad(verts, inds, idx, [o_bl, o_br, o_tr, o_tl], [apply(ao_bl), apply(ao_br), apply(ao_tr), apply(ao_tl)], true); 
-        }
+        // skylight of the open cell at an offset, or None if it's solid or off the face
+        let open_light = |[l, u, v]: [i32; 3]| -> Option<f32> {
+            let n = BlockId { face: id.face, layer: (id.layer as i32 + l) as u32, u: (id.u as i32 + u) as u32, v: (id.v as i32 + v) as u32 };
+            let on_face = id.layer as i32 + l >= 0 && (0..res as i32).contains(&(id.u as i32 + u)) && (0..res as i32).contains(&(id.v as i32 + v));
+            (on_face && !check(id.face, l, u, v)).then(|| LightEngine::brightness(n, data))
+        };
 
-        if !has_btm {
-            let c = apply(0.4); 
-            Self::quad(verts, inds, idx, [i_tl, i_tr, i_br, i_bl], [c,c,c,c], true); 
-        }
+        // ao and light at a corner (u, v, layer offsets) of the face looking along `normal` (layer, u, v). ao comes
+        // from the two blocks beside that corner one step out from the face, and the one diagonally between them,
+        // the same on every face so a pit or a cave corner darkens on its walls as well as its floor. light is the
+        // average over the open ones of those and the cell the face looks into, so it fades from block to block
+        // instead of stepping at the edge of a shadow
+        let corner = |normal: [i32; 3], [cu, cv, cl]: [u32; 3]| -> (f32, f32) {
+            let signs = [cl as i32 * 2 - 1, cu as i32 * 2 - 1, cv as i32 * 2 - 1];
+            let mut sides = (0..3).filter(|&axis| normal[axis] == 0).map(|axis| {
+                let mut d = normal;
+                d[axis] += signs[axis];
+                d
+            });
+            let (a, b) = (sides.next().unwrap(), sides.next().unwrap());
+            let diagonal = [a[0] + b[0] - normal[0], a[1] + b[1] - normal[1], a[2] + b[2] - normal[2]];
+            let n = |[l, u, v]: [i32; 3]| check(id.face, l, u, v);
+            let (side_a, side_b) = (n(a), n(b));
+            let ao = Self::calculate_ao(side_a, side_b, n(diagonal));
+            // light doesn't leak round a corner both sides of which are walled off
+            let cells = if side_a && side_b { &[normal, a, b][..] } else { &[normal, a, b, diagonal][..] };
+            let (sum, count) = cells.iter().filter_map(|&c| open_light(c)).fold((0.0, 0), |(s, n), l| (s + l, n + 1));
+            let light = if count == 0 { LightEngine::brightness(id, data) } else { sum / count as f32 };
+            (ao, light)
+        };
 
-        let side_c = apply(0.8); 
-        let colors = [side_c, side_c, side_c, side_c];
+        // `shade` darkens the face by which way it looks, on top of its ao
+        let mut face = |corners: [[u32; 3]; 4], normal: [i32; 3], shade: f32, radial: bool| {
+            let lit = corners.map(|c| {
+                let (ao, light) = corner(normal, c);
+                ao * light
+            });
+            let mut pos = corners.map(|[u, v, l]| p(u, v, l));
+            let mut colors = lit.map(|a| apply(shade * a));
+            // split along the brighter diagonal, or one dark corner smears across half the quad
+            if lit[0] + lit[2] < lit[1] + lit[3] {
+                pos.rotate_left(1);
+                colors.rotate_left(1);
+            }
+            Self::quad(verts, inds, idx, pos, colors, radial);
+        };
 
-        if !has_front { Self::quad(verts, inds, idx, [i_bl, i_br, o_br, o_bl], colors, false); }
-        if !has_back  { Self::quad(verts, inds, idx, [o_tl, o_tr, i_tr, i_tl], colors, false); }
-        if !has_left  { Self::quad(verts, inds, idx, [i_tl, i_bl, o_bl, o_tl], colors, false); }
-        if !has_right { Self::quad(verts, inds, idx, [i_br, i_tr, o_tr, o_br], colors, false); }
+        if !has_top   { face([[0, 0, 1], [1, 0, 1], [1, 1, 1], [0, 1, 1]], [1, 0, 0], 1.0, true); }
+        if !has_btm   { face([[0, 1, 0], [1, 1, 0], [1, 0, 0], [0, 0, 0]], [-1, 0, 0], 0.4, true); }
+        if !has_front { face([[0, 0, 0], [1, 0, 0], [1, 0, 1], [0, 0, 1]], [0, 0, -1], 0.8, false); }
+        if !has_back  { face([[0, 1, 1], [1, 1, 1], [1, 1, 0], [0, 1, 0]], [0, 0, 1], 0.8, false); }
+        if !has_left  { face([[0, 1, 0], [0, 0, 0], [0, 0, 1], [0, 1, 1]], [0, -1, 0], 0.8, false); }
+        if !has_right { face([[1, 0, 0], [1, 1, 0], [1, 1, 1], [1, 0, 1]], [0, 1, 0], 0.8, false); }
     }
     pub fn generate_cylinder(radius: f32, height: f32, segments: u32) -> (Vec<Vertex>, Vec<u32>) {
         let mut verts = Vec::new();
@@ -728,6 +842,35 @@ fn add_voxel(id: BlockId, data: &PlanetData, verts: &mut Vec<Vertex>, inds: &mut
         (verts, inds)
     }
 
+    // a box reaching `half` out from the origin along each axis, flat shaded
+    pub fn generate_box(half: Vec3, color: [f32; 3]) -> (Vec<Vertex>, Vec<u32>) {
+        let mut verts = Vec::new();
+        let mut inds = Vec::new();
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z, Vec3::NEG_X, Vec3::NEG_Y, Vec3::NEG_Z] {
+            // a x b = axis, so every side winds counter-clockwise seen from outside
+            let a = Vec3::new(axis.y.abs(), axis.z.abs(), axis.x.abs());
+            let b = axis.cross(a);
+            let base = verts.len() as u32;
+            for (s, t) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                let p = (axis + a * s + b * t) * half;
+                verts.push(Vertex { pos: p.to_array(), color, normal: axis.to_array() });
+            }
+            inds.extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
+        }
+        (verts, inds)
+    }
+
+    // a flat arrowhead pointing down -z, wings along x
+    pub fn generate_dart(length: f32, span: f32, color: [f32; 3]) -> (Vec<Vertex>, Vec<u32>) {
+        let normal = [0.0, 1.0, 0.0];
+        let nose = [0.0, 0.0, -length * 0.5];
+        let tail = [0.0, 0.0, length * 0.25];
+        let left = [-span * 0.5, 0.0, length * 0.5];
+        let right = [span * 0.5, 0.0, length * 0.5];
+        let verts = [nose, left, tail, right].map(|pos| Vertex { pos, color, normal }).to_vec();
+        (verts, vec![0, 1, 2, 0, 2, 3])
+    }
+
 
 
     
@@ -771,26 +914,53 @@ fn add_voxel(id: BlockId, data: &PlanetData, verts: &mut Vec<Vertex>, inds: &mut
 
 
 // generates a simple 2D crosshair for the center of the screen
-    pub fn generate_crosshair() -> (Vec<Vertex>, Vec<u32>) {
-        let s = 0.02; // size relative to screen (2%)
-        let color = [1.0, 1.0, 1.0]; 
-        let normal = [0.0, 0.0, 1.0]; 
+    // two bars in pixels, converted to ndc so the cross stays square on any aspect ratio
+    pub fn generate_crosshair(size: f32, thickness: f32, color: [f32; 3], width: f32, height: f32) -> (Vec<Vertex>, Vec<u32>) {
+        let normal = [0.0, 0.0, 1.0];
+        let (sx, sy) = (2.0 / width, 2.0 / height);
+        let (half_len, half_thick) = (size * 0.5, thickness * 0.5);
 
-        let verts = vec![
-           
-            Vertex { pos: [-s, 0.0, 0.0], color, normal },
-            Vertex { pos: [ s, 0.0, 0.0], color, normal },
-            
-            Vertex { pos: [0.0, -s, 0.0], color, normal },
-            Vertex { pos: [0.0,  s, 0.0], color, normal },
-        ];
-        let inds = vec![0, 1, 2, 3];
+        let mut verts = Vec::new();
+        let mut inds = Vec::new();
+        for (hw, hh) in [(half_len, half_thick), (half_thick, half_len)] {
+            let base = verts.len() as u32;
+            for (x, y) in [(-hw, hh), (hw, hh), (-hw, -hh), (hw, -hh)] {
+                verts.push(Vertex { pos: [x * sx, y * sy, 0.0], color, normal });
+            }
+            inds.extend([base, base + 2, base + 1, base + 1, base + 2, base + 3]);
+        }
         (verts, inds)
     }
 
+    // on-screen joystick: a ring where the thumb went down and a disc under it now, positions in pixels
+    pub fn generate_joystick(origin: Vec2, knob: Vec2, radius: f32, width: f32, height: f32) -> (Vec<Vertex>, Vec<u32>) {
+        const SEGMENTS: u32 = 32;
+        let normal = [0.0, 0.0, 1.0];
+        let to_ndc = |p: Vec2| [p.x * 2.0 / width - 1.0, 1.0 - p.y * 2.0 / height, 0.0];
+        let around = |i: u32| {
+            let a = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+            Vec2::new(a.cos(), a.sin())
+        };
 
+        let mut verts = Vec::new();
+        let mut inds = Vec::new();
+        let ring_color = [0.8, 0.8, 0.8];
+        for i in 0..SEGMENTS {
+            let base = verts.len() as u32;
+            for (dir, r) in [(around(i), radius), (around(i + 1), radius), (around(i), radius - 4.0), (around(i + 1), radius - 4.0)] {
+                verts.push(Vertex { pos: to_ndc(origin + dir * r), color: ring_color, normal });
+            }
+            inds.extend([base, base + 2, base + 1, base + 1, base + 2, base + 3]);
+        }
 
-
+        let center = verts.len() as u32;
+        verts.push(Vertex { pos: to_ndc(knob), color: [1.0; 3], normal });
+        for i in 0..SEGMENTS {
+            verts.push(Vertex { pos: to_ndc(knob + around(i) * radius * 0.4), color: [1.0; 3], normal });
+            inds.extend([center, center + 1 + i, center + 1 + (i + 1) % SEGMENTS]);
+        }
+        (verts, inds)
+    }
 
     fn quad(verts: &mut Vec<Vertex>, inds: &mut Vec<u32>, idx: &mut u32, pos: [Vec3; 4], colors: [[f32; 3]; 4], force_radial: bool) {
         let normal = if force_radial {
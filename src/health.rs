@@ -0,0 +1,127 @@
+//health.rs
+
+// player health and everything that takes it away. hazards don't touch the number themselves, they hand a
+// source and an amount to Health::hurt, which lets a hit through only when the last one is long enough ago
+// (invulnerability frames) and sets off the red flash. at zero the player dies and main respawns them.
+
+use glam::Vec3;
+use crate::gen::CoordSystem;
+use crate::i18n::tr;
+
+// --- CONSTANTS ---
+pub const MAX_HEALTH: f32 = 20.0;
+// no damage for this long after a hit, so a hazard touching the player every frame hurts at a steady pace
+const INVULNERABLE_TIME: f32 = 0.5;
+// health comes back at REGEN_RATE per second once the player hasn't been hurt for REGEN_DELAY seconds
+const REGEN_DELAY: f32 = 5.0;
+const REGEN_RATE: f32 = 0.5;
+// the red flash lasts this long, starting this strong for a hit that takes all of MAX_HEALTH
+const FLASH_TIME: f32 = 0.4;
+const MAX_FLASH: f32 = 0.6;
+// landings faster than this hurt, FALL_DAMAGE per unit of speed above it
+const SAFE_FALL_SPEED: f32 = 13.0;
+const FALL_DAMAGE: f32 = 1.0;
+// per hit while below the bottom layer
+const VOID_DAMAGE: f32 = 4.0;
+// at the centre of a blast, falling off to nothing at its edge
+const EXPLOSION_DAMAGE: f32 = 16.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageSource {
+    Fall,
+    // below the bottom layer of the planet, where there's nothing to stand on
+    Void,
+    // out of oxygen, see oxygen.rs
+    Suffocation,
+    // too long too far from a comfortable temperature, see temperature.rs
+    Cold,
+    Heat,
+    // bitten, see Mobs::update_lurkers
+    Lurker,
+    // caught in a /boom, see explosion.rs
+    Explosion,
+}
+
+impl DamageSource {
+    // how the console tells of a death by it
+    pub fn death_message(self) -> String {
+        match self {
+            DamageSource::Fall => tr!("health.died.fall"),
+            DamageSource::Void => tr!("health.died.void"),
+            DamageSource::Suffocation => tr!("health.died.suffocation"),
+            DamageSource::Cold => tr!("health.died.cold"),
+            DamageSource::Heat => tr!("health.died.heat"),
+            DamageSource::Lurker => tr!("health.died.lurker"),
+            DamageSource::Explosion => tr!("health.died.explosion"),
+        }
+    }
+}
+
+pub struct Health {
+    pub current: f32,
+    // off while connected, the server doesn't know about health
+    pub enabled: bool,
+    since_hurt: f32,
+    flash: f32,
+    flash_strength: f32,
+    death: Option<DamageSource>,
+}
+
+impl Health {
+    pub fn new() -> Self {
+        Self { current: MAX_HEALTH, enabled: true, since_hurt: INVULNERABLE_TIME, flash: 0.0, flash_strength: 0.0, death: None }
+    }
+
+    // true if the hit went through
+    pub fn hurt(&mut self, source: DamageSource, amount: f32) -> bool {
+        if !self.enabled || amount <= 0.0 || self.since_hurt < INVULNERABLE_TIME || self.death.is_some() {
+            return false;
+        }
+        self.current = (self.current - amount).max(0.0);
+        self.since_hurt = 0.0;
+        self.flash = FLASH_TIME;
+        self.flash_strength = (0.3 + amount / MAX_HEALTH).min(1.0) * MAX_FLASH;
+        if self.current <= 0.0 {
+            self.death = Some(source);
+        }
+        true
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.since_hurt += dt;
+        self.flash = (self.flash - dt).max(0.0);
+        if self.since_hurt >= REGEN_DELAY && self.death.is_none() {
+            self.current = (self.current + REGEN_RATE * dt).min(MAX_HEALTH);
+        }
+    }
+
+    // opacity of the red over the screen
+    pub fn flash(&self) -> f32 {
+        self.flash / FLASH_TIME * self.flash_strength
+    }
+
+    // what killed the player, once
+    pub fn take_death(&mut self) -> Option<DamageSource> {
+        self.death.take()
+    }
+
+    pub fn revive(&mut self) {
+        *self = Self { enabled: self.enabled, ..Self::new() };
+    }
+}
+
+// --- HAZARDS ---
+
+// damage for landing at `speed`
+pub fn fall_damage(speed: f32) -> f32 {
+    ((speed - SAFE_FALL_SPEED) * FALL_DAMAGE).max(0.0).round()
+}
+
+// standing `distance` from the centre of a blast `radius` across
+pub fn explosion_damage(distance: f32, radius: f32) -> f32 {
+    ((1.0 - distance / radius) * EXPLOSION_DAMAGE).max(0.0).round()
+}
+
+pub fn void_damage(pos: Vec3, res: u32) -> Option<f32> {
+    (pos.length() < CoordSystem::get_layer_radius(0, res)).then_some(VOID_DAMAGE)
+}
@@ -0,0 +1,99 @@
+//i18n.rs
+
+// user-facing text lives in lang/<code>.lang files as `key = value` lines, # starts a comment.
+// {0}, {1}, ... in a value are replaced by the arguments passed to tr!, and \n is a line break.
+// english is compiled in, so a missing or half-translated language file falls back to it key by key.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+
+// --- CONSTANTS ---
+pub const LANG_DIR: &str = "lang";
+pub const DEFAULT_LANGUAGE: &str = "en";
+pub const LANGUAGE_ENV: &str = "VOXANET_LANG";
+
+const ENGLISH: &str = include_str!("../lang/en.lang");
+
+struct Language {
+    code: String,
+    strings: HashMap<String, String>,
+}
+
+static ACTIVE: RwLock<Option<Language>> = RwLock::new(None);
+static FALLBACK: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+// looks a key up and fills in its placeholders
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::get($key)
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format($key, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+pub(crate) use tr;
+
+fn parse(text: &str) -> HashMap<String, String> {
+    let mut strings = HashMap::new();
+    for line in text.lines() {
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') { continue; }
+        if let Some((key, value)) = line.split_once('=') {
+            strings.insert(key.trim().to_string(), value.trim().replace("\\n", "\n"));
+        }
+    }
+    strings
+}
+
+fn fallback() -> &'static HashMap<String, String> {
+    FALLBACK.get_or_init(|| parse(ENGLISH))
+}
+
+// switches every string to another language. english needs no file
+pub fn set_language(code: &str) -> std::io::Result<()> {
+    let strings = if code == DEFAULT_LANGUAGE {
+        HashMap::new()
+    } else {
+        parse(&std::fs::read_to_string(Path::new(LANG_DIR).join(format!("{}.lang", code)))?)
+    };
+    *ACTIVE.write().unwrap() = Some(Language { code: code.to_string(), strings });
+    Ok(())
+}
+
+pub fn current_language() -> String {
+    ACTIVE.read().unwrap().as_ref().map(|l| l.code.clone()).unwrap_or_else(|| DEFAULT_LANGUAGE.to_string())
+}
+
+// codes of every language file in lang/, plus the built-in english
+pub fn available_languages() -> Vec<String> {
+    let mut codes = vec![DEFAULT_LANGUAGE.to_string()];
+    if let Ok(entries) = std::fs::read_dir(LANG_DIR) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("lang") { continue; }
+            if let Some(code) = path.file_stem().and_then(|s| s.to_str()) {
+                if code != DEFAULT_LANGUAGE { codes.push(code.to_string()); }
+            }
+        }
+    }
+    codes.sort();
+    codes
+}
+
+// an unknown key comes back as itself, so a typo shows up on screen instead of vanishing
+pub fn get(key: &str) -> String {
+    if let Some(lang) = ACTIVE.read().unwrap().as_ref() {
+        if let Some(s) = lang.strings.get(key) { return s.clone(); }
+    }
+    fallback().get(key).cloned().unwrap_or_else(|| key.to_string())
+}
+
+pub fn format(key: &str, args: &[&dyn Display]) -> String {
+    let mut text = get(key);
+    for (i, arg) in args.iter().enumerate() {
+        text = text.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    text
+}
@@ -0,0 +1,74 @@
+//instancing.rs
+
+// one mesh drawn many times in a single call: the vertices are uploaded once and each copy is an Instance,
+// a transform and a tint, in a buffer of its own that's rewritten when they move. for anything that repeats,
+// mobs and other players so far, instead of a buffer and a draw call per copy or baking every copy into one
+// mesh on the cpu each frame. drawn with the Instanced shader (see pipelines.rs).
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+use crate::common::Vertex;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Instance {
+    pub model: [f32; 16],
+    // rgb replaces the mesh's vertex colors as far as a says, 0 keeps them
+    pub tint: [f32; 4],
+}
+
+impl Instance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![3 => Float32x4, 4 => Float32x4, 5 => Float32x4, 6 => Float32x4, 7 => Float32x4];
+
+    pub fn new(model: glam::Mat4, tint: Option<[f32; 3]>) -> Self {
+        let tint = tint.map_or([0.0; 4], |[r, g, b]| [r, g, b, 1.0]);
+        Self { model: model.to_cols_array(), tint }
+    }
+
+    // the second vertex buffer of the Instanced shader, one step per copy
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout { array_stride: std::mem::size_of::<Instance>() as _, step_mode: wgpu::VertexStepMode::Instance, attributes: &Self::ATTRIBUTES }
+    }
+}
+
+pub struct InstancedMesh {
+    v_buf: wgpu::Buffer,
+    i_buf: wgpu::Buffer,
+    num_inds: u32,
+    instance_buf: wgpu::Buffer,
+    capacity: usize,
+    count: u32,
+}
+
+impl InstancedMesh {
+    pub fn new(device: &wgpu::Device, label: &str, (verts, inds): &(Vec<Vertex>, Vec<u32>), capacity: usize) -> Self {
+        let v_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some(label), contents: bytemuck::cast_slice(verts), usage: wgpu::BufferUsages::VERTEX });
+        let i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some(label), contents: bytemuck::cast_slice(inds), usage: wgpu::BufferUsages::INDEX });
+        let instance_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label), size: (capacity * std::mem::size_of::<Instance>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        Self { v_buf, i_buf, num_inds: inds.len() as u32, instance_buf, capacity, count: 0 }
+    }
+
+    // the copies to draw from now on. past capacity they're left out
+    pub fn upload(&mut self, queue: &wgpu::Queue, instances: &[Instance]) {
+        let instances = &instances[..instances.len().min(self.capacity)];
+        if !instances.is_empty() {
+            queue.write_buffer(&self.instance_buf, 0, bytemuck::cast_slice(instances));
+        }
+        self.count = instances.len() as u32;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // the pass needs an Instanced pipeline and both bind groups set
+    pub fn draw<'p>(&'p self, pass: &mut wgpu::RenderPass<'p>) {
+        if self.is_empty() { return; }
+        pass.set_vertex_buffer(0, self.v_buf.slice(..));
+        pass.set_vertex_buffer(1, self.instance_buf.slice(..));
+        pass.set_index_buffer(self.i_buf.slice(..), wgpu::IndexFormat::Uint32);
+        pass.draw_indexed(0..self.num_inds, 0, 0..self.count);
+    }
+}
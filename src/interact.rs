@@ -0,0 +1,122 @@
+//interact.rs
+
+// E on whatever the aim is on: a sign, a teleporter, a villager, a bike. interactable things are looked up by
+// kind in HANDLERS, which holds the prompt the hud shows while one is targeted and the fn that runs on E, so a
+// new kind is one enum variant, one row and one handler. nothing targeted, E still gets on the nearest bike.
+
+use crate::cmd::Console;
+use crate::common::{BlockEntity, PlanetData};
+use crate::controller::{ChaseCam, Controller, Target};
+use crate::entity::Player;
+use crate::gen::CoordSystem;
+use crate::i18n::tr;
+use crate::mob::{MobKind, Mobs};
+use crate::teleporter::{self, Travel};
+use crate::trade::TradeMenu;
+
+// --- CONSTANTS ---
+// how close to the player something has to be to interact with it
+pub const INTERACT_REACH: f32 = 4.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum InteractKind {
+    Sign,
+    Teleporter,
+    Villager,
+    Bike,
+}
+
+#[derive(Clone, Copy)]
+pub struct Interactable {
+    pub kind: InteractKind,
+    pub target: Target,
+}
+
+// what a handler gets to change
+pub struct InteractContext<'a> {
+    pub planet: &'a PlanetData,
+    pub mobs: &'a mut Mobs,
+    pub controller: &'a mut Controller,
+    pub travel: &'a mut Travel,
+    pub console: &'a mut Console,
+    pub trade: &'a mut TradeMenu,
+}
+
+type Handler = fn(&mut InteractContext, Target);
+
+// kind, lang key of the action the prompt offers, what E does
+const HANDLERS: &[(InteractKind, &str, Handler)] = &[
+    (InteractKind::Sign, "interact.sign", read_sign),
+    (InteractKind::Teleporter, "interact.teleporter", use_teleporter),
+    (InteractKind::Villager, "interact.villager", trade_with_villager),
+    (InteractKind::Bike, "interact.bike", ride_bike),
+];
+
+// what `picked` (see Controller::pick) can be interacted with, if it's in reach
+pub fn at(picked: Option<(Target, f32)>, player: &Player, planet: &PlanetData, mobs: &Mobs) -> Option<Interactable> {
+    let (target, _) = picked?;
+    let (kind, center) = match target {
+        Target::Block(id) => {
+            let kind = match planet.entity(id)? {
+                BlockEntity::Sign { .. } => InteractKind::Sign,
+                BlockEntity::Teleporter { .. } => InteractKind::Teleporter,
+            };
+            (kind, CoordSystem::get_block_center(id.face, id.u, id.v, id.layer, planet.resolution))
+        },
+        Target::Mob(e) => {
+            let kind = match mobs.kind(e)? {
+                MobKind::Villager => InteractKind::Villager,
+                MobKind::Bike => InteractKind::Bike,
+                _ => return None,
+            };
+            (kind, mobs.transforms.get(e)?.center())
+        },
+    };
+    (center.distance(player.position) <= INTERACT_REACH).then_some(Interactable { kind, target })
+}
+
+// the hud line for `interactable`
+pub fn prompt(interactable: &Interactable) -> Option<String> {
+    let (_, key, _) = HANDLERS.iter().find(|(kind, _, _)| *kind == interactable.kind)?;
+    Some(tr!("interact.prompt", tr!(key)))
+}
+
+pub fn run(ctx: &mut InteractContext, interactable: Interactable) {
+    if let Some((_, _, handler)) = HANDLERS.iter().find(|(kind, _, _)| *kind == interactable.kind) {
+        handler(ctx, interactable.target);
+    }
+}
+
+// --- HANDLERS ---
+
+fn read_sign(ctx: &mut InteractContext, target: Target) {
+    let Some(id) = target.block() else { return };
+    if let Some(BlockEntity::Sign { text, .. }) = ctx.planet.entity(id) {
+        ctx.console.log(&tr!("interact.sign.reads", text), [1.0, 1.0, 0.8]);
+    }
+}
+
+fn use_teleporter(ctx: &mut InteractContext, target: Target) {
+    let Some(id) = target.block() else { return };
+    if !ctx.travel.activate(id, ctx.planet) {
+        let channel = teleporter::channel(ctx.planet, id).unwrap_or_default();
+        ctx.console.log(&tr!("interact.teleporter.unlinked", channel), [1.0, 0.5, 0.0]);
+    }
+}
+
+// a greeting, and the villager's offers (see trade.rs)
+fn trade_with_villager(ctx: &mut InteractContext, target: Target) {
+    let Target::Mob(e) = target else { return };
+    // each villager sticks to one of the greetings
+    let line = tr!(&format!("interact.villager.greeting{}", e.index() % 3));
+    ctx.console.log(&tr!("interact.villager.says", line), [0.8, 1.0, 0.8]);
+    ctx.trade.open(e);
+}
+
+fn ride_bike(ctx: &mut InteractContext, target: Target) {
+    let Target::Mob(e) = target else { return };
+    if !ctx.mobs.ride(e) { return; }
+    if let Some(bike) = ctx.mobs.transforms.get(e) {
+        ctx.controller.chase = Some(ChaseCam::new(bike.position, bike.rotation));
+    }
+}
@@ -0,0 +1,150 @@
+//inventory.rs
+
+// the blocks the player carries, in a hotbar of HOTBAR_SLOTS stacks. mining a block puts it on a stack of its
+// kind with room, or in the first empty slot, and placing takes one from the slot that's selected (the
+// controller keeps which, the wheel and number keys pick it). villagers trade stacks for other stacks (see
+// trade.rs). kept in inventory.dat next to level.dat and saved along with it.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::codec::*;
+use crate::common::BlockType;
+use crate::world::write_atomic;
+
+// --- CONSTANTS ---
+pub const HOTBAR_SLOTS: usize = 9;
+pub const MAX_STACK: u32 = 64;
+const INVENTORY_FILE: &str = "inventory.dat";
+const INVENTORY_MAGIC: &[u8; 4] = b"VXNI";
+const INVENTORY_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Stack {
+    pub block: BlockType,
+    pub count: u32,
+}
+
+#[derive(Clone, Default)]
+pub struct Inventory {
+    pub slots: [Option<Stack>; HOTBAR_SLOTS],
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // a missing file is a player who hasn't mined anything yet
+    pub fn load(dir: &Path) -> io::Result<Self> {
+        match fs::read(dir.join(INVENTORY_FILE)) {
+            Ok(bytes) => Self::decode(&bytes),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        write_atomic(&dir.join(INVENTORY_FILE), &self.encode())
+    }
+
+    // blocks by name, so custom ones keep their stacks whatever order plugins registered them in.
+    // death markers keep what they hold the same way (see marker.rs)
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(INVENTORY_MAGIC);
+        put_u32(&mut out, INVENTORY_VERSION);
+        for slot in &self.slots {
+            put_str(&mut out, slot.map_or("", |s| s.block.name()));
+            put_u32(&mut out, slot.map_or(0, |s| s.count));
+        }
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ByteReader::new(bytes);
+        if r.bytes(4)? != INVENTORY_MAGIC { return Err(invalid("inventory.dat has a bad header")); }
+        let version = r.u32()?;
+        if version != INVENTORY_VERSION {
+            return Err(invalid(&format!("unsupported inventory version {}", version)));
+        }
+
+        let mut inventory = Self::new();
+        for slot in &mut inventory.slots {
+            let name = r.str()?;
+            let count = r.u32()?.min(MAX_STACK);
+            // a block whose plugin is gone leaves its slot empty
+            *slot = BlockType::parse(&name).filter(|_| count > 0).map(|block| Stack { block, count });
+        }
+        Ok(inventory)
+    }
+
+    // what the block in `slot` is, if there's any left
+    pub fn block(&self, slot: usize) -> Option<BlockType> {
+        self.slots.get(slot)?.map(|s| s.block)
+    }
+
+    // false if there's no room for it
+    pub fn add(&mut self, block: BlockType) -> bool {
+        let stack = self.slots.iter().position(|s| s.is_some_and(|s| s.block == block && s.count < MAX_STACK))
+            .or_else(|| self.slots.iter().position(Option::is_none));
+        let Some(i) = stack else { return false };
+        let slot = self.slots[i].get_or_insert(Stack { block, count: 0 });
+        slot.count += 1;
+        true
+    }
+
+    // how many of `block` there are across every stack
+    pub fn count(&self, block: BlockType) -> u32 {
+        self.slots.iter().flatten().filter(|s| s.block == block).map(|s| s.count).sum()
+    }
+
+    // hands `give` over for `get`, all or nothing: false, and nothing changes, if the blocks aren't there or
+    // there's no room for what comes back
+    pub fn exchange(&mut self, give: Stack, get: Stack) -> bool {
+        let mut after = self.clone();
+        let mut owed = give.count;
+        for slot in &mut after.slots {
+            if let Some(s) = slot.as_mut().filter(|s| s.block == give.block) {
+                let n = s.count.min(owed);
+                s.count -= n;
+                owed -= n;
+            }
+            if slot.is_some_and(|s| s.count == 0) { *slot = None; }
+        }
+        if owed > 0 || !(0..get.count).all(|_| after.add(get.block)) { return false; }
+        *self = after;
+        true
+    }
+
+    // moves whatever of `other` fits in here, returning how many blocks came across
+    pub fn absorb(&mut self, other: &mut Inventory) -> u32 {
+        let mut moved = 0;
+        for slot in &mut other.slots {
+            while let Some(s) = slot.as_mut() {
+                if !self.add(s.block) { break; }
+                moved += 1;
+                s.count -= 1;
+                if s.count == 0 { *slot = None; }
+            }
+        }
+        moved
+    }
+
+    // the first slot holding `block`
+    pub fn slot_of(&self, block: BlockType) -> Option<usize> {
+        self.slots.iter().position(|s| s.is_some_and(|s| s.block == block))
+    }
+
+    // one block off the stack in `slot`
+    pub fn take(&mut self, slot: usize) -> Option<BlockType> {
+        let stack = self.slots.get_mut(slot)?;
+        let block = stack.as_ref()?.block;
+        match stack {
+            Some(s) if s.count > 1 => s.count -= 1,
+            _ => *stack = None,
+        }
+        Some(block)
+    }
+}
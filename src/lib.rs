@@ -0,0 +1,17 @@
+// engine lib.rs
+
+// the headless core of the engine: planet data, terrain generation, the cube-sphere coordinate system,
+// block physics and mesh generation. nothing in here needs a window or a gpu, so tools such as map renderers
+// or servers can build the same geometry the game draws:
+//
+//     let planet = voxanet::common::PlanetData::with_seed(256, 42);
+//     let key = voxanet::common::ChunkKey { face: 0, u_idx: 0, v_idx: 0 };
+//     let (verts, inds) = voxanet::gen::MeshGen::build_chunk(key, &planet);
+//
+// the game binary uses these modules from here too, so both always agree.
+
+pub mod common;
+pub mod gen;
+pub mod lighting;
+pub mod noise;
+pub mod physics;
@@ -1,67 +1,115 @@
 //lighting.rs
 
+// sunlight for meshing. a block is lit by how little covers it: looking up to SUN_REACH blocks straight up, every
+// opaque block in the way takes COVER_LOSS off, so the mouth of a cave is dim and anything deeper is dark.
+// the natural surface always gets full sun. results are cached on the planet, add_block and remove_block drop
+// the column an edit shades.
+
 use crate::common::*;
-use crate::gen::CoordSystem;
-use std::collections::{VecDeque, HashSet};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 pub struct LightEngine;
 
 impl LightEngine {
-    const MAX_LIGHT: u8 = 15;
+    pub const MAX_LIGHT: u8 = 15;
     const SUNLIGHT_START: u8 = 15;
-    
-    pub fn calculate_light(id: BlockId, planet: &mut PlanetData) -> u8 {
-        if let Some(&cached) = planet.light_cache.get(&id) {
+    // blocks above that can shade a block
+    const SUN_REACH: u32 = 8;
+    const COVER_LOSS: u8 = 8;
+    // brightness of a block no sun reaches, so caves aren't pitch black
+    const AMBIENT: f32 = 0.15;
+
+    pub fn calculate_light(id: BlockId, planet: &PlanetData) -> u8 {
+        if let Some(cached) = planet.light_cache.get(id) {
             return cached;
         }
-        
+
         let light = Self::trace_sunlight(id, planet);
         planet.light_cache.insert(id, light);
         light
     }
-    
+
+    // colour multiplier for light at `id`. faces take it from the open cells around each of their corners
+    pub fn brightness(id: BlockId, planet: &PlanetData) -> f32 {
+        (Self::calculate_light(id, planet) as f32 / Self::MAX_LIGHT as f32).max(Self::AMBIENT)
+    }
+
     fn trace_sunlight(id: BlockId, planet: &PlanetData) -> u8 {
-        let res = planet.resolution;
+        // keeps the terrain bright however the ground around it has been built up
+        if id.layer >= planet.terrain.get_height(id.face, id.u, id.v) {
+            return Self::SUNLIGHT_START;
+        }
         let mut current_light = Self::SUNLIGHT_START;
-        
-        for i in 1..=8 {
-            let check_layer = id.layer as i32 + i;
-            if check_layer >= res as i32 {
+
+        for i in 1..=Self::SUN_REACH {
+            let check_id = BlockId { layer: id.layer + i, ..id };
+            if check_id.layer >= planet.resolution {
                 break;
             }
-            
-            let check_id = BlockId {
-                face: id.face,
-                layer: check_layer as u32,
-                u: id.u,
-                v: id.v,
-            };
-            
-            if planet.exists(check_id) {
-                current_light = current_light.saturating_sub(8);
+
+            // glass lets the sun through
+            if planet.exists(check_id) && !planet.block_type(check_id).see_through() {
+                current_light = current_light.saturating_sub(Self::COVER_LOSS);
                 if current_light == 0 {
                     return 0;
                 }
             }
         }
-        
+
         current_light
     }
-    
-    pub fn propagate_area(center: BlockId, planet: &mut PlanetData, radius: u32) {
-        let res = planet.resolution;
-        
-        for du in -(radius as i32)..=(radius as i32) {
-            for dv in -(radius as i32)..=(radius as i32) {
-                for dl in -(radius as i32)..=(radius as i32) {
-                    let u = (center.u as i32 + du).clamp(0, res as i32 - 1) as u32;
-                    let v = (center.v as i32 + dv).clamp(0, res as i32 - 1) as u32;
-                    let l = (center.layer as i32 + dl).clamp(0, res as i32 - 1) as u32;
-                    
-                    let id = BlockId { face: center.face, layer: l, u, v };
-                    planet.light_cache.remove(&id);
-                }
-            }
+}
+
+// the planet's cached light. copies of the planet (the mesh workers get one each) share it, but one taken before
+// the latest edit can only read: light it worked out from the blocks as they were isn't written back
+#[derive(Clone, Default)]
+pub struct LightCache {
+    shared: Arc<Mutex<SharedLight>>,
+    // the edit this copy of the planet is at
+    revision: u64,
+}
+
+#[derive(Default)]
+struct SharedLight {
+    revision: u64,
+    light: HashMap<BlockId, u8>,
+}
+
+impl LightCache {
+    fn lock(&self) -> MutexGuard<'_, SharedLight> {
+        self.shared.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn get(&self, id: BlockId) -> Option<u8> {
+        self.lock().light.get(&id).copied()
+    }
+
+    pub fn insert(&self, id: BlockId, light: u8) {
+        let mut shared = self.lock();
+        if shared.revision == self.revision {
+            shared.light.insert(id, light);
         }
     }
-}
\ No newline at end of file
+
+    // forgets the light of `id` and of the blocks under it that it can shade
+    pub fn invalidate(&mut self, id: BlockId) {
+        self.revision = {
+            let mut shared = self.lock();
+            shared.revision += 1;
+            for layer in id.layer.saturating_sub(LightEngine::SUN_REACH)..=id.layer {
+                shared.light.remove(&BlockId { layer, ..id });
+            }
+            shared.revision
+        };
+    }
+
+    pub fn clear(&mut self) {
+        self.revision = {
+            let mut shared = self.lock();
+            shared.revision += 1;
+            shared.light.clear();
+            shared.revision
+        };
+    }
+}
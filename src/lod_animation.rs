@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::time::Instant;
-use crate::common::{ChunkKey, LodKey, ChunkMesh};
+use crate::common::{ChunkKey, LodKey};
+use crate::renderer::ChunkMesh;
 
 #[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
 pub enum AnyKey {
@@ -8,10 +9,15 @@ pub enum AnyKey {
     Lod(LodKey),
 }
 
+// slow, cinematic transition between lods
+const DEFAULT_FADE_DURATION: f32 = 2.0;
+
 pub struct FadeState {
     pub mesh: ChunkMesh,
     pub start_time: Instant,
+    #[allow(dead_code)]
     pub start_alpha: f32, 
+    #[allow(dead_code)]
     pub target_alpha: f32, 
     pub duration: f32,
 }
@@ -27,8 +33,7 @@ impl LodAnimator {
         Self {
             dying_chunks: HashMap::new(),
             spawning_chunks: HashMap::new(),
-            // CHANGED: Increased to 2.0 seconds for a very slow, cinematic transition
-            fade_duration: 2.0, 
+            fade_duration: DEFAULT_FADE_DURATION, 
         }
     }
 
@@ -39,10 +44,14 @@ impl LodAnimator {
         t * t * (3.0 - 2.0 * t)
     }
 
+    // reduce motion: new meshes pop in and old ones vanish on the spot
+    pub fn set_reduce_motion(&mut self, on: bool) {
+        self.fade_duration = if on { 0.0 } else { DEFAULT_FADE_DURATION };
+    }
+
     pub fn start_spawn(&mut self, key: AnyKey) {
-        if let Some(_) = self.dying_chunks.remove(&key) {
-             // if reviving, we just reset.
-        }
+        // if reviving, we just reset.
+        self.dying_chunks.remove(&key);
         self.spawning_chunks.insert(key, Instant::now());
     }
 
@@ -57,8 +66,16 @@ impl LodAnimator {
         self.spawning_chunks.remove(&key);
     }
 
+    // drops every fading mesh at once, returns how many there were
+    pub fn drop_dying(&mut self) -> usize {
+        let n = self.dying_chunks.len();
+        self.dying_chunks.clear();
+        n
+    }
+
     pub fn get_opacity(&self, key: AnyKey, now: Instant) -> f32 {
         if let Some(start) = self.spawning_chunks.get(&key) {
+            if self.fade_duration <= 0.0 { return 1.0; }
             let elapsed = (now - *start).as_secs_f32();
             let linear_t = elapsed / self.fade_duration;
             return Self::smoothstep(linear_t);
@@ -74,7 +91,7 @@ impl LodAnimator {
             let elapsed = (now - state.start_time).as_secs_f32();
             let linear_t = elapsed / state.duration;
             
-            if linear_t >= 1.0 {
+            if state.duration <= 0.0 || linear_t >= 1.0 {
                 to_remove.push(*key);
             } else {
                 let alpha = 1.0 - Self::smoothstep(linear_t); 
@@ -0,0 +1,159 @@
+//lod_batch.rs
+
+// far out the quadtree is mostly small lods of a few hundred triangles each, where the draw calls cost more than
+// the triangles. lods of the same level in the same BATCH_SPAN x BATCH_SPAN block of their face are merged into
+// one vertex and index buffer and drawn once. a lod joins its batch when it has finished fading in and leaves it
+// when it's retired or remeshed, and only batches whose members changed are rebuilt, once per frame. the lods
+// keep their own buffers, for the fades and for drawing on their own until their batch has caught up.
+
+use std::collections::{HashMap, HashSet};
+use wgpu::util::DeviceExt;
+use crate::common::{LodKey, Obb, Vertex};
+use crate::renderer::{ChunkMesh, LocalUniform};
+
+// --- CONSTANTS ---
+// lods per side of a batch
+const BATCH_SPAN: u32 = 4;
+// lods with more indices than this are big enough to draw on their own
+const MAX_MEMBER_INDICES: usize = 6144;
+// a batch of one is just a copy
+const MIN_MEMBERS: usize = 2;
+
+// same level, same block of the face
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct BatchKey {
+    face: u8,
+    size: u32,
+    x: u32,
+    y: u32,
+}
+
+impl BatchKey {
+    fn of(key: LodKey) -> Self {
+        let span = key.size * BATCH_SPAN;
+        Self { face: key.face, size: key.size, x: key.x / span, y: key.y / span }
+    }
+}
+
+#[derive(Default)]
+pub struct LodBatches {
+    // index lists of the lods small enough to batch, since a merged list has to be rebased on the cpu.
+    // the vertices are copied on the gpu
+    indices: HashMap<LodKey, Vec<u32>>,
+    members: HashMap<BatchKey, Vec<LodKey>>,
+    built: HashMap<BatchKey, ChunkMesh>,
+    // lods drawn through a built batch
+    batched: HashSet<LodKey>,
+    dirty: HashSet<BatchKey>,
+}
+
+impl LodBatches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // a lod was uploaded, keeps its indices if it's small enough to batch later
+    pub fn uploaded(&mut self, key: LodKey, indices: &[u32]) {
+        self.remove(key);
+        if indices.len() <= MAX_MEMBER_INDICES {
+            self.indices.insert(key, indices.to_vec());
+        }
+    }
+
+    // a lod is fully faded in and can be drawn with the others
+    pub fn join(&mut self, key: LodKey) {
+        if !self.indices.contains_key(&key) { return; }
+        let batch = BatchKey::of(key);
+        let members = self.members.entry(batch).or_default();
+        if !members.contains(&key) {
+            members.push(key);
+            self.dirty.insert(batch);
+        }
+    }
+
+    // a lod is gone or about to be replaced. its batch is rebuilt without it before the next draw
+    pub fn remove(&mut self, key: LodKey) {
+        if self.indices.remove(&key).is_none() { return; }
+        let batch = BatchKey::of(key);
+        if let Some(members) = self.members.get_mut(&batch) {
+            if let Some(i) = members.iter().position(|k| *k == key) {
+                members.remove(i);
+                self.dirty.insert(batch);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn is_batched(&self, key: &LodKey) -> bool {
+        self.batched.contains(key)
+    }
+
+    pub fn meshes(&self) -> impl Iterator<Item = &ChunkMesh> {
+        self.built.values()
+    }
+
+    // merges the batches whose members changed
+    pub fn rebuild(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, layout: &wgpu::BindGroupLayout, lods: &HashMap<LodKey, ChunkMesh>) {
+        if self.dirty.is_empty() { return; }
+        let mut enc = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("LOD Batch Copy") });
+        for batch in std::mem::take(&mut self.dirty) {
+            if self.built.remove(&batch).is_some() {
+                self.batched.retain(|k| BatchKey::of(*k) != batch);
+            }
+            let Some(members) = self.members.get_mut(&batch) else { continue };
+            members.retain(|k| lods.contains_key(k));
+            if members.is_empty() {
+                self.members.remove(&batch);
+                continue;
+            }
+            if members.len() < MIN_MEMBERS { continue; }
+            self.built.insert(batch, merge(device, &mut enc, layout, members, lods, &self.indices));
+            self.batched.extend(members.iter().copied());
+        }
+        queue.submit(std::iter::once(enc.finish()));
+    }
+}
+
+// one mesh of `members`, the vertex buffers copied one after another and the indices moved up to match
+fn merge(device: &wgpu::Device, enc: &mut wgpu::CommandEncoder, layout: &wgpu::BindGroupLayout, members: &[LodKey], lods: &HashMap<LodKey, ChunkMesh>, indices: &HashMap<LodKey, Vec<u32>>) -> ChunkMesh {
+    let stride = std::mem::size_of::<Vertex>() as u64;
+    let num_verts: usize = members.iter().map(|k| lods[k].num_verts).sum();
+    let v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("LOD Batch"), size: (num_verts as u64 * stride).max(stride), usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+    });
+    let mut merged = Vec::new();
+    let (mut base, mut min, mut max) = (0, glam::Vec3::splat(f32::MAX), glam::Vec3::splat(f32::MIN));
+    // the members lie side by side on one face, so they share the first one's axes near enough
+    let bounds = Obb::fit(lods[&members[0]].bounds.axes, members.iter().flat_map(|k| lods[k].bounds.corners()));
+    for key in members {
+        let mesh = &lods[key];
+        enc.copy_buffer_to_buffer(&mesh.v_buf, 0, &v_buf, base as u64 * stride, mesh.num_verts as u64 * stride);
+        merged.extend(indices[key].iter().map(|i| i + base));
+        base += mesh.num_verts as u32;
+        min = min.min(mesh.center - glam::Vec3::splat(mesh.radius));
+        max = max.max(mesh.center + glam::Vec3::splat(mesh.radius));
+    }
+    let i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some("LOD Batch"), contents: bytemuck::cast_slice(&merged), usage: wgpu::BufferUsages::INDEX });
+    let lod = lods[&members[0]].lod;
+    let uniform_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("LOD Batch Uniform"),
+        contents: bytemuck::cast_slice(&[LocalUniform { model: glam::Mat4::IDENTITY.to_cols_array(), params: [1.0, 0.0, lod, 0.0] }]),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[wgpu::BindGroupEntry { binding: 0, resource: uniform_buf.as_entire_binding() }],
+        label: None,
+    });
+    ChunkMesh {
+        v_buf, i_buf, num_inds: merged.len() as u32, num_verts, uniform_buf, bind_group,
+        center: (min + max) * 0.5,
+        radius: min.distance(max) * 0.5,
+        bounds,
+        lod,
+        reflectivity: 0.0,
+    }
+}
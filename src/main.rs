@@ -1,28 +1,113 @@
 // engine main.rs
 
-mod common;
-mod gen;
-mod physics;
+// terrain, meshing and the coordinate system come from the library, see lib.rs
+use voxanet::{common, gen, lighting, noise, physics};
 mod entity;
 mod controller;
 mod renderer;
-mod noise;
 mod lod_animation;
+mod lod_batch;
 mod cmd;
+mod clipboard;
 mod system_diagnostics; 
+mod world;
+mod codec;
+mod net;
+mod server;
+mod rcon;
+mod i18n;
+mod settings;
+mod settings_menu;
+mod shake;
+mod schematic;
+mod prefab;
+mod blueprint;
+mod touch;
+mod gamepad;
+mod debug_view;
+mod monitor;
+mod sign;
+mod music;
+mod stats;
+mod mob;
+mod vehicle;
+mod teleporter;
+mod marker;
+mod capture;
+mod timelapse;
+mod recorder;
+mod panorama;
+mod mesh_profile;
+mod mesh_pool;
+mod frame_graph;
+mod pipelines;
+mod instancing;
+mod determinism;
+mod health;
+mod stamina;
+mod oxygen;
+mod temperature;
+mod village;
+mod pathfind;
+mod interact;
+mod ecs;
+mod events;
+mod plugin;
+mod weather;
+mod inventory;
+mod bindings;
+mod alias;
+mod toast;
+mod pacing;
+mod tick;
+mod explosion;
+mod trade;
 
 
 
 use winit::event::{DeviceEvent, ElementState, Event, MouseButton, WindowEvent}; // Added DeviceEvent
-use winit::event_loop::EventLoop;
+use winit::event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget};
 use winit::window::{WindowBuilder, CursorGrabMode};
-use winit::keyboard::{Key, PhysicalKey, KeyCode};
-use crate::common::PlanetData;
-use crate::renderer::Renderer;
-use crate::controller::Controller;
+use winit::keyboard::{Key, PhysicalKey, KeyCode, ModifiersState};
+use crate::common::{BlockEntity, BlockId, PlanetData};
+use crate::renderer::{Menu, Renderer};
+use crate::controller::{ChaseCam, Controller, Target};
+use crate::touch::TouchAction;
+use crate::gamepad::PadAction;
+use crate::prefab::Prefabs;
 use crate::entity::Player;
-use crate::cmd::Console;
-use crate::system_diagnostics::SystemDiagnostics;
+use crate::cmd::{Console, CommandContext, Permission, AUTOEXEC};
+use crate::system_diagnostics::{AutoTune, MemoryMonitor, PowerMonitor, SystemDiagnostics};
+use crate::world::{Autosave, World, WORLD_DIR};
+use crate::net::{Client, Message};
+use crate::i18n::tr;
+use crate::settings::{Settings, CLIENT_CONFIG};
+use crate::settings_menu::SettingsMenu;
+use crate::trade::TradeMenu;
+use crate::clipboard::Clipboard;
+use crate::toast::Toasts;
+use crate::pacing::{FramePacer, Pace};
+use crate::tick::SimClock;
+use crate::bindings::{Action, Bindings, BINDINGS_CONFIG};
+use crate::alias::{Aliases, ALIAS_CONFIG};
+use crate::prefab::PREFAB_DIR;
+use crate::blueprint::Blueprint;
+use crate::debug_view::DebugCamera;
+use crate::monitor::Monitors;
+use crate::music::{Music, MusicContext, MUSIC_DIR};
+use crate::stats::Stats;
+use crate::inventory::Inventory;
+use crate::health::DamageSource;
+use crate::mob::{Mobs, ATTACK_REACH};
+use crate::teleporter::Travel;
+use crate::interact::InteractContext;
+use crate::events::{EventBus, GameEvent, Subscriber};
+use crate::plugin::{Plugins, TickContext};
+use crate::marker::{MarkerEdit, Markers};
+use crate::timelapse::{TimeLapse, LAPSE_HEIGHT, LAPSE_WIDTH};
+use crate::recorder::Recorder;
+use crate::panorama::{Panorama, FACE_SIZE};
+use std::sync::Arc;
 use std::time::Instant;
 
 
@@ -30,37 +115,265 @@ use std::time::Instant;
 fn main() {
     
     SystemDiagnostics::print_startup_info(); 
+
+    // --headless [--port N]        dedicated server, no window
+    //   [--rcon-port N] [--rcon-token T]  remote console (token also read from VOXANET_RCON_TOKEN)
+    // --connect host:port [--name] [--secret S]  join a server instead of the local world, the secret is an op's
+    // --lang code                  ui language from lang/<code>.lang (also read from VOXANET_LANG)
+    // --terrain-hash [--seed N] [--resolution N] [--meshes]  print digests of the generated terrain and exit
+    let args: Vec<String> = std::env::args().collect();
+    let arg_value = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).cloned();
+
+    if let Some(code) = arg_value("--lang").or_else(|| std::env::var(i18n::LANGUAGE_ENV).ok()) {
+        if let Err(e) = i18n::set_language(&code) {
+            println!("Failed to load language '{}': {}", code, e);
+        }
+    }
+
+    if args.iter().any(|a| a == "--terrain-hash") {
+        let seed = arg_value("--seed").and_then(|s| s.parse().ok());
+        let resolution = arg_value("--resolution").and_then(|r| r.parse().ok());
+        determinism::run(seed, resolution, args.iter().any(|a| a == "--meshes"));
+        return;
+    }
+
+    if args.iter().any(|a| a == "--headless") {
+        // server.cfg first, command line and environment win over it
+        let mut config = server::ServerConfig::load(std::path::Path::new(server::SERVER_CONFIG));
+        if let Some(port) = arg_value("--port").and_then(|p| p.parse().ok()) { config.port = port; }
+        if let Some(port) = arg_value("--rcon-port").and_then(|p| p.parse().ok()) { config.rcon_port = Some(port); }
+        if let Some(token) = arg_value("--rcon-token").or_else(|| std::env::var(rcon::TOKEN_ENV).ok()) { config.rcon_token = Some(token); }
+        server::run_headless(config);
+        return;
+    }
+
     let event_loop = EventLoop::new().unwrap();
     let window = WindowBuilder::new().with_title("voxanet").build(&event_loop).unwrap();
     
     let mut renderer = pollster::block_on(Renderer::new(&window));
     let mut controller = Controller::new();
+    controller.bindings = Bindings::load(std::path::Path::new(BINDINGS_CONFIG));
     let mut player = Player::new();
-    let mut planet = PlanetData::new(49); // Keep high resolution
+    let mut autosave = Autosave::new(WORLD_DIR);
+    let first_run = !std::path::Path::new(CLIENT_CONFIG).exists();
+    let mut settings = Settings::load(std::path::Path::new(CLIENT_CONFIG));
 
+    let mut settings_menu = SettingsMenu::new();
+    let mut trade_menu = TradeMenu::new();
     let mut console = Console::new();
-    console.log("Welcome to voxanet.", [0.0, 1.0, 0.0]);
-    console.log("Press ` to open console.", [1.0, 1.0, 1.0]);
+    let mut toasts = Toasts::new();
+    let mut clipboard = Clipboard::new();
+    let mut aliases = Aliases::load(std::path::Path::new(ALIAS_CONFIG));
+    // ctrl (cmd on macos) for the console's copy and paste
+    let mut modifiers = ModifiersState::empty();
+    console.log(&tr!("main.welcome"), [0.0, 1.0, 0.0]);
+    console.log(&tr!("main.console_hint"), [1.0, 1.0, 1.0]);
 
+    // no client.cfg yet: start from a preset that suits this machine instead of one size for everyone
+    if first_run {
+        let preset = SystemDiagnostics::detect_preset(&renderer.adapter_info);
+        settings.apply_preset(preset);
+        console.log(&tr!("main.preset", preset.name()), [0.8, 0.8, 0.8]);
+        if let Err(e) = settings.save(std::path::Path::new(CLIENT_CONFIG)) {
+            console.log(&tr!("cmd.option.save_failed", e), [1.0, 0.0, 0.0]);
+        }
+    }
+
+    let (mut prefabs, prefab_errors) = Prefabs::load(PREFAB_DIR);
+    for (path, e) in prefab_errors {
+        console.log(&tr!("prefab.load_failed", path.display(), e), [1.0, 0.5, 0.0]);
+    }
+    let mut blueprint: Option<Blueprint> = None;
+    let mut monitors = Monitors::new();
+    let mut music = Music::load(MUSIC_DIR);
+    let mut mobs = Mobs::new();
+    let mut travel = Travel::new();
+    let mut events = EventBus::new();
+    let mut timelapse: Option<TimeLapse> = None;
+    let mut recorder: Option<Recorder> = None;
+    let mut panorama: Option<Panorama> = None;
+    let panorama_errors = std::sync::mpsc::channel();
+    let mut autotune = AutoTune::new();
+    let mut power = PowerMonitor::new();
+    let mut memory = MemoryMonitor::new();
+    let mut focused = true;
+    let mut pacer = FramePacer::new();
+    let mut sim_clock = SimClock::new();
 
-    // initialize player spawn
+    let name = arg_value("--name").unwrap_or_else(|| "player".to_string());
+    let mut client = arg_value("--connect").and_then(|addr| {
+        match Client::connect(&addr, &name, &arg_value("--secret").unwrap_or_default()) {
+            Ok(client) => {
+                console.log(&tr!("net.connecting", addr), [1.0, 1.0, 0.0]);
+                Some(client)
+            }
+            Err(e) => {
+                console.log(&tr!("net.connect_failed", addr, e), [1.0, 0.0, 0.0]);
+                None
+            }
+        }
+    });
 
-    // we query the height at face 0, u=res/2, v=res/2 (roughly the "North Pole" of face 0)
-    let center = planet.resolution / 2;
-    let ground_level = planet.terrain.get_height(0, center, center);
-    let spawn_h = crate::gen::CoordSystem::get_layer_radius(ground_level, planet.resolution) + 10.0;
-   
+    // the server owns the world while connected, so never write it over the local save
+    let online = client.is_some();
+    if online {
+        autosave.interval = 0.0;
+        player.health.enabled = false;
+    }
 
-    player.spawn(glam::Vec3::new(0.0, spawn_h, 0.0));
+    // resume the saved world if there is one
+    let (mut planet, loaded) = World::open(autosave.dir.as_path(), &mut player);
+    match loaded {
+        Ok(true) => console.log(&tr!("world.loaded", planet.chunks.len()), [0.0, 1.0, 0.0]),
+        Ok(false) => {}
+        Err(e) => console.log(&tr!("world.load_failed", e), [1.0, 0.0, 0.0]),
+    }
+    let mut plugins = Plugins::load(&planet, &mut console);
+    // a server's world isn't ours, so its stats start fresh and aren't saved
+    let mut stats = if online { Stats::new() } else {
+        Stats::load(&autosave.dir).unwrap_or_else(|e| {
+            console.log(&tr!("stats.load_failed", e), [1.0, 0.0, 0.0]);
+            Stats::new()
+        })
+    };
+    let mut inventory = if online { Inventory::new() } else {
+        Inventory::load(&autosave.dir).unwrap_or_else(|e| {
+            console.log(&tr!("inventory.load_failed", e), [1.0, 0.0, 0.0]);
+            Inventory::new()
+        })
+    };
+    // a server sends the markers we may see once we've joined
+    let mut markers = if online { Markers::new() } else {
+        Markers::load(&autosave.dir).unwrap_or_else(|e| {
+            console.log(&tr!("marker.load_failed", e), [1.0, 0.0, 0.0]);
+            Markers::new()
+        })
+    };
+    markers.me = name;
+    // the player's own setup, run as if typed before the first frame. of what commands hand back to the caller
+    // only the render mode is picked up here, the rest (quitting, signs, /server) needs the console
+    if std::path::Path::new(AUTOEXEC).exists() {
+        let mut ctx = CommandContext::new(&mut player, &mut planet, &mut autosave, Permission::Owner);
+        ctx.settings = Some(&mut settings);
+        ctx.bindings = Some(&mut controller.bindings);
+        ctx.aliases = Some(&mut aliases);
+        ctx.prefabs = Some(&mut prefabs);
+        ctx.monitors = Some(&mut monitors);
+        ctx.music = Some(&mut music);
+        ctx.stats = Some(&mut stats);
+        ctx.mobs = Some(&mut mobs);
+        ctx.markers = Some(&mut markers);
+        ctx.plugins = Some(&mut plugins);
+        ctx.online = online;
+        ctx.fly_mode = controller.fly_mode;
+        if let Err(e) = console.exec_file(std::path::Path::new(AUTOEXEC), &mut ctx) {
+            console.log(&tr!("cmd.exec.failed", AUTOEXEC, e), [1.0, 0.0, 0.0]);
+        }
+        if let Some(mode) = ctx.render_mode { renderer.render_mode = mode; }
+    }
+    // the ground around the spawn is meshed before the first frame, a server's world only arrives once we've joined
+    if !online {
+        let started = Instant::now();
+        renderer.view_distance = settings.view_distance();
+        let warmed = renderer.warm_up(player.position, &planet);
+        console.log(&tr!("world.warmed_up", warmed, started.elapsed().as_millis()), [0.8, 0.8, 0.8]);
+    }
+    let mut net_timer = 0.0;
+    let mut last_position = player.position;
+    // the world as the renderer and its meshing threads see it, published once the frame's edits are in
+    let mut published = planet.publish();
     let mut last_time = Instant::now();
     let mut current_mode_first_person = false; 
 
     event_loop.run(move |event, target| {
         let now = Instant::now();
-        let dt = (now - last_time).as_secs_f32();
+        let frame_dt = (now - last_time).as_secs_f32();
         last_time = now;
 
-        // cursor locking logic 
+        // the world steps at the tick rate, or once a pass at 0. see tick.rs. only the simulation runs per tick,
+        // everything the player sees or that talks to the outside runs once a pass, further down
+        let (ticks, dt) = sim_clock.advance(frame_dt, settings.tick_rate);
+        for _ in 0..ticks {
+            sim_clock.start_tick(player.position, player.rotation);
+
+            // physics & player Update
+            controller.update_player(&mut player, &planet, dt, &settings);
+            if let Some(source) = player.health.take_death() {
+                console.log(&source.death_message(), [1.0, 0.3, 0.3]);
+                // what they carried stays where they fell, see marker.rs
+                markers.add_grave(player.position, std::mem::take(&mut inventory));
+                console.log(&tr!("marker.grave.left"), [1.0, 0.6, 0.3]);
+                player.spawn(World::spawn_point(&planet));
+                player.health.revive();
+            }
+            if markers.expire_graves(dt, settings.death_marker_minutes) > 0 {
+                console.log(&tr!("marker.grave.expired"), [1.0, 0.5, 0.0]);
+            }
+            match markers.recover_grave(player.position, &mut inventory) {
+                Some((moved, 0)) if moved > 0 => console.log(&tr!("marker.grave.recovered", moved), [0.0, 1.0, 0.0]),
+                Some((moved, left)) if moved > 0 => console.log(&tr!("marker.grave.full", moved, left), [1.0, 1.0, 0.0]),
+                _ => {}
+            }
+
+            mobs.update_sky(dt, settings.birds, player.position, &planet);
+            mobs.update_villages(dt, player.position, &planet);
+            let lurkers = settings.lurkers && player.health.enabled && !player.spectator && !controller.fly_mode;
+            if let Some((damage, push)) = mobs.update_lurkers(dt, lurkers, player.position, &planet) {
+                if player.health.hurt(DamageSource::Lurker, damage) { player.velocity += push; }
+            }
+            mobs.update(dt, &planet, controller.drive_input());
+            match mobs.ridden() {
+                Some((bike, body)) => {
+                    vehicle::seat(&mut player, (bike, body));
+                    if let Some(chase) = controller.chase.as_mut() {
+                        chase.follow(dt, bike.position, bike.rotation, body.velocity);
+                    }
+                },
+                // the bike went away under the rider (/spawn clear)
+                None => controller.chase = None,
+            }
+            for (kind, position) in mobs.take_spawned() {
+                events.emit(GameEvent::EntitySpawned { kind, position });
+            }
+            plugins.tick(dt, &mut TickContext { player: &player, online, planet: &mut planet, events: &mut events });
+            if let Some((from, dest)) = travel.update(dt, &mut player, &planet, online) {
+                if let Some(c) = &client { c.send(&Message::Teleport { from }); }
+                let channel = teleporter::channel(&planet, dest).unwrap_or_default().to_string();
+                console.log(&tr!("teleporter.arrived", channel), [0.6, 0.8, 1.0]);
+            }
+
+            // BLOCK CONTROLS IF CONSOLE OPEN
+            // Only update player/physics if console is NOT hijacking input
+            if !console.is_open && !settings_menu.open {
+                 // (Existing Physics & Player Update)
+                 let pad_actions = controller.update_gamepad(dt, &settings);
+                 controller.update_player(&mut player, &planet, dt, &settings);
+
+
+                 let width = renderer.config.width as f32;
+                 let height = renderer.config.height as f32;
+                 controller.cursor_id = controller.pick(&player, &planet, &mobs, width, height).filter(|_| !player.spectator).and_then(|(target, _)| target.block());
+
+                 // touch long press
+                 if let Some(action) = controller.touch.update() {
+                     touch_action(action, &mut controller, &player, &mut planet, &mut renderer, &prefabs, &mut events, &mut mobs, &mut inventory);
+                 }
+
+                 // gamepad triggers act like the mouse buttons, but a trigger has no drag so it paints one brush
+                 for action in pad_actions {
+                     click(action == PadAction::Place, &mut controller, &player, &mut planet, &mut renderer, &prefabs, &mut events, &mut mobs, &mut inventory);
+                     controller.stroke = None;
+                 }
+
+                 // orbit-mode click-drag painting
+                 for id in controller.stroke_targets(&planet) {
+                     let placed = controller.stroke.as_ref().is_some_and(|s| s.placed);
+                     edit_block(&mut planet, &mut events, id, placed, &mut inventory, controller.slot);
+                 }
+            }
+        }
+
+        // cursor locking logic
         if controller.first_person != current_mode_first_person {
             current_mode_first_person = controller.first_person;
             if current_mode_first_person {
@@ -71,72 +384,406 @@ fn main() {
                 renderer.window.set_cursor_visible(true);
             }
         }
-        
-        // physics & player Update
-        controller.update_player(&mut player, &planet, dt);
-        
+        if console.is_open || settings_menu.open {
+            let _ = renderer.window.set_cursor_grab(CursorGrabMode::None);
+            renderer.window.set_cursor_visible(true);
+        }
+
+        // AUTOSAVE
+        match autosave.update(frame_dt, &mut planet, &player) {
+            Some(Ok(n)) => {
+                console.log(&tr!("world.autosaved", n), [0.6, 0.6, 0.6]);
+                toasts.push(tr!("toast.autosaved"), [1.0, 1.0, 1.0]);
+                if let Err(e) = stats.save(&autosave.dir) {
+                    console.log(&tr!("stats.save_failed", e), [1.0, 0.0, 0.0]);
+                }
+                if let Err(e) = inventory.save(&autosave.dir) {
+                    console.log(&tr!("inventory.save_failed", e), [1.0, 0.0, 0.0]);
+                }
+                if let Err(e) = markers.save(&autosave.dir) {
+                    console.log(&tr!("marker.save_failed", e), [1.0, 0.0, 0.0]);
+                }
+            },
+            Some(Err(e)) => {
+                console.log(&tr!("world.autosave_failed", e), [1.0, 0.0, 0.0]);
+                toasts.push(tr!("toast.autosave_failed"), [1.0, 0.0, 0.0]);
+            },
+            None => {}
+        }
+
+        // NETWORK
+        if let Some(c) = client.as_mut() {
+            for msg in c.poll() {
+                match msg {
+                    Message::Welcome { resolution, seed, .. } => {
+                        planet = PlanetData::with_seed(resolution, seed);
+                        player.spawn(World::spawn_point(&planet));
+                        published = planet.publish();
+                        renderer.force_reload_all(&published, player.position);
+                        console.log(&tr!("net.joined"), [0.0, 1.0, 0.0]);
+                    },
+                    Message::BlockEdit { id, placed, block } => {
+                        if placed { planet.add_block(id, block); } else { planet.remove_block(id); }
+                        events.emit(if placed { GameEvent::BlockPlaced { id, block, remote: true } } else { GameEvent::BlockMined { id, block, remote: true } });
+                    },
+                    Message::ChunkBatch { chunks } => {
+                        for (key, mods) in chunks {
+                            planet.chunks.insert(key, Arc::new(mods));
+                            events.emit(GameEvent::ChunkLoaded(key));
+                        }
+                        // the chunks came in whole, not through add_block, so light worked out before them is stale
+                        planet.light_cache.clear();
+                        // the batch may have brought signs along
+                        planet.entity_revision += 1;
+                    },
+                    Message::SignEdit { id, text, facing } => {
+                        let entity = (!text.is_empty()).then_some(BlockEntity::Sign { text, facing });
+                        planet.set_entity(id, entity);
+                    },
+                    Message::TeleporterEdit { id, channel } => {
+                        planet.set_entity(id, (!channel.is_empty()).then_some(BlockEntity::Teleporter { channel }));
+                    },
+                    Message::MarkerSet { marker } => { markers.set(marker); },
+                    Message::MarkerRemove { owner, name } => { markers.remove(&owner, &name); },
+                    Message::Log { text } => console.log(&text, [1.0, 1.0, 0.0]),
+                    Message::Spectator { enabled } => player.spectator = enabled,
+                    Message::Correction { position } => {
+                        player.position = position;
+                        player.velocity = glam::Vec3::ZERO;
+                    },
+                    Message::Disconnect { reason } => console.log(&tr!("net.disconnected", reason), [1.0, 0.0, 0.0]),
+                    _ => {}
+                }
+            }
+
+            net_timer += frame_dt;
+            if c.connected && c.client_id.is_some() && net_timer >= 1.0 / server::TICK_RATE as f32 {
+                net_timer = 0.0;
+                c.send(&Message::PlayerMove { position: player.position, rotation: player.rotation });
+            }
+            renderer.update_remote_players(&c.players);
+            if !c.connected { client = None; }
+        }
+
+        // EVENTS
+        // however the player got somewhere this frame: walking, riding, a teleporter, a respawn
+        if player.position != last_position {
+            events.emit(GameEvent::PlayerMoved { from: last_position, to: player.position, flying: controller.fly_mode || player.spectator });
+            last_position = player.position;
+        }
+        let frame_events = events.drain();
+        if frame_events.iter().any(GameEvent::edits_world) {
+            published = planet.publish();
+        }
+        for event in frame_events {
+            renderer.on_event(&event, &planet);
+            stats.on_event(&event, &planet);
+            plugins.on_event(&event, &planet);
+            if let Some(c) = client.as_mut() { c.on_event(&event, &planet); }
+            // a move every frame would bury everything else
+            if settings.log_events && !matches!(event, GameEvent::PlayerMoved { .. }) {
+                console.log(&event.describe(), [0.6, 0.6, 0.6]);
+            }
+        }
+
+        // the view, the cursor and the hud follow the player where they're drawn, part way between the last two
+        // ticks (see the redraw), and the world gets it back afterwards
+        let ticked = (player.position, player.rotation);
+        (player.position, player.rotation) = sim_clock.interpolate(settings.tick_rate, ticked.0, ticked.1);
+
         // raycast & cursor Update
         let width = renderer.config.width as f32;
         let height = renderer.config.height as f32;
-        let ray_result = controller.raycast(&player, &planet, width, height, false);
-        controller.cursor_id = ray_result.map(|(id, _)| id);
-        
-        renderer.update_cursor(&planet, controller.cursor_id);
-        renderer.update_view(player.position, &planet);
-
-
-        // UPDATE ANIMATION
-        console.update_animation(dt);
-
-        // BLOCK CONTROLS IF CONSOLE OPEN
-        // Only update player/physics if console is NOT hijacking input
-        if !console.is_open {
-             // (Existing Physics & Player Update)
-             controller.update_player(&mut player, &planet, dt);
-             
-            
-             let width = renderer.config.width as f32;
-             let height = renderer.config.height as f32;
-             let ray_result = controller.raycast(&player, &planet, width, height, false);
-             controller.cursor_id = ray_result.map(|(id, _)| id);
+        // a mob in front of the block under the aim hides the cursor, a click goes to the mob
+        let picked = controller.pick(&player, &planet, &mobs, width, height).filter(|_| !player.spectator);
+        controller.cursor_id = picked.and_then(|(target, _)| target.block());
+        // what E would do, offered under the crosshair. not while riding, E gets off then
+        let interaction = interact::at(picked, &player, &planet, &mobs).filter(|_| controller.chase.is_none());
+        renderer.update_interact_hud(interaction.as_ref().and_then(interact::prompt));
+        trade_menu.update(&mobs, player.position);
+        renderer.update_hotbar(&inventory, controller.slot);
+
+        let cursor = controller.cursor_id.map(|id| (id, controller.brush_area(id, planet.resolution)));
+        renderer.update_grid(&planet, cursor.as_ref().filter(|_| controller.editor.is_some()));
+        renderer.update_cursor(&planet, cursor, settings.cursor_color);
+        // background and battery throttling
+        power.update(frame_dt);
+        renderer.throttled = !focused || power.on_battery;
+
+        // memory pressure: over 90% of the limit drops meshes and pulls the view in, under 70% lets it grow back
+        if let Some(rss) = memory.update(frame_dt) {
+            const MB: u64 = 1024 * 1024;
+            let limit = settings.memory_limit_mb as u64 * MB;
+            if limit > 0 && rss > limit / 10 * 9 {
+                let e = renderer.trim_memory();
+                console.log(&tr!("mem.evicted", rss / MB, settings.memory_limit_mb, e.queued, e.fading, (e.scale * 100.0).round()), [1.0, 0.5, 0.0]);
+                toasts.push(tr!("toast.low_memory", (e.scale * 100.0).round()), [1.0, 0.5, 0.0]);
+            } else if limit == 0 || rss < limit / 10 * 7 {
+                renderer.relax_memory();
+            }
+        }
+
+        // view distance comes from the settings, or is tuned on the fly from fps and mesh memory.
+        // a throttled frame rate says nothing about the hardware, so tuning waits until it's lifted
+        if settings.auto_render_distance && !renderer.throttled {
+            let (fps, mesh_bytes, budget) = (renderer.fps(), renderer.mesh_memory(), renderer.mesh_budget);
+            autotune.update(frame_dt, fps, mesh_bytes, budget, &mut renderer.view_distance);
+        } else if !settings.auto_render_distance {
+            renderer.view_distance = settings.view_distance();
+        }
+        renderer.apply_graphics(&settings);
+        controller.fov = settings.fov;
+        let menu = if settings_menu.open {
+            Some(Menu { title: tr!("settings.title"), lines: settings_menu.lines(&settings), hint: tr!("settings.hint") })
         } else {
-            
-             let _ = renderer.window.set_cursor_grab(CursorGrabMode::None);
-             renderer.window.set_cursor_visible(true);
+            trade_menu.lines(&mobs, &inventory).map(|lines| Menu { title: tr!("trade.title"), lines, hint: tr!("trade.hint") })
+        };
+        renderer.update_menu(menu);
+        renderer.update_view(controller.view_center(&player), player.velocity, &published);
+
+        // prefab selection box, placement ghost at the block a right click would fill, and the picker
+        let ghost = prefabs.active_schematic().zip(controller.raycast(&player, &planet, width, height, true).map(|(id, _)| id));
+        let selection = prefabs.selection.and_then(|(a, b)| Some((a, b.or(controller.cursor_id)?)));
+        renderer.update_prefab(&planet, selection, ghost.as_ref().map(|(s, origin)| (s, *origin)), settings.cursor_color);
+        let radial = prefabs.menu.as_ref().map(|menu| {
+            let items = prefabs.menu_items();
+            let hovered = menu.hovered(items.len());
+            items.into_iter().enumerate().map(|(i, name)| (name, hovered == Some(i))).collect()
+        });
+        let label = prefabs.active.and_then(|i| prefabs.library.get(i)).map(|p| p.name.clone());
+        renderer.update_prefab_hud(label, radial.unwrap_or_default());
+
+        // blueprint guide follows the world as anyone builds
+        if let Some(bp) = blueprint.as_mut() {
+            let was_complete = bp.is_complete();
+            if bp.update(frame_dt, &planet) {
+                if bp.is_complete() && !was_complete {
+                    console.log(&tr!("blueprint.done", bp.name), [0.0, 1.0, 0.0]);
+                }
+                renderer.update_blueprint(&planet, Some(bp), settings.cursor_color);
+            }
+        }
+
+        renderer.update_signs(&planet);
+        music.update(frame_dt, MusicContext::at(controller.get_camera_pos(&player), &planet));
+        stats.update(frame_dt);
+        renderer.update_mobs(&mobs);
+        // a panorama has the still path to itself while it's taken, the time-lapse waits
+        if let Some(pano) = panorama.as_mut() {
+            if pano.waiting {
+                if let Some(frame) = renderer.take_still() { pano.add(frame); }
+            }
+            match pano.next_face() {
+                Some(view_proj) => {
+                    renderer.request_still(view_proj, pano.camera.eye, FACE_SIZE, FACE_SIZE);
+                    pano.waiting = true;
+                },
+                None => if let Some(pano) = panorama.take() {
+                    let path = pano.save(panorama_errors.0.clone());
+                    console.log(&tr!("panorama.saved", path.display()), [0.0, 1.0, 0.0]);
+                    toasts.push(tr!("panorama.saved", path.display()), [0.0, 1.0, 0.0]);
+                },
+            }
+        } else if let Some(lapse) = timelapse.as_mut() {
+            if let Some(frame) = renderer.take_still() { lapse.save(frame); }
+            if lapse.update(frame_dt) {
+                renderer.request_still(lapse.camera.view_proj(), lapse.camera.eye(), LAPSE_WIDTH, LAPSE_HEIGHT);
+            }
+            for e in lapse.errors() {
+                console.log(&tr!("timelapse.write_failed", e), [1.0, 0.0, 0.0]);
+            }
+        }
+        for timing in renderer.mesh_profile.take_outliers() {
+            console.log(&tr!("mesh_profile.outlier", timing.describe()), [1.0, 0.6, 0.2]);
+        }
+        for e in panorama_errors.1.try_iter() {
+            console.log(&tr!("panorama.write_failed", e), [1.0, 0.0, 0.0]);
+        }
+        if let Some(rec) = recorder.as_mut() {
+            let pushed = match renderer.take_frame() {
+                Some(frame) => rec.push(frame),
+                None => Ok(()),
+            };
+            match pushed {
+                Ok(()) => if rec.update(frame_dt) { renderer.request_frame(); },
+                Err(e) => {
+                    let _ = rec.finish();
+                    console.log(&tr!("record.failed", e), [1.0, 0.0, 0.0]);
+                    recorder = None;
+                },
+            }
+        }
+        renderer.update_markers(&markers, controller.get_camera_pos(&player));
+        renderer.update_teleporters(&planet, player.position, travel.charge(), travel.fade());
+        for milestone in stats.take_milestones() {
+            console.log(&milestone, [1.0, 0.85, 0.2]);
         }
 
+        if monitors.changed {
+            renderer.update_monitors(&monitors);
+            monitors.changed = false;
+        }
+        (player.position, player.rotation) = ticked;
 
+        // the ui animates every frame, however seldom the world ticks
+        console.update_animation(frame_dt, settings.reduce_motion);
+        toasts.update(frame_dt);
+        renderer.update_toasts(&toasts);
 
 
         match event {
             
             Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta }, .. } => {
-                controller.process_mouse_motion(delta);
+                // the prefab picker takes the mouse while it's open
+                match prefabs.menu.as_mut() {
+                    Some(menu) => menu.add_motion(delta),
+                    None if settings_menu.open || trade_menu.is_open() => {}
+                    None => controller.process_mouse_motion(delta, &settings),
+                }
+            },
+
+            Event::WindowEvent { event, window_id } if renderer.debug_view.as_ref().is_some_and(|d| d.window.id() == window_id) => {
+                match event {
+                    WindowEvent::CloseRequested => renderer.debug_view = None,
+                    WindowEvent::Resized(size) => renderer.resize_debug_view(size.width, size.height),
+                    _ => {}
+                }
             },
 
             Event::WindowEvent { event, window_id } if window_id == renderer.window.id() => {
                 
                 
+                if let WindowEvent::ModifiersChanged(m) = &event {
+                    modifiers = m.state();
+                }
+                let ctrl = modifiers.control_key() || modifiers.super_key();
+
                 // CONSOLE INPUT INTERCEPTION
                 if console.is_open {
-                    match event {
-                        WindowEvent::KeyboardInput { event: key_event, .. } => {
-                             if key_event.state == ElementState::Pressed {
-                                 match key_event.physical_key {
-                                     PhysicalKey::Code(KeyCode::Backquote) => console.toggle(),
-                                     PhysicalKey::Code(KeyCode::Enter) => console.submit(&mut player),
-                                     PhysicalKey::Code(KeyCode::Backspace) => console.handle_backspace(),
-                                     _ => {
-                                         if let Some(txt) = &key_event.text {
-                                             // Append text to console buffer
-                                             for c in txt.chars() { console.handle_char(c); }
+                    // dragging over the log picks whole lines to copy. clicks on the panel don't reach the world
+                    let window_height = renderer.config.height as f32;
+                    match &event {
+                        WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } if controller.mouse_pos.y < console.pixel_height(window_height) => {
+                            console.select_from(console.line_at(controller.mouse_pos.y, window_height));
+                            return;
+                        },
+                        WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. } if console.is_selecting() => {
+                            console.finish_selection();
+                            return;
+                        },
+                        WindowEvent::CursorMoved { position, .. } if console.is_selecting() => {
+                            console.select_to(console.line_at(position.y as f32, window_height));
+                        },
+                        _ => {}
+                    }
+                    if let WindowEvent::KeyboardInput { event: key_event, .. } = &event {
+                         if key_event.state == ElementState::Pressed {
+                             match key_event.physical_key {
+                                 PhysicalKey::Code(KeyCode::Backquote) => console.toggle(),
+                                 PhysicalKey::Code(KeyCode::KeyC) if ctrl => {
+                                     if let Some(text) = console.selected_text() {
+                                         let lines = text.lines().count();
+                                         match clipboard.copy(text) {
+                                             Ok(()) => console.log(&tr!("console.copied", lines), [0.6, 0.6, 0.6]),
+                                             Err(e) => console.log(&tr!("console.clipboard_failed", e), [1.0, 0.5, 0.0]),
                                          }
                                      }
+                                 },
+                                 PhysicalKey::Code(KeyCode::KeyV) if ctrl => match clipboard.paste() {
+                                     Ok(text) => console.paste(&text),
+                                     Err(e) => console.log(&tr!("console.clipboard_failed", e), [1.0, 0.5, 0.0]),
+                                 },
+                                 PhysicalKey::Code(KeyCode::Enter) => {
+                                     let was_spectator = player.spectator;
+                                     let mut ctx = CommandContext::new(&mut player, &mut planet, &mut autosave, Permission::Owner);
+                                     ctx.settings = Some(&mut settings);
+                                     ctx.bindings = Some(&mut controller.bindings);
+                                     ctx.aliases = Some(&mut aliases);
+                                     ctx.prefabs = Some(&mut prefabs);
+                                     ctx.monitors = Some(&mut monitors);
+                                     ctx.music = Some(&mut music);
+                                     ctx.stats = Some(&mut stats);
+                                     ctx.mobs = Some(&mut mobs);
+                                     ctx.markers = Some(&mut markers);
+                                     ctx.timelapse = Some(&mut timelapse);
+                                     ctx.recorder = Some(&mut recorder);
+                                     ctx.panorama = Some(&mut panorama);
+                                     ctx.target = controller.cursor_id;
+                                     ctx.plugins = Some(&mut plugins);
+                                     ctx.online = online;
+                                     ctx.fly_mode = controller.fly_mode;
+                                     console.submit(&mut ctx);
+                                     let (quit, forward, debug_view, sign, link) = (ctx.quit, ctx.forward.take(), ctx.debug_view, ctx.sign.take(), ctx.teleporter.take());
+                                     if let Some(mode) = ctx.render_mode { renderer.render_mode = mode; }
+                                     let marker_edit = ctx.marker.take();
+                                     let blasted = std::mem::take(&mut ctx.blasted);
+                                     if ctx.world_reloaded {
+                                         published = planet.publish();
+                                         renderer.force_reload_all(&published, player.position);
+                                     }
+                                     if recorder.is_some() && !renderer.can_record() {
+                                         recorder = None;
+                                         console.log(&tr!("record.unsupported"), [1.0, 0.0, 0.0]);
+                                     }
+                                     if !blasted.is_empty() {
+                                         events.emit(GameEvent::Blasted { mined: blasted });
+                                     }
+                                     if let Some((id, text)) = sign {
+                                         write_sign(&mut planet, &client, id, text, controller.get_camera_pos(&player));
+                                     }
+                                     match (marker_edit, &client) {
+                                         (Some(MarkerEdit::Set(marker)), Some(c)) => c.send(&Message::MarkerSet { marker }),
+                                         (Some(MarkerEdit::Remove { owner, name }), Some(c)) => c.send(&Message::MarkerRemove { owner, name }),
+                                         (Some(edit), None) => markers.apply(edit),
+                                         (None, _) => {}
+                                     }
+                                     if let Some((id, channel)) = link {
+                                         if let Some(c) = &client { c.send(&Message::TeleporterEdit { id, channel: channel.clone() }); }
+                                         planet.set_entity(id, (!channel.is_empty()).then_some(BlockEntity::Teleporter { channel }));
+                                     }
+                                     // the server decides whether we may spectate and hides us from the others
+                                     if let (Some(c), true) = (&client, player.spectator != was_spectator) {
+                                         c.send(&Message::Spectator { enabled: player.spectator });
+                                     }
+                                     if let Some(text) = forward {
+                                         match &client {
+                                             Some(c) => c.send(&Message::Command { text }),
+                                             None => console.log(&tr!("net.not_connected"), [1.0, 0.5, 0.0]),
+                                         }
+                                     }
+                                     match debug_view {
+                                         Some(Some(camera)) => open_debug_view(camera, target, &mut renderer, &mut console),
+                                         Some(None) => {
+                                             renderer.debug_view = None;
+                                             console.log(&tr!("debugview.closed"), [0.0, 1.0, 0.0]);
+                                         },
+                                         None => {}
+                                     }
+                                     if quit {
+                                         if !online {
+                                             let _ = autosave.save_blocking(&mut planet, &player);
+                                             let _ = stats.save(&autosave.dir);
+                                             let _ = inventory.save(&autosave.dir);
+                                             let _ = markers.save(&autosave.dir);
+                                         }
+                                         // lets the encoder write out what it has
+                                         if let Some(mut rec) = recorder.take() { let _ = rec.finish(); }
+                                         target.exit();
+                                     }
+                                 },
+                                 PhysicalKey::Code(KeyCode::Backspace) => console.handle_backspace(),
+                                 PhysicalKey::Code(KeyCode::ArrowUp) => console.history_up(),
+                                 PhysicalKey::Code(KeyCode::ArrowDown) => console.history_down(),
+                                 PhysicalKey::Code(KeyCode::Tab) => console.complete(),
+                                 _ => {
+                                     if let Some(txt) = &key_event.text {
+                                         // Append text to console buffer
+                                         for c in txt.chars() { console.handle_char(c); }
+                                     }
                                  }
-                             }                            
-                             return; 
-                        },
-                         _ => {} 
+                             }
+                         }                            
+                         return; 
                     }
                 }
                 
@@ -148,40 +795,155 @@ fn main() {
                          }
                      }
                 }
+
+                // TRADE MENU: up/down pick an offer, Enter takes it, E or Esc walks away. it has the keyboard and mouse
+                // while it's open, key releases still go through so nothing held down gets stuck
+                if trade_menu.is_open() {
+                    if let WindowEvent::KeyboardInput { event: key_event, .. } = &event {
+                        if key_event.state == ElementState::Pressed {
+                            let code = match key_event.physical_key { PhysicalKey::Code(code) => Some(code), _ => None };
+                            match code {
+                                Some(KeyCode::ArrowUp) => trade_menu.select(&mobs, -1),
+                                Some(KeyCode::ArrowDown) => trade_menu.select(&mobs, 1),
+                                Some(KeyCode::Enter) if !key_event.repeat => match trade_menu.accept(&mobs, &mut inventory) {
+                                    Ok(o) => console.log(&tr!("trade.done", o.give.count, o.give.block.name(), o.get.count, o.get.block.name()), [0.8, 1.0, 0.8]),
+                                    Err(e) => console.log(&e, [1.0, 0.5, 0.0]),
+                                },
+                                Some(KeyCode::Escape) => trade_menu.close(),
+                                Some(code) if controller.bindings.action(code) == Some(Action::Ride) => trade_menu.close(),
+                                _ => {}
+                            }
+                            return;
+                        }
+                    }
+                    if matches!(event, WindowEvent::MouseInput { .. } | WindowEvent::MouseWheel { .. }) {
+                        return;
+                    }
+                }
+
+                // SETTINGS MENU: Esc opens and closes it, and it has the keyboard and mouse while it's open
+                if let WindowEvent::KeyboardInput { event: key_event, .. } = &event {
+                    if key_event.state == ElementState::Pressed {
+                        match key_event.physical_key {
+                            PhysicalKey::Code(KeyCode::Escape) if !key_event.repeat => {
+                                if let Some(Err(e)) = settings_menu.toggle().then(|| settings.save(std::path::Path::new(CLIENT_CONFIG))) {
+                                    console.log(&tr!("cmd.option.save_failed", e), [1.0, 0.0, 0.0]);
+                                }
+                            },
+                            PhysicalKey::Code(KeyCode::ArrowUp) if settings_menu.open => settings_menu.select(-1),
+                            PhysicalKey::Code(KeyCode::ArrowDown) if settings_menu.open => settings_menu.select(1),
+                            PhysicalKey::Code(KeyCode::ArrowLeft) if settings_menu.open => settings_menu.adjust(&mut settings, -1),
+                            PhysicalKey::Code(KeyCode::ArrowRight) if settings_menu.open => settings_menu.adjust(&mut settings, 1),
+                            _ => {}
+                        }
+                    }
+                }
+                if settings_menu.open && matches!(event, WindowEvent::KeyboardInput { .. } | WindowEvent::MouseInput { .. } | WindowEvent::MouseWheel { .. }) {
+                    return;
+                }
                 
                 
                 
+                // prefab, grapple and ride keys, whatever they're bound to (see bindings.rs)
+                if let WindowEvent::KeyboardInput { event: key_event, .. } = &event {
+                    let pressed = key_event.state == ElementState::Pressed;
+                    let action = match key_event.physical_key {
+                        PhysicalKey::Code(code) => controller.bindings.action(code),
+                        _ => None,
+                    };
+                    match action {
+                        Some(Action::PrefabMenu) if pressed && !key_event.repeat => prefabs.open_menu(),
+                        Some(Action::PrefabMenu) if !pressed => prefabs.close_menu(),
+                        Some(Action::Select) if pressed && !key_event.repeat => {
+                            if let Some(id) = controller.cursor_id {
+                                prefabs.mark(id);
+                                let corner = if matches!(prefabs.selection, Some((_, Some(_)))) { 2 } else { 1 };
+                                console.log(&tr!("prefab.corner", corner), [0.8, 0.8, 0.8]);
+                            }
+                        },
+                        Some(Action::Blueprint) if pressed && !key_event.repeat => {
+                            // pin the prefab being placed where its ghost is, or drop the current guide
+                            let origin = controller.raycast(&player, &planet, renderer.config.width as f32, renderer.config.height as f32, true).map(|(id, _)| id);
+                            let pinned = prefabs.active.and_then(|i| prefabs.library.get(i)).map(|p| p.name.clone())
+                                .zip(prefabs.active_schematic()).zip(origin);
+                            if let Some(((name, schematic), origin)) = pinned {
+                                console.log(&tr!("blueprint.pinned", name), [0.0, 1.0, 0.0]);
+                                blueprint = Some(Blueprint::new(name, schematic, origin, &planet));
+                                prefabs.active = None;
+                            } else if blueprint.take().is_some() {
+                                console.log(&tr!("blueprint.cleared"), [0.8, 0.8, 0.8]);
+                            }
+                            renderer.update_blueprint(&planet, blueprint.as_ref(), settings.cursor_color);
+                        },
+                        Some(Action::Grapple) if pressed && !key_event.repeat => {
+                            player.rope = match player.rope {
+                                Some(_) => None,
+                                None => controller.grapple(&player, &planet, renderer.config.width as f32, renderer.config.height as f32),
+                            };
+                        },
+                        Some(Action::Ride) if pressed && !key_event.repeat => {
+                            if controller.chase.take().is_some() {
+                                mobs.dismount(&mut player, &planet);
+                            } else if let Some(interactable) = interaction {
+                                let mut ctx = InteractContext { planet: &planet, mobs: &mut mobs, controller: &mut controller, travel: &mut travel, console: &mut console, trade: &mut trade_menu };
+                                interact::run(&mut ctx, interactable);
+                            } else if mobs.mount(&player) {
+                                if let Some((bike, _)) = mobs.ridden() {
+                                    controller.chase = Some(ChaseCam::new(bike.position, bike.rotation));
+                                }
+                            }
+                        },
+                        Some(Action::Rotate) if pressed && !key_event.repeat && prefabs.active.is_some() => {
+                            prefabs.rotation = (prefabs.rotation + 1) % 4;
+                        },
+                        _ => {}
+                    }
+                }
+
                 controller.process_events(&event, &mut player, &planet);
                 
                 match event {
-                    WindowEvent::CloseRequested => target.exit(),
+                    WindowEvent::CloseRequested => {
+                        // never lose edits on exit, even if the autosave timer hasn't fired yet
+                        if !online {
+                            match autosave.save_blocking(&mut planet, &player) {
+                                Ok(n) => println!("{}", tr!("world.saved", n)),
+                                Err(e) => println!("{}", tr!("world.save_failed", e)),
+                            }
+                            if let Err(e) = stats.save(&autosave.dir) {
+                                println!("{}", tr!("stats.save_failed", e));
+                            }
+                            if let Err(e) = inventory.save(&autosave.dir) {
+                                println!("{}", tr!("inventory.save_failed", e));
+                            }
+                            if let Err(e) = markers.save(&autosave.dir) {
+                                println!("{}", tr!("marker.save_failed", e));
+                            }
+                        }
+                        if let Some(mut rec) = recorder.take() {
+                            if let Err(e) = rec.finish() { println!("{}", tr!("record.failed", e)); }
+                        }
+                        target.exit();
+                    },
                     WindowEvent::Resized(size) => renderer.resize(size.width, size.height),
+                    WindowEvent::Focused(f) => focused = f,
                     
                     WindowEvent::MouseInput { state: ElementState::Pressed, button, .. } => {
-                        let is_right = button == MouseButton::Right;
-                        if let Some(id) = controller.cursor_id {
-                             if is_right { 
-                                 let place_info = controller.raycast(&player, &planet, renderer.config.width as f32, renderer.config.height as f32, true);
-                                 if let Some((place_id, _)) = place_info {
-                                     planet.add_block(place_id);
-                                     renderer.refresh_neighbors(place_id, &planet);
-                                 }
-                             } else { 
-                                 planet.remove_block(id); 
-                                 renderer.refresh_neighbors(id, &planet);
-                             }
-                            renderer.window.request_redraw();
-                        } else {
-                            if controller.first_person {
-                                let _ = renderer.window.set_cursor_grab(CursorGrabMode::Locked);
-                                renderer.window.set_cursor_visible(false);
-                            }
+                        click(button == MouseButton::Right, &mut controller, &player, &mut planet, &mut renderer, &prefabs, &mut events, &mut mobs, &mut inventory);
+                    },
+                    WindowEvent::Touch(touch) => {
+                        let (look, action) = controller.touch.handle(&touch, renderer.config.width as f32);
+                        if let Some(delta) = look { controller.process_mouse_motion(delta, &settings); }
+                        if let Some(action) = action {
+                            touch_action(action, &mut controller, &player, &mut planet, &mut renderer, &prefabs, &mut events, &mut mobs, &mut inventory);
                         }
                     },
                     
                     WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
                          if let Key::Character(ref s) = event.logical_key {
-                            if s == "]" || s == "[" {
+                            if (s == "]" || s == "[") && client.is_some() {
+                                console.log(&tr!("net.no_resize"), [1.0, 0.5, 0.0]);
+                            } else if s == "]" || s == "[" {
                                 if s == "]" { planet.resize(true); } 
                                 else { planet.resize(false); }
                                 
@@ -200,7 +962,8 @@ fn main() {
                                 player.position = current_dir * spawn_radius;
                                 player.velocity = glam::Vec3::ZERO;
                                 
-                                renderer.force_reload_all(&planet, player.position);
+                                published = planet.publish();
+                                renderer.force_reload_all(&published, player.position);
                                 renderer.log_memory(&planet);
                                 renderer.window.request_redraw();
                             }
@@ -208,14 +971,153 @@ fn main() {
                     },
 
                     WindowEvent::RedrawRequested => {
-                            renderer.render(&controller, &player, &planet, &console);
+                            pacer.frame_started(frame_cap(&settings, focused, power.on_battery, settings_menu.open));
+                            for (file, result) in renderer.reload_shaders() {
+                                match result {
+                                    Ok(()) => console.log(&tr!("shader.reloaded", file), [0.6, 0.6, 0.6]),
+                                    Err(e) => console.log(&tr!("shader.failed", file, e), [1.0, 0.5, 0.0]),
+                                }
+                            }
+                            // drawn part way between the last two ticks, then put back where the world has it
+                            let ticked = (player.position, player.rotation);
+                            (player.position, player.rotation) = sim_clock.interpolate(settings.tick_rate, ticked.0, ticked.1);
+                            renderer.render(&controller, &player, &planet, &console, &settings, autosave.is_saving());
+                            (player.position, player.rotation) = ticked;
 
                         },
                     _ => {}
                 }
             },
-            Event::AboutToWait => renderer.window.request_redraw(),
+            Event::AboutToWait => {
+                match pacer.pace() {
+                    Pace::WaitUntil(t) => target.set_control_flow(ControlFlow::WaitUntil(t)),
+                    Pace::Draw => {
+                        target.set_control_flow(ControlFlow::Wait);
+                        renderer.window.request_redraw();
+                    }
+                }
+            },
             _ => {}
         }
     }).unwrap();
-}
\ No newline at end of file
+}
+
+// the lowest frame rate cap that applies right now: the player's own, a lower one in the settings menu, and ones
+// in the background and on battery so an idle game doesn't cook the laptop. None when nothing caps it
+fn frame_cap(settings: &Settings, focused: bool, on_battery: bool, in_menu: bool) -> Option<u32> {
+    [(true, settings.max_fps), (in_menu, settings.menu_fps), (!focused, settings.background_fps), (on_battery, settings.battery_fps)]
+        .into_iter()
+        .filter(|&(active, fps)| active && fps > 0)
+        .map(|(_, fps)| fps)
+        .min()
+}
+
+// applies a block edit locally and tells the server about it. placed blocks come out of the hotbar's `slot`,
+// mined ones go into the inventory
+fn edit_block(planet: &mut PlanetData, events: &mut EventBus, id: BlockId, placed: bool, inventory: &mut Inventory, slot: usize) {
+    let (existed, was) = (planet.exists(id), planet.block_type(id));
+    let block = if placed {
+        let Some(block) = inventory.block(slot) else { return };
+        planet.add_block(id, block);
+        block
+    } else {
+        planet.remove_block(id);
+        was
+    };
+    // the core refuses to be mined, so only edits that changed something are news
+    if planet.exists(id) == existed { return; }
+    // a full hotbar lets a mined block go
+    if placed { inventory.take(slot); } else { inventory.add(was); }
+    events.emit(if placed { GameEvent::BlockPlaced { id, block, remote: false } } else { GameEvent::BlockMined { id, block, remote: false } });
+}
+
+// puts a sign on the side of `id` facing `eye`, or takes it down when `text` is empty
+fn write_sign(planet: &mut PlanetData, client: &Option<Client>, id: BlockId, text: String, eye: glam::Vec3) {
+    let facing = sign::facing_towards(id, planet.resolution, eye);
+    if let Some(c) = client { c.send(&Message::SignEdit { id, text: text.clone(), facing }); }
+    let entity = (!text.is_empty()).then_some(BlockEntity::Sign { text, facing });
+    planet.set_entity(id, entity);
+}
+
+// a mine (left) or place (right) click on the block under the cursor, from the mouse or a touch. a left click
+// on a mob in reach hits it instead
+#[allow(clippy::too_many_arguments)]
+fn click(is_right: bool, controller: &mut Controller, player: &Player, planet: &mut PlanetData, renderer: &mut Renderer, prefabs: &Prefabs, events: &mut EventBus, mobs: &mut Mobs, inventory: &mut Inventory) {
+    let (width, height) = (renderer.config.width as f32, renderer.config.height as f32);
+    if let Some((Target::Mob(e), _)) = controller.pick(player, planet, mobs, width, height).filter(|_| !is_right && !player.spectator) {
+        if mobs.transforms.get(e).is_some_and(|t| t.center().distance(player.position) <= ATTACK_REACH) {
+            mobs.attack(e, player.position);
+            controller.shake.add_trauma(shake::MINE_TRAUMA);
+        }
+        return;
+    }
+    if let Some(id) = controller.cursor_id {
+        let target = if is_right {
+            controller.raycast(player, planet, renderer.config.width as f32, renderer.config.height as f32, true).map(|(place_id, _)| place_id)
+        } else {
+            Some(id)
+        };
+        let prefab = if is_right { prefabs.active_schematic() } else { None };
+        if let Some(target) = target {
+            if let Some(schematic) = prefab {
+                // stamp the prefab with its lowest corner on the targeted cell, keeping whatever is already there.
+                // each block comes out of whichever slot holds its kind, and is skipped when none does
+                for (id, block) in schematic.blocks_at(target, planet.resolution) {
+                    if let Some(slot) = inventory.slot_of(block).filter(|_| !planet.exists(id)) {
+                        edit_block(planet, events, id, true, inventory, slot);
+                    }
+                }
+            } else if controller.first_person {
+                edit_block(planet, events, target, is_right, inventory, controller.slot);
+            } else {
+                // the stroke paints the first brush right away and follows the cursor until release
+                controller.start_stroke(is_right, target.layer);
+                for id in controller.stroke_targets(planet) {
+                    edit_block(planet, events, id, is_right, inventory, controller.slot);
+                }
+            }
+            if !is_right { controller.shake.add_trauma(shake::MINE_TRAUMA); }
+        }
+        renderer.window.request_redraw();
+    } else if controller.first_person {
+        let _ = renderer.window.set_cursor_grab(CursorGrabMode::Locked);
+        renderer.window.set_cursor_visible(false);
+    }
+}
+
+// taps mine and long presses place. in first person that's the crosshair, otherwise the block under the finger
+#[allow(clippy::too_many_arguments)]
+fn touch_action(action: TouchAction, controller: &mut Controller, player: &Player, planet: &mut PlanetData, renderer: &mut Renderer, prefabs: &Prefabs, events: &mut EventBus, mobs: &mut Mobs, inventory: &mut Inventory) {
+    let (pos, is_right) = match action {
+        TouchAction::Mine(pos) => (pos, false),
+        TouchAction::Place(pos) => (pos, true),
+    };
+    if !controller.first_person {
+        controller.mouse_pos = pos;
+        let (width, height) = (renderer.config.width as f32, renderer.config.height as f32);
+        controller.cursor_id = controller.raycast(player, planet, width, height, false).filter(|_| !player.spectator).map(|(id, _)| id);
+    }
+    click(is_right, controller, player, planet, renderer, prefabs, events, mobs, inventory);
+    // a touch has no button to release, so it paints a single brush
+    controller.stroke = None;
+}
+
+// windows can only be made from inside the event loop, so /debugview hands its request back to us
+fn open_debug_view(camera: DebugCamera, target: &EventLoopWindowTarget<()>, renderer: &mut Renderer, console: &mut Console) {
+    let result = match &mut renderer.debug_view {
+        Some(debug) => {
+            debug.camera = camera;
+            debug.window.set_title(&format!("voxanet - {}", camera.name()));
+            Ok(())
+        },
+        None => WindowBuilder::new()
+            .with_title(format!("voxanet - {}", camera.name()))
+            .build(target)
+            .map_err(|e| e.to_string())
+            .and_then(|window| renderer.open_debug_view(Arc::new(window), camera)),
+    };
+    match result {
+        Ok(()) => console.log(&tr!("debugview.opened", camera.name()), [0.0, 1.0, 0.0]),
+        Err(e) => console.log(&tr!("debugview.failed", e), [1.0, 0.0, 0.0]),
+    }
+}
@@ -0,0 +1,244 @@
+//marker.rs
+
+// map markers: named points in the world with a label floating over them, set with /marker add <name>.
+// each belongs to the player who set it and is private to them or public. on a server the server keeps
+// them (markers.dat in its world), checks who may change what and sends each client the ones it may see.
+// offline they're kept in the local world the same way.
+//
+// dying leaves a grave: a private marker of the player's own where they fell, holding everything they carried.
+// walking up to it puts the blocks back in the hotbar, and it's gone once it's older than death_marker_minutes.
+// graves stay on this client, like the inventory they hold, and are saved in markers.dat with the rest.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use glam::Vec3;
+use crate::codec::*;
+use crate::i18n::tr;
+use crate::inventory::Inventory;
+use crate::world::write_atomic;
+
+// --- CONSTANTS ---
+const MARKERS_FILE: &str = "markers.dat";
+const MARKERS_MAGIC: &[u8; 4] = b"VXNM";
+// 2 added the graves
+const MARKERS_VERSION: u32 = 2;
+pub const MAX_MARKER_NAME: usize = 32;
+// per player
+pub const MAX_MARKERS: usize = 64;
+// the label floats this high over the marked point
+pub const LABEL_HEIGHT: f32 = 1.5;
+// how close the player has to come to a grave to get their things back
+const GRAVE_REACH: f32 = 2.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    // only the owner sees it
+    Private,
+    Public,
+}
+
+impl Visibility {
+    pub fn name(self) -> &'static str {
+        match self {
+            Visibility::Private => "private",
+            Visibility::Public => "public",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "private" => Some(Visibility::Private),
+            "public" => Some(Visibility::Public),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Marker {
+    pub owner: String,
+    pub name: String,
+    pub position: Vec3,
+    pub visibility: Visibility,
+}
+
+impl Marker {
+    pub fn visible_to(&self, player: &str) -> bool {
+        self.visibility == Visibility::Public || self.owner == player
+    }
+}
+
+pub fn put_marker(out: &mut Vec<u8>, marker: &Marker) {
+    put_str(out, &marker.owner);
+    put_str(out, &marker.name);
+    put_vec3(out, marker.position);
+    out.push(marker.visibility as u8);
+}
+
+pub fn read_marker(r: &mut ByteReader) -> io::Result<Marker> {
+    Ok(Marker {
+        owner: r.str()?,
+        name: r.str()?,
+        position: r.vec3()?,
+        visibility: if r.u8()? == 0 { Visibility::Private } else { Visibility::Public },
+    })
+}
+
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.chars().count() > MAX_MARKER_NAME || name.chars().any(char::is_control) {
+        return Err(tr!("marker.bad_name", MAX_MARKER_NAME));
+    }
+    Ok(())
+}
+
+// where the player died, with what they were carrying
+pub struct Grave {
+    pub marker: Marker,
+    pub inventory: Inventory,
+    // seconds since the death, in play time
+    pub age: f32,
+}
+
+// a change a command asks for. offline the caller applies it, online it goes to the server, which answers
+// with the markers it actually changed
+pub enum MarkerEdit {
+    Set(Marker),
+    Remove { owner: String, name: String },
+}
+
+#[derive(Default)]
+pub struct Markers {
+    pub list: Vec<Marker>,
+    // the local player, who owns the markers set from this client. empty on a server
+    pub me: String,
+    // the local player's graves, never sent to or from a server
+    pub graves: Vec<Grave>,
+}
+
+impl Markers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apply(&mut self, edit: MarkerEdit) {
+        match edit {
+            MarkerEdit::Set(marker) => { self.set(marker); },
+            MarkerEdit::Remove { owner, name } => { self.remove(&owner, &name); },
+        }
+    }
+
+    // a missing file just means nobody has set a marker here yet
+    pub fn load(dir: &Path) -> io::Result<Self> {
+        let bytes = match fs::read(dir.join(MARKERS_FILE)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(e),
+        };
+        let mut r = ByteReader::new(&bytes);
+        if r.bytes(4)? != MARKERS_MAGIC { return Err(invalid("markers.dat has a bad header")); }
+        let version = r.u32()?;
+        if !(1..=MARKERS_VERSION).contains(&version) {
+            return Err(invalid(&format!("unsupported markers version {}", version)));
+        }
+        let mut markers = Self::new();
+        for _ in 0..r.u32()? {
+            markers.list.push(read_marker(&mut r)?);
+        }
+        if version >= 2 {
+            for _ in 0..r.u32()? {
+                let marker = read_marker(&mut r)?;
+                let age = r.f32()?;
+                let inventory = Inventory::decode(r.blob()?)?;
+                markers.graves.push(Grave { marker, inventory, age });
+            }
+        }
+        Ok(markers)
+    }
+
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MARKERS_MAGIC);
+        put_u32(&mut out, MARKERS_VERSION);
+        put_u32(&mut out, self.list.len() as u32);
+        for marker in &self.list {
+            put_marker(&mut out, marker);
+        }
+        put_u32(&mut out, self.graves.len() as u32);
+        for grave in &self.graves {
+            put_marker(&mut out, &grave.marker);
+            put_f32(&mut out, grave.age);
+            put_bytes(&mut out, &grave.inventory.encode());
+        }
+        fs::create_dir_all(dir)?;
+        write_atomic(&dir.join(MARKERS_FILE), &out)
+    }
+
+    pub fn get(&self, owner: &str, name: &str) -> Option<&Marker> {
+        self.list.iter().find(|m| m.owner == owner && m.name == name)
+    }
+
+    // adds `marker` or replaces the owner's one of the same name, returning the one it replaced
+    pub fn set(&mut self, marker: Marker) -> Option<Marker> {
+        match self.list.iter_mut().find(|m| m.owner == marker.owner && m.name == marker.name) {
+            Some(old) => Some(std::mem::replace(old, marker)),
+            None => {
+                self.list.push(marker);
+                None
+            },
+        }
+    }
+
+    pub fn remove(&mut self, owner: &str, name: &str) -> Option<Marker> {
+        let i = self.list.iter().position(|m| m.owner == owner && m.name == name)?;
+        Some(self.list.remove(i))
+    }
+
+    pub fn owned_by(&self, owner: &str) -> usize {
+        self.list.iter().filter(|m| m.owner == owner).count()
+    }
+
+    // whether `owner` may set `marker`: a sane name, and room for it if it's new
+    pub fn check_set(&self, owner: &str, name: &str) -> Result<(), String> {
+        check_name(name)?;
+        if self.get(owner, name).is_none() && self.owned_by(owner) >= MAX_MARKERS {
+            return Err(tr!("marker.too_many", MAX_MARKERS));
+        }
+        Ok(())
+    }
+
+    pub fn visible_to<'a>(&'a self, player: &'a str) -> impl Iterator<Item = &'a Marker> {
+        self.list.iter().filter(move |m| m.visible_to(player))
+    }
+
+    // a grave of the local player's where they fell, holding `inventory`
+    pub fn add_grave(&mut self, position: Vec3, inventory: Inventory) {
+        let marker = Marker { owner: self.me.clone(), name: tr!("marker.grave"), position, visibility: Visibility::Private };
+        self.graves.push(Grave { marker, inventory, age: 0.0 });
+    }
+
+    // ages the graves and lets the ones past `minutes` go, what they held with them. 0 keeps them.
+    // returns how many went
+    pub fn expire_graves(&mut self, dt: f32, minutes: f32) -> usize {
+        let before = self.graves.len();
+        for grave in &mut self.graves {
+            grave.age += dt;
+        }
+        self.graves.retain(|g| minutes <= 0.0 || g.age < minutes * 60.0);
+        before - self.graves.len()
+    }
+
+    // puts what the nearest grave within GRAVE_REACH of `player` holds into `inventory`, and drops the grave once
+    // it's empty. how many blocks came back and how many are left in it, None if there's no grave in reach
+    pub fn recover_grave(&mut self, player: Vec3, inventory: &mut Inventory) -> Option<(u32, u32)> {
+        let i = self.graves.iter().enumerate()
+            .map(|(i, g)| (i, g.marker.position.distance(player)))
+            .filter(|(_, d)| *d <= GRAVE_REACH)
+            .min_by(|a, b| a.1.total_cmp(&b.1))?.0;
+        let grave = &mut self.graves[i];
+        let moved = inventory.absorb(&mut grave.inventory);
+        let left = grave.inventory.slots.iter().flatten().map(|s| s.count).sum();
+        if left == 0 { self.graves.remove(i); }
+        Some((moved, left))
+    }
+}
@@ -0,0 +1,113 @@
+//mesh_pool.rs
+
+// the threads chunk and lod meshes are built on. a fixed rayon pool takes jobs nearest first from a bounded
+// queue, block edits' remeshes ahead of all of them, and every job reads the snapshot of the planet that was
+// published when it was queued (see PlanetData::publish). finished meshes go back over the renderer's channels, same as before.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use crate::common::{ChunkKey, LodKey, PlanetData, Vertex};
+use crate::gen::{MeshGen, ViewDistance};
+use crate::lod_animation::AnyKey;
+use crate::mesh_profile::MeshTiming;
+
+// --- CONSTANTS ---
+// jobs waiting for a thread at most. past this submit turns them away and they're asked for again later
+const QUEUE_CAPACITY: usize = 64;
+
+struct Job {
+    key: AnyKey,
+    // squared distance to the player, lowest goes first
+    priority: f32,
+    // remeshing a chunk a block edit changed, for the profiler
+    edited: bool,
+    planet: Arc<PlanetData>,
+    view: ViewDistance,
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Job {}
+
+impl PartialOrd for Job {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// BinaryHeap pops the greatest, so the nearest has to compare greatest
+impl Ord for Job {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.total_cmp(&self.priority)
+    }
+}
+
+pub struct MeshPool {
+    pool: rayon::ThreadPool,
+    queue: Arc<Mutex<BinaryHeap<Job>>>,
+    mesh_tx: Sender<(ChunkKey, Vec<Vertex>, Vec<u32>)>,
+    lod_tx: Sender<(LodKey, Vec<Vertex>, Vec<u32>)>,
+    profile_tx: Sender<MeshTiming>,
+    // how far lods reach, which decides how finely they're meshed. the renderer keeps it current
+    pub view: ViewDistance,
+}
+
+impl MeshPool {
+    pub fn new(mesh_tx: Sender<(ChunkKey, Vec<Vertex>, Vec<u32>)>, lod_tx: Sender<(LodKey, Vec<Vertex>, Vec<u32>)>, profile_tx: Sender<MeshTiming>) -> Self {
+        // one core left for the main thread
+        let threads = std::thread::available_parallelism().map_or(4, |n| n.get()).saturating_sub(1).max(1);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("mesh-{}", i))
+            .build()
+            .expect("failed to start the meshing threads");
+        Self { pool, queue: Arc::new(Mutex::new(BinaryHeap::new())), mesh_tx, lod_tx, profile_tx, view: ViewDistance::default() }
+    }
+
+    // queues a mesh of `key` in `planet`. false if the queue is full
+    pub fn submit(&mut self, key: AnyKey, priority: f32, planet: &Arc<PlanetData>) -> bool {
+        if self.queue.lock().unwrap().len() >= QUEUE_CAPACITY { return false; }
+        self.push(Job { key, priority, edited: false, planet: planet.clone(), view: self.view });
+        true
+    }
+
+    // remeshes a chunk after a block edit, ahead of everything waiting and even with the queue full, since it's
+    // what the player is looking at
+    pub fn submit_edit(&mut self, key: ChunkKey, planet: &Arc<PlanetData>) {
+        self.push(Job { key: AnyKey::Voxel(key), priority: f32::NEG_INFINITY, edited: true, planet: planet.clone(), view: self.view });
+    }
+
+    fn push(&mut self, job: Job) {
+        self.queue.lock().unwrap().push(job);
+        // each task runs whichever job is nearest by the time a thread is free, not necessarily this one
+        let (queue, mesh_tx, lod_tx, profile_tx) = (self.queue.clone(), self.mesh_tx.clone(), self.lod_tx.clone(), self.profile_tx.clone());
+        self.pool.spawn(move || {
+            let Some(job) = queue.lock().unwrap().pop() else { return };
+            let started = Instant::now();
+            match job.key {
+                AnyKey::Voxel(key) => {
+                    let (v, i) = MeshGen::build_chunk(key, &job.planet);
+                    let _ = profile_tx.send(MeshTiming::new(job.key, started, v.len(), i.len(), job.edited));
+                    let _ = mesh_tx.send((key, v, i));
+                },
+                AnyKey::Lod(key) => {
+                    let (v, i) = MeshGen::generate_lod_mesh(key, &job.planet, job.view);
+                    let _ = profile_tx.send(MeshTiming::new(job.key, started, v.len(), i.len(), false));
+                    let _ = lod_tx.send((key, v, i));
+                },
+            }
+        });
+    }
+
+    // forgets the queued jobs, ones already running still finish
+    pub fn clear(&mut self) {
+        self.queue.lock().unwrap().clear();
+    }
+}
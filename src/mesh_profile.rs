@@ -0,0 +1,90 @@
+//mesh_profile.rs
+
+// how long meshing takes, chunk by chunk. the worker threads time every mesh they build and send the timing
+// back, remeshes after an edit are timed where they run. the slowest of the last minute show in the debug
+// overlay, and any mesh far slower than usual for its kind is handed out once so it can be logged.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use crate::i18n::tr;
+use crate::lod_animation::AnyKey;
+
+// --- CONSTANTS ---
+pub const SLOWEST_SHOWN: usize = 10;
+// timings older than this drop out of the slowest list
+const PROFILE_WINDOW: Duration = Duration::from_secs(60);
+// a mesh is an outlier when it takes this many times the running average of its kind, and at least OUTLIER_MIN_MS
+const OUTLIER_FACTOR: f32 = 4.0;
+const OUTLIER_MIN_MS: f32 = 20.0;
+// weight of the newest timing in the running averages
+const AVERAGE_WEIGHT: f32 = 0.05;
+
+#[derive(Clone, Copy)]
+pub struct MeshTiming {
+    pub key: AnyKey,
+    pub millis: f32,
+    pub verts: usize,
+    pub tris: usize,
+    // remeshed right after an edit rather than streamed in
+    pub edited: bool,
+}
+
+impl MeshTiming {
+    pub fn new(key: AnyKey, started: Instant, verts: usize, inds: usize, edited: bool) -> Self {
+        Self { key, millis: started.elapsed().as_secs_f32() * 1000.0, verts, tris: inds / 3, edited }
+    }
+
+    // one line for the overlay or the console
+    pub fn describe(&self) -> String {
+        let what = match self.key {
+            AnyKey::Voxel(k) => tr!("mesh_profile.chunk", k.face, k.u_idx, k.v_idx),
+            AnyKey::Lod(k) => tr!("mesh_profile.lod", k.face, k.x, k.y, k.size),
+        };
+        let edited = if self.edited { tr!("mesh_profile.edited") } else { String::new() };
+        tr!("mesh_profile.timing", format!("{:.1}", self.millis), what, self.verts, self.tris, edited)
+    }
+}
+
+#[derive(Default)]
+pub struct MeshProfile {
+    // newest last, only the ones still inside the window
+    recent: VecDeque<(Instant, MeshTiming)>,
+    // running averages for chunks and for lods, None until the first of each kind
+    average: [Option<f32>; 2],
+    pub meshed: u64,
+    outliers: Vec<MeshTiming>,
+}
+
+impl MeshProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, timing: MeshTiming) {
+        let now = Instant::now();
+        self.meshed += 1;
+        let kind = matches!(timing.key, AnyKey::Lod(_)) as usize;
+        let average = self.average[kind].get_or_insert(timing.millis);
+        if timing.millis >= OUTLIER_MIN_MS && timing.millis > *average * OUTLIER_FACTOR {
+            self.outliers.push(timing);
+        }
+        *average += (timing.millis - *average) * AVERAGE_WEIGHT;
+        while self.recent.front().is_some_and(|(t, _)| now.duration_since(*t) > PROFILE_WINDOW) {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((now, timing));
+    }
+
+    // the slowest meshes of the last minute, slowest first
+    pub fn slowest(&self) -> Vec<MeshTiming> {
+        let mut timings: Vec<MeshTiming> = self.recent.iter().map(|(_, t)| *t).collect();
+        timings.sort_by(|a, b| b.millis.total_cmp(&a.millis));
+        timings.truncate(SLOWEST_SHOWN);
+        timings
+    }
+
+    // outliers since the last call
+    pub fn take_outliers(&mut self) -> Vec<MeshTiming> {
+        std::mem::take(&mut self.outliers)
+    }
+}
@@ -0,0 +1,757 @@
+//mob.rs
+
+// non-player entities: test dummies spawned with /spawn (cubes that wander about and jump over steps,
+// balls that bounce and roll), birds, hover bikes (see vehicle.rs) and villagers (see village.rs), who trade
+// (see trade.rs). the
+// dummies move through the same Physics as the player and push each other apart so piles of them behave.
+// birds flock as boids in a shell over the ground, and a few ambient flocks are kept around the player.
+// lurkers come out of dark caves near the player, find their way to them and bite. mobs are local to this
+// client and aren't saved.
+//
+// every mob is an entity (see ecs.rs) made of the components under COMPONENTS: Mobs::add puts each kind
+// together, and Mobs::update runs one system per behaviour over the entities that have its components.
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use glam::{IVec3, Mat3, Quat, Vec3};
+use crate::common::{BlockId, PlanetData};
+use crate::ecs::{Components, Entities, Entity};
+use crate::gen::CoordSystem;
+use crate::lighting::LightEngine;
+use crate::i18n::tr;
+use crate::pathfind;
+use crate::trade::Trades;
+use crate::physics::Physics;
+use crate::vehicle::{self, DriveInput};
+use crate::village::{self, VILLAGE_RADIUS};
+
+// --- CONSTANTS ---
+pub const MAX_MOBS: usize = 2048;
+pub const MAX_SPAWN: usize = 500;
+// half the side of a cube and the radius of a ball, both rest on their lowest point
+pub const MOB_RADIUS: f32 = 0.4;
+// the length and wingspan of a bird
+pub const BIRD_SIZE: (f32, f32) = (0.6, 0.9);
+const WANDER_SPEED: f32 = 2.0;
+const WANDER_ACCEL: f32 = 10.0;
+// how far a cube picks its next spot, and how long it heads there before picking another
+const WANDER_RANGE: (f32, f32) = (3.0, 10.0);
+const WANDER_TIME: (f32, f32) = (2.0, 6.0);
+const CUBE_JUMP: f32 = 6.0;
+// share of speed a ball keeps when it bounces, and how fast it slows down rolling
+const BALL_RESTITUTION: f32 = 0.6;
+const BALL_ROLL_FRICTION: f32 = 0.8;
+// slower hits than this don't bounce, the ball just settles
+const MIN_BOUNCE_SPEED: f32 = 1.5;
+// how hard overlapping mobs get pushed apart, per block of overlap
+const SEPARATION: f32 = 20.0;
+
+// --- FLOCKING ---
+// birds see flockmates this far away, and steer clear of ones closer than AVOID_RADIUS
+const NEIGHBOR_RADIUS: f32 = 8.0;
+const AVOID_RADIUS: f32 = 2.0;
+const AVOID_WEIGHT: f32 = 8.0;
+const ALIGN_WEIGHT: f32 = 1.0;
+const COHESION_WEIGHT: f32 = 0.5;
+const BIRD_WANDER: f32 = 2.0;
+const BIRD_SPEED: (f32, f32) = (5.0, 11.0);
+// height band above the natural ground the birds keep to, and how hard they're steered back into it
+const SHELL: (f32, f32) = (12.0, 40.0);
+const SHELL_PUSH: f32 = 2.0;
+// ambient flocks: how big, how many at once, where new ones appear and when far ones go away
+const FLOCK_SIZE: usize = 16;
+const AMBIENT_FLOCKS: usize = 3;
+const FLOCK_SPAWN_DISTANCE: f32 = 80.0;
+const FLOCK_DESPAWN_DISTANCE: f32 = 300.0;
+// seconds between checks for whether another ambient flock is needed
+const SKY_INTERVAL: f32 = 5.0;
+
+// --- VILLAGERS ---
+// villages closer than this get their villagers, and lose them again past VILLAGE_DESPAWN_DISTANCE
+const VILLAGERS: usize = 4;
+const VILLAGE_SPAWN_DISTANCE: f32 = 120.0;
+const VILLAGE_DESPAWN_DISTANCE: f32 = 200.0;
+
+// --- LURKERS ---
+const LURKER_HEALTH: f32 = 6.0;
+const LURKER_COLOR: [f32; 3] = [0.25, 0.1, 0.3];
+const LURKER_SPEED: f32 = 3.5;
+// lurkers notice the player this close, and go after them
+const SIGHT_RANGE: f32 = 24.0;
+// seconds between working out the way to the player again
+const PATH_INTERVAL: f32 = 0.5;
+// a bite: from how far between centers, how hard, how often, and how far it throws the player
+const BITE_RANGE: f32 = 1.3;
+const BITE_DAMAGE: f32 = 3.0;
+const BITE_COOLDOWN: f32 = 1.2;
+const BITE_KNOCKBACK: f32 = 7.0;
+// how many lurkers may be out at once, the ring around the player new ones come out in, and spots tried per check
+const MAX_LURKERS: usize = 6;
+const LURKER_SPAWN_RING: (f32, f32) = (10.0, 28.0);
+const LURKER_SPAWN_TRIES: usize = 8;
+const LURKER_DESPAWN_DISTANCE: f32 = 64.0;
+
+// --- COMBAT ---
+// a player's hit: how far it reaches, how much it takes off a lurker and how hard it pushes any mob
+pub const ATTACK_REACH: f32 = 4.0;
+const ATTACK_DAMAGE: f32 = 2.0;
+const ATTACK_KNOCKBACK: f32 = 8.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MobKind {
+    Cube,
+    Ball,
+    Bird,
+    Bike,
+    Villager,
+    Lurker,
+}
+
+impl MobKind {
+    pub const ALL: [MobKind; 6] = [MobKind::Cube, MobKind::Ball, MobKind::Bird, MobKind::Bike, MobKind::Villager, MobKind::Lurker];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MobKind::Cube => "cube",
+            MobKind::Ball => "ball",
+            MobKind::Bird => "bird",
+            MobKind::Bike => "bike",
+            MobKind::Villager => "villager",
+            MobKind::Lurker => "lurker",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == s)
+    }
+
+    // half the extent of the model along its own axes, y up and -z forward
+    pub fn half_size(self) -> Vec3 {
+        match self {
+            MobKind::Cube | MobKind::Ball => Vec3::splat(MOB_RADIUS),
+            MobKind::Bird => Vec3::new(BIRD_SIZE.1 * 0.5, 0.1, BIRD_SIZE.0 * 0.5),
+            MobKind::Bike => Vec3::new(0.45, 0.3, 1.1),
+            MobKind::Villager => Vec3::new(0.3, MOB_RADIUS, 0.25),
+            MobKind::Lurker => Vec3::new(0.35, 0.3, 0.5),
+        }
+    }
+}
+
+// --- COMPONENTS ---
+
+// where a mob is: its lowest point, like the player's feet, and which way the model faces
+pub struct Transform {
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+impl Transform {
+    pub fn center(&self) -> Vec3 {
+        self.position + Physics::get_up_vector(self.position) * MOB_RADIUS
+    }
+
+    // the capsule round a model of `half` size: its ends and radius. it runs along the model's longest axis and
+    // is as thick as the widest of the other two
+    pub fn capsule(&self, half: Vec3) -> (Vec3, Vec3, f32) {
+        let (axis, length, radius) = if half.x >= half.y && half.x >= half.z {
+            (Vec3::X, half.x, half.y.max(half.z))
+        } else if half.y >= half.z {
+            (Vec3::Y, half.y, half.x.max(half.z))
+        } else {
+            (Vec3::Z, half.z, half.x.max(half.y))
+        };
+        let reach = self.rotation * axis * (length - radius).max(0.0);
+        let center = self.center();
+        (center - reach, center + reach, radius)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    // falls and walks through the same Physics as the player
+    Walk,
+    // the same, bouncing off what it hits and rolling along the ground
+    Roll,
+    // steered by its flock, see Mobs::flock
+    Fly,
+    // floats on a spring, see vehicle::drive
+    Hover,
+}
+
+pub struct Body {
+    pub velocity: Vec3,
+    pub grounded: bool,
+    pub motion: Motion,
+    // the last move ran into a wall
+    blocked: bool,
+}
+
+// the model drawn for a mob and its tint
+pub struct Renderable {
+    pub kind: MobKind,
+    pub color: [f32; 3],
+}
+
+// only mobs with hitpoints can be killed, everything else just gets pushed about
+pub struct Hitpoints(pub f32);
+
+// ai: walking to a spot picked around home (or where it stands), and picking another every so often
+pub struct Wander {
+    target: Option<Vec3>,
+    timer: f32,
+    speed: f32,
+    // middle of the village a villager keeps to
+    home: Option<Vec3>,
+}
+
+impl Wander {
+    fn new(speed: f32) -> Self {
+        Self { target: None, timer: 0.0, speed, home: None }
+    }
+}
+
+// ai: the floor points a lurker follows to the player, when it works them out again, and its bite
+#[derive(Default)]
+pub struct Hunter {
+    path: Vec<Vec3>,
+    path_timer: f32,
+    bite_timer: f32,
+}
+
+pub struct Vehicle {
+    // the player is sitting on it
+    pub ridden: bool,
+}
+
+// spawned by the world rather than a command, so it's let go when the player moves away
+pub struct Ambient;
+
+pub struct Mobs {
+    pub entities: Entities,
+    pub transforms: Components<Transform>,
+    pub bodies: Components<Body>,
+    pub renderables: Components<Renderable>,
+    pub hitpoints: Components<Hitpoints>,
+    wanderers: Components<Wander>,
+    hunters: Components<Hunter>,
+    pub vehicles: Components<Vehicle>,
+    pub traders: Components<Trades>,
+    ambient: Components<Ambient>,
+    rng: Rng,
+    sky_timer: f32,
+    village_timer: f32,
+    lurker_timer: f32,
+    // middles of the villages, worked out once for each seed and resolution
+    villages: Option<((u32, u32), Vec<Vec3>)>,
+    // kind and position of every mob added since the last take_spawned
+    spawned: Vec<(MobKind, Vec3)>,
+}
+
+impl Mobs {
+    pub fn new() -> Self {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(1);
+        Self {
+            entities: Entities::default(), transforms: Components::default(), bodies: Components::default(), renderables: Components::default(),
+            hitpoints: Components::default(), wanderers: Components::default(), hunters: Components::default(), vehicles: Components::default(),
+            traders: Components::default(), ambient: Components::default(), rng: Rng::new(seed), sky_timer: 0.0, village_timer: 0.0, lurker_timer: 0.0, villages: None, spawned: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn kind(&self, e: Entity) -> Option<MobKind> {
+        self.renderables.get(e).map(|r| r.kind)
+    }
+
+    // a new mob of `kind`, put together from the components that kind is made of
+    fn add(&mut self, kind: MobKind, position: Vec3, velocity: Vec3, rotation: Quat, color: [f32; 3], ambient: bool) -> Entity {
+        let e = self.entities.spawn();
+        let motion = match kind {
+            MobKind::Ball => Motion::Roll,
+            MobKind::Bird => Motion::Fly,
+            MobKind::Bike => Motion::Hover,
+            MobKind::Cube | MobKind::Villager | MobKind::Lurker => Motion::Walk,
+        };
+        self.transforms.insert(e, Transform { position, rotation });
+        self.bodies.insert(e, Body { velocity, grounded: false, motion, blocked: false });
+        self.renderables.insert(e, Renderable { kind, color });
+        match kind {
+            MobKind::Cube => self.wanderers.insert(e, Wander::new(WANDER_SPEED)),
+            MobKind::Villager => {
+                self.wanderers.insert(e, Wander::new(WANDER_SPEED));
+                self.traders.insert(e, Trades::roll(&mut self.rng));
+            },
+            MobKind::Lurker => {
+                self.wanderers.insert(e, Wander::new(LURKER_SPEED));
+                self.hunters.insert(e, Hunter::default());
+                self.hitpoints.insert(e, Hitpoints(LURKER_HEALTH));
+            },
+            MobKind::Bike => self.vehicles.insert(e, Vehicle { ridden: false }),
+            MobKind::Ball | MobKind::Bird => {},
+        }
+        if ambient { self.ambient.insert(e, Ambient); }
+        self.spawned.push((kind, position));
+        e
+    }
+
+    pub fn despawn(&mut self, e: Entity) {
+        if !self.entities.despawn(e) { return; }
+        self.transforms.remove(e);
+        self.bodies.remove(e);
+        self.renderables.remove(e);
+        self.hitpoints.remove(e);
+        self.wanderers.remove(e);
+        self.hunters.remove(e);
+        self.vehicles.remove(e);
+        self.traders.remove(e);
+        self.ambient.remove(e);
+    }
+
+    fn despawn_where(&mut self, remove: impl Fn(&Self, Entity) -> bool) {
+        let gone: Vec<Entity> = self.entities.iter().filter(|&e| remove(self, e)).collect();
+        for e in gone {
+            self.despawn(e);
+        }
+    }
+
+    fn distance(&self, e: Entity, to: Vec3) -> f32 {
+        self.transforms.get(e).map_or(f32::INFINITY, |t| t.position.distance(to))
+    }
+
+    // scatters `count` mobs on top of `around`, further out the more there are. returns how many fit
+    pub fn spawn(&mut self, kind: MobKind, count: usize, around: BlockId, planet: &PlanetData) -> Result<usize, String> {
+        let room = MAX_MOBS - self.len();
+        if room == 0 {
+            return Err(tr!("mob.too_many", MAX_MOBS));
+        }
+        let res = planet.resolution;
+        let top = |u, v| CoordSystem::get_vertex_pos(around.face, u, v, around.layer + 1, res);
+        let center = (top(around.u, around.v) + top(around.u + 1, around.v) + top(around.u, around.v + 1) + top(around.u + 1, around.v + 1)) / 4.0;
+        let count = count.min(room);
+        if kind == MobKind::Bird {
+            self.spawn_flock(center, count, planet, false);
+            return Ok(count);
+        }
+        let up = Physics::get_up_vector(center);
+        let (a, b) = up.any_orthonormal_pair();
+        let spread = (count as f32).sqrt() * MOB_RADIUS * 2.0;
+
+        for _ in 0..count {
+            let mut position = center + (a * (self.rng.next() - 0.5) + b * (self.rng.next() - 0.5)) * spread * 2.0;
+            // lift out of any hill the offset landed in
+            for _ in 0..16 {
+                if !Physics::check_collision(position, planet) { break; }
+                position += Physics::get_up_vector(position);
+            }
+            let color = match kind {
+                MobKind::Cube => [0.9, 0.5 + self.rng.next() * 0.3, 0.1],
+                MobKind::Bike => [0.8, 0.1, 0.15],
+                MobKind::Villager => villager_color(&mut self.rng),
+                MobKind::Lurker => LURKER_COLOR,
+                _ => [0.2 + self.rng.next() * 0.6, 0.3, 0.9],
+            };
+            let rotation = Quat::from_rotation_arc(Vec3::Y, Physics::get_up_vector(position));
+            self.add(kind, position, Vec3::ZERO, rotation, color, false);
+        }
+        Ok(count)
+    }
+
+    // birds in the middle of the shell above `center`, all setting off roughly the same way
+    fn spawn_flock(&mut self, center: Vec3, count: usize, planet: &PlanetData, ambient: bool) {
+        let up = Physics::get_up_vector(center);
+        let (a, b) = up.any_orthonormal_pair();
+        let heading = self.rng.next() * std::f32::consts::TAU;
+        let heading = a * heading.cos() + b * heading.sin();
+        let altitude = ground_radius(center, planet) + (SHELL.0 + SHELL.1) * 0.5;
+        let spread = (count as f32).sqrt() * AVOID_RADIUS;
+        for _ in 0..count.min(MAX_MOBS - self.len()) {
+            let offset = (a * (self.rng.next() - 0.5) + b * (self.rng.next() - 0.5)) * spread * 2.0;
+            let position = (up * altitude + offset).normalize_or_zero() * (altitude + (self.rng.next() - 0.5) * AVOID_RADIUS * 2.0);
+            let velocity = (heading + offset.normalize_or_zero() * 0.3) * BIRD_SPEED.0;
+            let shade = 0.15 + self.rng.next() * 0.15;
+            self.add(MobKind::Bird, position, velocity, Quat::IDENTITY, [shade, shade, shade * 1.2], ambient);
+        }
+    }
+
+    // keeps a few flocks within sight of `around` and lets far ones go. `enabled` off removes them all
+    pub fn update_sky(&mut self, dt: f32, enabled: bool, around: Vec3, planet: &PlanetData) {
+        self.sky_timer -= dt;
+        if self.sky_timer > 0.0 { return; }
+        self.sky_timer = SKY_INTERVAL;
+
+        let ambient_bird = |mobs: &Self, e| mobs.ambient.contains(e) && mobs.kind(e) == Some(MobKind::Bird);
+        self.despawn_where(|mobs, e| ambient_bird(mobs, e) && (!enabled || mobs.distance(e, around) >= FLOCK_DESPAWN_DISTANCE));
+        let ambient = self.entities.iter().filter(|&e| ambient_bird(self, e)).count();
+        if !enabled || ambient >= FLOCK_SIZE * AMBIENT_FLOCKS || self.len() + FLOCK_SIZE > MAX_MOBS { return; }
+
+        let up = Physics::get_up_vector(around);
+        let (a, b) = up.any_orthonormal_pair();
+        let angle = self.rng.next() * std::f32::consts::TAU;
+        let center = around + (a * angle.cos() + b * angle.sin()) * FLOCK_SPAWN_DISTANCE;
+        self.spawn_flock(center, FLOCK_SIZE, planet, true);
+    }
+
+    // villagers for the villages near `around`, the ones of villages far away are let go
+    pub fn update_villages(&mut self, dt: f32, around: Vec3, planet: &PlanetData) {
+        self.village_timer -= dt;
+        if self.village_timer > 0.0 { return; }
+        self.village_timer = SKY_INTERVAL;
+
+        let key = (planet.seed, planet.resolution);
+        if self.villages.as_ref().is_none_or(|(k, _)| *k != key) {
+            let centers = village::sites(planet).into_iter().map(|site| village::center(site, planet.resolution)).collect();
+            self.villages = Some((key, centers));
+        }
+        let home = |mobs: &Self, e| mobs.wanderers.get(e).and_then(|w| w.home);
+        self.despawn_where(|mobs, e| home(mobs, e).is_some_and(|home| home.distance(around) >= VILLAGE_DESPAWN_DISTANCE));
+        let Some((_, centers)) = &self.villages else { return };
+        for center in centers.clone() {
+            if center.distance(around) > VILLAGE_SPAWN_DISTANCE || self.wanderers.iter().any(|(_, w)| w.home == Some(center)) { continue; }
+            let up = Physics::get_up_vector(center);
+            let (a, b) = up.any_orthonormal_pair();
+            for _ in 0..VILLAGERS.min(MAX_MOBS - self.len()) {
+                let angle = self.rng.next() * std::f32::consts::TAU;
+                let mut position = center + (a * angle.cos() + b * angle.sin()) * self.rng.range((1.0, 3.0));
+                for _ in 0..16 {
+                    if !Physics::check_collision(position, planet) { break; }
+                    position += Physics::get_up_vector(position);
+                }
+                let color = villager_color(&mut self.rng);
+                let e = self.add(MobKind::Villager, position, Vec3::ZERO, Quat::from_rotation_arc(Vec3::Y, up), color, false);
+                if let Some(wander) = self.wanderers.get_mut(e) { wander.home = Some(center); }
+            }
+        }
+    }
+
+    // lurkers: new ones out of the dark near `player`, far ones let go, and the rest chasing and biting.
+    // returns a bite that landed this frame, its damage and the push it gives the player. `enabled` off
+    // sends every lurker that came out on its own away
+    pub fn update_lurkers(&mut self, dt: f32, enabled: bool, player: Vec3, planet: &PlanetData) -> Option<(f32, Vec3)> {
+        self.despawn_where(|mobs, e| mobs.hunters.contains(e) && mobs.ambient.contains(e) && (!enabled || mobs.distance(e, player) >= LURKER_DESPAWN_DISTANCE));
+        self.lurker_timer -= dt;
+        if enabled && self.lurker_timer <= 0.0 {
+            self.lurker_timer = SKY_INTERVAL;
+            self.spawn_lurkers(player, planet);
+        }
+
+        let mut bite = None;
+        for (e, hunter) in self.hunters.iter_mut() {
+            let (Some(transform), Some(wander)) = (self.transforms.get(e), self.wanderers.get_mut(e)) else { continue };
+            hunter.bite_timer -= dt;
+            if transform.position.distance(player) > SIGHT_RANGE {
+                hunter.path.clear();
+                continue;
+            }
+            hunter.path_timer -= dt;
+            if hunter.path_timer <= 0.0 {
+                hunter.path_timer = PATH_INTERVAL;
+                hunter.path = pathfind::find_path(transform.position, player, planet).unwrap_or_default();
+            }
+            // waypoints reached are dropped, with none left it goes straight at the player
+            let up = Physics::get_up_vector(transform.position);
+            while hunter.path.first().is_some_and(|p| { let d = *p - transform.position; (d - up * d.dot(up)).length() < 0.5 }) {
+                hunter.path.remove(0);
+            }
+            wander.target = Some(hunter.path.first().copied().unwrap_or(player));
+            wander.timer = PATH_INTERVAL;
+
+            let body = player + Physics::get_up_vector(player) * Physics::PLAYER_HEIGHT * 0.5;
+            if hunter.bite_timer <= 0.0 && transform.center().distance(body) < BITE_RANGE + Physics::PLAYER_RADIUS {
+                hunter.bite_timer = BITE_COOLDOWN;
+                bite = Some((BITE_DAMAGE, Physics::knockback(transform.position, player, BITE_KNOCKBACK)));
+            }
+        }
+        bite
+    }
+
+    // tries a few spots round `player` for a dark floor to put a lurker on
+    fn spawn_lurkers(&mut self, player: Vec3, planet: &PlanetData) {
+        if self.hunters.iter().count() >= MAX_LURKERS || self.len() >= MAX_MOBS { return; }
+        let up = Physics::get_up_vector(player);
+        let (a, b) = up.any_orthonormal_pair();
+        for _ in 0..LURKER_SPAWN_TRIES {
+            let angle = self.rng.next() * std::f32::consts::TAU;
+            let spot = player + (a * angle.cos() + b * angle.sin()) * self.rng.range(LURKER_SPAWN_RING) + up * (self.rng.next() - 0.5) * LURKER_SPAWN_RING.0;
+            let Some(cell) = pathfind::cell_at(spot, planet) else { continue };
+            let floor = BlockId { layer: cell.layer - 1, ..cell };
+            if LightEngine::calculate_light(floor, planet) >= LightEngine::MAX_LIGHT { continue; }
+            let position = pathfind::floor_point(cell, planet.resolution);
+            let rotation = Quat::from_rotation_arc(Vec3::Y, Physics::get_up_vector(position));
+            self.add(MobKind::Lurker, position, Vec3::ZERO, rotation, LURKER_COLOR, true);
+            return;
+        }
+    }
+
+    // the closest mob whose capsule the ray touches within `reach`, and how far along it
+    pub fn raycast(&self, start: Vec3, dir: Vec3, reach: f32) -> Option<(Entity, f32)> {
+        self.renderables.iter()
+            .filter(|(e, _)| !self.vehicles.get(*e).is_some_and(|v| v.ridden))
+            .filter_map(|(e, r)| {
+                let (a, b, radius) = self.transforms.get(e)?.capsule(r.kind.half_size());
+                Physics::ray_capsule(start, dir, a, b, radius).filter(|&t| t <= reach).map(|t| (e, t))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    // the player hits `e` from `from`: pushed away, and hurt if it has hitpoints, gone when they run out.
+    // returns whether it died
+    pub fn attack(&mut self, e: Entity, from: Vec3) -> bool {
+        let (Some(transform), Some(body)) = (self.transforms.get(e), self.bodies.get_mut(e)) else { return false };
+        body.velocity += Physics::knockback(from, transform.position, ATTACK_KNOCKBACK);
+        body.grounded = false;
+        let Some(hp) = self.hitpoints.get_mut(e) else { return false };
+        hp.0 -= ATTACK_DAMAGE;
+        if hp.0 > 0.0 { return false; }
+        self.despawn(e);
+        true
+    }
+
+    // mobs added since the last call, for main to put on the event bus
+    pub fn take_spawned(&mut self) -> Vec<(MobKind, Vec3)> {
+        std::mem::take(&mut self.spawned)
+    }
+
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        self.transforms.clear();
+        self.bodies.clear();
+        self.renderables.clear();
+        self.hitpoints.clear();
+        self.wanderers.clear();
+        self.hunters.clear();
+        self.vehicles.clear();
+        self.traders.clear();
+        self.ambient.clear();
+    }
+
+    // runs every system once. `drive` steers the bike being ridden, if any
+    pub fn update(&mut self, dt: f32, planet: &PlanetData, drive: DriveInput) {
+        if self.entities.is_empty() { return; }
+        // a long hitch would throw everything through the floor
+        let dt = dt.min(0.1);
+        self.drive(dt, planet, drive);
+        self.wander(dt);
+        self.move_bodies(dt, planet);
+        self.separate(dt);
+        self.flock(dt, planet);
+    }
+
+    fn drive(&mut self, dt: f32, planet: &PlanetData, drive: DriveInput) {
+        for (e, vehicle) in self.vehicles.iter() {
+            let (Some(transform), Some(body)) = (self.transforms.get_mut(e), self.bodies.get_mut(e)) else { continue };
+            vehicle::drive(transform, body, if vehicle.ridden { drive } else { DriveInput::default() }, dt, planet);
+        }
+    }
+
+    // steers every wanderer towards its spot, picking a new one when it's due
+    fn wander(&mut self, dt: f32) {
+        for (e, wander) in self.wanderers.iter_mut() {
+            let (Some(transform), Some(body)) = (self.transforms.get_mut(e), self.bodies.get_mut(e)) else { continue };
+            let up = Physics::get_up_vector(transform.position);
+            wander.timer -= dt;
+            if wander.timer <= 0.0 || wander.target.is_none() {
+                let (a, b) = up.any_orthonormal_pair();
+                let angle = self.rng.next() * std::f32::consts::TAU;
+                // villagers pick spots around the middle of their village, so they never stray far from it
+                let (from, range) = match wander.home {
+                    Some(home) => (home, (0.0, VILLAGE_RADIUS)),
+                    None => (transform.position, WANDER_RANGE),
+                };
+                wander.target = Some(from + (a * angle.cos() + b * angle.sin()) * self.rng.range(range));
+                wander.timer = self.rng.range(WANDER_TIME);
+            }
+            let Some(target) = wander.target else { continue };
+            let to_target = target - transform.position;
+            let to_target = to_target - up * to_target.dot(up);
+            let horz = body.velocity - up * body.velocity.dot(up);
+            let wanted = if to_target.length() > 0.5 { to_target.normalize() * wander.speed } else { Vec3::ZERO };
+            let new_horz = horz + (wanted - horz).clamp_length_max(WANDER_ACCEL * dt);
+            body.velocity = new_horz + up * body.velocity.dot(up);
+
+            if let Some(dir) = wanted.try_normalize() {
+                let forward = transform.rotation * Vec3::NEG_Z;
+                transform.rotation = Quat::from_rotation_arc(forward, forward.lerp(dir, (4.0 * dt).min(1.0)).normalize_or_zero()) * transform.rotation;
+                // blocked by a step the solver can't climb, hop over it
+                if body.grounded && body.blocked {
+                    body.velocity += up * CUBE_JUMP;
+                }
+            }
+        }
+    }
+
+    // gravity and collisions for the walkers and rollers
+    fn move_bodies(&mut self, dt: f32, planet: &PlanetData) {
+        for (e, body) in self.bodies.iter_mut() {
+            if !matches!(body.motion, Motion::Walk | Motion::Roll) { continue; }
+            let Some(transform) = self.transforms.get_mut(e) else { continue };
+            let up = Physics::get_up_vector(transform.position);
+            if body.motion == Motion::Roll && body.grounded {
+                let horz = body.velocity - up * body.velocity.dot(up);
+                body.velocity -= horz * (BALL_ROLL_FRICTION * dt).min(1.0);
+            }
+            body.velocity -= up * Physics::GRAVITY * dt;
+
+            let before = body.velocity;
+            let (pos, vel, grounded) = Physics::solve_movement(transform.position, body.velocity, dt, planet, false, false, None);
+            let moved = pos - transform.position;
+            transform.position = pos;
+            body.velocity = vel;
+            body.grounded = grounded;
+            // whatever sideways speed the solver took away was a wall
+            let lost = (before - up * before.dot(up)) - (vel - up * vel.dot(up));
+            body.blocked = lost.length() > WANDER_SPEED * 0.5;
+
+            if body.motion == Motion::Roll {
+                let fall = -before.dot(up);
+                if grounded && fall > MIN_BOUNCE_SPEED {
+                    body.velocity += up * fall * BALL_RESTITUTION;
+                    body.grounded = false;
+                }
+                if lost.length() > MIN_BOUNCE_SPEED {
+                    body.velocity -= lost * BALL_RESTITUTION;
+                }
+                // roll along with the ground
+                let horz = moved - up * moved.dot(up);
+                if let Some(axis) = up.cross(horz).try_normalize() {
+                    transform.rotation = Quat::from_axis_angle(axis, horz.length() / MOB_RADIUS) * transform.rotation;
+                }
+            }
+            transform.rotation = Physics::align_to_planet(transform.rotation, Physics::get_up_vector(transform.position));
+        }
+    }
+
+    // pushes overlapping bodies apart, only checking the ones in neighbouring grid cells. birds keep their
+    // distance in the flock instead
+    fn separate(&mut self, dt: f32) {
+        let cell_size = MOB_RADIUS * 2.0;
+        let bodies: Vec<(Entity, Vec3)> = self.bodies.iter()
+            .filter(|(_, b)| b.motion != Motion::Fly)
+            .filter_map(|(e, _)| Some((e, self.transforms.get(e)?.center())))
+            .collect();
+        let grid = grid(bodies.iter().enumerate().map(|(i, (_, center))| (i, *center)), cell_size);
+
+        for (i, &(e, center)) in bodies.iter().enumerate() {
+            let mut push = Vec3::ZERO;
+            for j in nearby(&grid, center, cell_size) {
+                if j == i { continue; }
+                let away = center - bodies[j].1;
+                let overlap = MOB_RADIUS * 2.0 - away.length();
+                if overlap > 0.0 {
+                    // two mobs in the exact same spot still need some way out
+                    push += away.try_normalize().unwrap_or(Vec3::X) * overlap;
+                }
+            }
+            if let Some(body) = self.bodies.get_mut(e) {
+                body.velocity += push * SEPARATION * dt;
+            }
+        }
+    }
+
+    // boids: every bird steers away from crowding flockmates, towards their heading and towards their middle,
+    // and back into the shell when it strays out of it
+    fn flock(&mut self, dt: f32, planet: &PlanetData) {
+        let birds: Vec<(Entity, Vec3, Vec3)> = self.bodies.iter()
+            .filter(|(_, b)| b.motion == Motion::Fly)
+            .filter_map(|(e, b)| Some((e, self.transforms.get(e)?.position, b.velocity)))
+            .collect();
+        if birds.is_empty() { return; }
+        let grid = grid(birds.iter().enumerate().map(|(b, (_, pos, _))| (b, *pos)), NEIGHBOR_RADIUS);
+
+        for &(e, pos, vel) in &birds {
+            let (mut avoid, mut heading, mut middle, mut n) = (Vec3::ZERO, Vec3::ZERO, Vec3::ZERO, 0.0);
+            for b in nearby(&grid, pos, NEIGHBOR_RADIUS) {
+                let (other, other_pos, other_vel) = birds[b];
+                let away = pos - other_pos;
+                let d = away.length();
+                if other == e || d > NEIGHBOR_RADIUS { continue; }
+                if d < AVOID_RADIUS {
+                    avoid += away.try_normalize().unwrap_or(Vec3::X) * (AVOID_RADIUS - d);
+                }
+                heading += other_vel;
+                middle += other_pos;
+                n += 1.0;
+            }
+            let mut steer = avoid * AVOID_WEIGHT;
+            if n > 0.0 {
+                steer += (heading / n - vel) * ALIGN_WEIGHT + (middle / n - pos) * COHESION_WEIGHT;
+            }
+
+            let up = Physics::get_up_vector(pos);
+            let altitude = pos.length() - ground_radius(pos, planet);
+            if altitude < SHELL.0 {
+                steer += up * (SHELL.0 - altitude) * SHELL_PUSH;
+            } else if altitude > SHELL.1 {
+                steer -= up * (altitude - SHELL.1) * SHELL_PUSH;
+            }
+            let (a, b) = up.any_orthonormal_pair();
+            steer += (a * (self.rng.next() - 0.5) + b * (self.rng.next() - 0.5)) * BIRD_WANDER;
+
+            let vel = vel + steer * dt;
+            let speed = vel.length().clamp(BIRD_SPEED.0, BIRD_SPEED.1);
+            let forward = vel.try_normalize().unwrap_or(a);
+            let (Some(transform), Some(body)) = (self.transforms.get_mut(e), self.bodies.get_mut(e)) else { continue };
+            body.velocity = forward * speed;
+            transform.position += body.velocity * dt;
+            // nose along the flight, wings level with the ground below
+            if let Some(right) = forward.cross(up).try_normalize() {
+                transform.rotation = Quat::from_mat3(&Mat3::from_cols(right, right.cross(forward), -forward));
+            }
+        }
+    }
+}
+
+// clothes in a muted earthy color
+fn villager_color(rng: &mut Rng) -> [f32; 3] {
+    let shade = 0.35 + rng.next() * 0.35;
+    [shade, shade * (0.6 + rng.next() * 0.3), shade * 0.4]
+}
+
+// radius of the natural ground under `pos`, player edits aside
+fn ground_radius(pos: Vec3, planet: &PlanetData) -> f32 {
+    let res = planet.resolution;
+    // a point that's surely inside the layers, straight below or above `pos`
+    let probe = pos.normalize_or_zero() * CoordSystem::get_layer_radius(res / 2, res);
+    let layer = CoordSystem::pos_to_id(probe, res).map_or(res / 2, |id| planet.terrain.get_height(id.face, id.u, id.v));
+    CoordSystem::get_layer_radius(layer + 1, res)
+}
+
+// indices bucketed by the grid cell their point is in
+fn grid(points: impl Iterator<Item = (usize, Vec3)>, cell_size: f32) -> HashMap<IVec3, Vec<usize>> {
+    let mut grid: HashMap<IVec3, Vec<usize>> = HashMap::new();
+    for (i, p) in points {
+        grid.entry((p / cell_size).floor().as_ivec3()).or_default().push(i);
+    }
+    grid
+}
+
+// everything in the cell of `p` and the 26 around it
+fn nearby(grid: &HashMap<IVec3, Vec<usize>>, p: Vec3, cell_size: f32) -> impl Iterator<Item = usize> + '_ {
+    let home = (p / cell_size).floor().as_ivec3();
+    (0..27).flat_map(move |n| {
+        let offset = IVec3::new(n % 3 - 1, n / 3 % 3 - 1, n / 9 - 1);
+        grid.get(&(home + offset)).into_iter().flatten().copied()
+    })
+}
+
+// xorshift, plenty for scattering dummies and picking wander spots
+pub struct Rng(u32);
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Self(seed.max(1))
+    }
+
+    // uniform in [0, 1)
+    pub fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    pub fn range(&mut self, (lo, hi): (f32, f32)) -> f32 {
+        lo + (hi - lo) * self.next()
+    }
+}
@@ -0,0 +1,101 @@
+//monitor.rs
+
+// security cameras and the in-world screens showing them. /monitor camera puts a camera where the player
+// stands and looks, /monitor screen <n> puts a screen for camera n in front of them.
+// the renderer draws every camera into its own texture and maps it onto the screens, see Renderer::update_monitors.
+
+use glam::{Mat4, Quat, Vec3};
+use crate::common::reverse_z;
+use crate::entity::Player;
+use crate::i18n::tr;
+use crate::physics::Physics;
+
+// --- CONSTANTS ---
+pub const MAX_CAMERAS: usize = 8;
+pub const MAX_SCREENS: usize = 16;
+// screen size in blocks, same 4:3 as the camera textures
+const SCREEN_WIDTH: f32 = 4.0;
+const SCREEN_HEIGHT: f32 = 3.0;
+// how far in front of the player a new screen goes
+const SCREEN_DISTANCE: f32 = 4.0;
+const CAMERA_FOV: f32 = 70.0;
+const CAMERA_FAR: f32 = 2000.0;
+
+pub struct MonitorCamera {
+    pub eye: Vec3,
+    pub rotation: Quat,
+}
+
+impl MonitorCamera {
+    pub fn view_proj(&self, aspect: f32) -> Mat4 {
+        let up = Physics::get_up_vector(self.eye);
+        let forward = self.rotation * Vec3::NEG_Z;
+        let proj = reverse_z(Mat4::perspective_rh(CAMERA_FOV.to_radians(), aspect, 0.1, CAMERA_FAR));
+        proj * Mat4::look_at_rh(self.eye, self.eye + forward, up)
+    }
+}
+
+pub struct Screen {
+    pub camera: usize,
+    pub center: Vec3,
+    // half extents along the screen, as seen from the front
+    pub right: Vec3,
+    pub up: Vec3,
+}
+
+impl Screen {
+    // top left, top right, bottom right, bottom left
+    pub fn corners(&self) -> [Vec3; 4] {
+        let c = self.center;
+        [c - self.right + self.up, c + self.right + self.up, c + self.right - self.up, c - self.right - self.up]
+    }
+}
+
+pub struct Monitors {
+    pub cameras: Vec<MonitorCamera>,
+    pub screens: Vec<Screen>,
+    // set on every change, the caller rebuilds the screen meshes and clears it
+    pub changed: bool,
+}
+
+impl Monitors {
+    pub fn new() -> Self {
+        Self { cameras: Vec::new(), screens: Vec::new(), changed: false }
+    }
+
+    // a camera at the player's eyes looking where they look, returns its number
+    pub fn add_camera(&mut self, player: &Player) -> Result<usize, String> {
+        if self.cameras.len() >= MAX_CAMERAS {
+            return Err(tr!("monitor.too_many_cameras", MAX_CAMERAS));
+        }
+        let up = Physics::get_up_vector(player.position);
+        let eye = player.position + up * Physics::EYE_HEIGHT;
+        let rotation = player.rotation * Quat::from_axis_angle(Vec3::X, player.cam_pitch);
+        self.cameras.push(MonitorCamera { eye, rotation });
+        self.changed = true;
+        Ok(self.cameras.len())
+    }
+
+    // a screen for camera `number` (counting from 1) standing in front of the player, facing them
+    pub fn add_screen(&mut self, number: usize, player: &Player) -> Result<(), String> {
+        if number == 0 || number > self.cameras.len() {
+            return Err(tr!("monitor.no_such_camera", number));
+        }
+        if self.screens.len() >= MAX_SCREENS {
+            return Err(tr!("monitor.too_many_screens", MAX_SCREENS));
+        }
+        let up = Physics::get_up_vector(player.position);
+        let forward = player.rotation * Vec3::NEG_Z;
+        let right = player.rotation * Vec3::X;
+        let center = player.position + forward * SCREEN_DISTANCE + up * (SCREEN_HEIGHT * 0.5 + 0.5);
+        self.screens.push(Screen { camera: number - 1, center, right: right * (SCREEN_WIDTH * 0.5), up: up * (SCREEN_HEIGHT * 0.5) });
+        self.changed = true;
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.cameras.clear();
+        self.screens.clear();
+        self.changed = true;
+    }
+}
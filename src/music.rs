@@ -0,0 +1,140 @@
+//music.rs
+
+// picks background music from where the player is: the sunlit side, the night side, underground or out in space.
+// tracks are files under music/<context>/, and a change of context crossfades into a track of the new one.
+// there's no audio output in the engine yet, so this only decides what should play and how loud,
+// a backend streams `voices()` once there is one. until then AUDIO_OUTPUT is off and /music and the music
+// options only say that nothing plays.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use glam::Vec3;
+use crate::common::{sun_dir, PlanetData};
+use crate::gen::CoordSystem;
+
+// --- CONSTANTS ---
+pub const MUSIC_DIR: &str = "music";
+// whether anything actually plays. false until there's a backend
+pub const AUDIO_OUTPUT: bool = false;
+// the options that only matter with audio output
+pub const MUSIC_OPTIONS: [&str; 2] = ["music", "music_volume"];
+// seconds for one track to fade out and the next to fade in
+const CROSSFADE: f32 = 4.0;
+// a new context has to last this long before the music follows, so walking along a cave mouth doesn't flip-flop
+const CONTEXT_HOLD: f32 = 3.0;
+// layers of ground overhead that count as underground
+const UNDERGROUND_DEPTH: u32 = 4;
+// height above the tallest possible terrain where space starts, in blocks
+const SPACE_HEIGHT: f32 = 150.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MusicContext {
+    Day,
+    Night,
+    Underground,
+    Space,
+}
+
+impl MusicContext {
+    const ALL: [MusicContext; 4] = [MusicContext::Day, MusicContext::Night, MusicContext::Underground, MusicContext::Space];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MusicContext::Day => "day",
+            MusicContext::Night => "night",
+            MusicContext::Underground => "underground",
+            MusicContext::Space => "space",
+        }
+    }
+
+    // where `pos` is: far above the surface, under the ground, or on the day or night side
+    pub fn at(pos: Vec3, planet: &PlanetData) -> Self {
+        let res = planet.resolution;
+        if pos.length() > CoordSystem::get_layer_radius(res, res) + SPACE_HEIGHT {
+            return MusicContext::Space;
+        }
+        if let Some(id) = CoordSystem::pos_to_id(pos, res) {
+            if id.layer + UNDERGROUND_DEPTH <= planet.terrain.get_height(id.face, id.u, id.v) {
+                return MusicContext::Underground;
+            }
+        }
+        if pos.normalize_or_zero().dot(sun_dir()) >= 0.0 { MusicContext::Day } else { MusicContext::Night }
+    }
+}
+
+struct Track {
+    path: PathBuf,
+    context: MusicContext,
+}
+
+pub struct Music {
+    tracks: Vec<Track>,
+    // tracks sounding right now and their fade gain. the last one is fading in, the rest out
+    voices: Vec<(usize, f32)>,
+    pub context: MusicContext,
+    // context seen lately and for how long
+    candidate: (MusicContext, f32),
+    // bumped on every pick so each context cycles through its tracks
+    picks: usize,
+}
+
+impl Music {
+    pub fn load(dir: impl AsRef<Path>) -> Self {
+        let mut tracks = Vec::new();
+        for context in MusicContext::ALL {
+            let Ok(entries) = fs::read_dir(dir.as_ref().join(context.name())) else { continue };
+            let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| is_audio_file(p)).collect();
+            paths.sort();
+            tracks.extend(paths.into_iter().map(|path| Track { path, context }));
+        }
+        Self { tracks, voices: Vec::new(), context: MusicContext::Day, candidate: (MusicContext::Day, 0.0), picks: 0 }
+    }
+
+    pub fn update(&mut self, dt: f32, context: MusicContext) {
+        if context == self.candidate.0 {
+            self.candidate.1 += dt;
+        } else {
+            self.candidate = (context, 0.0);
+        }
+        let switched = self.candidate.0 != self.context && self.candidate.1 >= CONTEXT_HOLD;
+        if switched || self.voices.is_empty() {
+            self.context = self.candidate.0;
+            self.next();
+        }
+
+        let step = dt / CROSSFADE;
+        let last = self.voices.len().saturating_sub(1);
+        for (i, (_, gain)) in self.voices.iter_mut().enumerate() {
+            *gain = if i == last { (*gain + step).min(1.0) } else { *gain - step };
+        }
+        self.voices.retain(|(_, gain)| *gain > 0.0);
+    }
+
+    // fades over to the next track of the current context
+    pub fn next(&mut self) {
+        let choices: Vec<usize> = (0..self.tracks.len()).filter(|i| self.tracks[*i].context == self.context).collect();
+        if choices.is_empty() { return; }
+        let pick = choices[self.picks % choices.len()];
+        self.picks += 1;
+        if self.voices.last().is_some_and(|(track, _)| *track == pick) { return; }
+        self.voices.retain(|(track, _)| *track != pick);
+        self.voices.push((pick, 0.0));
+    }
+
+    pub fn track_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn now_playing(&self) -> Option<&Path> {
+        self.voices.last().map(|(track, _)| self.tracks[*track].path.as_path())
+    }
+
+    // every track that should be audible and its gain, `volume` folded in
+    pub fn voices(&self, volume: f32) -> impl Iterator<Item = (&Path, f32)> {
+        self.voices.iter().map(move |(track, gain)| (self.tracks[*track].path.as_path(), gain * volume))
+    }
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    matches!(path.extension().and_then(|x| x.to_str()), Some("ogg" | "wav" | "flac" | "mp3"))
+}
@@ -0,0 +1,499 @@
+//net.rs
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use glam::{Vec3, Quat};
+use crate::codec::*;
+use crate::common::{BlockId, BlockType, ChunkKey, ChunkMods, PlanetData, CHUNK_SIZE};
+use crate::gen::{CoordSystem, ViewDistance};
+use crate::marker::{put_marker, read_marker, Marker};
+use crate::events::{GameEvent, Subscriber};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+// --- CONSTANTS ---
+pub const DEFAULT_PORT: u16 = 27015;
+pub const PROTOCOL_VERSION: u32 = 11;
+
+// things are replicated while they stay inside the voxel detail bubble,
+// and dropped once they're this much further out so they don't flicker at the edge
+pub const INTEREST_EXIT_FACTOR: f32 = 1.2;
+
+// frames bigger than this are treated as a broken or hostile connection
+const MAX_FRAME: usize = 16 * 1024 * 1024;
+
+// --- MESSAGES ---
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    // client -> server
+    // `secret` proves the name is an op's, see ServerConfig. empty for everyone else
+    Hello { version: u32, name: String, secret: String },
+    PlayerMove { position: Vec3, rotation: Quat },
+    Command { text: String },
+
+    // server -> client
+    Welcome { client_id: u32, resolution: u32, seed: u32 },
+    // another player came into (or left) this client's interest radius
+    PlayerEnter { client_id: u32, name: String, position: Vec3, rotation: Quat },
+    PlayerState { client_id: u32, position: Vec3, rotation: Quat },
+    PlayerExit { client_id: u32 },
+    // the server rejected our last move, go back here
+    Correction { position: Vec3 },
+    // every edit in the chunks that came into range, gzipped. terrain itself is never sent,
+    // the client regenerates it from the seed in Welcome
+    ChunkBatch { chunks: Vec<(ChunkKey, ChunkMods)> },
+    Log { text: String },
+
+    // both directions
+    // `block` is what a placed block is made of, a mined one ignores it
+    BlockEdit { id: BlockId, placed: bool, block: BlockType },
+    // writes a sign on a block, empty text takes it down
+    SignEdit { id: BlockId, text: String, facing: u8 },
+    // makes a block a teleporter on `channel`, empty takes the teleporter off
+    TeleporterEdit { id: BlockId, channel: String },
+    // adds or changes a map marker. the server fills in the owner from who sent it
+    MarkerSet { marker: Marker },
+    MarkerRemove { owner: String, name: String },
+    // client -> server: the teleporter at `from` charged up under us. the server moves us to its other end and
+    // answers with a Correction there, a jump that far would fail move validation otherwise
+    Teleport { from: BlockId },
+    // client asks to enter/leave spectator mode, the server answers with the state it allowed
+    Spectator { enabled: bool },
+    Disconnect { reason: String },
+}
+
+impl Message {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            Message::Hello { version, name, secret } => {
+                out.push(0);
+                put_u32(&mut out, *version);
+                put_str(&mut out, name);
+                put_str(&mut out, secret);
+            },
+            Message::PlayerMove { position, rotation } => {
+                out.push(1);
+                put_vec3(&mut out, *position);
+                put_quat(&mut out, *rotation);
+            },
+            Message::Welcome { client_id, resolution, seed } => {
+                out.push(2);
+                put_u32(&mut out, *client_id);
+                put_u32(&mut out, *resolution);
+                put_u32(&mut out, *seed);
+            },
+            Message::PlayerEnter { client_id, name, position, rotation } => {
+                out.push(3);
+                put_u32(&mut out, *client_id);
+                put_str(&mut out, name);
+                put_vec3(&mut out, *position);
+                put_quat(&mut out, *rotation);
+            },
+            Message::PlayerExit { client_id } => {
+                out.push(4);
+                put_u32(&mut out, *client_id);
+            },
+            Message::Log { text } => {
+                out.push(5);
+                put_str(&mut out, text);
+            },
+            Message::BlockEdit { id, placed, block } => {
+                out.push(6);
+                put_block_id(&mut out, *id);
+                out.push(*placed as u8);
+                out.push(block.index());
+            },
+            Message::Disconnect { reason } => {
+                out.push(7);
+                put_str(&mut out, reason);
+            },
+            Message::Command { text } => {
+                out.push(8);
+                put_str(&mut out, text);
+            },
+            Message::PlayerState { client_id, position, rotation } => {
+                out.push(9);
+                put_u32(&mut out, *client_id);
+                put_vec3(&mut out, *position);
+                put_quat(&mut out, *rotation);
+            },
+            Message::Spectator { enabled } => {
+                out.push(11);
+                out.push(*enabled as u8);
+            },
+            Message::Correction { position } => {
+                out.push(12);
+                put_vec3(&mut out, *position);
+            },
+            Message::SignEdit { id, text, facing } => {
+                out.push(13);
+                put_block_id(&mut out, *id);
+                put_str(&mut out, text);
+                out.push(*facing);
+            },
+            Message::TeleporterEdit { id, channel } => {
+                out.push(14);
+                put_block_id(&mut out, *id);
+                put_str(&mut out, channel);
+            },
+            Message::MarkerSet { marker } => {
+                out.push(15);
+                put_marker(&mut out, marker);
+            },
+            Message::MarkerRemove { owner, name } => {
+                out.push(16);
+                put_str(&mut out, owner);
+                put_str(&mut out, name);
+            },
+            Message::Teleport { from } => {
+                out.push(17);
+                put_block_id(&mut out, *from);
+            },
+            Message::ChunkBatch { chunks } => {
+                out.push(10);
+                put_bytes(&mut out, &encode_chunk_batch(chunks));
+            },
+        }
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ByteReader::new(bytes);
+        let msg = match r.u8()? {
+            0 => Message::Hello { version: r.u32()?, name: r.str()?, secret: r.str()? },
+            1 => Message::PlayerMove { position: r.vec3()?, rotation: r.quat()? },
+            2 => Message::Welcome { client_id: r.u32()?, resolution: r.u32()?, seed: r.u32()? },
+            3 => Message::PlayerEnter { client_id: r.u32()?, name: r.str()?, position: r.vec3()?, rotation: r.quat()? },
+            4 => Message::PlayerExit { client_id: r.u32()? },
+            5 => Message::Log { text: r.str()? },
+            6 => Message::BlockEdit { id: r.block_id()?, placed: r.u8()? != 0, block: r.block_type()? },
+            7 => Message::Disconnect { reason: r.str()? },
+            8 => Message::Command { text: r.str()? },
+            9 => Message::PlayerState { client_id: r.u32()?, position: r.vec3()?, rotation: r.quat()? },
+            10 => Message::ChunkBatch { chunks: decode_chunk_batch(r.blob()?)? },
+            11 => Message::Spectator { enabled: r.u8()? != 0 },
+            12 => Message::Correction { position: r.vec3()? },
+            13 => Message::SignEdit { id: r.block_id()?, text: r.str()?, facing: r.u8()? },
+            14 => Message::TeleporterEdit { id: r.block_id()?, channel: r.str()? },
+            15 => Message::MarkerSet { marker: read_marker(&mut r)? },
+            16 => Message::MarkerRemove { owner: r.str()?, name: r.str()? },
+            17 => Message::Teleport { from: r.block_id()? },
+            tag => return Err(invalid(&format!("unknown message tag {}", tag))),
+        };
+        Ok(msg)
+    }
+}
+
+// --- INTEREST ---
+
+// how far a client can see edits and other players: the edge of the full-detail voxel chunks
+// at the default view distance, the same for everyone whatever they set locally
+pub fn interest_radius(res: u32) -> f32 {
+    CoordSystem::lod_split_distance(CHUNK_SIZE * 2, res, ViewDistance::default())
+}
+
+pub fn chunk_center(key: ChunkKey, res: u32) -> Vec3 {
+    let u = (key.u_idx * CHUNK_SIZE + CHUNK_SIZE / 2).min(res - 1);
+    let v = (key.v_idx * CHUNK_SIZE + CHUNK_SIZE / 2).min(res - 1);
+    CoordSystem::get_vertex_pos(key.face, u, v, res / 2, res)
+}
+
+// --- CHUNK DELTAS ---
+
+// blocks are stored relative to their chunk: u/v offsets fit in a byte each,
+// so an edit costs 6 bytes before compression instead of the 13 the save format uses
+fn encode_chunk_batch(chunks: &[(ChunkKey, ChunkMods)]) -> Vec<u8> {
+    let mut raw = Vec::new();
+    put_u32(&mut raw, chunks.len() as u32);
+    for (key, mods) in chunks {
+        raw.push(key.face);
+        put_u32(&mut raw, key.u_idx);
+        put_u32(&mut raw, key.v_idx);
+        for set in [&mods.mined, &mods.placed] {
+            put_u32(&mut raw, set.len() as u32);
+            for id in set {
+                raw.push((id.u - key.u_idx * CHUNK_SIZE) as u8);
+                raw.push((id.v - key.v_idx * CHUNK_SIZE) as u8);
+                put_u32(&mut raw, id.layer);
+            }
+        }
+        put_block_entities(&mut raw, &mods.entities);
+        put_block_types(&mut raw, &mods.types);
+    }
+
+    let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+    let _ = enc.write_all(&raw);
+    enc.finish().unwrap_or_default()
+}
+
+fn decode_chunk_batch(bytes: &[u8]) -> io::Result<Vec<(ChunkKey, ChunkMods)>> {
+    // cap the inflated size so a tiny hostile frame can't balloon in memory
+    let mut raw = Vec::new();
+    GzDecoder::new(bytes).take(MAX_FRAME as u64).read_to_end(&mut raw)?;
+
+    let mut r = ByteReader::new(&raw);
+    let count = r.u32()?;
+    let mut chunks = Vec::new();
+    for _ in 0..count {
+        let key = ChunkKey { face: r.u8()?, u_idx: r.u32()?, v_idx: r.u32()? };
+        // the indices come off the wire, a broken or hostile frame mustn't overflow them into a panic
+        let (Some(u_base), Some(v_base)) = (key.u_idx.checked_mul(CHUNK_SIZE), key.v_idx.checked_mul(CHUNK_SIZE)) else {
+            return Err(invalid("chunk index out of range"));
+        };
+        let mut mods = ChunkMods::new();
+        for set in [&mut mods.mined, &mut mods.placed] {
+            let n = r.u32()?;
+            for _ in 0..n {
+                let (du, dv) = (r.u8()? as u32, r.u8()? as u32);
+                let (Some(u), Some(v)) = (u_base.checked_add(du), v_base.checked_add(dv)) else {
+                    return Err(invalid("block index out of range"));
+                };
+                set.insert(BlockId { face: key.face, layer: r.u32()?, u, v });
+            }
+        }
+        mods.entities = r.block_entities()?;
+        mods.types = r.block_types()?;
+        chunks.push((key, mods));
+    }
+    Ok(chunks)
+}
+
+// --- FRAMING ---
+
+fn write_frame(stream: &mut TcpStream, payload: &[u8]) -> io::Result<()> {
+    stream.write_all(&(payload.len() as u32).to_le_bytes())?;
+    stream.write_all(payload)
+}
+
+fn read_frame(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME { return Err(invalid("frame too large")); }
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+// spawns the reader/writer threads for one socket.
+// the writer owns its half so a slow peer never blocks the tick loop.
+fn spawn_io<T: Send + 'static>(
+    stream: TcpStream,
+    events: Sender<T>,
+    on_message: impl Fn(Message) -> T + Send + 'static,
+    on_close: impl FnOnce(String) -> T + Send + 'static,
+) -> io::Result<Sender<Vec<u8>>> {
+    let _ = stream.set_nodelay(true);
+    let mut read_half = stream.try_clone()?;
+    let mut write_half = stream;
+
+    let (out_tx, out_rx) = channel::<Vec<u8>>();
+    std::thread::spawn(move || {
+        for payload in out_rx {
+            if write_frame(&mut write_half, &payload).is_err() { break; }
+        }
+        let _ = write_half.shutdown(std::net::Shutdown::Both);
+    });
+
+    std::thread::spawn(move || {
+        let reason = loop {
+            match read_frame(&mut read_half).and_then(|f| Message::decode(&f)) {
+                Ok(msg) => {
+                    if events.send(on_message(msg)).is_err() { return; }
+                }
+                Err(e) => break e.to_string(),
+            }
+        };
+        let _ = events.send(on_close(reason));
+    });
+
+    Ok(out_tx)
+}
+
+// --- SERVER ---
+
+pub enum ServerEvent {
+    Connected(u32),
+    Message(u32, Message),
+    Disconnected(u32, String),
+}
+
+struct Connection {
+    tx: Sender<Vec<u8>>,
+    pub addr: String,
+}
+
+pub struct Server {
+    pub port: u16,
+    connections: HashMap<u32, Connection>,
+    events: Receiver<ServerEvent>,
+    new_conns: Receiver<(u32, Sender<Vec<u8>>, String)>,
+}
+
+impl Server {
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let port = listener.local_addr()?.port();
+
+        let (event_tx, events) = channel();
+        let (conn_tx, new_conns) = channel();
+
+        std::thread::spawn(move || {
+            let mut next_id = 1u32;
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let id = next_id;
+                next_id += 1;
+
+                let addr = stream.peer_addr().map(|a| a.to_string()).unwrap_or_default();
+                let io = spawn_io(
+                    stream,
+                    event_tx.clone(),
+                    move |msg| ServerEvent::Message(id, msg),
+                    move |reason| ServerEvent::Disconnected(id, reason),
+                );
+                if let Ok(tx) = io {
+                    if conn_tx.send((id, tx, addr)).is_err() { break; }
+                }
+            }
+        });
+
+        Ok(Self { port, connections: HashMap::new(), events, new_conns })
+    }
+
+    // drains everything that happened on the network since the last tick
+    pub fn poll(&mut self) -> Vec<ServerEvent> {
+        let mut out = Vec::new();
+        while let Ok((id, tx, addr)) = self.new_conns.try_recv() {
+            self.connections.insert(id, Connection { tx, addr });
+            out.push(ServerEvent::Connected(id));
+        }
+        while let Ok(event) = self.events.try_recv() {
+            if let ServerEvent::Disconnected(id, _) = &event {
+                if self.connections.remove(id).is_none() { continue; }
+            }
+            out.push(event);
+        }
+        out
+    }
+
+    pub fn send(&self, id: u32, msg: &Message) {
+        if let Some(conn) = self.connections.get(&id) {
+            let _ = conn.tx.send(msg.encode());
+        }
+    }
+
+    pub fn broadcast(&self, msg: &Message, except: Option<u32>) {
+        let payload = msg.encode();
+        for (id, conn) in &self.connections {
+            if Some(*id) == except { continue; }
+            let _ = conn.tx.send(payload.clone());
+        }
+    }
+
+    pub fn kick(&mut self, id: u32, reason: &str) {
+        self.send(id, &Message::Disconnect { reason: reason.to_string() });
+        // dropping the sender ends the writer thread, which closes the socket
+        self.connections.remove(&id);
+    }
+
+    pub fn addr_of(&self, id: u32) -> Option<&str> {
+        self.connections.get(&id).map(|c| c.addr.as_str())
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.connections.len()
+    }
+}
+
+// --- CLIENT ---
+
+pub enum ClientEvent {
+    Message(Message),
+    Closed(String),
+}
+
+// another player as this client last heard about them
+pub struct RemotePlayer {
+    pub name: String,
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+pub struct Client {
+    tx: Sender<Vec<u8>>,
+    events: Receiver<ClientEvent>,
+    pub client_id: Option<u32>,
+    pub connected: bool,
+    // players inside our interest radius, kept current by poll()
+    pub players: HashMap<u32, RemotePlayer>,
+}
+
+impl Client {
+    pub fn connect(addr: &str, name: &str, secret: &str) -> io::Result<Self> {
+        let addr = addr.to_socket_addrs()?.next()
+            .ok_or_else(|| invalid("address did not resolve"))?;
+        let stream = TcpStream::connect_timeout(&addr, std::time::Duration::from_secs(5))?;
+
+        let (event_tx, events) = channel();
+        let tx = spawn_io(stream, event_tx, ClientEvent::Message, ClientEvent::Closed)?;
+
+        let client = Self { tx, events, client_id: None, connected: true, players: HashMap::new() };
+        client.send(&Message::Hello { version: PROTOCOL_VERSION, name: name.to_string(), secret: secret.to_string() });
+        Ok(client)
+    }
+
+    pub fn send(&self, msg: &Message) {
+        let _ = self.tx.send(msg.encode());
+    }
+
+    pub fn poll(&mut self) -> Vec<Message> {
+        let mut out = Vec::new();
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                ClientEvent::Message(Message::Welcome { client_id, resolution, seed }) => {
+                    self.client_id = Some(client_id);
+                    self.players.clear();
+                    out.push(Message::Welcome { client_id, resolution, seed });
+                }
+                ClientEvent::Message(Message::PlayerEnter { client_id, name, position, rotation }) => {
+                    self.players.insert(client_id, RemotePlayer { name, position, rotation });
+                }
+                ClientEvent::Message(Message::PlayerState { client_id, position, rotation }) => {
+                    if let Some(p) = self.players.get_mut(&client_id) {
+                        p.position = position;
+                        p.rotation = rotation;
+                    }
+                }
+                ClientEvent::Message(Message::PlayerExit { client_id }) => {
+                    self.players.remove(&client_id);
+                }
+                ClientEvent::Message(msg) => out.push(msg),
+                ClientEvent::Closed(reason) => {
+                    if self.connected {
+                        self.connected = false;
+                        self.players.clear();
+                        out.push(Message::Disconnect { reason });
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+// the player's own edits go to the server, which relays them to everyone else
+impl Subscriber for Client {
+    fn on_event(&mut self, event: &GameEvent, _planet: &PlanetData) {
+        match *event {
+            GameEvent::BlockMined { id, block, remote: false } => self.send(&Message::BlockEdit { id, placed: false, block }),
+            GameEvent::BlockPlaced { id, block, remote: false } => self.send(&Message::BlockEdit { id, placed: true, block }),
+            _ => {}
+        }
+    }
+}
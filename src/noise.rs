@@ -1,10 +1,12 @@
 use glam::Vec3;
 use crate::gen::CoordSystem;
+use crate::common::Obb;
 use std::sync::Arc; 
 
 // --- SETTINGS & ENUMS ---
 
 #[derive(Clone, Copy, Debug)]
+#[allow(dead_code)]
 pub enum NoiseType {
     Perlin,
     Simplex,  
@@ -39,17 +41,33 @@ impl NoiseSettings {
 
 // --- PLANET TERRAIN DATA ---
 
+// one texel of a height mip: the lowest, highest and mean height of the blocks under it
+#[derive(Clone, Copy, Debug)]
+pub struct HeightSpan {
+    pub min: u16,
+    pub max: u16,
+    pub avg: f32,
+}
+
+// a level of the mip chain, `size` texels a side on each face, laid out like the height map
+struct HeightMip {
+    size: u32,
+    texels: Vec<HeightSpan>,
+}
+
 pub struct PlanetTerrain {
     // Flattened height map
     heights: Arc<Vec<u16>>, 
+    // level 1 (2x2 blocks a texel) and up, down to a texel a face
+    mips: Arc<Vec<HeightMip>>,
     resolution: u32,
 }
 
 impl PlanetTerrain {
-    pub fn new(resolution: u32) -> Self {
+    pub fn new(resolution: u32, seed: u32) -> Self {
         let size = (6 * resolution * resolution) as usize;
         let mut heights = vec![0; size];
-        let generator = NoiseGenerator::new(42); // Seed 42
+        let generator = NoiseGenerator::new(seed);
         let settings = NoiseSettings::default_terrain(resolution);
         let base_radius = resolution as f32 / 2.0;
         for face in 0..6 {
@@ -65,8 +83,44 @@ impl PlanetTerrain {
             }
         }
 
+        let mips = Self::build_mips(&heights, resolution);
         // Wrap in Arc for cheap cloning
-        Self { heights: Arc::new(heights), resolution } 
+        Self { heights: Arc::new(heights), mips: Arc::new(mips), resolution } 
+    }
+
+    // each level halves the one below, a texel at the edge of an odd-sized level covering what's left
+    fn build_mips(heights: &[u16], res: u32) -> Vec<HeightMip> {
+        let mut mips: Vec<HeightMip> = Vec::new();
+        let mut size = res;
+        while size > 1 {
+            let next = size.div_ceil(2);
+            let mut texels = Vec::with_capacity(6 * (next * next) as usize);
+            for face in 0..6 {
+                for tv in 0..next {
+                    for tu in 0..next {
+                        let mut span = HeightSpan { min: u16::MAX, max: 0, avg: 0.0 };
+                        let mut count = 0;
+                        for (u, v) in [(tu * 2, tv * 2), (tu * 2 + 1, tv * 2), (tu * 2, tv * 2 + 1), (tu * 2 + 1, tv * 2 + 1)] {
+                            if u >= size || v >= size { continue; }
+                            let idx = Self::get_index(face, u, v, size);
+                            let child = match mips.last() {
+                                Some(below) => below.texels[idx],
+                                None => HeightSpan { min: heights[idx], max: heights[idx], avg: heights[idx] as f32 },
+                            };
+                            span.min = span.min.min(child.min);
+                            span.max = span.max.max(child.max);
+                            span.avg += child.avg;
+                            count += 1;
+                        }
+                        span.avg /= count as f32;
+                        texels.push(span);
+                    }
+                }
+            }
+            mips.push(HeightMip { size: next, texels });
+            size = next;
+        }
+        mips
     }
 
     #[inline(always)]
@@ -76,6 +130,11 @@ impl PlanetTerrain {
         face_offset + row_offset + (u as usize)
     }
 
+    // every height, face by face, row by row
+    pub fn heights(&self) -> &[u16] {
+        &self.heights
+    }
+
     pub fn get_height(&self, face: u8, u: u32, v: u32) -> u32 {
         let u_safe = u.min(self.resolution - 1);
         let v_safe = v.min(self.resolution - 1);
@@ -83,6 +142,63 @@ impl PlanetTerrain {
         let idx = Self::get_index(face, u_safe, v_safe, self.resolution);
         self.heights[idx] as u32
     }
+
+    // the texel over block (u, v) of the mip with 2^level blocks a side, level 0 being the block itself.
+    // past the top of the chain it's the whole face
+    pub fn height_span(&self, level: u32, face: u8, u: u32, v: u32) -> HeightSpan {
+        let level = level.min(self.mips.len() as u32);
+        if level == 0 {
+            let h = self.get_height(face, u, v) as u16;
+            return HeightSpan { min: h, max: h, avg: h as f32 };
+        }
+        let mip = &self.mips[level as usize - 1];
+        let (tu, tv) = ((u >> level).min(mip.size - 1), (v >> level).min(mip.size - 1));
+        mip.texels[Self::get_index(face, tu, tv, mip.size)]
+    }
+
+    // lowest and highest ground over the square of `size` blocks at (u, v), from the texels of the biggest mip
+    // that still fits in it, at most two a side
+    pub fn height_range(&self, face: u8, u: u32, v: u32, size: u32) -> (u32, u32) {
+        let level = (31 - size.max(1).leading_zeros()).min(self.mips.len() as u32);
+        let (last_u, last_v) = ((u + size - 1).min(self.resolution - 1), (v + size - 1).min(self.resolution - 1));
+        let (mut lo, mut hi) = (u16::MAX, 0);
+        for tv in (v >> level)..=(last_v >> level) {
+            for tu in (u >> level)..=(last_u >> level) {
+                let span = self.height_span(level, face, tu << level, tv << level);
+                (lo, hi) = (lo.min(span.min), hi.max(span.max));
+            }
+        }
+        (lo as u32, hi as u32)
+    }
+
+    // bounding sphere of the natural ground over the square of `size` blocks at (u, v), top faces included
+    pub fn bounds(&self, face: u8, u: u32, v: u32, size: u32) -> (Vec3, f32) {
+        CoordSystem::patch_bounds(face, u, v, size, self.ground_radii(face, u, v, size), self.resolution)
+    }
+
+    // the box of the same, what the frustum culls with
+    pub fn obb(&self, face: u8, u: u32, v: u32, size: u32) -> Obb {
+        CoordSystem::patch_obb(face, u, v, size, self.ground_radii(face, u, v, size), self.resolution)
+    }
+
+    // from under the lowest block to over the top face of the highest
+    fn ground_radii(&self, face: u8, u: u32, v: u32, size: u32) -> (f32, f32) {
+        let res = self.resolution;
+        let (lo, hi) = self.height_range(face, u.min(res - 1), v.min(res - 1), size);
+        (CoordSystem::get_layer_radius(lo.saturating_sub(1), res), CoordSystem::get_layer_radius(hi + 1, res))
+    }
+
+    // the height at the corner (u, v) smoothed over 2^level blocks each way, for meshes with samples that far
+    // apart: the mean of the four texels of that mip meeting there, so a peak between samples can't flicker in
+    // and out as the samples shift. level 0 is the plain height
+    pub fn smooth_height(&self, level: u32, face: u8, u: u32, v: u32) -> f32 {
+        if level == 0 { return self.get_height(face, u, v) as f32; }
+        let t = 1 << level.min(self.mips.len() as u32);
+        let (u0, v0) = (u.saturating_sub(t), v.saturating_sub(t));
+        [(u0, v0), (u, v0), (u0, v), (u, v)].iter()
+            .map(|&(u, v)| self.height_span(level, face, u, v).avg)
+            .sum::<f32>() * 0.25
+    }
     
     }
 
@@ -90,6 +206,7 @@ impl Clone for PlanetTerrain {
     fn clone(&self) -> Self {
         Self {
             heights: self.heights.clone(),
+            mips: self.mips.clone(),
             resolution: self.resolution,
         }
     }
@@ -113,10 +230,8 @@ impl NoiseGenerator {
             permutation.swap(i, j);
         }
 
-        for i in 0..256 {
-            p[i] = permutation[i];
-            p[i + 256] = permutation[i];
-        }
+        p[..256].copy_from_slice(&permutation);
+        p[256..].copy_from_slice(&permutation);
         Self { perm: p }
     }
 
@@ -131,7 +246,7 @@ impl NoiseGenerator {
         
         let mut amp = 1.0;
         let mut freq = settings.frequency;
-        let mut p = pos;
+        let p = pos;
 
         for _ in 0..settings.octaves {
             let sample_pos = p * freq + settings.offset;
@@ -162,6 +277,7 @@ impl NoiseGenerator {
 
     // --- PERLIN MATH ---
     
+    #[allow(non_snake_case)]
     fn perlin(&self, pos: Vec3) -> f32 {
         let x = pos.x.floor();
         let y = pos.y.floor();
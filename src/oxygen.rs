@@ -0,0 +1,40 @@
+//oxygen.rs
+
+// breath: it runs out while the player is somewhere without air and comes back quickly once they're out.
+// with none left every hit of SUFFOCATION_DAMAGE goes through Health::hurt like any other damage. the only
+// airless place so far is space, past the top layer of the planet.
+
+use glam::Vec3;
+use crate::gen::CoordSystem;
+
+// --- CONSTANTS ---
+// seconds of breath
+pub const MAX_OXYGEN: f32 = 15.0;
+// per second back in the air
+const REFILL_RATE: f32 = 5.0;
+pub const SUFFOCATION_DAMAGE: f32 = 1.0;
+
+pub struct Oxygen {
+    pub current: f32,
+}
+
+impl Oxygen {
+    pub fn new() -> Self {
+        Self { current: MAX_OXYGEN }
+    }
+
+    // true while there's none left
+    pub fn update(&mut self, dt: f32, breathing: bool) -> bool {
+        self.current = if breathing { (self.current + REFILL_RATE * dt).min(MAX_OXYGEN) } else { (self.current - dt).max(0.0) };
+        self.current <= 0.0
+    }
+
+    pub fn refill(&mut self) {
+        *self = Self::new();
+    }
+}
+
+// whether there's air at `pos`: everywhere below the atmosphere, which ends where the top layer does
+pub fn has_air(pos: Vec3, res: u32) -> bool {
+    pos.length() < CoordSystem::get_layer_radius(res, res)
+}
@@ -0,0 +1,56 @@
+//pacing.rs
+
+// holds the frame rate to a cap, whatever the present mode. the os wakes a waiting thread late by anything up to a
+// couple of milliseconds (more on windows), so the event loop is only asked to sleep until shortly before a frame
+// is due and the rest is spun away. frames are due a fixed time after the one before rather than after the last
+// one finished, so a 60 fps cap gives an even 60 and not a little under.
+
+use std::time::{Duration, Instant};
+
+// --- CONSTANTS ---
+// the last stretch before a frame that's spun rather than slept
+const SPIN_MARGIN: Duration = Duration::from_micros(1500);
+
+pub enum Pace {
+    Draw,
+    // nothing to do until then, but input still wakes the loop early
+    WaitUntil(Instant),
+}
+
+#[derive(Default)]
+pub struct FramePacer {
+    // when the next frame should start, None while uncapped
+    due: Option<Instant>,
+}
+
+impl FramePacer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // a frame is starting now, the next one at `fps` is due a frame's time after this one was
+    pub fn frame_started(&mut self, fps: Option<u32>) {
+        let now = Instant::now();
+        self.due = fps.map(|fps| {
+            let frame_time = Duration::from_secs_f64(1.0 / fps as f64);
+            match self.due {
+                // on schedule, keep to it. a frame that ran long starts the count again instead of rushing to catch up
+                Some(due) if now < due + frame_time => due + frame_time,
+                _ => now + frame_time,
+            }
+        });
+    }
+
+    // whether to draw now, spinning out the last moment before the frame is due, or how long to wait first
+    pub fn pace(&self) -> Pace {
+        let Some(due) = self.due else { return Pace::Draw };
+        let left = due.saturating_duration_since(Instant::now());
+        if left > SPIN_MARGIN {
+            return Pace::WaitUntil(due - SPIN_MARGIN);
+        }
+        while Instant::now() < due {
+            std::hint::spin_loop();
+        }
+        Pace::Draw
+    }
+}
@@ -0,0 +1,131 @@
+//panorama.rs
+
+// /panorama: six 90 degree views from the camera, one per cube face, stitched into one equirectangular png
+// under panoramas/ that any 360 viewer can open. the faces go through the renderer's still path one frame
+// after another, the stitching and writing happen on a thread.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use glam::{Mat4, Vec3};
+use crate::common::reverse_z;
+use crate::capture::{self, Frame};
+use crate::entity::Player;
+use crate::physics::Physics;
+
+// --- CONSTANTS ---
+pub const PANORAMA_DIR: &str = "panoramas";
+// side of every cube face
+pub const FACE_SIZE: u32 = 1024;
+// the stitched image, a full turn across and pole to pole down
+const PANORAMA_WIDTH: u32 = 4 * FACE_SIZE;
+const PANORAMA_HEIGHT: u32 = 2 * FACE_SIZE;
+const PANORAMA_FAR: f32 = 4000.0;
+
+// view directions of the faces in the camera's (forward, right, up) frame, and which way is up in each
+const FACES: [([f32; 3], [f32; 3]); 6] = [
+    ([1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+    ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, -1.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, 0.0, 1.0], [-1.0, 0.0, 0.0]),
+    ([0.0, 0.0, -1.0], [1.0, 0.0, 0.0]),
+];
+
+// a camera frame at `eye`: level with the ground, facing `forward` as far as that's possible
+#[derive(Clone, Copy)]
+pub struct CubeCamera {
+    pub eye: Vec3,
+    forward: Vec3,
+    right: Vec3,
+    up: Vec3,
+}
+
+impl CubeCamera {
+    pub fn new(eye: Vec3, forward: Vec3) -> Self {
+        let up = Physics::get_up_vector(eye);
+        let level = forward - up * forward.dot(up);
+        let forward = if level.length_squared() > 1e-6 { level.normalize() } else { up.any_orthonormal_vector() };
+        Self { eye, forward, right: forward.cross(up), up }
+    }
+
+    fn world(&self, v: [f32; 3]) -> Vec3 {
+        self.forward * v[0] + self.right * v[1] + self.up * v[2]
+    }
+
+    pub fn face_view_proj(&self, face: usize) -> Mat4 {
+        let (dir, up) = FACES[face];
+        let proj = reverse_z(Mat4::perspective_rh(90f32.to_radians(), 1.0, 0.1, PANORAMA_FAR));
+        proj * Mat4::look_at_rh(self.eye, self.eye + self.world(dir), self.world(up))
+    }
+
+    // the face looking closest along `dir`
+    pub fn face_for(&self, dir: Vec3) -> usize {
+        let (f, r, u) = (dir.dot(self.forward), dir.dot(self.right), dir.dot(self.up));
+        if u.abs() >= f.abs() && u.abs() >= r.abs() {
+            if u > 0.0 { 4 } else { 5 }
+        } else if f.abs() >= r.abs() {
+            if f > 0.0 { 0 } else { 2 }
+        } else if r > 0.0 { 1 } else { 3 }
+    }
+}
+
+pub struct Panorama {
+    pub camera: CubeCamera,
+    pub faces: Vec<Frame>,
+    // a face has been asked of the renderer and comes back with the next still
+    pub waiting: bool,
+}
+
+impl Panorama {
+    // from the player's eyes, the middle of the image where they're looking
+    pub fn start(player: &Player) -> Self {
+        let eye = player.position + Physics::get_up_vector(player.position) * Physics::EYE_HEIGHT;
+        Self { camera: CubeCamera::new(eye, player.rotation * Vec3::NEG_Z), faces: Vec::with_capacity(6), waiting: false }
+    }
+
+    // the view of the face still missing, None once all six are in
+    pub fn next_face(&self) -> Option<Mat4> {
+        (self.faces.len() < FACES.len()).then(|| self.camera.face_view_proj(self.faces.len()))
+    }
+
+    pub fn add(&mut self, frame: Frame) {
+        self.faces.push(frame);
+        self.waiting = false;
+    }
+
+    // stitches and writes the panorama in the background, returning where it goes
+    pub fn save(self, errors: Sender<String>) -> PathBuf {
+        let path = PathBuf::from(PANORAMA_DIR).join(format!("{}.png", capture::unix_stamp()));
+        let out = path.clone();
+        std::thread::spawn(move || {
+            let png = capture::encode_png(&self.stitch());
+            if let Err(e) = fs::create_dir_all(PANORAMA_DIR).and_then(|_| fs::write(&out, png)) {
+                let _ = errors.send(format!("{}: {}", out.display(), e));
+            }
+        });
+        path
+    }
+
+    // looks every pixel's direction up in the face that sees it
+    fn stitch(&self) -> Frame {
+        let (width, height) = (PANORAMA_WIDTH, PANORAMA_HEIGHT);
+        let view_projs: Vec<Mat4> = (0..FACES.len()).map(|face| self.camera.face_view_proj(face)).collect();
+        let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            let lat = std::f32::consts::FRAC_PI_2 - (y as f32 + 0.5) / height as f32 * std::f32::consts::PI;
+            for x in 0..width {
+                let lon = (x as f32 + 0.5) / width as f32 * std::f32::consts::TAU - std::f32::consts::PI;
+                let dir = self.camera.world([lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]);
+                let face = self.camera.face_for(dir);
+                let ndc = view_projs[face].project_point3(self.camera.eye + dir);
+                let frame = &self.faces[face];
+                let u = (((ndc.x + 1.0) * 0.5 * frame.width as f32) as u32).min(frame.width - 1);
+                let v = (((1.0 - ndc.y) * 0.5 * frame.height as f32) as u32).min(frame.height - 1);
+                let i = ((v * frame.width + u) * 4) as usize;
+                rgba.extend_from_slice(&frame.rgba[i..i + 4]);
+            }
+        }
+        Frame { width, height, rgba }
+    }
+}
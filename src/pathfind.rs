@@ -0,0 +1,92 @@
+//pathfind.rs
+
+// a* over the blocks of one face, for mobs that walk. a cell is somewhere to stand: an empty block with a solid
+// one under it. from a cell a walker steps onto one of the four beside it, climbing at most one layer (with
+// room over its head to jump) or dropping at most MAX_DROP. the search gives up after MAX_NODES cells, so a
+// target round a long wall costs a bounded amount and the walker just heads straight for it.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use glam::Vec3;
+use crate::common::{BlockId, PlanetData};
+use crate::gen::CoordSystem;
+use crate::physics::Physics;
+
+// --- CONSTANTS ---
+const MAX_NODES: usize = 600;
+const MAX_DROP: u32 = 3;
+
+fn standable(id: BlockId, planet: &PlanetData) -> bool {
+    id.layer > 0 && id.layer < planet.resolution && !planet.exists(id) && planet.exists(BlockId { layer: id.layer - 1, ..id })
+}
+
+// the cell `pos` stands in, or the first one under it
+pub fn cell_at(pos: Vec3, planet: &PlanetData) -> Option<BlockId> {
+    let id = CoordSystem::pos_to_id(pos + Physics::get_up_vector(pos) * 0.1, planet.resolution)?;
+    (0..=MAX_DROP.min(id.layer)).map(|d| BlockId { layer: id.layer - d, ..id }).find(|&c| standable(c, planet))
+}
+
+// middle of the floor of a cell, where a walker's feet go
+pub fn floor_point(id: BlockId, res: u32) -> Vec3 {
+    let corner = |u, v| CoordSystem::get_vertex_pos(id.face, u, v, id.layer, res);
+    (corner(id.u, id.v) + corner(id.u + 1, id.v) + corner(id.u, id.v + 1) + corner(id.u + 1, id.v + 1)) / 4.0
+}
+
+// the cell a walker in `from` ends up in stepping by (du, dv), if it can
+fn step(from: BlockId, du: i32, dv: i32, planet: &PlanetData) -> Option<BlockId> {
+    let (u, v) = (from.u as i32 + du, from.v as i32 + dv);
+    let res = planet.resolution as i32;
+    if u < 0 || v < 0 || u >= res || v >= res { return None; }
+    let beside = BlockId { u: u as u32, v: v as u32, ..from };
+    let over = |id: BlockId| BlockId { layer: id.layer + 1, ..id };
+    if planet.exists(beside) {
+        // a step up needs the room to jump it
+        let up = over(beside);
+        return (standable(up, planet) && !planet.exists(over(from))).then_some(up);
+    }
+    (0..=MAX_DROP.min(beside.layer)).map(|d| BlockId { layer: beside.layer - d, ..beside })
+        .take_while(|&id| !planet.exists(id))
+        .find(|&id| standable(id, planet))
+}
+
+fn distance(a: BlockId, b: BlockId) -> u32 {
+    a.u.abs_diff(b.u) + a.v.abs_diff(b.v) + a.layer.abs_diff(b.layer)
+}
+
+// the floor points from `from` to `to`, the start left out. None when they're on different faces, either has
+// nowhere to stand, or the search ran out of cells first
+pub fn find_path(from: Vec3, to: Vec3, planet: &PlanetData) -> Option<Vec<Vec3>> {
+    let (start, goal) = (cell_at(from, planet)?, cell_at(to, planet)?);
+    if start.face != goal.face { return None; }
+
+    let key = |id: BlockId| (id.u, id.v, id.layer);
+    let mut open = BinaryHeap::new();
+    let mut cost = HashMap::from([(start, 0u32)]);
+    let mut came_from: HashMap<BlockId, BlockId> = HashMap::new();
+    open.push(Reverse((distance(start, goal), key(start))));
+
+    while let Some(Reverse((_, (u, v, layer)))) = open.pop() {
+        let current = BlockId { face: start.face, u, v, layer };
+        if current == goal {
+            let mut path = vec![floor_point(goal, planet.resolution)];
+            let mut at = goal;
+            while let Some(&prev) = came_from.get(&at) {
+                if prev == start { break; }
+                path.push(floor_point(prev, planet.resolution));
+                at = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if cost.len() > MAX_NODES { return None; }
+        let g = cost[&current] + 1;
+        for (du, dv) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let Some(next) = step(current, du, dv, planet) else { continue };
+            if cost.get(&next).is_some_and(|&c| c <= g) { continue; }
+            cost.insert(next, g);
+            came_from.insert(next, current);
+            open.push(Reverse((g + distance(next, goal), key(next))));
+        }
+    }
+    None
+}
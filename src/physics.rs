@@ -2,6 +2,30 @@ use glam::{Vec3, Quat};
 use crate::common::{PlanetData, BlockId};
 use crate::gen::CoordSystem;
 
+// a grappling hook line from the player to a point on the terrain. it goes slack when the player is
+// closer than `length` and pulls like a stiff spring past it, so the player swings around the anchor
+#[derive(Clone, Copy, Debug)]
+pub struct Rope {
+    pub anchor: Vec3,
+    pub length: f32,
+}
+
+impl Rope {
+    // how hard a stretched rope pulls back, per block of stretch
+    const STIFFNESS: f32 = 40.0;
+
+    fn constrain(&self, pos: Vec3, velocity: Vec3, dt: f32) -> Vec3 {
+        let to_anchor = self.anchor - pos;
+        let dist = to_anchor.length();
+        if dist <= self.length || dist < 0.001 { return velocity; }
+        let dir = to_anchor / dist;
+        // a taut rope takes away any speed away from the anchor, which turns a fall into a swing
+        let outward = -velocity.dot(dir);
+        let velocity = if outward > 0.0 { velocity + dir * outward } else { velocity };
+        velocity + dir * (dist - self.length) * Self::STIFFNESS * dt
+    }
+}
+
 pub struct Physics; 
 impl Physics {
     pub const GRAVITY: f32 = 12.0; 
@@ -20,6 +44,48 @@ impl Physics {
         (rotation_diff * rotation).normalize()
     }
 
+    // how far along the ray (`dir` of unit length) it first touches the capsule round the segment a-b, if it does
+    // ahead of `start`
+    pub fn ray_capsule(start: Vec3, dir: Vec3, a: Vec3, b: Vec3, radius: f32) -> Option<f32> {
+        let axis = b - a;
+        let length_sq = axis.length_squared();
+        let mut best: Option<f32> = None;
+        let mut keep = |t: f32| if t >= 0.0 && best.is_none_or(|b| t < b) { best = Some(t) };
+
+        // the side: the infinite cylinder, kept where the hit lies between the ends
+        if length_sq > 1e-6 {
+            let (oa, along) = (start - a, dir.dot(axis));
+            let qa = length_sq - along * along;
+            let qb = length_sq * dir.dot(oa) - oa.dot(axis) * along;
+            let qc = length_sq * oa.length_squared() - oa.dot(axis).powi(2) - radius * radius * length_sq;
+            let h = qb * qb - qa * qc;
+            if qa > 1e-6 && h >= 0.0 {
+                let t = (-qb - h.sqrt()) / qa;
+                let y = oa.dot(axis) + t * along;
+                if y > 0.0 && y < length_sq { keep(t); }
+            }
+        }
+        // the round ends
+        for end in [a, b] {
+            let oc = start - end;
+            let (qb, qc) = (dir.dot(oc), oc.length_squared() - radius * radius);
+            let h = qb * qb - qc;
+            if h >= 0.0 { keep(-qb - h.sqrt()); }
+        }
+        best
+    }
+
+    // share of a knockback that goes into lifting the target off the ground, so friction doesn't eat it at once
+    const KNOCKBACK_LIFT: f32 = 0.4;
+
+    // the velocity change of a hit at `target` from `source`: away along the ground and a little up
+    pub fn knockback(source: Vec3, target: Vec3, strength: f32) -> Vec3 {
+        let up = Self::get_up_vector(target);
+        let away = target - source;
+        let away = (away - up * away.dot(up)).try_normalize().unwrap_or(Vec3::ZERO);
+        (away + up * Self::KNOCKBACK_LIFT) * strength
+    }
+
 pub fn is_solid(pos: Vec3, planet: &PlanetData) -> bool {
         let res = planet.resolution;
         
@@ -75,9 +141,8 @@ pub fn is_solid(pos: Vec3, planet: &PlanetData) -> bool {
     fn get_grid_axes(up: Vec3, pos: Vec3) -> (Vec3, Vec3) {
         let abs_p = pos.abs();
         // determine dominant axis (Face) to align hitboxes with walls
-        let rigid_axis = if abs_p.y >= abs_p.x && abs_p.y >= abs_p.z { Vec3::X } // Top/Bottom Face -> X is grid axis
-                         else if abs_p.x >= abs_p.y && abs_p.x >= abs_p.z { Vec3::Y } // Right/Left Face -> Y is grid axis
-                         else { Vec3::Y }; // Front/Back Face -> Y is grid axis
+        // Top/Bottom Face -> X is grid axis, Right/Left and Front/Back Faces -> Y is grid axis
+        let rigid_axis = if abs_p.y >= abs_p.x && abs_p.y >= abs_p.z { Vec3::X } else { Vec3::Y };
                          
         let right = up.cross(rigid_axis).normalize_or_zero();
         let fwd = up.cross(right).normalize_or_zero();
@@ -114,10 +179,11 @@ pub fn is_solid(pos: Vec3, planet: &PlanetData) -> bool {
         false
     }
 
-    pub fn solve_movement(start_pos: Vec3, velocity: Vec3, dt: f32, planet: &PlanetData, flying: bool) -> (Vec3, Vec3, bool) {
+    pub fn solve_movement(start_pos: Vec3, velocity: Vec3, dt: f32, planet: &PlanetData, flying: bool, noclip: bool, rope: Option<&Rope>) -> (Vec3, Vec3, bool) {
         if flying { 
-            return (start_pos + velocity * dt, velocity, false); 
+            return Self::solve_flight(start_pos, velocity, dt, planet, noclip);
         }
+        let velocity = rope.map_or(velocity, |r| r.constrain(start_pos, velocity, dt));
         
         let up = Self::get_up_vector(start_pos);
         let vert_speed = velocity.dot(up);
@@ -194,7 +260,7 @@ pub fn is_solid(pos: Vec3, planet: &PlanetData) -> bool {
 
         // --- AUTO STEP-UP ---
         if grounded && final_horz_vel.length() < horz_vel.length() * 0.5 && horz_vel.length() > 0.001 {
-            for step_height in [0.3, 0.6] {
+            for step_height in [Self::STEP_HEIGHT * 0.5, Self::STEP_HEIGHT] {
                 let step_test = curr_pos + up * step_height;
                 
                 let step_forward = step_test + horz_vel.normalize() * Self::PLAYER_RADIUS * 1.5;
@@ -213,4 +279,63 @@ pub fn is_solid(pos: Vec3, planet: &PlanetData) -> bool {
 
         (curr_pos, final_vel, grounded)
     }
-}
\ No newline at end of file
+
+    // no gravity or stepping, just slide along whatever is in the way one axis at a time.
+    // noclip (spectators) skips collision entirely
+    fn solve_flight(start_pos: Vec3, velocity: Vec3, dt: f32, planet: &PlanetData, noclip: bool) -> (Vec3, Vec3, bool) {
+        let desired_pos = start_pos + velocity * dt;
+
+        // already stuck inside something (e.g. fly was toggled underground), let them move out freely
+        if noclip || !Self::check_collision(desired_pos, planet) || Self::check_collision(start_pos, planet) {
+            return (desired_pos, velocity, false);
+        }
+
+        let mut curr_pos = start_pos;
+        let mut final_vel = velocity;
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            let axis_vel = axis * velocity.dot(axis);
+            let try_pos = curr_pos + axis_vel * dt;
+            if !Self::check_collision(try_pos, planet) {
+                curr_pos = try_pos;
+            } else {
+                final_vel -= axis_vel;
+            }
+        }
+        (curr_pos, final_vel, false)
+    }
+
+    // glider aerodynamics, used in place of plain gravity while gliding. `nose` is where the player looks:
+    // pitching it above the flight path makes lift (and drag) until the wing stalls, so diving trades height
+    // for speed and pulling up trades it back. `up` follows the position, so a long glide curves with the planet
+    const AIR_DENSITY: f32 = 0.1;
+    const LIFT_SLOPE: f32 = 6.0;
+    const STALL_ANGLE: f32 = 0.35;
+    const BASE_DRAG: f32 = 0.03;
+    const INDUCED_DRAG: f32 = 0.13;
+    // how fast sideways slip turns into the direction the nose points
+    const WEATHERVANE: f32 = 2.5;
+    const MAX_GLIDE_SPEED: f32 = 60.0;
+
+    pub fn glide(velocity: Vec3, nose: Vec3, up: Vec3, dt: f32) -> Vec3 {
+        let mut vel = velocity - up * Self::GRAVITY * dt;
+        let speed = vel.length();
+        let right = nose.cross(up).normalize_or_zero();
+        if speed < 0.001 || right == Vec3::ZERO { return vel; }
+
+        // slip sideways out of the velocity, keeping the speed
+        let slip = right * vel.dot(right) * (1.0 - (-Self::WEATHERVANE * dt).exp());
+        vel = (vel - slip).normalize_or_zero() * speed;
+        let dir = vel / speed;
+
+        let lift_dir = right.cross(dir).normalize_or_zero();
+        let aoa = nose.dot(lift_dir).clamp(-1.0, 1.0).asin();
+        // past the stall angle lift falls away again
+        let stalled = (aoa.abs() - Self::STALL_ANGLE).max(0.0);
+        let lift_coeff = (aoa.clamp(-Self::STALL_ANGLE, Self::STALL_ANGLE) * Self::LIFT_SLOPE) * (1.0 - stalled * 2.0).max(0.0);
+        let drag_coeff = Self::BASE_DRAG + Self::INDUCED_DRAG * lift_coeff * lift_coeff + stalled;
+
+        let pressure = Self::AIR_DENSITY * speed * speed;
+        vel += (lift_dir * lift_coeff - dir * drag_coeff) * pressure * dt;
+        vel.clamp_length_max(Self::MAX_GLIDE_SPEED)
+    }
+}
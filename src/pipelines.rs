@@ -0,0 +1,326 @@
+//pipelines.rs
+
+// every render pipeline the renderer draws with, kept by what it's made of: shader, topology, fill, blend,
+// depth and cull. a pass names the PipelineKey it wants and the pipeline is built the first frame anything
+// asks for it, so a new material or debug mode is one more key rather than one more field. in debug builds
+// the wgsl files are watched: an edited one is compiled again and the pipelines built from it are dropped,
+// to be rebuilt from the new code when they're next wanted.
+//
+// the wgsl is split into files under wgsl/ that the entry files pull in with a small preprocessor (see
+// compose), and features like shadows are #ifdef'd so a cheaper variant is the same code with a define
+// left out, set for every pipeline with set_features.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime};
+use crate::common::Vertex;
+use crate::instancing::Instance;
+
+// --- CONSTANTS ---
+// seconds between looking at the wgsl files for edits
+const WATCH_INTERVAL: f32 = 0.5;
+// every wgsl file under src and the code compiled in, entry files first in Source order
+const FILES: [(&str, &str); 8] = [
+    ("shader.wgsl", include_str!("shader.wgsl")),
+    ("screen.wgsl", include_str!("screen.wgsl")),
+    ("sky.wgsl", include_str!("sky.wgsl")),
+    ("wgsl/global.wgsl", include_str!("wgsl/global.wgsl")),
+    ("wgsl/color.wgsl", include_str!("wgsl/color.wgsl")),
+    ("wgsl/fog.wgsl", include_str!("wgsl/fog.wgsl")),
+    ("wgsl/debug.wgsl", include_str!("wgsl/debug.wgsl")),
+    ("wgsl/shadow.wgsl", include_str!("wgsl/shadow.wgsl")),
+];
+const SOURCES: [Source; 3] = [Source::World, Source::Screen, Source::Sky];
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum Source {
+    World,
+    Screen,
+    Sky,
+}
+
+impl Source {
+    fn file(self) -> &'static str {
+        FILES[self as usize].0
+    }
+}
+
+// what the wgsl can #ifdef on. the same for every pipeline, so changing one rebuilds them all
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ShaderFeatures {
+    // the shadow map is sampled, otherwise everything is lit as if in the sun
+    pub shadows: bool,
+}
+
+impl Default for ShaderFeatures {
+    fn default() -> Self {
+        Self { shadows: true }
+    }
+}
+
+impl ShaderFeatures {
+    fn defines(self) -> Vec<&'static str> {
+        [("SHADOWS", self.shadows)].into_iter().filter(|(_, on)| *on).map(|(name, _)| name).collect()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Shader {
+    // blocks, meshes and overlays, lit and shadowed
+    World,
+    // World drawing one mesh many times, see instancing.rs
+    Instanced,
+    // the world counting fragments, for the overdraw render mode
+    Overdraw,
+    // the world's vertex stage and nothing else, for the shadow map
+    DepthOnly,
+    // a texture on a quad: monitor screens and signs
+    Screen,
+    // the sky behind the world, on a full-screen quad
+    Sky,
+}
+
+impl Shader {
+    fn source(self) -> Source {
+        match self {
+            Shader::World | Shader::Instanced | Shader::Overdraw | Shader::DepthOnly => Source::World,
+            Shader::Screen => Source::Screen,
+            Shader::Sky => Source::Sky,
+        }
+    }
+
+    fn vertex(self) -> &'static str {
+        if self == Shader::Instanced { "vs_instanced" } else { "vs_main" }
+    }
+
+    fn fragment(self) -> Option<&'static str> {
+        match self {
+            Shader::World | Shader::Instanced | Shader::Screen | Shader::Sky => Some("fs_main"),
+            Shader::Overdraw => Some("fs_overdraw"),
+            Shader::DepthOnly => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Blend {
+    Opaque,
+    Alpha,
+    Additive,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Depth {
+    // nearest wins and is written. reverse-z, see common::reverse_z
+    Test,
+    // the shadow map's: nearest wins with near at 0, pushed back a little so surfaces don't shadow themselves
+    Biased,
+    // drawn over everything, nothing written
+    Always,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PipelineKey {
+    pub shader: Shader,
+    pub topology: wgpu::PrimitiveTopology,
+    pub polygon: wgpu::PolygonMode,
+    pub blend: Blend,
+    pub depth: Depth,
+    pub cull: Option<wgpu::Face>,
+}
+
+pub struct PipelineCache {
+    format: wgpu::TextureFormat,
+    // global + local uniforms, and global + a screen texture
+    world_layout: wgpu::PipelineLayout,
+    screen_layout: wgpu::PipelineLayout,
+    features: ShaderFeatures,
+    // the text of every file in FILES, as edited on disk since in debug builds
+    code: HashMap<&'static str, String>,
+    modules: HashMap<Source, wgpu::ShaderModule>,
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+    // debug builds only: when the files were last looked at, and when each was modified then
+    watch: Option<(Instant, HashMap<&'static str, SystemTime>)>,
+}
+
+impl PipelineCache {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, world_layout: wgpu::PipelineLayout, screen_layout: wgpu::PipelineLayout) -> Self {
+        let features = ShaderFeatures::default();
+        let code: HashMap<&'static str, String> = FILES.iter().map(|(name, code)| (*name, code.to_string())).collect();
+        let modules = SOURCES.into_iter()
+            .map(|source| {
+                let wgsl = compose(source.file(), &code, &features.defines()).unwrap_or_else(|e| panic!("{}", e));
+                (source, device.create_shader_module(wgpu::ShaderModuleDescriptor { label: Some(source.file()), source: wgpu::ShaderSource::Wgsl(wgsl.into()) }))
+            })
+            .collect();
+        let watch = cfg!(debug_assertions).then(|| (Instant::now(), Self::modified_times()));
+        Self { format, world_layout, screen_layout, features, code, modules, pipelines: HashMap::new(), watch }
+    }
+
+    // compiles every module again with the defines of `features`. one that doesn't compile (only possible
+    // with a file broken on disk) keeps the module it had
+    pub fn set_features(&mut self, device: &wgpu::Device, features: ShaderFeatures) {
+        if features == self.features { return; }
+        self.features = features;
+        self.rebuild(device);
+    }
+
+    // builds `key` if it hasn't been yet. passes borrow pipelines for as long as they record, so everything a
+    // frame draws with is prepared before the first pass starts
+    pub fn prepare(&mut self, device: &wgpu::Device, key: PipelineKey) {
+        if !self.pipelines.contains_key(&key) {
+            let pipeline = self.build(device, key);
+            self.pipelines.insert(key, pipeline);
+        }
+    }
+
+    pub fn get(&self, key: PipelineKey) -> &wgpu::RenderPipeline {
+        self.pipelines.get(&key).unwrap_or_else(|| panic!("pipeline {:?} drawn with before it was prepared", key))
+    }
+
+    fn build(&self, device: &wgpu::Device, key: PipelineKey) -> wgpu::RenderPipeline {
+        let module = &self.modules[&key.shader.source()];
+        let layout = match key.shader.source() {
+            Source::World | Source::Sky => &self.world_layout,
+            Source::Screen => &self.screen_layout,
+        };
+        let blend = match key.blend {
+            Blend::Opaque => None,
+            Blend::Alpha => Some(wgpu::BlendState::ALPHA_BLENDING),
+            Blend::Additive => {
+                let add = wgpu::BlendComponent { src_factor: wgpu::BlendFactor::One, dst_factor: wgpu::BlendFactor::One, operation: wgpu::BlendOperation::Add };
+                Some(wgpu::BlendState { color: add, alpha: add })
+            },
+        };
+        let (depth_write_enabled, depth_compare, bias) = match key.depth {
+            Depth::Test => (true, wgpu::CompareFunction::GreaterEqual, Default::default()),
+            Depth::Biased => (true, wgpu::CompareFunction::Less, wgpu::DepthBiasState { constant: 2, slope_scale: 2.0, clamp: 0.0 }),
+            Depth::Always => (false, wgpu::CompareFunction::Always, Default::default()),
+        };
+        let targets = [Some(wgpu::ColorTargetState { format: self.format, blend, write_mask: wgpu::ColorWrites::ALL })];
+        let buffers = [
+            wgpu::VertexBufferLayout { array_stride: std::mem::size_of::<Vertex>() as _, step_mode: wgpu::VertexStepMode::Vertex, attributes: &[wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 0, shader_location: 0 }, wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 12, shader_location: 1 }, wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 24, shader_location: 2 }] },
+            Instance::layout(),
+        ];
+        let buffers = if key.shader == Shader::Instanced { &buffers[..] } else { &buffers[..1] };
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("{:?}", key)),
+            layout: Some(layout),
+            vertex: wgpu::VertexState { module, entry_point: key.shader.vertex(), buffers },
+            fragment: key.shader.fragment().map(|entry_point| wgpu::FragmentState { module, entry_point, targets: &targets }),
+            primitive: wgpu::PrimitiveState { topology: key.topology, cull_mode: key.cull, polygon_mode: key.polygon, ..Default::default() },
+            depth_stencil: Some(wgpu::DepthStencilState { format: wgpu::TextureFormat::Depth32Float, depth_write_enabled, depth_compare, stencil: Default::default(), bias }),
+            multisample: Default::default(), multiview: None,
+        })
+    }
+
+    // --- HOT RELOAD ---
+
+    fn path(file: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src").join(file)
+    }
+
+    fn modified_times() -> HashMap<&'static str, SystemTime> {
+        FILES.iter()
+            .filter_map(|(file, _)| Some((*file, std::fs::metadata(Self::path(file)).ok()?.modified().ok()?)))
+            .collect()
+    }
+
+    // when any wgsl file was edited since the last look, every entry file with whether it compiled.
+    // one that didn't keeps drawing with the code it had
+    pub fn reload_edited(&mut self, device: &wgpu::Device) -> Vec<(&'static str, Result<(), String>)> {
+        let Some((checked, seen)) = &mut self.watch else { return Vec::new() };
+        if checked.elapsed().as_secs_f32() < WATCH_INTERVAL { return Vec::new(); }
+        *checked = Instant::now();
+        let now = Self::modified_times();
+        let edited: Vec<&'static str> = now.iter().filter(|(f, t)| seen.get(*f) != Some(t)).map(|(f, _)| *f).collect();
+        *seen = now;
+        if edited.is_empty() { return Vec::new(); }
+
+        let mut results = Vec::new();
+        for file in edited {
+            match std::fs::read_to_string(Self::path(file)) {
+                Ok(code) => { self.code.insert(file, code); },
+                Err(e) => results.push((file, Err(e.to_string()))),
+            }
+        }
+        results.extend(self.rebuild(device));
+        results
+    }
+
+    // every module composed and compiled again from `code`, dropping the pipelines built from the old ones
+    fn rebuild(&mut self, device: &wgpu::Device) -> Vec<(&'static str, Result<(), String>)> {
+        let defines = self.features.defines();
+        let mut results = Vec::new();
+        for source in SOURCES {
+            let result = compose(source.file(), &self.code, &defines)
+                .and_then(|wgsl| Self::compile(device, source.file(), &wgsl));
+            match result {
+                Ok(module) => {
+                    self.modules.insert(source, module);
+                    self.pipelines.retain(|key, _| key.shader.source() != source);
+                    results.push((source.file(), Ok(())));
+                },
+                Err(e) => results.push((source.file(), Err(e))),
+            }
+        }
+        results
+    }
+
+    // a broken module would otherwise only turn up as a panic when a pipeline is built from it
+    fn compile(device: &wgpu::Device, name: &str, code: &str) -> Result<wgpu::ShaderModule, String> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor { label: Some(name), source: wgpu::ShaderSource::Wgsl(code.into()) });
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(e) => Err(e.to_string()),
+            None => Ok(module),
+        }
+    }
+}
+
+// --- PREPROCESSOR ---
+// on top of wgsl, a line can be one of
+//   #include "wgsl/fog.wgsl"       pastes in a file from FILES, once however often it's included
+//   #ifdef NAME / #ifndef NAME     keeps the lines up to the matching #else or #endif only with (without) NAME
+//   #else / #endif
+fn compose(file: &'static str, code: &HashMap<&'static str, String>, defines: &[&str]) -> Result<String, String> {
+    let mut out = String::new();
+    expand(file, code, defines, &mut HashSet::new(), &mut out)?;
+    Ok(out)
+}
+
+fn expand(file: &'static str, code: &HashMap<&'static str, String>, defines: &[&str], included: &mut HashSet<&'static str>, out: &mut String) -> Result<(), String> {
+    if !included.insert(file) { return Ok(()); }
+    let text = code.get(file).ok_or_else(|| format!("no shader file {}", file))?;
+    // whether each open #ifdef keeps its lines
+    let mut open: Vec<bool> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let error = |message: &str| format!("{}:{}: {}", file, i + 1, message);
+        let keep = open.iter().all(|k| *k);
+        let Some(directive) = line.trim().strip_prefix('#') else {
+            if keep {
+                out.push_str(line);
+                out.push('\n');
+            }
+            continue;
+        };
+        let (word, arg) = directive.split_once(' ').map_or((directive, ""), |(w, a)| (w, a.trim()));
+        match word {
+            "ifdef" | "ifndef" => open.push(defines.contains(&arg) == (word == "ifdef")),
+            "else" => {
+                let top = open.last_mut().ok_or_else(|| error("#else without #ifdef"))?;
+                *top = !*top;
+            },
+            "endif" => { open.pop().ok_or_else(|| error("#endif without #ifdef"))?; },
+            "include" if !keep => {},
+            "include" => {
+                let path = arg.strip_prefix('"').and_then(|a| a.strip_suffix('"')).ok_or_else(|| error("#include needs a quoted path"))?;
+                let (path, _) = code.get_key_value(path).ok_or_else(|| error(&format!("no shader file {}", path)))?;
+                expand(path, code, defines, included, out)?;
+            },
+            _ => return Err(error(&format!("unknown directive #{}", word))),
+        }
+    }
+    if !open.is_empty() { return Err(format!("{}: #ifdef without #endif", file)); }
+    Ok(())
+}
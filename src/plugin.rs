@@ -0,0 +1,108 @@
+//plugin.rs
+
+// features that don't have to be part of the engine: a Plugin hooks into startup, the event bus, the frame
+// tick and the console, and can add block types. plugins are compiled in and listed in `registered`, loaded
+// once at startup in that order. there's no loading them from dylibs: rust has no stable abi to pass a trait
+// object across, and the contexts they get are types of this binary.
+
+use crate::cmd::{self, CommandContext, Console};
+use crate::common::{register_block, BlockDef, PlanetData};
+use crate::entity::Player;
+use crate::events::{EventBus, GameEvent, Subscriber};
+use crate::i18n::tr;
+use crate::weather::Weather;
+
+// every plugin compiled into the game, in load order. custom blocks are numbered in this order too, so
+// appending is fine but reordering changes what saved worlds are made of
+fn registered() -> Vec<Box<dyn Plugin>> {
+    vec![Box::new(Weather::new())]
+}
+
+// what a plugin gets to change once a frame
+pub struct TickContext<'a> {
+    pub player: &'a Player,
+    // connected to a server, which owns the world
+    pub online: bool,
+    pub planet: &'a mut PlanetData,
+    // edits a plugin makes go out here like the player's, so meshes and other subscribers hear of them
+    pub events: &'a mut EventBus,
+}
+
+pub trait Plugin {
+    fn name(&self) -> &'static str;
+
+    // once, after its blocks and commands are registered and the world is loaded
+    fn init(&mut self, _planet: &PlanetData, _console: &mut Console) {}
+
+    fn on_event(&mut self, _event: &GameEvent, _planet: &PlanetData) {}
+
+    fn on_tick(&mut self, _dt: f32, _ctx: &mut TickContext) {}
+
+    // usage and language key of the summary of each command it answers, for `help` and the F1 overlay.
+    // the first word of the usage is the command
+    fn register_commands(&self) -> Vec<(&'static str, &'static str)> {
+        Vec::new()
+    }
+
+    // runs one of its commands, `parts` as typed, command first
+    fn run_command(&mut self, _parts: &[&str], _ctx: &mut CommandContext, _console: &mut Console) {}
+
+    fn register_blocks(&self) -> Vec<BlockDef> {
+        Vec::new()
+    }
+}
+
+pub struct Plugins {
+    loaded: Vec<Box<dyn Plugin>>,
+    // command name and the plugin answering it
+    commands: Vec<(&'static str, usize)>,
+}
+
+impl Plugins {
+    pub fn load(planet: &PlanetData, console: &mut Console) -> Self {
+        let mut plugins = Self { loaded: Vec::new(), commands: Vec::new() };
+        for mut plugin in registered() {
+            for def in plugin.register_blocks() {
+                if register_block(def).is_none() {
+                    console.log(&tr!("plugin.block_taken", plugin.name(), def.name), [1.0, 0.5, 0.0]);
+                }
+            }
+            for (usage, summary) in plugin.register_commands() {
+                let name = usage.split_whitespace().next().unwrap_or(usage);
+                if !cmd::register_command(usage, summary) {
+                    console.log(&tr!("plugin.command_taken", plugin.name(), name), [1.0, 0.5, 0.0]);
+                    continue;
+                }
+                plugins.commands.push((name, plugins.loaded.len()));
+            }
+            plugin.init(planet, console);
+            plugins.loaded.push(plugin);
+        }
+        if !plugins.loaded.is_empty() {
+            let names: Vec<&str> = plugins.loaded.iter().map(|p| p.name()).collect();
+            console.log(&tr!("plugin.loaded", names.join(", ")), [0.6, 0.6, 0.6]);
+        }
+        plugins
+    }
+
+    pub fn tick(&mut self, dt: f32, ctx: &mut TickContext) {
+        for plugin in &mut self.loaded {
+            plugin.on_tick(dt, ctx);
+        }
+    }
+
+    // false if no plugin answers `parts[0]`
+    pub fn run_command(&mut self, parts: &[&str], ctx: &mut CommandContext, console: &mut Console) -> bool {
+        let Some(&(_, i)) = self.commands.iter().find(|(name, _)| Some(name) == parts.first()) else { return false };
+        self.loaded[i].run_command(parts, ctx, console);
+        true
+    }
+}
+
+impl Subscriber for Plugins {
+    fn on_event(&mut self, event: &GameEvent, planet: &PlanetData) {
+        for plugin in &mut self.loaded {
+            plugin.on_event(event, planet);
+        }
+    }
+}
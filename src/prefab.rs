@@ -0,0 +1,180 @@
+//prefab.rs
+
+// named schematics saved under prefabs/, picked from a radial menu and stamped into the world.
+// B marks the two corners of a selection, /prefab save <name> stores it, Tab opens the menu, R rotates.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use glam::Vec2;
+use crate::codec::*;
+use crate::common::{BlockId, PlanetData};
+use crate::i18n::tr;
+use crate::schematic::Schematic;
+
+// --- CONSTANTS ---
+pub const PREFAB_DIR: &str = "prefabs";
+
+const PREFAB_MAGIC: &[u8; 4] = b"VXNP";
+// 2 added a block to every cell
+const FORMAT_VERSION: u32 = 2;
+
+// mouse travel (in raw counts) before the radial menu picks a slice
+const MENU_DEADZONE: f32 = 30.0;
+const MENU_RADIUS: f32 = 120.0;
+
+pub struct Prefab {
+    pub name: String,
+    pub schematic: Schematic,
+}
+
+// open while Tab is held. the pointer is built from raw mouse motion so it works with a locked cursor too
+pub struct RadialMenu {
+    pointer: Vec2,
+}
+
+impl RadialMenu {
+    pub fn add_motion(&mut self, delta: (f64, f64)) {
+        self.pointer = (self.pointer + Vec2::new(delta.0 as f32, delta.1 as f32)).clamp_length_max(MENU_RADIUS);
+    }
+
+    // slice under the pointer, slice 0 at the top and going clockwise
+    pub fn hovered(&self, slices: usize) -> Option<usize> {
+        if slices == 0 || self.pointer.length() < MENU_DEADZONE { return None; }
+        let angle = self.pointer.x.atan2(-self.pointer.y).rem_euclid(std::f32::consts::TAU);
+        let slice = std::f32::consts::TAU / slices as f32;
+        Some(((angle + slice * 0.5) / slice) as usize % slices)
+    }
+}
+
+pub struct Prefabs {
+    dir: PathBuf,
+    // sorted by name
+    pub library: Vec<Prefab>,
+    // corners marked with B, the second one is None while picking
+    pub selection: Option<(BlockId, Option<BlockId>)>,
+    // prefab being placed, and its quarter turns
+    pub active: Option<usize>,
+    pub rotation: u8,
+    pub menu: Option<RadialMenu>,
+}
+
+impl Prefabs {
+    // loads every prefab in `dir`, returning the files that couldn't be read
+    pub fn load(dir: impl Into<PathBuf>) -> (Self, Vec<(PathBuf, io::Error)>) {
+        let dir = dir.into();
+        let mut library = Vec::new();
+        let mut errors = Vec::new();
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for path in entries.flatten().map(|e| e.path()) {
+                if !is_prefab_file(&path) { continue; }
+                match fs::read(&path).and_then(|bytes| decode_prefab(&bytes)) {
+                    Ok(prefab) => library.push(prefab),
+                    Err(e) => errors.push((path, e)),
+                }
+            }
+        }
+        library.sort_by(|a, b| a.name.cmp(&b.name));
+        (Self { dir, library, selection: None, active: None, rotation: 0, menu: None }, errors)
+    }
+
+    // B: first press starts a selection, second press closes it, third starts over
+    pub fn mark(&mut self, id: BlockId) {
+        self.selection = match self.selection {
+            Some((a, None)) => Some((a, Some(id))),
+            _ => Some((id, None)),
+        };
+    }
+
+    // captures the selection and writes it to prefabs/<name>.prefab, replacing one of the same name
+    pub fn save_selection(&mut self, name: &str, planet: &PlanetData) -> Result<usize, String> {
+        if !valid_name(name) {
+            return Err(tr!("prefab.bad_name"));
+        }
+        let Some((a, Some(b))) = self.selection else {
+            return Err(tr!("prefab.no_selection"));
+        };
+        let schematic = Schematic::capture(planet, a, b)?;
+        let prefab = Prefab { name: name.to_string(), schematic };
+
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        fs::write(self.path_of(name), encode_prefab(&prefab)).map_err(|e| e.to_string())?;
+
+        let blocks = prefab.schematic.solid.len();
+        self.active = None;
+        self.library.retain(|p| p.name != name);
+        self.library.push(prefab);
+        self.library.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(blocks)
+    }
+
+    pub fn delete(&mut self, name: &str) -> io::Result<()> {
+        if !valid_name(name) || !self.library.iter().any(|p| p.name == name) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, name.to_string()));
+        }
+        fs::remove_file(self.path_of(name))?;
+        self.active = None;
+        self.library.retain(|p| p.name != name);
+        Ok(())
+    }
+
+    // the prefab being placed, turned the way the player set it
+    pub fn active_schematic(&self) -> Option<Schematic> {
+        let prefab = self.library.get(self.active?)?;
+        Some(prefab.schematic.rotated(self.rotation))
+    }
+
+    // entries of the radial menu: "none" first, then the library
+    pub fn menu_items(&self) -> Vec<String> {
+        std::iter::once(tr!("prefab.none"))
+            .chain(self.library.iter().map(|p| p.name.clone()))
+            .collect()
+    }
+
+    pub fn open_menu(&mut self) {
+        self.menu = Some(RadialMenu { pointer: Vec2::ZERO });
+    }
+
+    // closes the menu and activates whatever was hovered, if anything
+    pub fn close_menu(&mut self) {
+        let Some(menu) = self.menu.take() else { return };
+        if let Some(slice) = menu.hovered(self.library.len() + 1) {
+            self.active = slice.checked_sub(1);
+            self.rotation = 0;
+        }
+    }
+
+    fn path_of(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.prefab", name))
+    }
+}
+
+// names end up as file names, so keep them to something every filesystem accepts
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 32 && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn encode_prefab(prefab: &Prefab) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(PREFAB_MAGIC);
+    put_u32(&mut out, FORMAT_VERSION);
+    put_str(&mut out, &prefab.name);
+    prefab.schematic.encode(&mut out);
+    out
+}
+
+fn decode_prefab(bytes: &[u8]) -> io::Result<Prefab> {
+    let mut r = ByteReader::new(bytes);
+    if r.bytes(4)? != PREFAB_MAGIC { return Err(invalid("bad prefab header")); }
+    let version = r.u32()?;
+    if !(1..=FORMAT_VERSION).contains(&version) {
+        return Err(invalid(&format!("unsupported prefab version {}", version)));
+    }
+    let name = r.str()?;
+    let schematic = Schematic::decode(&mut r, version >= 2)?;
+    Ok(Prefab { name, schematic })
+}
+
+fn is_prefab_file(path: &Path) -> bool {
+    path.extension().and_then(|x| x.to_str()) == Some("prefab")
+}
@@ -0,0 +1,137 @@
+//rcon.rs
+
+// remote admin console for headless servers.
+// plain text over TCP so netcat works as a client: the first line is the token,
+// every line after that is a console command, and all console output is streamed back.
+
+use std::io::{self, BufRead, BufReader, Read, Take, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::cmd::Console;
+use crate::i18n::tr;
+
+// --- CONSTANTS ---
+pub const TOKEN_ENV: &str = "VOXANET_RCON_TOKEN";
+
+// slows down token guessing
+const AUTH_FAIL_DELAY: Duration = Duration::from_secs(1);
+// for the whole token line, however slowly it trickles in
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+// longest line read, token or command, so a client can't make a session buffer without end
+const MAX_LINE: u64 = 4096;
+// sessions open at once, including ones still authenticating. more get turned away
+const MAX_SESSIONS: usize = 8;
+
+pub struct Rcon {
+    pub port: u16,
+    // log sinks of freshly authenticated sessions, handed to the console on the next poll
+    new_sessions: Receiver<Sender<String>>,
+}
+
+impl Rcon {
+    // commands from every session are pushed into `commands`, the same queue stdin feeds
+    pub fn start(port: u16, token: String, commands: Sender<String>) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let port = listener.local_addr()?.port();
+        let (session_tx, new_sessions) = channel();
+
+        std::thread::spawn(move || {
+            let active = Arc::new(AtomicUsize::new(0));
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                // only this thread adds sessions, so the count can't pass the cap between the check and the add
+                if active.load(Ordering::SeqCst) >= MAX_SESSIONS {
+                    let _ = stream.write_all(b"ERR busy\n");
+                    continue;
+                }
+                active.fetch_add(1, Ordering::SeqCst);
+                let token = token.clone();
+                let commands = commands.clone();
+                let session_tx = session_tx.clone();
+                let active = active.clone();
+                std::thread::spawn(move || {
+                    let _ = run_session(stream, &token, commands, session_tx);
+                    active.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        Ok(Self { port, new_sessions })
+    }
+
+    pub fn poll(&mut self, console: &mut Console) {
+        while let Ok(tap) = self.new_sessions.try_recv() {
+            console.add_tap(tap);
+            console.log(&tr!("rcon.session_opened"), [1.0, 1.0, 0.0]);
+        }
+    }
+}
+
+fn run_session(stream: TcpStream, token: &str, commands: Sender<String>, session_tx: Sender<Sender<String>>) -> io::Result<()> {
+    let addr = stream.peer_addr()?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream.take(MAX_LINE));
+
+    // AUTH
+    let line = read_token(&mut reader)?;
+    if !token_matches(line.trim(), token) {
+        println!("{}", tr!("rcon.auth_failed", addr));
+        std::thread::sleep(AUTH_FAIL_DELAY);
+        writer.write_all(b"ERR bad token\n")?;
+        return Ok(());
+    }
+    reader.get_ref().get_ref().set_read_timeout(None)?;
+    writer.write_all(b"OK\n")?;
+
+    // OUTPUT: the console drops this sender once the writer hangs up
+    let (log_tx, log_rx) = channel::<String>();
+    if session_tx.send(log_tx).is_err() { return Ok(()); }
+    std::thread::spawn(move || {
+        for text in log_rx {
+            if writeln!(writer, "{}", text).is_err() { break; }
+        }
+        let _ = writer.shutdown(std::net::Shutdown::Both);
+    });
+
+    // INPUT: a line that runs past MAX_LINE is cut off there and the rest read as the next one
+    loop {
+        reader.get_mut().set_limit(MAX_LINE);
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 { break; }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.trim().is_empty() { continue; }
+        if commands.send(line.to_string()).is_err() { break; }
+    }
+    Ok(())
+}
+
+// the first line, which has to arrive in full within AUTH_TIMEOUT of connecting. read a buffer at a time with the
+// socket timeout set to what's left, since a timeout per read alone lets a client send a byte every few seconds
+fn read_token(reader: &mut BufReader<Take<TcpStream>>) -> io::Result<String> {
+    let deadline = Instant::now() + AUTH_TIMEOUT;
+    let mut line = Vec::new();
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() { return Err(io::ErrorKind::TimedOut.into()); }
+        reader.get_ref().get_ref().set_read_timeout(Some(left))?;
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() { break; }
+        let (n, done) = match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (buf.len(), false),
+        };
+        line.extend_from_slice(&buf[..n]);
+        reader.consume(n);
+        if done { break; }
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+// compares every byte so response timing doesn't leak how much of the token was right
+pub fn token_matches(given: &str, expected: &str) -> bool {
+    if given.len() != expected.len() { return false; }
+    given.bytes().zip(expected.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
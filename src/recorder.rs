@@ -0,0 +1,159 @@
+//recorder.rs
+
+// /record: every shown frame copied out of the window and handed to an encoder thread, which writes them as
+// a numbered png sequence or pipes them into ffmpeg. frames go out at a fixed rate of their own, a slow frame
+// is repeated and a fast one skipped, so the video plays at the right speed whatever the render rate was.
+// screen capture tools tend to stutter with the immediate present mode, this doesn't care.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+use crate::capture::{self, Frame};
+use crate::i18n::tr;
+
+// --- CONSTANTS ---
+pub const RECORD_DIR: &str = "recordings";
+pub const DEFAULT_RECORD_FPS: u32 = 30;
+pub const MAX_RECORD_FPS: u32 = 120;
+// frames waiting for the encoder before new ones get dropped
+const QUEUE_LEN: usize = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    Png,
+    // raw frames piped into an ffmpeg found on the path
+    Ffmpeg,
+}
+
+impl RecordFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            RecordFormat::Png => "png",
+            RecordFormat::Ffmpeg => "ffmpeg",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "png" => Some(RecordFormat::Png),
+            "ffmpeg" => Some(RecordFormat::Ffmpeg),
+            _ => None,
+        }
+    }
+}
+
+pub struct Recorder {
+    pub dir: PathBuf,
+    pub format: RecordFormat,
+    pub fps: u32,
+    // seconds recorded so far
+    pub elapsed: f32,
+    // frames written into the video, repeats included
+    pub frames: u64,
+    // frames the encoder couldn't keep up with
+    pub dropped: u64,
+    // the first frame's size, the encoder can't change it halfway
+    size: Option<(u32, u32)>,
+    // a frame and how many times it goes into the video
+    tx: Option<SyncSender<(Frame, u64)>>,
+    worker: Option<JoinHandle<Result<(), String>>>,
+}
+
+impl Recorder {
+    pub fn start(format: RecordFormat, fps: u32) -> std::io::Result<Self> {
+        let dir = capture::new_session_dir(RECORD_DIR)?;
+        Ok(Self { dir, format, fps, elapsed: 0.0, frames: 0, dropped: 0, size: None, tx: None, worker: None })
+    }
+
+    // whether the video needs a frame after `dt` more seconds
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.elapsed += dt;
+        self.due() > 0
+    }
+
+    // frames the video is behind the clock
+    fn due(&self) -> u64 {
+        ((self.elapsed * self.fps as f32) as u64 + 1).saturating_sub(self.frames)
+    }
+
+    // hands `frame` to the encoder, filling every frame that's due with it
+    pub fn push(&mut self, frame: Frame) -> Result<(), String> {
+        let size = (frame.width, frame.height);
+        if *self.size.get_or_insert(size) != size {
+            return Err(tr!("record.resized"));
+        }
+        if self.tx.is_none() {
+            let (tx, rx) = sync_channel(QUEUE_LEN);
+            let (dir, format, fps) = (self.dir.clone(), self.format, self.fps);
+            self.worker = Some(std::thread::spawn(move || encode(rx, &dir, format, fps, size)));
+            self.tx = Some(tx);
+        }
+        let repeats = self.due();
+        if repeats == 0 { return Ok(()); }
+        let Some(tx) = &self.tx else { return Ok(()); };
+        match tx.try_send((frame, repeats)) {
+            Ok(()) => {},
+            // the video still gets the frames, the next one that's accepted covers them
+            Err(TrySendError::Full(_)) => { self.dropped += 1; return Ok(()); },
+            Err(TrySendError::Disconnected(_)) => return Err(self.finish().err().unwrap_or_default()),
+        }
+        self.frames += repeats;
+        Ok(())
+    }
+
+    // waits for the encoder to write everything it was given
+    pub fn finish(&mut self) -> Result<(), String> {
+        self.tx = None;
+        match self.worker.take() {
+            Some(worker) => worker.join().unwrap_or_else(|_| Err(tr!("record.panicked"))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn encode(rx: Receiver<(Frame, u64)>, dir: &Path, format: RecordFormat, fps: u32, (width, height): (u32, u32)) -> Result<(), String> {
+    match format {
+        RecordFormat::Png => {
+            let mut n = 0u64;
+            for (frame, repeats) in rx {
+                let png = capture::encode_png(&frame);
+                for _ in 0..repeats {
+                    let path = dir.join(format!("frame_{:06}.png", n));
+                    fs::write(&path, &png).map_err(|e| format!("{}: {}", path.display(), e))?;
+                    n += 1;
+                }
+            }
+            Ok(())
+        },
+        RecordFormat::Ffmpeg => {
+            let out = dir.join("video.mp4");
+            let mut child = Command::new("ffmpeg")
+                .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+                .args(["-s", &format!("{}x{}", width, height), "-r", &fps.to_string(), "-i", "-"])
+                .args(["-c:v", "libx264", "-pix_fmt", "yuv420p"])
+                .arg(&out)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .spawn()
+                .map_err(|e| format!("ffmpeg: {}", e))?;
+            let written = (|| {
+                let stdin = child.stdin.as_mut().ok_or("ffmpeg: no stdin")?;
+                for (frame, repeats) in rx {
+                    for _ in 0..repeats {
+                        stdin.write_all(&frame.rgba).map_err(|e| format!("ffmpeg: {}", e))?;
+                    }
+                }
+                Ok::<(), String>(())
+            })();
+            // closing stdin lets ffmpeg finish the file
+            drop(child.stdin.take());
+            let status = child.wait().map_err(|e| format!("ffmpeg: {}", e))?;
+            written?;
+            if !status.success() { return Err(format!("ffmpeg: {}", status)); }
+            Ok(())
+        },
+    }
+}
@@ -5,15 +5,44 @@ use wgpu::PresentMode;
 use winit::window::Window;
 use wgpu::util::DeviceExt;
 use glyphon::{FontSystem, SwashCache, TextAtlas, TextArea, TextRenderer as GlyphRenderer, TextBounds, Resolution, Buffer, Metrics, Shaping, Attrs, Family};
-use crate::cmd::Console;
+use crate::cmd::{self, Console, CONSOLE_LINE_HEIGHT};
 use crate::common::*;
-use crate::gen::{MeshGen, CoordSystem};
-use crate::controller::Controller;
+use crate::gen::{MeshGen, CoordSystem, ViewDistance};
+use crate::noise::PlanetTerrain;
+use crate::controller::{BrushArea, Controller, Keys, KEYBINDS};
+use crate::bindings::Bindings;
 use crate::entity::Player;
+use crate::physics::Physics;
+use crate::net::RemotePlayer;
+use crate::i18n::{self, tr};
+use crate::settings::{Settings, MIN_LOD_DETAIL, MIN_RENDER_DISTANCE};
+use crate::schematic::Schematic;
+use crate::blueprint::Blueprint;
 use glam::Vec3;
 use crate::lod_animation::{LodAnimator, AnyKey};
+use crate::lod_batch::LodBatches;
 use bytemuck::{Pod, Zeroable};
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use crate::debug_view::{DebugCamera, DebugView};
+use crate::monitor::{Monitors, MAX_SCREENS};
+use crate::sign;
+use crate::mob::{MobKind, Mobs, BIRD_SIZE, MAX_MOBS, MOB_RADIUS};
+use crate::teleporter;
+use crate::marker::{Markers, LABEL_HEIGHT};
+use crate::events::{GameEvent, Subscriber};
+use crate::capture::Frame;
+use crate::toast::Toasts;
+use crate::mesh_profile::{MeshProfile, MeshTiming};
+use crate::mesh_pool::MeshPool;
+use crate::frame_graph::{FrameGraph, Resource};
+use crate::instancing::{Instance, InstancedMesh};
+use crate::inventory::{Inventory, Stack, HOTBAR_SLOTS};
+use crate::pipelines::{Blend, Depth, PipelineCache, PipelineKey, Shader, ShaderFeatures};
+use crate::health::MAX_HEALTH;
+use crate::stamina::MAX_STAMINA;
+use crate::oxygen::MAX_OXYGEN;
+use crate::temperature::{COLD_LIMIT, HEAT_LIMIT};
 
 // --- UNIFORMS ---
 
@@ -22,15 +51,371 @@ use std::sync::mpsc::{channel, Receiver, Sender};
 pub struct GlobalUniform {
     pub view_proj: [f32; 16],
     pub light_view_proj: [f32; 16],
-    pub cam_pos: [f32; 4],
+    pub cam_pos: [f32; 4], // w = radius of the planet's base surface, where the fog is thickest
     pub sun_dir: [f32; 4],   
+    pub fog: [f32; 4], // rgb = colour, w = density, from the fog options
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct LocalUniform {
     pub model: [f32; 16],
-    pub params: [f32; 4], // x = opacity
+    pub params: [f32; 4], // x = opacity, z = lod level, w = reflectivity
+}
+
+// shader debug views picked with /rendermode, sent in GlobalUniform.sun_dir.w
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    #[default]
+    Normal,
+    Normals,
+    // the shade baked into the vertex colors: skylight and ambient occlusion
+    Ao,
+    // lighting alone on white
+    Light,
+    Lod,
+    Depth,
+    // every terrain fragment adds the same bit of light, so bright spots are drawn many times over
+    Overdraw,
+}
+
+impl RenderMode {
+    pub const ALL: [RenderMode; 7] = [RenderMode::Normal, RenderMode::Normals, RenderMode::Ao, RenderMode::Light, RenderMode::Lod, RenderMode::Depth, RenderMode::Overdraw];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Normal => "normal",
+            RenderMode::Normals => "normals",
+            RenderMode::Ao => "ao",
+            RenderMode::Light => "light",
+            RenderMode::Lod => "lod",
+            RenderMode::Depth => "depth",
+            RenderMode::Overdraw => "overdraw",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == s)
+    }
+}
+
+// --- CONSTANTS ---
+// prefab preview limits, bigger prefabs only show their bounding box
+const GHOST_MAX_BLOCKS: usize = 4096;
+const GHOST_OPACITY: f32 = 0.45;
+const GHOST_GROW: f32 = 0.04;
+// distance of the radial menu labels from the screen centre, in pixels
+const RADIAL_RADIUS: f32 = 140.0;
+// line height of the settings menu, in pixels
+const MENU_LINE: f32 = 22.0;
+// meshing threads started per frame (and chunk meshes in flight) while throttled
+const THROTTLED_SPAWNS: usize = 1;
+// chunks and lods out of view are meshed as if they were this many times further away, so after turning around
+// what's ahead fills in first. within NEAR_STREAM of the player everything is in view, it's what they stand on
+const OFF_SCREEN_DISTANCE: f32 = 3.0;
+const NEAR_STREAM: f32 = 48.0;
+// moving at least PREFETCH_MIN_SPEED, the chunks and lods the view will want PREFETCH_SECONDS further along are
+// meshed ahead of time, a few at once and only once nothing nearer is waiting. PREFETCH_PRIORITY puts them behind
+// anything needed now in the pool's queue
+const PREFETCH_SECONDS: f32 = 3.0;
+const PREFETCH_MIN_SPEED: f32 = 12.0;
+const PREFETCH_SPAWNS: usize = 2;
+const PREFETCH_PRIORITY: f32 = 1.0e12;
+// chunks meshed around the spawn before the first frame, and how often the loading bar is redrawn meanwhile
+const WARM_UP_CHUNKS: usize = 48;
+const LOADING_FRAME: std::time::Duration = std::time::Duration::from_millis(50);
+// bars along the bottom of the screen (health and the like) and the loading bar share one buffer
+const MAX_BARS: usize = 4;
+const BAR_WIDTH: f32 = 240.0;
+const BAR_HEIGHT: f32 = 10.0;
+const BAR_TRACK: [f32; 3] = [0.15, 0.15, 0.2];
+const HEALTH_COLOR: [f32; 3] = [0.85, 0.2, 0.2];
+const STAMINA_COLOR: [f32; 3] = [0.9, 0.75, 0.2];
+const OXYGEN_COLOR: [f32; 3] = [0.3, 0.7, 0.95];
+const FLASH_COLOR: [f32; 3] = [0.9, 0.05, 0.05];
+// hotbar slots along the bottom edge, with the bars and temperature stacked above them
+const SLOT_SIZE: f32 = 40.0;
+const SLOT_GAP: f32 = 4.0;
+const SLOT_BORDER: f32 = 3.0;
+const HOTBAR_SPACE: f32 = 56.0;
+const SLOT_SELECTED: [f32; 3] = [0.95, 0.95, 0.95];
+// view distance lost per memory trim, and the least it may shrink to
+const MEMORY_SCALE_STEP: f32 = 0.8;
+const MIN_MEMORY_SCALE: f32 = 0.3;
+// shadow map side until the settings say otherwise
+const DEFAULT_SHADOW_SIZE: u32 = 4096;
+// monitor camera textures, and how many of them are redrawn per frame, taking turns
+const MONITOR_WIDTH: u32 = 320;
+const MONITOR_HEIGHT: u32 = 240;
+const MONITOR_UPDATES_PER_FRAME: usize = 1;
+// signs drawn at once, and the texture each one's text is drawn into
+const MAX_SIGNS: usize = 256;
+const SIGN_TEXTURE_SIZE: u32 = 256;
+const SIGN_BACKGROUND: wgpu::Color = wgpu::Color { r: 0.55, g: 0.4, b: 0.22, a: 1.0 };
+// rings and segments of a ball mob
+const BALL_SEGMENTS: u32 = 8;
+// the grapple line runs from this high over the player's feet to the anchor
+const ROPE_HAND: f32 = 1.1;
+const ROPE_COLOR: [f32; 3] = [0.55, 0.42, 0.25];
+// sparks rising off teleporters: how many get them, how near they have to be, and the sparks on each
+const MAX_TELEPORTER_SPARKS: usize = 64;
+const SPARK_RANGE: f32 = 96.0;
+const SPARKS: u32 = 16;
+const SPARK_RISE: f32 = 1.5;
+const SPARK_LENGTH: f32 = 0.15;
+const SPARK_LINKED: [f32; 3] = [0.3, 0.8, 1.0];
+const SPARK_UNLINKED: [f32; 3] = [0.45, 0.45, 0.55];
+// beams standing on the player's graves, drawn through the ground so they can be found from anywhere
+const MAX_BEACONS: usize = 16;
+const BEACON_HEIGHT: f32 = 64.0;
+const BEACON_WIDTH: f32 = 0.5;
+const BEACON_COLOR: [f32; 3] = [1.0, 0.35, 0.3];
+// wire boxes of the frozen camera frustum and shadow volume while culling is frozen
+const GIZMO_FRUSTUM_COLOR: [f32; 3] = [1.0, 1.0, 0.2];
+const GIZMO_SHADOW_COLOR: [f32; 3] = [1.0, 0.45, 0.1];
+// side of each reflection probe face, and seconds between two captures. a capture draws one face per frame
+const PROBE_SIZE: u32 = 128;
+const PROBE_INTERVAL: f32 = 2.0;
+const PROBE_FAR: f32 = 4000.0;
+// other players drawn at most, any past this aren't
+const MAX_REMOTE_PLAYERS: usize = 256;
+// cube faces in wgpu's order (+x, -x, +y, -y, +z, -z) and the up each one is drawn with
+const PROBE_FACES: [(Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::Y), (Vec3::NEG_X, Vec3::Y),
+    (Vec3::Y, Vec3::NEG_Z), (Vec3::NEG_Y, Vec3::Z),
+    (Vec3::Z, Vec3::Y), (Vec3::NEG_Z, Vec3::Y),
+];
+
+// --- PIPELINES ---
+const FILL: PipelineKey = PipelineKey { shader: Shader::World, topology: wgpu::PrimitiveTopology::TriangleList, polygon: wgpu::PolygonMode::Fill, blend: Blend::Opaque, depth: Depth::Test, cull: None };
+const WIRE: PipelineKey = PipelineKey { polygon: wgpu::PolygonMode::Line, ..FILL };
+const LINE: PipelineKey = PipelineKey { topology: wgpu::PrimitiveTopology::LineList, ..FILL };
+// overlays, over whatever is already drawn
+const UI: PipelineKey = PipelineKey { blend: Blend::Alpha, depth: Depth::Always, ..FILL };
+// every fragment counts, hidden or not
+const OVERDRAW: PipelineKey = PipelineKey { shader: Shader::Overdraw, blend: Blend::Additive, depth: Depth::Always, ..FILL };
+const SHADOW: PipelineKey = PipelineKey { shader: Shader::DepthOnly, depth: Depth::Biased, cull: Some(wgpu::Face::Front), ..FILL };
+const SCREEN: PipelineKey = PipelineKey { shader: Shader::Screen, ..FILL };
+// behind everything, the world is drawn over it
+const SKY: PipelineKey = PipelineKey { shader: Shader::Sky, depth: Depth::Always, ..FILL };
+
+// blocks and models, as wireframes while those are on
+fn mesh_key(wireframe: bool) -> PipelineKey {
+    if wireframe { WIRE } else { FILL }
+}
+
+// mesh_key for an InstancedMesh
+fn instanced_key(wireframe: bool) -> PipelineKey {
+    PipelineKey { shader: Shader::Instanced, ..mesh_key(wireframe) }
+}
+
+// lod meshes get the level of their size, so levels count up from 1 past full-detail chunks
+fn lod_level(key: LodKey) -> f32 {
+    (key.size.max(1) as f32).log2() + 1.0
+}
+
+// middle of a chunk at half the planet's height, what the streamer measures distances from
+fn chunk_center(key: &ChunkKey, res: u32) -> Vec3 {
+    let u = key.u_idx * CHUNK_SIZE + CHUNK_SIZE / 2;
+    let v = key.v_idx * CHUNK_SIZE + CHUNK_SIZE / 2;
+    CoordSystem::get_vertex_pos(key.face, u, v, res / 2, res)
+}
+
+// bounds of the natural ground of a chunk, for streaming it before there's a mesh to measure
+fn chunk_bounds(key: &ChunkKey, planet: &PlanetData) -> (Vec3, f32) {
+    planet.terrain.bounds(key.face, key.u_idx * CHUNK_SIZE, key.v_idx * CHUNK_SIZE, CHUNK_SIZE)
+}
+
+// corners of the box a view-projection matrix sees, bit 0 of the index is x, bit 1 y and bit 2 depth
+fn frustum_corners(view_proj: glam::Mat4) -> [Vec3; 8] {
+    let inv = view_proj.inverse();
+    std::array::from_fn(|i| {
+        let ndc = Vec3::new(if i & 1 == 0 { -1.0 } else { 1.0 }, if i & 2 == 0 { -1.0 } else { 1.0 }, if i & 4 == 0 { 0.0 } else { 1.0 });
+        inv.project_point3(ndc)
+    })
+}
+
+// corner pairs of frustum_corners() joined by an edge
+const BOX_EDGES: [(u32, u32); 12] = [
+    (0, 1), (2, 3), (4, 5), (6, 7),
+    (0, 2), (1, 3), (4, 6), (5, 7),
+    (0, 4), (1, 5), (2, 6), (3, 7),
+];
+
+// what a memory trim threw away
+pub struct Eviction {
+    pub queued: usize,
+    pub fading: usize,
+    // view distance multiplier now in effect
+    pub scale: f32,
+}
+
+// a panel of rows over the game, one row picked: the settings menu or a villager's offers
+pub struct Menu {
+    pub title: String,
+    // (text, selected)
+    pub lines: Vec<(String, bool)>,
+    pub hint: String,
+}
+
+// a chunk or lod mesh on the gpu
+pub struct ChunkMesh {
+    pub v_buf: wgpu::Buffer,
+    pub i_buf: wgpu::Buffer,
+    pub num_inds: u32,
+    pub num_verts: usize,
+    pub uniform_buf: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    pub center: glam::Vec3,
+    pub radius: f32,
+    // what the frustum culls with. the sphere is left for distances
+    pub bounds: Obb,
+    // detail level for the lod render mode, 0 for full-detail chunks
+    pub lod: f32,
+    // how much of the reflection probe it mirrors, params.w. terrain meshes mix every block glass and all, so
+    // they can't mirror as a whole and stay at 0
+    pub reflectivity: f32,
+}
+
+// offscreen render target of one monitor camera
+struct CameraTarget {
+    view: wgpu::TextureView,
+    depth: wgpu::TextureView,
+    view_proj: glam::Mat4,
+    eye: Vec3,
+    global_buf: wgpu::Buffer,
+    global_bind: wgpu::BindGroup,
+    // the texture as the screen pipeline reads it
+    screen_bind: wgpu::BindGroup,
+}
+
+// a buffer a whole image gets copied into to read it on the cpu
+struct Readback {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    // rows are padded to the copy alignment
+    padded_row: u32,
+}
+
+impl Readback {
+    fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row = (width * 4).div_ceil(align) * align;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback"),
+            size: (padded_row * height) as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+        Self { buffer, width, height, padded_row }
+    }
+
+    fn copy_from(&self, enc: &mut wgpu::CommandEncoder, texture: &wgpu::Texture) {
+        enc.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.buffer,
+                layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(self.padded_row), rows_per_image: Some(self.height) },
+            },
+            wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+        );
+    }
+
+    // waits for the copy submitted this frame and unpads it into rgba rows
+    fn read(&self, device: &wgpu::Device, format: wgpu::TextureFormat) -> Frame {
+        let slice = self.buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        device.poll(wgpu::Maintain::Wait);
+        let mut rgba = Vec::with_capacity((self.width * self.height * 4) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks_exact(self.padded_row as usize) {
+                rgba.extend_from_slice(&row[..self.width as usize * 4]);
+            }
+        }
+        self.buffer.unmap();
+        if matches!(format, wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb) {
+            for px in rgba.chunks_exact_mut(4) { px.swap(0, 2); }
+        }
+        Frame { width: self.width, height: self.height, rgba }
+    }
+}
+
+// offscreen target stills (time-lapse) are drawn into and read back from
+struct StillTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    depth: wgpu::TextureView,
+    global_buf: wgpu::Buffer,
+    global_bind: wgpu::BindGroup,
+    readback: Readback,
+}
+
+// low-res cube map of the surroundings near the camera that reflective surfaces mirror. faces are drawn into
+// `faces` one per frame and copied over to `cube` once all six are done, so the cube is never half updated
+// and never drawn into while it's bound
+struct ReflectionProbe {
+    cube: wgpu::Texture,
+    cube_view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    faces: wgpu::Texture,
+    face_views: Vec<wgpu::TextureView>,
+    depth: wgpu::TextureView,
+    global_buf: wgpu::Buffer,
+    global_bind: wgpu::BindGroup,
+    center: Vec3,
+    // face drawn next, 6 while waiting for the next capture
+    face: usize,
+    clock: std::time::Instant,
+}
+
+impl ReflectionProbe {
+    fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, global_layout: &wgpu::BindGroupLayout, shadow_view: &wgpu::TextureView, shadow_sampler: &wgpu::Sampler) -> Self {
+        let size = wgpu::Extent3d { width: PROBE_SIZE, height: PROBE_SIZE, depth_or_array_layers: 6 };
+        let mk_texture = |label, usage| device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label), size, mip_level_count: 1, sample_count: 1,
+            dimension: wgpu::TextureDimension::D2, format: config.format, usage, view_formats: &[],
+        });
+        let cube = mk_texture("Probe Cube", wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST);
+        let cube_view = cube.create_view(&wgpu::TextureViewDescriptor { dimension: Some(wgpu::TextureViewDimension::Cube), ..Default::default() });
+        let faces = mk_texture("Probe Faces", wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC);
+        let face_views = (0..6).map(|layer| faces.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2), base_array_layer: layer, array_layer_count: Some(1), ..Default::default()
+        })).collect();
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Probe Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let depth = Renderer::mk_depth(device, &wgpu::SurfaceConfiguration { width: PROBE_SIZE, height: PROBE_SIZE, ..config.clone() });
+        let global_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Probe Uniform"),
+            size: std::mem::size_of::<GlobalUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+        // binds the finished cube while the faces are drawn, like every other view
+        let global_bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: global_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: global_buf.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(shadow_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(shadow_sampler) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&cube_view) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::Sampler(&sampler) },
+            ],
+            label: None,
+        });
+        Self { cube, cube_view, sampler, faces, face_views, depth, global_buf, global_bind, center: Vec3::ZERO, face: 0, clock: std::time::Instant::now() }
+    }
+
+    // cube maps are addressed left-handed, a right-handed view would mirror every face
+    fn face_view_proj(&self, face: usize) -> glam::Mat4 {
+        let (dir, up) = PROBE_FACES[face];
+        reverse_z(glam::Mat4::perspective_lh(90f32.to_radians(), 1.0, 0.1, PROBE_FAR)) * glam::Mat4::look_at_lh(self.center, self.center + dir, up)
+    }
 }
 
 // --- RENDERER STRUCT ---
@@ -38,6 +423,9 @@ pub struct LocalUniform {
 pub struct Renderer<'a> {
     pub window: &'a Window,
     surface: wgpu::Surface<'a>,
+    // kept for surfaces of extra windows
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
     pub config: wgpu::SurfaceConfiguration,
@@ -45,39 +433,62 @@ pub struct Renderer<'a> {
     // --- TEXT ENGINE ---
     font_system: FontSystem,
     swash_cache: SwashCache,
+    #[allow(dead_code)]
     text_viewport: wgpu::TextureView, 
     text_atlas: TextAtlas,
     text_renderer: GlyphRenderer,
     
     // --- SHADOWS ---
+    #[allow(dead_code)]
     shadow_texture: wgpu::Texture,
+    #[allow(dead_code)]
     shadow_view: wgpu::TextureView,
+    #[allow(dead_code)]
     shadow_sampler: wgpu::Sampler,
-    pipeline_shadow: wgpu::RenderPipeline,
+    // side of the square shadow map in texels, from the graphics settings
+    shadow_size: u32,
+    probe: ReflectionProbe,
     shadow_global_buf: wgpu::Buffer,      
     shadow_global_bind: wgpu::BindGroup,
 
     // --- UI ---
-    pipeline_ui: wgpu::RenderPipeline, 
     console_v_buf: wgpu::Buffer,
+    console_local_buf: wgpu::Buffer,
+    console_local_bind: wgpu::BindGroup,
     console_i_buf: wgpu::Buffer,
     console_inds: u32,
+    help_v_buf: wgpu::Buffer,
+    // menu panel (settings, trading), drawn with help_i_buf
+    menu_v_buf: wgpu::Buffer,
+    // what it shows while one is open
+    menu: Option<Menu>,
+    // notices at the top of the screen (text, colour, opacity), newest first
+    toasts: Vec<(String, [f32; 3], f32)>,
+    help_i_buf: wgpu::Buffer,
+    // status bars at the bottom, or the loading bar: a track and a filled quad each
+    bar_v_buf: wgpu::Buffer,
+    bar_i_buf: wgpu::Buffer,
+    bar_inds: u32,
 
     // --- CORE ---
     animator: LodAnimator,
     local_layout: wgpu::BindGroupLayout,
 
-    pipeline_fill: wgpu::RenderPipeline,
-    pipeline_wire: wgpu::RenderPipeline,
-    pipeline_line: wgpu::RenderPipeline,
+    pipelines: PipelineCache,
     
     chunks: HashMap<ChunkKey, ChunkMesh>,     
     lod_chunks: HashMap<LodKey, ChunkMesh>, 
+    // small settled lods merged into fewer draws, see lod_batch.rs
+    lod_batches: LodBatches,
 
     // --- UNIFORMS ---
     global_buf: wgpu::Buffer,
     global_bind: wgpu::BindGroup,
+    // right eye camera in stereo mode, global_buf holds the left one then
+    global_buf_right: wgpu::Buffer,
+    global_bind_right: wgpu::BindGroup,
     
+    #[allow(dead_code)]
     local_buf_identity: wgpu::Buffer,
     local_bind_identity: wgpu::BindGroup,
     
@@ -85,24 +496,49 @@ pub struct Renderer<'a> {
     local_bind_player: wgpu::BindGroup,
 
     local_buf_guide: wgpu::Buffer,
+    #[allow(dead_code)]
     local_bind_guide: wgpu::BindGroup,
 
     depth: wgpu::TextureView,
     global_bind_identity: wgpu::BindGroup, // For UI to access dummy shadows
+    // kept to rebuild the bind groups above when the shadow map is resized
+    global_layout: wgpu::BindGroupLayout,
+    global_buf_identity: wgpu::Buffer,
+
+    // --- GRAPHICS OPTIONS ---
+    pub adapter_info: wgpu::AdapterInfo,
+    present_modes: Vec<PresentMode>,
+    vsync: bool,
+    // colour and density, as sent in GlobalUniform.fog
+    fog: [f32; 4],
 
     // --- MESHES ---
     player_v_buf: wgpu::Buffer,
     player_i_buf: wgpu::Buffer,
     player_inds: u32,
 
+    // other players replicated from the server, copies of the player cylinder
+    remote_players: InstancedMesh,
+    remote_tags: Vec<(String, Vec3)>,
+    // labels over map markers, drawn the same way as the name tags
+    marker_tags: Vec<(String, Vec3, [f32; 3])>,
+
+    #[allow(dead_code)]
     guide_v_buf: wgpu::Buffer,
+    #[allow(dead_code)]
     guide_i_buf: wgpu::Buffer,
+    #[allow(dead_code)]
     guide_inds: u32,
 
     cross_v_buf: wgpu::Buffer,
     cross_i_buf: wgpu::Buffer,
     cross_inds: u32,
 
+    // touch joystick
+    touch_v_buf: wgpu::Buffer,
+    touch_i_buf: wgpu::Buffer,
+    touch_inds: u32,
+
     cursor_v_buf: wgpu::Buffer,
     cursor_i_buf: wgpu::Buffer,
     cursor_inds: u32,
@@ -110,20 +546,130 @@ pub struct Renderer<'a> {
     collision_v_buf: wgpu::Buffer,
     collision_i_buf: wgpu::Buffer,
     collision_inds: u32,
+
+    // editor snapping grid
+    grid_v_buf: wgpu::Buffer,
+    grid_i_buf: wgpu::Buffer,
+    grid_inds: u32,
+
+    // prefab selection box and placement outline
+    select_v_buf: wgpu::Buffer,
+    select_i_buf: wgpu::Buffer,
+    select_inds: u32,
+    // see-through preview of the prefab being placed
+    ghost_v_buf: wgpu::Buffer,
+    ghost_i_buf: wgpu::Buffer,
+    ghost_inds: u32,
+    ghost_local_bind: wgpu::BindGroup,
+    // blueprint build guide: missing and wrongly placed blocks, plus its box
+    blueprint_v_buf: wgpu::Buffer,
+    blueprint_i_buf: wgpu::Buffer,
+    blueprint_inds: u32,
+    blueprint_label: Option<String>,
+    // a model per mob kind, one copy for each mob of it
+    mob_meshes: [InstancedMesh; 6],
+    // one line from the player's hand to the grapple anchor
+    rope_v_buf: wgpu::Buffer,
+    rope_i_buf: wgpu::Buffer,
+    rope_drawn: bool,
+    // sparks over nearby teleporters, and the full-screen black the travel fade draws
+    spark_v_buf: wgpu::Buffer,
+    spark_i_buf: wgpu::Buffer,
+    spark_inds: u32,
+    spark_clock: std::time::Instant,
+    // a beam over each grave, drawn with the UI pipeline so nothing hides it
+    beacon_v_buf: wgpu::Buffer,
+    beacon_i_buf: wgpu::Buffer,
+    beacon_inds: u32,
+    fade_v_buf: wgpu::Buffer,
+    fade_local_buf: wgpu::Buffer,
+    fade_local_bind: wgpu::BindGroup,
+    fade_drawn: bool,
+    // the sky's way from clip space back to a view direction, one per eye
+    sky_local_bufs: [wgpu::Buffer; 2],
+    sky_local_binds: [wgpu::BindGroup; 2],
+    // red over the screen when the player gets hurt
+    flash_v_buf: wgpu::Buffer,
+    flash_local_buf: wgpu::Buffer,
+    flash_local_bind: wgpu::BindGroup,
+    flash_drawn: bool,
+    // active prefab name and radial menu entries (name, hovered)
+    prefab_label: Option<String>,
+    // "press E to ..." under the crosshair
+    interact_label: Option<String>,
+    // stacks and the selected slot, as the hud last heard
+    hotbar: ([Option<Stack>; HOTBAR_SLOTS], usize),
+    hotbar_v_buf: wgpu::Buffer,
+    hotbar_i_buf: wgpu::Buffer,
+    hotbar_inds: u32,
+    radial_items: Vec<(String, bool)>,
     frozen_frustum: Option<crate::common::Frustum>, 
+    // the main camera's view last frame, for what to stream first
+    view_frustum: Option<Frustum>,
+    pub render_mode: RenderMode,
+    // two wire boxes, drawn while frozen_frustum is set
+    gizmo_v_buf: wgpu::Buffer,
+    gizmo_i_buf: wgpu::Buffer,
+    // second window with another camera, see debug_view.rs
+    pub debug_view: Option<DebugView>,
+    // monitor cameras and the screens showing them, see monitor.rs
+    screen_layout: wgpu::BindGroupLayout,
+    screen_sampler: wgpu::Sampler,
+    camera_targets: Vec<CameraTarget>,
+    next_camera: usize,
+    // a still to draw with the next frame (camera, eye, size), and the last one read back
+    still: Option<StillTarget>,
+    still_request: Option<(glam::Mat4, Vec3, u32, u32)>,
+    still_frame: Option<Frame>,
+    // /record: copy this frame out of the window, and the last one copied
+    frame_request: bool,
+    frame_readback: Option<Readback>,
+    recorded_frame: Option<Frame>,
+    screen_v_buf: wgpu::Buffer,
+    screen_i_buf: wgpu::Buffer,
+    // camera of each screen, 6 indices apiece
+    screens: Vec<usize>,
+    // sign text textures by block, with the text they show, and the sign quads in the same order as sign_order
+    sign_textures: HashMap<BlockId, (String, wgpu::BindGroup)>,
+    sign_v_buf: wgpu::Buffer,
+    sign_i_buf: wgpu::Buffer,
+    sign_order: Vec<BlockId>,
+    // planet.entity_revision the signs were built from
+    sign_revision: Option<u32>,
 
 
     // --- THREADING ---
     load_queue: Vec<ChunkKey>, 
+    // chunk radius and lod reach, from the settings or the auto tuner
+    pub view_distance: ViewDistance,
+    // rough gpu memory chunk meshes may take, see SystemDiagnostics::mesh_memory_budget
+    pub mesh_budget: u64,
+    // set while unfocused or on battery: meshing trickles along on fewer worker threads
+    pub throttled: bool,
+    // shrinks the view distance while memory is tight, 1 = as configured
+    memory_scale: f32,
     player_chunk_pos: Option<ChunkKey>, 
     
-    mesh_tx: Sender<(ChunkKey, Vec<Vertex>, Vec<u32>)>,
+    // builds chunk and lod meshes off the main thread, they come back on mesh_rx and lod_rx
+    mesh_pool: MeshPool,
     mesh_rx: Receiver<(ChunkKey, Vec<Vertex>, Vec<u32>)>,
     pending_chunks: HashSet<ChunkKey>, 
+    // loaded chunks a block edit changed, waiting to be remeshed, and the ones being remeshed on the pool
+    remesh: HashSet<ChunkKey>,
+    remeshing: HashSet<ChunkKey>,
 
-    lod_tx: Sender<(LodKey, Vec<Vertex>, Vec<u32>)>,
     lod_rx: Receiver<(LodKey, Vec<Vertex>, Vec<u32>)>,
+    // meshing times sent back by the worker threads
+    profile_rx: Receiver<MeshTiming>,
+    pub mesh_profile: MeshProfile,
     pending_lods: HashSet<LodKey>,
+    // meshes built ahead of the player on the pool, false once an edit has made one out of date, and the finished
+    // ones kept off the gpu until the view asks for them
+    prefetching: HashMap<AnyKey, bool>,
+    prefetched: HashMap<AnyKey, (Vec<Vertex>, Vec<u32>)>,
+
+    // chunks and lods the main pass drew last frame, for the debug overlay
+    rendered: (usize, usize),
 
     // --- FPS ---
     last_fps_time: std::time::Instant,
@@ -131,6 +677,34 @@ pub struct Renderer<'a> {
     current_fps: u32,
 }
 
+// what every pass of a frame shares, worked out once before the frame graph runs
+struct PassContext<'f> {
+    controller: &'f Controller,
+    player: &'f Player,
+    console: &'f Console,
+    settings: &'f Settings,
+    // the autosave is writing, for the hud
+    saving: bool,
+    // the window's texture this frame and a view of it
+    surface: &'f wgpu::Texture,
+    view: &'f wgpu::TextureView,
+    debug_target: Option<wgpu::TextureView>,
+    light_view_proj: glam::Mat4,
+    sun_dir: Vec3,
+    cam_pos: Vec3,
+    // radius of the planet's base surface, for the fog
+    ground: f32,
+    mvp: glam::Mat4,
+    frustum: Frustum,
+    // what the main view culls against, held still while culling is frozen
+    cull_frustum: Frustum,
+    stereo: bool,
+    width: f32,
+    height: f32,
+    still_request: Option<(glam::Mat4, Vec3, u32, u32)>,
+    record: bool,
+}
+
 impl<'a> Renderer<'a> {
     pub async fn new(window: &'a Window) -> Self {
         let instance = wgpu::Instance::default();
@@ -143,7 +717,9 @@ impl<'a> Renderer<'a> {
         }).await.unwrap();
         
         // log GPU info
-        crate::system_diagnostics::SystemDiagnostics::log_gpu(&adapter.get_info());
+        let adapter_info = adapter.get_info();
+        crate::system_diagnostics::SystemDiagnostics::log_gpu(&adapter_info);
+        let mesh_budget = crate::system_diagnostics::SystemDiagnostics::mesh_memory_budget(&adapter_info);
 
         let target_buffer_size: u64 = 8 * 1024 * 1024 * 1024;
         let mut limits = adapter.limits();
@@ -162,16 +738,12 @@ impl<'a> Renderer<'a> {
 let size = window.inner_size();
         let mut config = surface.get_default_config(&adapter, size.width, size.height).unwrap();
 
-        let available_present_modes = surface.get_capabilities(&adapter).present_modes;
-
-        config.present_mode = [
-            // presentation preference order.
-            PresentMode::Immediate,
-            PresentMode::Mailbox,
-        ]
-        .into_iter()
-        .find(|&mode| available_present_modes.contains(&mode))
-        .unwrap_or(PresentMode::Fifo);
+        let caps = surface.get_capabilities(&adapter);
+        config.present_mode = Self::pick_present_mode(&caps.present_modes, false);
+        // /record copies frames out of the surface, where the surface allows it
+        if caps.usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            config.usage |= wgpu::TextureUsages::COPY_SRC;
+        }
         
         surface.configure(&device, &config);
 
@@ -182,18 +754,8 @@ let size = window.inner_size();
         let text_renderer = GlyphRenderer::new(&mut text_atlas, &device, wgpu::MultisampleState::default(), None);
         let text_viewport = surface.get_current_texture().unwrap().texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let shadow_size = 4096; 
-        let shadow_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Shadow Map"),
-            size: wgpu::Extent3d { width: shadow_size, height: shadow_size, depth_or_array_layers: 1 },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth32Float,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-            view_formats: &[],
-        });
-        let shadow_view = shadow_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let shadow_size = DEFAULT_SHADOW_SIZE; 
+        let (shadow_texture, shadow_view) = Self::mk_shadow_map(&device, shadow_size);
         
         let shadow_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("Shadow Sampler"),
@@ -229,11 +791,28 @@ let size = window.inner_size();
                     visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
                     count: None,
+                },
+                // 3: reflection probe
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture { sample_type: wgpu::TextureSampleType::Float { filterable: true }, view_dimension: wgpu::TextureViewDimension::Cube, multisampled: false },
+                    count: None,
+                },
+                // 4: probe sampler
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
                 }
             ],
             label: Some("global_layout"),
         });
 
+        // --- REFLECTION PROBE ---
+        let probe = ReflectionProbe::new(&device, &config, &global_layout, &shadow_view, &shadow_sampler);
+
         let local_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[wgpu::BindGroupLayoutEntry { 
                 binding: 0, 
@@ -247,7 +826,7 @@ let size = window.inner_size();
         // --- BUFFERS ---
         let global_buf = device.create_buffer(&wgpu::BufferDescriptor { 
             label: Some("Global Uniform"), 
-            size: 160, 
+            size: std::mem::size_of::<GlobalUniform>() as u64,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST, 
             mapped_at_creation: false 
         });
@@ -258,15 +837,36 @@ let size = window.inner_size();
                 wgpu::BindGroupEntry { binding: 0, resource: global_buf.as_entire_binding() },
                 wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&shadow_view) },
                 wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&shadow_sampler) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&probe.cube_view) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::Sampler(&probe.sampler) },
             ], 
             label: None 
         });
 
+        let global_buf_right = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Global Uniform Right Eye"),
+            size: std::mem::size_of::<GlobalUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+
+        let global_bind_right = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &global_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: global_buf_right.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&shadow_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&shadow_sampler) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&probe.cube_view) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::Sampler(&probe.sampler) },
+            ],
+            label: None
+        });
+
         // --- SHADOW PASS RESOURCES ---
         // shadow uniform buffer
         let shadow_global_buf = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Shadow Global Uniform"),
-            size: 160,
+            size: std::mem::size_of::<GlobalUniform>() as u64,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
@@ -292,6 +892,8 @@ let size = window.inner_size();
                 wgpu::BindGroupEntry { binding: 0, resource: shadow_global_buf.as_entire_binding() },
                 wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&dummy_depth_view) },
                 wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&shadow_sampler) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&probe.cube_view) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::Sampler(&probe.sampler) },
             ],
         });
 
@@ -309,6 +911,44 @@ let size = window.inner_size();
             label: Some("Console I"), size: 1024, usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
         });
 
+        let console_local_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Console Uniform"),
+            contents: bytemuck::cast_slice(&[default_local]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST
+        });
+        let console_local_bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &local_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: console_local_buf.as_entire_binding() }],
+            label: None
+        });
+
+        // F1 help panel
+        let help_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Help V"), size: 1024, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let help_i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Help I"), contents: bytemuck::cast_slice(&[0u32, 2, 1, 1, 2, 3]), usage: wgpu::BufferUsages::INDEX
+        });
+        let menu_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Settings Menu V"), size: 1024, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+
+        let bar_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Bar V"), size: (MAX_BARS * 8 * std::mem::size_of::<Vertex>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let bar_inds: Vec<u32> = (0..MAX_BARS as u32 * 2).flat_map(|q| [0, 2, 1, 1, 2, 3].map(|i| q * 4 + i)).collect();
+        let bar_i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Bar I"), contents: bytemuck::cast_slice(&bar_inds), usage: wgpu::BufferUsages::INDEX
+        });
+        // a frame and a swatch per slot
+        let hotbar_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Hotbar V"), size: (HOTBAR_SLOTS * 8 * std::mem::size_of::<Vertex>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let hotbar_inds: Vec<u32> = (0..HOTBAR_SLOTS as u32 * 2).flat_map(|q| [0, 2, 1, 1, 2, 3].map(|i| q * 4 + i)).collect();
+        let hotbar_i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Hotbar I"), contents: bytemuck::cast_slice(&hotbar_inds), usage: wgpu::BufferUsages::INDEX
+        });
+
         let local_buf_identity = device.create_buffer_init(&wgpu::util::BufferInitDescriptor { 
             label: Some("Identity Uniform"), 
             contents: bytemuck::cast_slice(&[default_local]), 
@@ -346,61 +986,71 @@ let size = window.inner_size();
         });
 
         // --- PIPELINES ---
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor { label: None, source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()) });
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor { label: None, bind_group_layouts: &[&global_layout, &local_layout], push_constant_ranges: &[] });
 
-        let pipeline_shadow = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Shadow Pipeline"),
-            layout: Some(&layout),
-            vertex: wgpu::VertexState { module: &shader, entry_point: "vs_main", buffers: &[wgpu::VertexBufferLayout { array_stride: std::mem::size_of::<Vertex>() as _, step_mode: wgpu::VertexStepMode::Vertex, attributes: &[wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 0, shader_location: 0 }, wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 12, shader_location: 1 }, wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 24, shader_location: 2 }] }]},
-            fragment: None, 
-            primitive: wgpu::PrimitiveState { topology: wgpu::PrimitiveTopology::TriangleList, cull_mode: Some(wgpu::Face::Front), ..Default::default() }, 
-            depth_stencil: Some(wgpu::DepthStencilState { format: wgpu::TextureFormat::Depth32Float, depth_write_enabled: true, depth_compare: wgpu::CompareFunction::Less, stencil: Default::default(), bias: wgpu::DepthBiasState { constant: 2, slope_scale: 2.0, clamp: 0.0 } }),
-            multisample: Default::default(), multiview: None,
+        let screen_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture { sample_type: wgpu::TextureSampleType::Float { filterable: true }, view_dimension: wgpu::TextureViewDimension::D2, multisampled: false },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("screen_layout"),
         });
-
-        let pipeline_fill = Self::create_pipeline(&device, &config, &layout, &shader, wgpu::PrimitiveTopology::TriangleList, false);
-        let pipeline_wire = Self::create_pipeline(&device, &config, &layout, &shader, wgpu::PrimitiveTopology::TriangleList, true);
-        let pipeline_line = Self::create_pipeline(&device, &config, &layout, &shader, wgpu::PrimitiveTopology::LineList, false);
-        let depth = Self::mk_depth(&device, &config);
-
-        // --- UI PIPELINE ---
-        let pipeline_ui = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("UI Pipeline"),
-            layout: Some(&layout),
-            vertex: wgpu::VertexState { module: &shader, entry_point: "vs_main", buffers: &[wgpu::VertexBufferLayout { array_stride: std::mem::size_of::<Vertex>() as _, step_mode: wgpu::VertexStepMode::Vertex, attributes: &[wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 0, shader_location: 0 }, wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 12, shader_location: 1 }, wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 24, shader_location: 2 }] }]},
-            fragment: Some(wgpu::FragmentState { 
-                module: &shader, 
-                entry_point: "fs_main", 
-                targets: &[Some(wgpu::ColorTargetState { 
-                    format: config.format, 
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL 
-                })] 
-            }),
-            primitive: wgpu::PrimitiveState { topology: wgpu::PrimitiveTopology::TriangleList, ..Default::default() },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: false,
-                depth_compare: wgpu::CompareFunction::Always,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: Default::default(), multiview: None,
+        let screen_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor { label: None, bind_group_layouts: &[&global_layout, &screen_layout], push_constant_ranges: &[] });
+        let pipelines = PipelineCache::new(&device, config.format, layout, screen_pipeline_layout);
+        let screen_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Screen Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let screen_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Screen V"), size: (MAX_SCREENS * 4 * std::mem::size_of::<Vertex>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
         });
+        let screen_i_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Screen I"), size: (MAX_SCREENS * 6 * 4) as u64, usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let sign_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sign V"), size: (MAX_SIGNS * 4 * std::mem::size_of::<Vertex>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let sign_i_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sign I"), size: (MAX_SIGNS * 6 * 4) as u64, usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let depth = Self::mk_depth(&device, &config);
 
         // --- MESHES ---
         let (pv, pi) = MeshGen::generate_cylinder(0.4, 1.8, 16);
         let player_v_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&pv), usage: wgpu::BufferUsages::VERTEX });
         let player_i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&pi), usage: wgpu::BufferUsages::INDEX });
+        let remote_players = InstancedMesh::new(&device, "Remote Players", &(pv.clone(), pi.clone()), MAX_REMOTE_PLAYERS);
 
         let (gv, gi) = MeshGen::generate_sphere_guide(1.0, 64);
         let guide_v_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&gv), usage: wgpu::BufferUsages::VERTEX });
         let guide_i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&gi), usage: wgpu::BufferUsages::INDEX });
 
-        let (cv, ci) = MeshGen::generate_crosshair();
-        let cross_v_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&cv), usage: wgpu::BufferUsages::VERTEX });
-        let cross_i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&ci), usage: wgpu::BufferUsages::INDEX });
+        // rebuilt every frame from the crosshair settings
+        let cross_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Crosshair V"), size: 1024, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let cross_i_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Crosshair I"), size: 1024, usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+
+        let touch_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Touch V"), size: 8192, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let touch_i_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Touch I"), size: 2048, usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
 
         let cursor_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Cursor V"), size: 4096, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
@@ -411,6 +1061,118 @@ let size = window.inner_size();
 
 
 
+        let grid_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Grid V"), size: 16384, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let grid_i_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Grid I"), size: 8192, usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+
+        let select_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Selection V"), size: 8192, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let select_i_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Selection I"), size: 4096, usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let ghost_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Ghost V"), size: (GHOST_MAX_BLOCKS * 24 * std::mem::size_of::<Vertex>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let ghost_i_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Ghost I"), size: (GHOST_MAX_BLOCKS * 36 * 4) as u64, usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let blueprint_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Blueprint V"), size: ((GHOST_MAX_BLOCKS * 24 + 96) * std::mem::size_of::<Vertex>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let blueprint_i_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Blueprint I"), size: ((GHOST_MAX_BLOCKS * 36 + 288) * 4) as u64, usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let mob_meshes = [
+            MeshGen::generate_box(Vec3::splat(MOB_RADIUS), [1.0; 3]),
+            MeshGen::generate_sphere_guide(MOB_RADIUS, BALL_SEGMENTS),
+            MeshGen::generate_dart(BIRD_SIZE.0, BIRD_SIZE.1, [1.0; 3]),
+            MeshGen::generate_box(MobKind::Bike.half_size(), [1.0; 3]),
+            MeshGen::generate_box(MobKind::Villager.half_size(), [1.0; 3]),
+            MeshGen::generate_box(MobKind::Lurker.half_size(), [1.0; 3]),
+        ].map(|model| InstancedMesh::new(&device, "Mobs", &model, MAX_MOBS));
+        let rope_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Rope V"), size: (2 * std::mem::size_of::<Vertex>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let rope_i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Rope I"), contents: bytemuck::cast_slice(&[0u32, 1]), usage: wgpu::BufferUsages::INDEX
+        });
+        let spark_verts = MAX_TELEPORTER_SPARKS * SPARKS as usize * 2;
+        let spark_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Spark V"), size: (spark_verts * std::mem::size_of::<Vertex>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let spark_i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Spark I"), contents: bytemuck::cast_slice(&(0..spark_verts as u32).collect::<Vec<u32>>()), usage: wgpu::BufferUsages::INDEX
+        });
+        let beacon_verts = MAX_BEACONS * 6;
+        let beacon_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Beacon V"), size: (beacon_verts * std::mem::size_of::<Vertex>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let beacon_i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Beacon I"), contents: bytemuck::cast_slice(&(0..beacon_verts as u32).collect::<Vec<u32>>()), usage: wgpu::BufferUsages::INDEX
+        });
+        let gizmo_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Gizmo V"), size: (16 * std::mem::size_of::<Vertex>()) as u64, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
+        });
+        let gizmo_inds: Vec<u32> = [0, 8].iter().flat_map(|base| BOX_EDGES.iter().flat_map(move |(a, b)| [base + a, base + b])).collect();
+        let gizmo_i_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Gizmo I"), contents: bytemuck::cast_slice(&gizmo_inds), usage: wgpu::BufferUsages::INDEX
+        });
+        let fade_normal = [0.0, 0.0, 1.0];
+        let fade_v_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Fade V"),
+            contents: bytemuck::cast_slice(&[[-1.0, 1.0], [1.0, 1.0], [-1.0, -1.0], [1.0, -1.0]].map(|[x, y]| Vertex { pos: [x, y, 0.0], color: [0.0; 3], normal: fade_normal })),
+            usage: wgpu::BufferUsages::VERTEX
+        });
+        let flash_v_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Flash V"),
+            contents: bytemuck::cast_slice(&[[-1.0, 1.0], [1.0, 1.0], [-1.0, -1.0], [1.0, -1.0]].map(|[x, y]| Vertex { pos: [x, y, 0.0], color: FLASH_COLOR, normal: fade_normal })),
+            usage: wgpu::BufferUsages::VERTEX
+        });
+        let flash_local_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Flash Uniform"),
+            contents: bytemuck::cast_slice(&[default_local]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST
+        });
+        let flash_local_bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &local_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: flash_local_buf.as_entire_binding() }],
+            label: None
+        });
+        let fade_local_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Fade Uniform"),
+            contents: bytemuck::cast_slice(&[default_local]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST
+        });
+        let fade_local_bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &local_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: fade_local_buf.as_entire_binding() }],
+            label: None
+        });
+        let sky_local_bufs = ["Sky Uniform", "Sky Uniform Right"].map(|label| device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: bytemuck::cast_slice(&[default_local]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST
+        }));
+        let sky_local_binds = [0, 1].map(|eye| device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &local_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: sky_local_bufs[eye].as_entire_binding() }],
+            label: None
+        }));
+        let ghost_local_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ghost Uniform"),
+            contents: bytemuck::cast_slice(&[LocalUniform { model: identity_mat.to_cols_array(), params: [GHOST_OPACITY, 0.0, 1.0, 0.0] }]),
+            usage: wgpu::BufferUsages::UNIFORM
+        });
+        let ghost_local_bind = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &local_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: ghost_local_buf.as_entire_binding() }],
+            label: None
+        });
+
         let collision_v_buf = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Collision V"), size: 65536, usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST, mapped_at_creation: false
         });
@@ -428,6 +1190,7 @@ let size = window.inner_size();
             light_view_proj: identity_mat.to_cols_array(),
             cam_pos: [0.0, 0.0, 0.0, 0.0],
             sun_dir: [0.0, 1.0, 0.0, 0.0],
+            fog: [0.0; 4],
         };
         
         let global_buf_identity = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -442,19 +1205,24 @@ let size = window.inner_size();
                 wgpu::BindGroupEntry { binding: 0, resource: global_buf_identity.as_entire_binding() },
                 wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&shadow_view) },
                 wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&shadow_sampler) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&probe.cube_view) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::Sampler(&probe.sampler) },
             ],
             label: Some("Identity Bind Group"), 
         });
 
         let (mesh_tx, mesh_rx) = channel(); 
         let (lod_tx, lod_rx) = channel();
+        let (profile_tx, profile_rx) = channel();
 
         Self { 
-            window, surface, device, queue, config, 
-            pipeline_fill, pipeline_wire, pipeline_line,
+            window, surface, instance, adapter, device, queue, config, 
+            pipelines,
             chunks: HashMap::new(), 
             lod_chunks: HashMap::new(),
+            lod_batches: LodBatches::new(),
             global_buf, global_bind, 
+            global_buf_right, global_bind_right,
             local_buf_identity, local_bind_identity,
             local_buf_player, local_bind_player,
             local_buf_guide, local_bind_guide,
@@ -468,119 +1236,662 @@ let size = window.inner_size();
             text_viewport,
             shadow_view,
             shadow_sampler,
-            pipeline_shadow,
+            shadow_size,
+            probe,
             shadow_global_buf,
             shadow_global_bind,
             collision_v_buf, collision_i_buf, collision_inds: 0,
+            grid_v_buf, grid_i_buf, grid_inds: 0,
+            select_v_buf, select_i_buf, select_inds: 0,
+            ghost_v_buf, ghost_i_buf, ghost_inds: 0, ghost_local_bind,
+            blueprint_v_buf, blueprint_i_buf, blueprint_inds: 0, blueprint_label: None,
+            mob_meshes,
+            rope_v_buf, rope_i_buf, rope_drawn: false,
+            spark_v_buf, spark_i_buf, spark_inds: 0, spark_clock: std::time::Instant::now(),
+            beacon_v_buf, beacon_i_buf, beacon_inds: 0,
+            fade_v_buf, fade_local_buf, fade_local_bind, fade_drawn: false,
+            sky_local_bufs, sky_local_binds,
+            flash_v_buf, flash_local_buf, flash_local_bind, flash_drawn: false,
+            prefab_label: None, interact_label: None, radial_items: Vec::new(),
+            hotbar: ([None; HOTBAR_SLOTS], 0), hotbar_v_buf, hotbar_i_buf, hotbar_inds: 0,
             frozen_frustum: None,
+            view_frustum: None,
+            render_mode: RenderMode::default(),
+            gizmo_v_buf, gizmo_i_buf,
+            debug_view: None,
+            screen_layout, screen_sampler,
+            camera_targets: Vec::new(), next_camera: 0,
+            still: None, still_request: None, still_frame: None,
+            frame_request: false, frame_readback: None, recorded_frame: None,
+            screen_v_buf, screen_i_buf, screens: Vec::new(),
+            sign_textures: HashMap::new(), sign_v_buf, sign_i_buf, sign_order: Vec::new(), sign_revision: None,
             player_v_buf, player_i_buf, player_inds: pi.len() as u32,
-            pipeline_ui,
-            console_v_buf,
+            remote_players, remote_tags: Vec::new(), marker_tags: Vec::new(),
+            console_v_buf, console_local_buf, console_local_bind,
             console_i_buf,
             console_inds: 0,
+            help_v_buf,
+            help_i_buf,
+            menu_v_buf,
+            menu: None,
+            toasts: Vec::new(),
+            bar_v_buf,
+            bar_i_buf,
+            bar_inds: 0,
             guide_v_buf, guide_i_buf, guide_inds: gi.len() as u32,
-            cross_v_buf, cross_i_buf, cross_inds: ci.len() as u32,
+            cross_v_buf, cross_i_buf, cross_inds: 0,
+            touch_v_buf, touch_i_buf, touch_inds: 0,
             global_bind_identity,
+            global_layout,
+            global_buf_identity,
+            adapter_info,
+            present_modes: caps.present_modes,
+            vsync: false,
+            fog: [0.0; 4],
             cursor_v_buf, cursor_i_buf, cursor_inds: 0,
             animator: LodAnimator::new(),
             local_layout,
             load_queue: Vec::new(),
+            view_distance: ViewDistance::default(),
+            mesh_budget,
+            throttled: false,
+            memory_scale: 1.0,
             player_chunk_pos: None,
-            mesh_tx,
+            mesh_pool: MeshPool::new(mesh_tx, lod_tx, profile_tx),
             mesh_rx,
             pending_chunks: HashSet::new(),
-            lod_tx,
+            remesh: HashSet::new(),
+            remeshing: HashSet::new(),
             lod_rx,
+            profile_rx,
+            mesh_profile: MeshProfile::new(),
             pending_lods: HashSet::new(),
+            prefetching: HashMap::new(),
+            prefetched: HashMap::new(),
             
+            rendered: (0, 0),
             last_fps_time: std::time::Instant::now(),
             frame_count: 0,
             current_fps: 0,
         }
     }
 
-    fn create_pipeline(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, layout: &wgpu::PipelineLayout, shader: &wgpu::ShaderModule, topology: wgpu::PrimitiveTopology, wireframe: bool) -> wgpu::RenderPipeline {
-        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: None, layout: Some(layout),
-            vertex: wgpu::VertexState { module: shader, entry_point: "vs_main", buffers: &[wgpu::VertexBufferLayout { array_stride: std::mem::size_of::<Vertex>() as _, step_mode: wgpu::VertexStepMode::Vertex, attributes: &[wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 0, shader_location: 0 }, wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 12, shader_location: 1 }, wgpu::VertexAttribute { format: wgpu::VertexFormat::Float32x3, offset: 24, shader_location: 2 }] }]},
-            fragment: Some(wgpu::FragmentState { module: shader, entry_point: "fs_main", targets: &[Some(config.format.into())] }),
-            primitive: wgpu::PrimitiveState { 
-                topology, 
-                cull_mode: None, 
-                polygon_mode: if wireframe { wgpu::PolygonMode::Line } else { wgpu::PolygonMode::Fill }, 
-                ..Default::default() 
-            },
-            depth_stencil: Some(wgpu::DepthStencilState { format: wgpu::TextureFormat::Depth32Float, depth_write_enabled: true, depth_compare: wgpu::CompareFunction::Less, stencil: Default::default(), bias: Default::default() }),
-            multisample: Default::default(), multiview: None,
-        })
-    }
-
     fn mk_depth(dev: &wgpu::Device, cfg: &wgpu::SurfaceConfiguration) -> wgpu::TextureView {
         dev.create_texture(&wgpu::TextureDescriptor { size: wgpu::Extent3d { width: cfg.width, height: cfg.height, depth_or_array_layers: 1 }, mip_level_count: 1, sample_count: 1, dimension: wgpu::TextureDimension::D2, format: wgpu::TextureFormat::Depth32Float, usage: wgpu::TextureUsages::RENDER_ATTACHMENT, label: None, view_formats: &[] }).create_view(&wgpu::TextureViewDescriptor::default())
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) {
-        self.config.width = width;
-        self.config.height = height;
-        self.surface.configure(&self.device, &self.config);
-        self.depth = Self::mk_depth(&self.device, &self.config);
+    fn mk_shadow_map(dev: &wgpu::Device, size: u32) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = dev.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Map"),
+            size: wgpu::Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
     }
 
-    pub fn update_console_mesh(&mut self, t: f32) {
-        if t <= 0.001 {
-            self.console_inds = 0;
-            return;
-        }
-
-        let height = t * 1.0; 
-        let bottom_y = 1.0 - height;
-
-        let color = [0.1, 0.1, 0.15]; 
-        let normal = [0.0, 0.0, 1.0];
-
-        let verts = vec![
-            Vertex { pos: [-1.0, 1.0, 0.0], color, normal },      
-            Vertex { pos: [ 1.0, 1.0, 0.0], color, normal },      
-            Vertex { pos: [-1.0, bottom_y, 0.0], color, normal }, 
-            Vertex { pos: [ 1.0, bottom_y, 0.0], color, normal }, 
-        ];
+    // vsync waits for the display, otherwise the lowest latency mode the surface has
+    fn pick_present_mode(available: &[PresentMode], vsync: bool) -> PresentMode {
+        if vsync { return PresentMode::Fifo; }
+        [
+            // presentation preference order.
+            PresentMode::Immediate,
+            PresentMode::Mailbox,
+        ]
+        .into_iter()
+        .find(|mode| available.contains(mode))
+        .unwrap_or(PresentMode::Fifo)
+    }
 
-        let inds = vec![0, 2, 1, 1, 2, 3];
+    // applies the shadow and vsync settings, rebuilding only what changed
+    pub fn apply_graphics(&mut self, settings: &Settings) {
+        self.pipelines.set_features(&self.device, ShaderFeatures { shadows: settings.shadows });
+        let [r, g, b] = settings.fog_color;
+        self.fog = [r, g, b, settings.fog_density];
+        if settings.vsync != self.vsync {
+            self.vsync = settings.vsync;
+            self.config.present_mode = Self::pick_present_mode(&self.present_modes, self.vsync);
+            self.surface.configure(&self.device, &self.config);
+        }
 
-        self.queue.write_buffer(&self.console_v_buf, 0, bytemuck::cast_slice(&verts));
-        self.queue.write_buffer(&self.console_i_buf, 0, bytemuck::cast_slice(&inds));
-        self.console_inds = inds.len() as u32;
+        let size = settings.shadow_resolution.min(self.device.limits().max_texture_dimension_2d);
+        if size == self.shadow_size { return; }
+        let (texture, view) = Self::mk_shadow_map(&self.device, size);
+        self.shadow_texture = texture;
+        self.shadow_view = view;
+        self.shadow_size = size;
+        self.global_bind = self.mk_global_bind(&self.global_buf);
+        self.global_bind_right = self.mk_global_bind(&self.global_buf_right);
+        self.global_bind_identity = self.mk_global_bind(&self.global_buf_identity);
+        self.probe.global_bind = self.mk_global_bind(&self.probe.global_buf);
+        if let Some(debug) = &self.debug_view {
+            let bind = self.mk_global_bind(&debug.global_buf);
+            self.debug_view.as_mut().unwrap().global_bind = bind;
+        }
+        let binds: Vec<_> = self.camera_targets.iter().map(|t| self.mk_global_bind(&t.global_buf)).collect();
+        for (target, bind) in self.camera_targets.iter_mut().zip(binds) {
+            target.global_bind = bind;
+        }
     }
 
-    pub fn update_view(&mut self, player_pos: Vec3, planet: &PlanetData) {
-        let res = planet.resolution;        
-        let player_id = CoordSystem::pos_to_id(player_pos, res);
-        let mut upload_count = 0;
-        while let Ok((key, v, i)) = self.lod_rx.try_recv() {
-            self.pending_lods.remove(&key);
-            self.upload_lod_buffer(key, v, i);
-            upload_count += 1;
-            if upload_count > 20 { break; }
+    // brings the camera textures and screen quads in line with `monitors` after it changed
+    pub fn update_monitors(&mut self, monitors: &Monitors) {
+        self.camera_targets.truncate(monitors.cameras.len());
+        while self.camera_targets.len() < monitors.cameras.len() {
+            let target = self.mk_camera_target();
+            self.camera_targets.push(target);
+        }
+        let aspect = MONITOR_WIDTH as f32 / MONITOR_HEIGHT as f32;
+        for (target, camera) in self.camera_targets.iter_mut().zip(&monitors.cameras) {
+            target.view_proj = camera.view_proj(aspect);
+            target.eye = camera.eye;
         }
-        let mut required_voxels: HashSet<ChunkKey> = HashSet::new();
-        let mut required_lods: HashSet<LodKey> = HashSet::new();
-        let logical_size = res.next_power_of_two();
 
-        for face in 0..6 {
-            self.process_quadtree(
-                face, 0, 0, logical_size, 
-                player_pos, planet, 
-                player_id, 
-                &mut required_voxels, 
-                &mut required_lods
-            );
+        let mut verts = Vec::new();
+        let mut inds = Vec::new();
+        self.screens.clear();
+        for screen in &monitors.screens {
+            let base = verts.len() as u32;
+            let uvs = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+            for (p, [u, v]) in screen.corners().into_iter().zip(uvs) {
+                verts.push(Vertex { pos: p.to_array(), color: [u, v, 0.0], normal: [0.0; 3] });
+            }
+            inds.extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
+            self.screens.push(screen.camera);
         }
+        self.queue.write_buffer(&self.screen_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.queue.write_buffer(&self.screen_i_buf, 0, bytemuck::cast_slice(&inds));
+    }
 
-        let missing_voxels: Vec<ChunkKey> = required_voxels.iter()
-            .filter(|k| !self.chunks.contains_key(k))
-            .cloned()
+    // rebuilds the sign quads when a block entity changed, redrawing only the signs whose text is new
+    pub fn update_signs(&mut self, planet: &PlanetData) {
+        if self.sign_revision == Some(planet.entity_revision) { return; }
+        self.sign_revision = Some(planet.entity_revision);
+
+        let signs: Vec<(BlockId, String, u8)> = planet.entities()
+            .filter_map(|(id, entity)| match entity {
+                BlockEntity::Sign { text, facing } => Some((*id, text.clone(), *facing)),
+                _ => None,
+            })
+            .take(MAX_SIGNS)
             .collect();
-
+        self.sign_textures.retain(|id, _| signs.iter().any(|(s, _, _)| s == id));
+
+        let mut verts = Vec::new();
+        let mut inds = Vec::new();
+        self.sign_order.clear();
+        for (id, text, facing) in signs {
+            if self.sign_textures.get(&id).is_none_or(|(shown, _)| *shown != text) {
+                let bind = self.draw_sign_text(&text);
+                self.sign_textures.insert(id, (text, bind));
+            }
+            let base = verts.len() as u32;
+            for (p, [u, v]) in sign::quad(id, planet.resolution, facing) {
+                verts.push(Vertex { pos: p.to_array(), color: [u, v, 0.0], normal: [0.0; 3] });
+            }
+            inds.extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
+            self.sign_order.push(id);
+        }
+        self.queue.write_buffer(&self.sign_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.queue.write_buffer(&self.sign_i_buf, 0, bytemuck::cast_slice(&inds));
+    }
+
+    // draws `text` into a fresh texture with glyphon. submits right away,
+    // the text renderer only holds one batch of glyphs and the next sign (or the hud) would overwrite it
+    fn draw_sign_text(&mut self, text: &str) -> wgpu::BindGroup {
+        let size = SIGN_TEXTURE_SIZE as f32;
+        let margin = 16.0;
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Sign"),
+            size: wgpu::Extent3d { width: SIGN_TEXTURE_SIZE, height: SIGN_TEXTURE_SIZE, depth_or_array_layers: 1 },
+            mip_level_count: 1, sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut buffer = Buffer::new(&mut self.font_system, Metrics::new(36.0, 42.0));
+        buffer.set_size(&mut self.font_system, size - margin * 2.0, size - margin * 2.0);
+        buffer.set_text(&mut self.font_system, text, Attrs::new().family(Family::SansSerif), Shaping::Advanced);
+        for line in buffer.lines.iter_mut() {
+            line.set_align(Some(glyphon::cosmic_text::Align::Center));
+        }
+        buffer.shape_until_scroll(&mut self.font_system);
+
+        let area = TextArea {
+            buffer: &buffer,
+            left: margin,
+            top: margin,
+            scale: 1.0,
+            bounds: TextBounds { left: 0, top: 0, right: SIGN_TEXTURE_SIZE as i32, bottom: SIGN_TEXTURE_SIZE as i32 },
+            default_color: glyphon::Color::rgb(30, 20, 10),
+        };
+        let prepared = self.text_renderer.prepare(
+            &self.device, &self.queue, &mut self.font_system, &mut self.text_atlas,
+            Resolution { width: SIGN_TEXTURE_SIZE, height: SIGN_TEXTURE_SIZE },
+            [area], &mut self.swash_cache,
+        );
+
+        let mut enc = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Sign Text") });
+        {
+            let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Sign Text Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(SIGN_BACKGROUND), store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None, occlusion_query_set: None,
+            });
+            // a full atlas just leaves the sign blank, it's not worth failing the frame over
+            if prepared.is_ok() {
+                let _ = self.text_renderer.render(&self.text_atlas, &mut pass);
+            }
+        }
+        self.queue.submit(std::iter::once(enc.finish()));
+
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.screen_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.screen_sampler) },
+            ],
+            label: None,
+        })
+    }
+
+    fn mk_camera_target(&self) -> CameraTarget {
+        let size = wgpu::Extent3d { width: MONITOR_WIDTH, height: MONITOR_HEIGHT, depth_or_array_layers: 1 };
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Monitor Camera"),
+            size, mip_level_count: 1, sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_config = wgpu::SurfaceConfiguration { width: MONITOR_WIDTH, height: MONITOR_HEIGHT, ..self.config.clone() };
+        let depth = Self::mk_depth(&self.device, &depth_config);
+        let global_buf = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Monitor Camera Uniform"),
+            size: std::mem::size_of::<GlobalUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+        let global_bind = self.mk_global_bind(&global_buf);
+        let screen_bind = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.screen_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.screen_sampler) },
+            ],
+            label: None,
+        });
+        CameraTarget { view, depth, view_proj: glam::Mat4::IDENTITY, eye: Vec3::ZERO, global_buf, global_bind, screen_bind }
+    }
+
+    fn mk_still_target(&self, width: u32, height: u32) -> StillTarget {
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Still"),
+            size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+            mip_level_count: 1, sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth = Self::mk_depth(&self.device, &wgpu::SurfaceConfiguration { width, height, ..self.config.clone() });
+        let global_buf = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Still Uniform"),
+            size: std::mem::size_of::<GlobalUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+        let global_bind = self.mk_global_bind(&global_buf);
+        StillTarget { texture, view, depth, global_buf, global_bind, readback: Readback::new(&self.device, width, height) }
+    }
+
+    // draws the world from `view_proj` into a `width` x `height` image with the next frame, take_still() hands it over
+    pub fn request_still(&mut self, view_proj: glam::Mat4, eye: Vec3, width: u32, height: u32) {
+        self.still_request = Some((view_proj, eye, width, height));
+    }
+
+    pub fn take_still(&mut self) -> Option<Frame> {
+        self.still_frame.take()
+    }
+
+    // whether /record can copy frames out of the window
+    pub fn can_record(&self) -> bool {
+        self.config.usage.contains(wgpu::TextureUsages::COPY_SRC)
+    }
+
+    // copies the next frame as shown, hud and all, take_frame() hands it over
+    pub fn request_frame(&mut self) {
+        self.frame_request = self.can_record();
+    }
+
+    pub fn take_frame(&mut self) -> Option<Frame> {
+        self.recorded_frame.take()
+    }
+
+    // what a view of the world draws: the lod batches, the lods not in one and the full-detail chunks
+    fn world_meshes(&self) -> impl Iterator<Item = &ChunkMesh> {
+        self.lod_chunks.iter()
+            .filter(|(key, _)| !self.lod_batches.is_batched(key))
+            .map(|(_, mesh)| mesh)
+            .chain(self.lod_batches.meshes())
+            .chain(self.chunks.values())
+    }
+
+    // the world and every player, for views other than the main one
+    fn draw_scene<'p>(&'p self, pass: &mut wgpu::RenderPass<'p>, global: &'p wgpu::BindGroup, frustum: &crate::common::Frustum) {
+        pass.set_pipeline(self.pipelines.get(FILL));
+        pass.set_bind_group(0, global, &[]);
+        for mesh in self.world_meshes() {
+            if frustum.intersects_obb(&mesh.bounds) {
+                pass.set_bind_group(1, &mesh.bind_group, &[]);
+                pass.set_vertex_buffer(0, mesh.v_buf.slice(..));
+                pass.set_index_buffer(mesh.i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..mesh.num_inds, 0, 0..1);
+            }
+        }
+        // other cameras see the player and everyone else, even in first person
+        pass.set_bind_group(1, &self.local_bind_player, &[]);
+        pass.set_vertex_buffer(0, self.player_v_buf.slice(..));
+        pass.set_index_buffer(self.player_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+        pass.draw_indexed(0..self.player_inds, 0, 0..1);
+        pass.set_pipeline(self.pipelines.get(instanced_key(false)));
+        pass.set_bind_group(1, &self.local_bind_identity, &[]);
+        self.remote_players.draw(pass);
+    }
+
+    // camera uniform plus the current shadow map
+    fn mk_global_bind(&self, buf: &wgpu::Buffer) -> wgpu::BindGroup {
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.global_layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: buf.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::TextureView(&self.shadow_view) },
+                wgpu::BindGroupEntry { binding: 2, resource: wgpu::BindingResource::Sampler(&self.shadow_sampler) },
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&self.probe.cube_view) },
+                wgpu::BindGroupEntry { binding: 4, resource: wgpu::BindingResource::Sampler(&self.probe.sampler) },
+            ],
+            label: None,
+        })
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.config.width = width;
+        self.config.height = height;
+        self.surface.configure(&self.device, &self.config);
+        self.depth = Self::mk_depth(&self.device, &self.config);
+    }
+
+    // renders the debug camera into `window` from now on
+    pub fn open_debug_view(&mut self, window: Arc<Window>, camera: DebugCamera) -> Result<(), String> {
+        let surface = self.instance.create_surface(window.clone()).map_err(|e| e.to_string())?;
+        // the pipelines are built for the main window's format, so the second surface has to take it too
+        if !surface.get_capabilities(&self.adapter).formats.contains(&self.config.format) {
+            return Err(tr!("debugview.bad_format", format!("{:?}", self.config.format)));
+        }
+        let size = window.inner_size();
+        let config = wgpu::SurfaceConfiguration {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            ..self.config.clone()
+        };
+        surface.configure(&self.device, &config);
+        let depth = Self::mk_depth(&self.device, &config);
+        let global_buf = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Debug View Uniform"),
+            size: std::mem::size_of::<GlobalUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+        let global_bind = self.mk_global_bind(&global_buf);
+        self.debug_view = Some(DebugView { window, camera, surface, config, depth, global_buf, global_bind });
+        Ok(())
+    }
+
+    pub fn resize_debug_view(&mut self, width: u32, height: u32) {
+        let Some(debug) = &mut self.debug_view else { return };
+        if width == 0 || height == 0 { return; }
+        debug.config.width = width;
+        debug.config.height = height;
+        debug.surface.configure(&self.device, &debug.config);
+        debug.depth = Self::mk_depth(&self.device, &debug.config);
+    }
+
+    pub fn update_console_mesh(&mut self, console: &Console, opacity: f32) {
+        let t = console.height_fraction;
+        if t <= 0.001 || opacity <= 0.0 {
+            self.console_inds = 0;
+            return;
+        }
+
+        let height = t * 1.0; 
+        let bottom_y = 1.0 - height;
+
+        let color = [0.1, 0.1, 0.15]; 
+        let normal = [0.0, 0.0, 1.0];
+
+        let mut verts = vec![
+            Vertex { pos: [-1.0, 1.0, 0.0], color, normal },      
+            Vertex { pos: [ 1.0, 1.0, 0.0], color, normal },      
+            Vertex { pos: [-1.0, bottom_y, 0.0], color, normal }, 
+            Vertex { pos: [ 1.0, bottom_y, 0.0], color, normal }, 
+        ];
+
+        let mut inds = vec![0, 2, 1, 1, 2, 3];
+
+        // a lighter band behind the lines picked for copying, cut off where the panel ends
+        if let Some((first, last)) = console.selected_lines() {
+            let (w, h) = (self.config.width as f32, self.config.height as f32);
+            let top = console.line_top(first, h).max(0.0);
+            let bottom = console.line_top(last, h) + CONSOLE_LINE_HEIGHT;
+            if bottom > top {
+                push_quad(&mut verts, (w, h), (0.0, top, w, bottom - top), [0.25, 0.3, 0.45]);
+                inds.extend([4, 6, 5, 5, 6, 7]);
+            }
+        }
+
+        self.queue.write_buffer(&self.console_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.queue.write_buffer(&self.console_i_buf, 0, bytemuck::cast_slice(&inds));
+        self.console_inds = inds.len() as u32;
+
+        // see-through background uses the same dithering as lod fades
+        let data = LocalUniform { model: glam::Mat4::IDENTITY.to_cols_array(), params: [opacity, 0.0, 1.0, 0.0] };
+        self.queue.write_buffer(&self.console_local_buf, 0, bytemuck::cast_slice(&[data]));
+    }
+
+    // health, stamina and, while short of breath, oxygen along the bottom of the screen, and the red flash of a hit
+    fn update_status_bars(&mut self, player: &Player, settings: &Settings) {
+        let flash = player.health.flash();
+        self.flash_drawn = flash > 0.0;
+        if self.flash_drawn {
+            let data = LocalUniform { model: glam::Mat4::IDENTITY.to_cols_array(), params: [flash, 0.0, 1.0, 0.0] };
+            self.queue.write_buffer(&self.flash_local_buf, 0, bytemuck::cast_slice(&[data]));
+        }
+
+        let mut bars = Vec::new();
+        if player.health.enabled && !player.spectator {
+            bars.push((player.health.current / MAX_HEALTH, HEALTH_COLOR));
+        }
+        if settings.stamina && !player.spectator {
+            bars.push((player.stamina.current / MAX_STAMINA, STAMINA_COLOR));
+        }
+        if player.oxygen.current < MAX_OXYGEN {
+            bars.push((player.oxygen.current / MAX_OXYGEN, OXYGEN_COLOR));
+        }
+        let (w, h) = (self.config.width as f32, self.config.height as f32);
+        let mut verts = Vec::with_capacity(bars.len() * 8);
+        for (i, (fraction, color)) in bars.iter().take(MAX_BARS).enumerate() {
+            let top = h - 60.0 - HOTBAR_SPACE - i as f32 * (BAR_HEIGHT + 6.0);
+            push_bar(&mut verts, (w, h), ((w - BAR_WIDTH) * 0.5, top, BAR_WIDTH, BAR_HEIGHT), *fraction, settings.palette.apply(*color));
+        }
+        self.queue.write_buffer(&self.bar_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.bar_inds = (verts.len() / 4 * 6) as u32;
+    }
+
+    fn text_color(c: [f32; 3]) -> glyphon::Color {
+        let [r, g, b] = c.map(|x| (x.clamp(0.0, 1.0) * 255.0) as u8);
+        glyphon::Color::rgb(r, g, b)
+    }
+
+    fn update_crosshair_mesh(&mut self, settings: &Settings) {
+        let (w, h) = (self.config.width as f32, self.config.height as f32);
+        if settings.crosshair_size <= 0.0 {
+            self.cross_inds = 0;
+            return;
+        }
+        let (verts, inds) = MeshGen::generate_crosshair(settings.crosshair_size, settings.crosshair_thickness, settings.crosshair_color, w, h);
+        self.queue.write_buffer(&self.cross_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.queue.write_buffer(&self.cross_i_buf, 0, bytemuck::cast_slice(&inds));
+        self.cross_inds = inds.len() as u32;
+    }
+
+    // joystick under the left thumb, only while it's held
+    fn update_touch_mesh(&mut self, controller: &Controller) {
+        let Some((origin, knob)) = controller.touch.stick() else {
+            self.touch_inds = 0;
+            return;
+        };
+        let (w, h) = (self.config.width as f32, self.config.height as f32);
+        let (verts, inds) = MeshGen::generate_joystick(origin, knob, crate::touch::STICK_RADIUS, w, h);
+        self.queue.write_buffer(&self.touch_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.queue.write_buffer(&self.touch_i_buf, 0, bytemuck::cast_slice(&inds));
+        self.touch_inds = inds.len() as u32;
+    }
+
+    // text of the F1 overlay, built from the keybind and command tables so it can't go stale
+    fn help_text(bindings: &Bindings) -> String {
+        let mut text = format!("{}\n", tr!("help.controls"));
+        for (keys, action) in KEYBINDS {
+            let keys = match keys {
+                Keys::Fixed(label) => label.to_string(),
+                Keys::Bound(actions) => bindings.describe(actions),
+            };
+            text.push_str(&format!("  {:<12} {}\n", keys, i18n::get(action)));
+        }
+        text.push_str(&format!("\n{}\n", tr!("help.commands")));
+        for command in cmd::commands() {
+            text.push_str(&format!("  {:<34} {}\n", command.usage, i18n::get(command.summary)));
+        }
+        text.push_str(&format!("\n{}", tr!("help.close")));
+        text
+    }
+
+    // panel behind the help text, in pixels
+    fn help_rect(&self) -> (f32, f32, f32, f32) {
+        let lines = (KEYBINDS.len() + cmd::commands().len() + 5) as f32;
+        let (w, h) = (self.config.width as f32, self.config.height as f32);
+        let panel_w = 640.0_f32.min(w - 20.0);
+        let panel_h = (lines * 18.0 + 30.0).min(h - 20.0);
+        let left = (w - panel_w) * 0.5;
+        let top = (h - panel_h) * 0.5;
+        (left, top, panel_w, panel_h)
+    }
+
+    fn update_help_mesh(&mut self) {
+        self.write_panel(&self.help_v_buf, self.help_rect());
+    }
+
+    // panel behind a menu: a title, a blank line, the rows, a blank line and the hint
+    fn menu_rect(&self, rows: usize) -> (f32, f32, f32, f32) {
+        let (w, h) = (self.config.width as f32, self.config.height as f32);
+        let panel_w = 420.0_f32.min(w - 20.0);
+        let panel_h = ((rows + 4) as f32 * MENU_LINE + 30.0).min(h - 20.0);
+        ((w - panel_w) * 0.5, (h - panel_h) * 0.5, panel_w, panel_h)
+    }
+
+    // the open menu, None while there's none
+    pub fn update_menu(&mut self, menu: Option<Menu>) {
+        if let Some(menu) = &menu {
+            self.write_panel(&self.menu_v_buf, self.menu_rect(menu.lines.len()));
+        }
+        self.menu = menu;
+    }
+
+    pub fn update_toasts(&mut self, toasts: &Toasts) {
+        self.toasts = toasts.visible();
+    }
+
+    // a dark quad over `rect`, in pixels, for a panel of text
+    fn write_panel(&self, v_buf: &wgpu::Buffer, (left, top, pw, ph): (f32, f32, f32, f32)) {
+        let (w, h) = (self.config.width as f32, self.config.height as f32);
+        let to_ndc = |x: f32, y: f32| [x / w * 2.0 - 1.0, 1.0 - y / h * 2.0, 0.0];
+
+        let color = [0.05, 0.05, 0.08];
+        let normal = [0.0, 0.0, 1.0];
+        let verts = vec![
+            Vertex { pos: to_ndc(left, top), color, normal },
+            Vertex { pos: to_ndc(left + pw, top), color, normal },
+            Vertex { pos: to_ndc(left, top + ph), color, normal },
+            Vertex { pos: to_ndc(left + pw, top + ph), color, normal },
+        ];
+        self.queue.write_buffer(v_buf, 0, bytemuck::cast_slice(&verts));
+    }
+
+    // `planet` is the published snapshot, the meshing threads keep it while they work
+    pub fn update_view(&mut self, player_pos: Vec3, velocity: Vec3, planet: &Arc<PlanetData>) {
+        let res = planet.resolution;        
+        let player_id = CoordSystem::pos_to_id(player_pos, res);
+        let mut upload_count = 0;
+        while let Ok((key, v, i)) = self.lod_rx.try_recv() {
+            if let Some(current) = self.prefetching.remove(&AnyKey::Lod(key)) {
+                if current { self.prefetched.insert(AnyKey::Lod(key), (v, i)); }
+                continue;
+            }
+            self.pending_lods.remove(&key);
+            self.upload_lod_buffer(key, v, i, &planet.terrain);
+            upload_count += 1;
+            if upload_count > 20 { break; }
+        }
+        let mut required_voxels: HashSet<ChunkKey> = HashSet::new();
+        let mut required_lods: HashSet<LodKey> = HashSet::new();
+        let logical_size = res.next_power_of_two();
+
+        for face in 0..6 {
+            self.process_quadtree(
+                face, 0, 0, logical_size, 
+                player_pos, planet, 
+                player_id, 
+                &mut required_voxels, 
+                &mut required_lods
+            );
+        }
+
+        // the player got to where a prefetch was for, its meshes go straight up. one still on the pool counts as
+        // pending, an out of date one is meshed again once it's landed and been dropped
+        for &key in &required_voxels {
+            if self.chunks.contains_key(&key) { continue; }
+            if let Some((v, i)) = self.prefetched.remove(&AnyKey::Voxel(key)) {
+                if !v.is_empty() { self.upload_chunk_buffers(key, v, i, res); }
+            }
+        }
+        for &key in &required_lods {
+            if self.lod_chunks.contains_key(&key) { continue; }
+            if let Some((v, i)) = self.prefetched.remove(&AnyKey::Lod(key)) {
+                self.upload_lod_buffer(key, v, i, &planet.terrain);
+            }
+        }
+
+        let missing_voxels: Vec<ChunkKey> = required_voxels.iter()
+            .filter(|k| !self.chunks.contains_key(k))
+            .cloned()
+            .collect();
+
         let current_lods: Vec<LodKey> = self.lod_chunks.keys().cloned().collect();
         
         for k in current_lods {
@@ -589,9 +1900,9 @@ let size = window.inner_size();
             let mut children_missing = false;
             for v_key in &missing_voxels {
                 if v_key.face != k.face { continue; }
-                let v_x = v_key.u_idx * CHUNK_SIZE as u32;
-                let v_y = v_key.v_idx * CHUNK_SIZE as u32;
-                let v_s = CHUNK_SIZE as u32;
+                let v_x = v_key.u_idx * CHUNK_SIZE;
+                let v_y = v_key.v_idx * CHUNK_SIZE;
+                let v_s = CHUNK_SIZE;
                 let overlap = k.x < v_x + v_s && k.x + k.size > v_x &&
                               k.y < v_y + v_s && k.y + k.size > v_y;
                 if overlap { children_missing = true; break; }
@@ -601,24 +1912,22 @@ let size = window.inner_size();
                 required_lods.insert(k);
             } else {
                 if let Some(mesh) = self.lod_chunks.remove(&k) {
+                    self.lod_batches.remove(k);
                     self.animator.retire(AnyKey::Lod(k), mesh);
                 }
             }
         }
 
-        let mut spawn_count = 0;
-        for key in required_lods {
-            if !self.lod_chunks.contains_key(&key) && !self.pending_lods.contains(&key) {
-                if spawn_count >= 8 { break; }
-                self.pending_lods.insert(key);
-                let tx = self.lod_tx.clone();
-                let p = planet.clone();
-                std::thread::spawn(move || {
-                    let (v, i) = MeshGen::generate_lod_mesh(key, &p);
-                    let _ = tx.send((key, v, i));
-                });
-                spawn_count += 1;
-            }
+        let lod_spawns = if self.throttled { THROTTLED_SPAWNS } else { 8 };
+        let mut missing_lods: Vec<(LodKey, f32)> = required_lods.into_iter()
+            .filter(|key| !self.lod_chunks.contains_key(key) && !self.pending_lods.contains(key) && !self.prefetching.contains_key(&AnyKey::Lod(*key)))
+            .map(|key| (key, self.stream_priority(planet.terrain.bounds(key.face, key.x, key.y, key.size), player_pos)))
+            .collect();
+        missing_lods.sort_by(|a, b| a.1.total_cmp(&b.1));
+        self.mesh_pool.view = self.effective_view();
+        for (key, priority) in missing_lods.into_iter().take(lod_spawns) {
+            if !self.mesh_pool.submit(AnyKey::Lod(key), priority, planet) { break; }
+            self.pending_lods.insert(key);
         }
 
         let current_voxels: Vec<ChunkKey> = self.chunks.keys().cloned().collect();
@@ -632,27 +1941,193 @@ let size = window.inner_size();
 
         self.load_queue.retain(|k| required_voxels.contains(k));
         for k in required_voxels {
-            if !self.chunks.contains_key(&k) && !self.load_queue.contains(&k) {
+            if !self.chunks.contains_key(&k) && !self.load_queue.contains(&k) && !self.prefetching.contains_key(&AnyKey::Voxel(k)) {
                 self.load_queue.push(k);
             }
         }
 
-        self.load_queue.sort_by(|a, b| {
-            let get_center = |k: &ChunkKey| -> glam::Vec3 {
-                let u = k.u_idx * CHUNK_SIZE + CHUNK_SIZE / 2;
-                let v = k.v_idx * CHUNK_SIZE + CHUNK_SIZE / 2;
-                let h = planet.resolution / 2; 
-                CoordSystem::get_vertex_pos(k.face, u, v, h, planet.resolution)
-            };
-            let da = get_center(a).distance_squared(player_pos);
-            let db = get_center(b).distance_squared(player_pos);
-            db.partial_cmp(&da).unwrap_or(std::cmp::Ordering::Equal)
-        });
+        // nearest last, it's popped first
+        let mut queue: Vec<(ChunkKey, f32)> = self.load_queue.iter()
+            .map(|k| (*k, self.stream_priority(chunk_bounds(k, planet), player_pos)))
+            .collect();
+        queue.sort_by(|a, b| b.1.total_cmp(&a.1));
+        self.load_queue = queue.into_iter().map(|(k, _)| k).collect();
 
+        self.submit_remeshes(planet);
         self.process_load_queue(player_pos, planet);
+        self.prefetch(player_pos, velocity, planet);
+    }
+
+    // meshes what the view will want PREFETCH_SECONDS from now if the player keeps going the way they are, with
+    // whatever room streaming leaves in the pool. meshes already built for somewhere the player has turned away
+    // from are dropped
+    fn prefetch(&mut self, player_pos: Vec3, velocity: Vec3, planet: &Arc<PlanetData>) {
+        if self.throttled || velocity.length() < PREFETCH_MIN_SPEED { return; }
+        let ahead = player_pos + velocity * PREFETCH_SECONDS;
+        let res = planet.resolution;
+        let (mut voxels, mut lods) = (HashSet::new(), HashSet::new());
+        for face in 0..6 {
+            self.process_quadtree(face, 0, 0, res.next_power_of_two(), ahead, planet, CoordSystem::pos_to_id(ahead, res), &mut voxels, &mut lods);
+        }
+        let wanted: HashSet<AnyKey> = voxels.into_iter().map(AnyKey::Voxel).chain(lods.into_iter().map(AnyKey::Lod)).collect();
+        self.prefetched.retain(|key, _| wanted.contains(key));
+        if !self.load_queue.is_empty() || !self.pending_chunks.is_empty() { return; }
+
+        let mut todo: Vec<(AnyKey, f32)> = wanted.into_iter()
+            .filter(|key| !self.prefetching.contains_key(key) && !self.prefetched.contains_key(key))
+            .filter(|key| match key {
+                AnyKey::Voxel(k) => !self.chunks.contains_key(k),
+                AnyKey::Lod(k) => !self.lod_chunks.contains_key(k) && !self.pending_lods.contains(k),
+            })
+            .map(|key| {
+                let (center, _) = match key {
+                    AnyKey::Voxel(k) => chunk_bounds(&k, planet),
+                    AnyKey::Lod(k) => planet.terrain.bounds(k.face, k.x, k.y, k.size),
+                };
+                (key, center.distance_squared(ahead))
+            })
+            .collect();
+        todo.sort_by(|a, b| a.1.total_cmp(&b.1));
+        for (key, d2) in todo.into_iter().take(PREFETCH_SPAWNS) {
+            if !self.mesh_pool.submit(key, PREFETCH_PRIORITY + d2, planet) { break; }
+            self.prefetching.insert(key, true);
+        }
+    }
+
+    // --- WARM-UP ---
+    // meshes the chunks nearest `player_pos` on every core before the first frame, with a loading bar, so a fresh
+    // spawn lands on ground that's already there instead of waiting for the streamer. returns how many were meshed
+    pub fn warm_up(&mut self, player_pos: Vec3, planet: &PlanetData) -> usize {
+        let res = planet.resolution;
+        let (mut voxels, mut lods) = (HashSet::new(), HashSet::new());
+        for face in 0..6 {
+            self.process_quadtree(face, 0, 0, res.next_power_of_two(), player_pos, planet, CoordSystem::pos_to_id(player_pos, res), &mut voxels, &mut lods);
+        }
+        let mut keys: Vec<ChunkKey> = voxels.into_iter().collect();
+        keys.sort_by(|a, b| chunk_center(a, res).distance_squared(player_pos).total_cmp(&chunk_center(b, res).distance_squared(player_pos)));
+        keys.truncate(WARM_UP_CHUNKS);
+
+        let total = keys.len();
+        let workers = std::thread::available_parallelism().map_or(4, |n| n.get()).min(total.max(1));
+        let next = std::sync::atomic::AtomicUsize::new(0);
+        let (tx, rx) = channel();
+        self.draw_loading(0, total);
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                let (tx, keys, next) = (tx.clone(), &keys, &next);
+                scope.spawn(move || {
+                    while let Some(&key) = keys.get(next.fetch_add(1, std::sync::atomic::Ordering::Relaxed)) {
+                        let started = std::time::Instant::now();
+                        let (v, i) = MeshGen::build_chunk(key, planet);
+                        let timing = MeshTiming::new(AnyKey::Voxel(key), started, v.len(), i.len(), false);
+                        if tx.send((key, v, i, timing)).is_err() { break; }
+                    }
+                });
+            }
+            drop(tx);
+            let mut drawn = std::time::Instant::now();
+            for (done, (key, v, i, timing)) in rx.into_iter().enumerate() {
+                self.mesh_profile.record(timing);
+                if !v.is_empty() {
+                    self.upload_chunk_buffers(key, v, i, planet.resolution);
+                    // there from the first frame, no fade in
+                    self.animator.spawning_chunks.remove(&AnyKey::Voxel(key));
+                }
+                if drawn.elapsed() >= LOADING_FRAME {
+                    self.draw_loading(done + 1, total);
+                    drawn = std::time::Instant::now();
+                }
+            }
+        });
+        total
+    }
+
+    // one frame of nothing but the loading bar and how far along it is
+    fn draw_loading(&mut self, done: usize, total: usize) {
+        let out = match self.surface.get_current_texture() { Ok(o) => o, _ => return };
+        let view = out.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let (w, h) = (self.config.width as f32, self.config.height as f32);
+        let bar_w = 400.0_f32.min(w - 40.0);
+        let (left, top) = ((w - bar_w) * 0.5, h * 0.5);
+        let mut verts = Vec::with_capacity(8);
+        push_bar(&mut verts, (w, h), (left, top, bar_w, 12.0), done as f32 / total.max(1) as f32, [0.3, 0.75, 0.35]);
+        self.queue.write_buffer(&self.bar_v_buf, 0, bytemuck::cast_slice(&verts));
+
+        let mut text = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+        text.set_size(&mut self.font_system, w, h);
+        text.set_text(&mut self.font_system, &tr!("world.warming_up", done, total), Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(220, 220, 220)), Shaping::Advanced);
+        let area = TextArea {
+            buffer: &text,
+            left,
+            top: top - 28.0,
+            scale: 1.0,
+            bounds: TextBounds { left: 0, top: 0, right: w as i32, bottom: h as i32 },
+            default_color: glyphon::Color::rgb(255, 255, 255),
+        };
+        let resolution = Resolution { width: self.config.width, height: self.config.height };
+        if self.text_renderer.prepare(&self.device, &self.queue, &mut self.font_system, &mut self.text_atlas, resolution, [area], &mut self.swash_cache).is_err() {
+            return;
+        }
+
+        let mut enc = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("Loading") });
+        {
+            let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Loading Bar Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.02, g: 0.02, b: 0.03, a: 1.0 }), store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment { view: &self.depth, depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(FAR_DEPTH), store: wgpu::StoreOp::Store }), stencil_ops: None }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(self.pipelines.get(UI));
+            pass.set_bind_group(0, &self.global_bind_identity, &[]);
+            pass.set_bind_group(1, &self.local_bind_identity, &[]);
+            pass.set_vertex_buffer(0, self.bar_v_buf.slice(..));
+            pass.set_index_buffer(self.bar_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..12, 0, 0..1);
+        }
+        {
+            let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Loading Text Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            let _ = self.text_renderer.render(&self.text_atlas, &mut pass);
+        }
+        self.queue.submit(std::iter::once(enc.finish()));
+        out.present();
+        self.text_atlas.trim();
+    }
+
+    // squared distance to the player for the load queue and the meshing pool, pushed back for what the camera
+    // didn't see last frame
+    fn stream_priority(&self, (center, radius): (Vec3, f32), player_pos: Vec3) -> f32 {
+        let d2 = center.distance_squared(player_pos);
+        match &self.view_frustum {
+            Some(frustum) if d2 > NEAR_STREAM * NEAR_STREAM && !frustum.intersects_sphere(center, radius) => d2 * OFF_SCREEN_DISTANCE * OFF_SCREEN_DISTANCE,
+            _ => d2,
+        }
+    }
+
+    // the view distance with the memory pressure taken off
+    fn effective_view(&self) -> ViewDistance {
+        ViewDistance {
+            chunks: (self.view_distance.chunks * self.memory_scale).max(MIN_RENDER_DISTANCE),
+            lod_detail: (self.view_distance.lod_detail * self.memory_scale).max(MIN_LOD_DETAIL),
+        }
     }
 
     // QUADTREE LOGIC
+    #[allow(clippy::too_many_arguments)]
     fn process_quadtree(
         &self, 
         face: u8, x: u32, y: u32, size: u32, 
@@ -673,22 +2148,12 @@ let size = window.inner_size();
         let mut dist = world_pos.distance(cam_pos);
 
         if let Some(pid) = player_id {
-            if pid.face == face {
-                if pid.u >= x && pid.u < x + size && pid.v >= y && pid.v < y + size {
-                    dist = 0.0;
-                }
+            if pid.face == face && pid.u >= x && pid.u < x + size && pid.v >= y && pid.v < y + size {
+                dist = 0.0;
             }
         }
 
-        let node_radius_world = (size as f32 * CoordSystem::get_layer_radius(h, planet.resolution)) / planet.resolution as f32;
-        
-        let mut lod_factor = 4.0; 
-        if size <= CHUNK_SIZE * 8 { lod_factor = 5.0; }
-        if size <= CHUNK_SIZE * 4 { lod_factor = 7.0; }
-        if size <= CHUNK_SIZE * 2 { lod_factor = 12.0; } 
-        if size <= CHUNK_SIZE     { lod_factor = 18.0; } 
-
-        let split_distance = node_radius_world * lod_factor;
+        let split_distance = CoordSystem::lod_split_distance(size, planet.resolution, self.effective_view());
         let is_smallest = size <= CHUNK_SIZE;
         
         if dist < split_distance && !is_smallest {
@@ -710,13 +2175,14 @@ let size = window.inner_size();
         }
     }
 
-    fn upload_lod_buffer(&mut self, key: LodKey, v: Vec<Vertex>, i: Vec<u32>) {
-        let v_buf = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&v), usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST });
+    fn upload_lod_buffer(&mut self, key: LodKey, v: Vec<Vertex>, i: Vec<u32>, terrain: &PlanetTerrain) {
+        // batches copy their vertices straight out of it
+        let v_buf = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&v), usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC });
         let i_buf = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&i), usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST });
 
         let uniform_data = LocalUniform {
             model: glam::Mat4::IDENTITY.to_cols_array(),
-            params: [0.0, 0.0, 0.0, 0.0], 
+            params: [0.0, 0.0, lod_level(key), 0.0], 
         };
         
         let uniform_buf = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -731,36 +2197,40 @@ let size = window.inner_size();
             label: None,
         });
 
-        // calculate bounds
-        let (center, radius) = self.calculate_bounds(key.face, key.x, key.y, key.size, 100); // 100 is placeholder, see fix below
-
-        // we need actual planet resolution here
-        // since we dont pass planet to this func, we approximate or pass it
-        // for now, just calculate it using the vertices provided to be precise.
-
-        let mut min = Vec3::splat(f32::MAX);
-        let mut max = Vec3::splat(f32::MIN);
-        for vert in &v {
-            let p = Vec3::from_array(vert.pos);
-            min = min.min(p);
-            max = max.max(p);
-        }
-        let real_center = (min + max) * 0.5;
-        let real_radius = min.distance(max) * 0.5;
+        // the ground it stands for, leaving out the skirts hanging under it
+        let (center, radius) = terrain.bounds(key.face, key.x, key.y, key.size);
+        let bounds = terrain.obb(key.face, key.x, key.y, key.size);
 
+        self.lod_batches.uploaded(key, &i);
         self.lod_chunks.insert(key, ChunkMesh { 
             v_buf, i_buf, num_inds: i.len() as u32, num_verts: v.len(), uniform_buf, bind_group,
-            center: real_center, // <--- ADDED
-            radius: real_radius  // <--- ADDED
+            center,
+            radius,
+            bounds,
+            lod: lod_level(key),
+            reflectivity: 0.0,
         });
         self.animator.start_spawn(AnyKey::Lod(key));
     }
-    fn process_load_queue(&mut self, _player_pos: Vec3, planet: &PlanetData) {
+    fn process_load_queue(&mut self, player_pos: Vec3, planet: &Arc<PlanetData>) {
         let mut upload_budget = 4; 
         while let Ok((key, v, i)) = self.mesh_rx.try_recv() {
+            if let Some(current) = self.prefetching.remove(&AnyKey::Voxel(key)) {
+                if current { self.prefetched.insert(AnyKey::Voxel(key), (v, i)); }
+                continue;
+            }
+            // an edit's remesh replaces the mesh there, empty or not, whatever the budget
+            if self.remeshing.remove(&key) {
+                if v.is_empty() {
+                    self.chunks.remove(&key);
+                } else if self.chunks.contains_key(&key) {
+                    self.upload_chunk_buffers(key, v, i, planet.resolution);
+                }
+                continue;
+            }
             self.pending_chunks.remove(&key);
             if !v.is_empty() {
-                self.upload_chunk_buffers(key, v, i);
+                self.upload_chunk_buffers(key, v, i, planet.resolution);
                 upload_budget -= 1;
             }
             if upload_budget <= 0 { break; }
@@ -768,93 +2238,159 @@ let size = window.inner_size();
 
         if upload_budget <= 0 { return; }
         if self.load_queue.is_empty() { return; }
-        if self.pending_chunks.len() >= 12 { return; } 
+        let (max_pending, chunks_to_spawn) = if self.throttled { (THROTTLED_SPAWNS, THROTTLED_SPAWNS) } else { (12, 4) };
+        if self.pending_chunks.len() >= max_pending { return; } 
 
-        let chunks_to_spawn = 4;
         for _ in 0..chunks_to_spawn {
             if let Some(key) = self.load_queue.pop() {
                 if self.chunks.contains_key(&key) || self.pending_chunks.contains(&key) {
                     continue;
                 }
+                let priority = self.stream_priority(chunk_bounds(&key, planet), player_pos);
+                if !self.mesh_pool.submit(AnyKey::Voxel(key), priority, planet) {
+                    // the pool is busy, try again next frame
+                    self.load_queue.push(key);
+                    break;
+                }
                 self.pending_chunks.insert(key);
-                let planet_clone = planet.clone();
-                let tx = self.mesh_tx.clone();
-                std::thread::spawn(move || {
-                    let (v, i) = MeshGen::build_chunk(key, &planet_clone);
-                    let _ = tx.send((key, v, i));
-                });
             } else {
                 break;
             }
         }
     }
 
+    #[allow(dead_code)]
     pub fn rebuild_all(&mut self, _planet: &PlanetData) {
         self.chunks.clear();
         self.lod_chunks.clear(); 
+        self.lod_batches.clear();
         self.load_queue.clear();
         self.pending_chunks.clear();
+        self.remesh.clear();
+        self.remeshing.clear();
         self.pending_lods.clear(); 
+        self.prefetching.clear();
+        self.prefetched.clear();
+        self.mesh_pool.clear();
         self.player_chunk_pos = None; 
         self.animator.dying_chunks.clear();
     }
 
-    pub fn force_reload_all(&mut self, planet: &PlanetData, player_pos: Vec3) {
+    pub fn force_reload_all(&mut self, planet: &Arc<PlanetData>, player_pos: Vec3) {
+        self.sign_revision = None;
         self.chunks.clear();
         self.lod_chunks.clear();
+        self.lod_batches.clear();
         self.load_queue.clear();
         self.pending_chunks.clear();
+        self.remesh.clear();
+        self.remeshing.clear();
         self.pending_lods.clear(); 
+        self.prefetching.clear();
+        self.prefetched.clear();
+        self.mesh_pool.clear();
         self.player_chunk_pos = None; 
-        self.update_view(player_pos, planet);
-    }
-
-    pub fn refresh_neighbors(&mut self, id: BlockId, planet: &PlanetData) {
-        let u_c = id.u / CHUNK_SIZE;
-        let v_c = id.v / CHUNK_SIZE;
-        let keys = vec![
-            ChunkKey { face: id.face, u_idx: u_c, v_idx: v_c },
-            ChunkKey { face: id.face, u_idx: u_c.saturating_sub(1), v_idx: v_c },
-            ChunkKey { face: id.face, u_idx: u_c + 1, v_idx: v_c },
-            ChunkKey { face: id.face, u_idx: u_c, v_idx: v_c.saturating_sub(1) },
-            ChunkKey { face: id.face, u_idx: u_c, v_idx: v_c + 1 },
-        ];
-        for key in keys {
-            if self.chunks.contains_key(&key) {
-                let (v, i) = MeshGen::build_chunk(key, planet);
-                if v.is_empty() { 
-                    self.chunks.remove(&key);
-                } else {
-                    self.upload_chunk_buffers(key, v, i);
-                }
+        self.update_view(player_pos, Vec3::ZERO, planet);
+    }
+
+    // queues the chunk an edited block is in, and the neighbours it borders, since their faces and corner shading
+    // against it change too
+    pub fn refresh_neighbors(&mut self, id: BlockId) {
+        let (u, v) = (id.u % CHUNK_SIZE, id.v % CHUNK_SIZE);
+        let (u_c, v_c) = (id.u / CHUNK_SIZE, id.v / CHUNK_SIZE);
+        let across = |at: u32, c: u32| match at {
+            0 => vec![c, c.saturating_sub(1)],
+            _ if at == CHUNK_SIZE - 1 => vec![c, c + 1],
+            _ => vec![c],
+        };
+        for u_idx in across(u, u_c) {
+            for v_idx in across(v, v_c) {
+                self.remesh.insert(ChunkKey { face: id.face, u_idx, v_idx });
             }
         }
     }
 
+    // queues every chunk a batch of edits touched, and the ones along their borders, for one remesh together. a
+    // chunk only goes in once however many of its blocks changed
+    pub fn refresh_blocks(&mut self, ids: impl IntoIterator<Item = BlockId>) {
+        for id in ids {
+            self.refresh_neighbors(id);
+        }
+    }
 
-    fn calculate_bounds(&self, face: u8, u_start: u32, v_start: u32, size: u32, planet_res: u32) -> (Vec3, f32) {
-        // calculate center
-        let u_center = u_start + size / 2;
-        let v_center = v_start + size / 2;
-        let h_mid = planet_res / 2; // approx surface height
-        
-        let center_pos = CoordSystem::get_vertex_pos(face, u_center, v_center, h_mid, planet_res);
-
-        // use the corner + a buffer to be safe against height variations (mountains)
-        let corner_pos = CoordSystem::get_vertex_pos(face, u_start, v_start, h_mid, planet_res);
-        
-        // add 32.0 buffer for terrain height variation
-        let radius = center_pos.distance(corner_pos) + 32.0; 
+    // queues a chunk and the four around it, one arriving from the server changes the faces along its borders
+    pub fn refresh_chunk(&mut self, center: ChunkKey) {
+        let (face, u_c, v_c) = (center.face, center.u_idx, center.v_idx);
+        self.remesh.extend([
+            ChunkKey { face, u_idx: u_c, v_idx: v_c },
+            ChunkKey { face, u_idx: u_c.saturating_sub(1), v_idx: v_c },
+            ChunkKey { face, u_idx: u_c + 1, v_idx: v_c },
+            ChunkKey { face, u_idx: u_c, v_idx: v_c.saturating_sub(1) },
+            ChunkKey { face, u_idx: u_c, v_idx: v_c + 1 },
+        ]);
+    }
 
-        (center_pos, radius)
+    // hands the queued edits to the pool, ahead of any streaming. a chunk with one already running waits for it,
+    // so an older snapshot's mesh can't land on top of a newer one
+    fn submit_remeshes(&mut self, planet: &Arc<PlanetData>) {
+        // a prefetch of an edited chunk is out of date, whether it's built yet or not
+        for key in &self.remesh {
+            self.prefetched.remove(&AnyKey::Voxel(*key));
+            if let Some(current) = self.prefetching.get_mut(&AnyKey::Voxel(*key)) { *current = false; }
+        }
+        let ready: Vec<ChunkKey> = self.remesh.iter().filter(|k| !self.remeshing.contains(k)).copied().collect();
+        for key in ready {
+            self.remesh.remove(&key);
+            if !self.chunks.contains_key(&key) { continue; }
+            self.mesh_pool.submit_edit(key, planet);
+            self.remeshing.insert(key);
+        }
     }
 
 
+    pub fn update_remote_players(&mut self, players: &HashMap<u32, RemotePlayer>) {
+        self.remote_tags.clear();
+        let mut instances = Vec::with_capacity(players.len());
 
+        for p in players.values() {
+            let model = glam::Mat4::from_translation(p.position) * glam::Mat4::from_quat(p.rotation);
+            instances.push(Instance::new(model, None));
 
+            // name tag floats just above the head
+            let up = p.position.normalize_or_zero();
+            self.remote_tags.push((p.name.clone(), p.position + up * 2.2));
+        }
+    }
 
+    // markers the local player set are yellow, everyone else's light blue, graves red under a beacon
+    pub fn update_markers(&mut self, markers: &Markers, eye: Vec3) {
+        let graves = markers.graves.iter().map(|g| (&g.marker, BEACON_COLOR));
+        self.marker_tags = markers.visible_to(&markers.me)
+            .map(|m| (m, if m.owner == markers.me { [1.0, 0.9, 0.3] } else { [0.5, 0.8, 1.0] }))
+            .chain(graves)
+            .map(|(m, color)| {
+                let pos = m.position + m.position.normalize_or_zero() * LABEL_HEIGHT;
+                (tr!("hud.marker", m.name, m.position.distance(eye).round()), pos, color)
+            })
+            .collect();
 
-    fn upload_chunk_buffers(&mut self, key: ChunkKey, v: Vec<Vertex>, i: Vec<u32>) {
+        // each beacon is a quad turned to face the eye
+        let mut verts = Vec::new();
+        for grave in markers.graves.iter().take(MAX_BEACONS) {
+            let base = grave.marker.position;
+            let up = base.normalize_or_zero();
+            let side = up.cross(eye - base).try_normalize().unwrap_or_else(|| up.any_orthonormal_vector()) * BEACON_WIDTH * 0.5;
+            let top = base + up * BEACON_HEIGHT;
+            let corners = [base - side, base + side, top - side, top + side];
+            let normal = up.to_array();
+            verts.extend([0, 1, 2, 2, 1, 3].map(|i| Vertex { pos: corners[i].to_array(), color: BEACON_COLOR, normal }));
+        }
+        if verts.is_empty() && self.beacon_inds == 0 { return; }
+        self.queue.write_buffer(&self.beacon_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.beacon_inds = verts.len() as u32;
+    }
+
+    fn upload_chunk_buffers(&mut self, key: ChunkKey, v: Vec<Vertex>, i: Vec<u32>, res: u32) {
         let v_buf = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&v), usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST });
         let i_buf = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: None, contents: bytemuck::cast_slice(&i), usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST });
         
@@ -889,19 +2425,65 @@ let size = window.inner_size();
                 max = max.max(p);
             }
         }
+        // built blocks can stand above the ground and holes go below it, so a chunk is measured from its mesh.
+        // out to its farthest vertex, which on a curved patch is well inside the corner of the box
         let real_center = (min + max) * 0.5;
-        let real_radius = min.distance(max) * 0.5;
+        let real_radius = v.iter().map(|vert| Vec3::from_array(vert.pos).distance(real_center)).fold(0.0, f32::max);
+        let axes = CoordSystem::patch_axes(key.face, key.u_idx * CHUNK_SIZE, key.v_idx * CHUNK_SIZE, CHUNK_SIZE, res);
+        let bounds = Obb::fit(axes, v.iter().map(|vert| Vec3::from_array(vert.pos)));
 
         self.chunks.insert(key, ChunkMesh { 
             v_buf, i_buf, num_inds: i.len() as u32, num_verts: v.len(), uniform_buf, bind_group,
             center: real_center, 
-            radius: real_radius  
+            radius: real_radius,
+            bounds,
+            lod: 0.0,
+            reflectivity: 0.0,
         });
         
         if !is_update {
             self.animator.start_spawn(AnyKey::Voxel(key));
         }
     }
+    // memory pressure: throws away meshes sitting in memory without being drawn (finished but not uploaded yet,
+    // or fading out) and pulls the view in so fewer lods stay loaded
+    pub fn trim_memory(&mut self) -> Eviction {
+        let mut queued = self.prefetched.len();
+        self.prefetched.clear();
+        while let Ok((key, _, _)) = self.mesh_rx.try_recv() {
+            if self.prefetching.remove(&AnyKey::Voxel(key)).is_none() { self.pending_chunks.remove(&key); }
+            queued += 1;
+        }
+        while let Ok((key, _, _)) = self.lod_rx.try_recv() {
+            if self.prefetching.remove(&AnyKey::Lod(key)).is_none() { self.pending_lods.remove(&key); }
+            queued += 1;
+        }
+        let fading = self.animator.drop_dying();
+        self.memory_scale = (self.memory_scale * MEMORY_SCALE_STEP).max(MIN_MEMORY_SCALE);
+        Eviction { queued, fading, scale: self.memory_scale }
+    }
+
+    // memory is fine again, let the view grow back a step
+    pub fn relax_memory(&mut self) {
+        self.memory_scale = (self.memory_scale / MEMORY_SCALE_STEP).min(1.0);
+    }
+
+    // vertex and index bytes of every voxel and lod mesh, and the lod batches copied from them
+    pub fn mesh_memory(&self) -> u64 {
+        self.chunks.values().chain(self.lod_chunks.values()).chain(self.lod_batches.meshes())
+            .map(|c| (c.num_verts * std::mem::size_of::<Vertex>() + c.num_inds as usize * 4) as u64)
+            .sum()
+    }
+
+    // shader files edited on disk since the last frame, see PipelineCache::reload_edited
+    pub fn reload_shaders(&mut self) -> Vec<(&'static str, Result<(), String>)> {
+        self.pipelines.reload_edited(&self.device)
+    }
+
+    pub fn fps(&self) -> u32 {
+        self.current_fps
+    }
+
     pub fn log_memory(&self, planet: &PlanetData) {
         let mut total_v = 0;
         let mut total_i = 0;
@@ -919,49 +2501,50 @@ let size = window.inner_size();
         println!("------------------------------------------");
     }
 
-    pub fn update_cursor(&mut self, planet: &PlanetData, id: Option<BlockId>) {
-        if let Some(id) = id {
+    // block grid lying on top of the targeted block, a few cells past the brush, so edits can be lined up
+    pub fn update_grid(&mut self, planet: &PlanetData, cursor: Option<&(BlockId, BrushArea)>) {
+        const MARGIN: u32 = 6;
+        let Some((id, (us, vs))) = cursor else {
+            self.grid_inds = 0;
+            return;
+        };
+        let res = planet.resolution;
+        let (u0, u1) = (us.start.saturating_sub(MARGIN), (us.end + MARGIN).min(res));
+        let (v0, v1) = (vs.start.saturating_sub(MARGIN), (vs.end + MARGIN).min(res));
+        let p = |u, v| CoordSystem::get_vertex_pos(id.face, u, v, id.layer + 1, res).to_array();
+        let (color, normal) = ([0.6, 0.6, 0.6], [0.0; 3]);
+
+        let mut verts = Vec::new();
+        for u in u0..=u1 {
+            verts.push(Vertex { pos: p(u, v0), color, normal });
+            verts.push(Vertex { pos: p(u, v1), color, normal });
+        }
+        for v in v0..=v1 {
+            verts.push(Vertex { pos: p(u0, v), color, normal });
+            verts.push(Vertex { pos: p(u1, v), color, normal });
+        }
+        let inds: Vec<u32> = (0..verts.len() as u32).collect();
+
+        self.queue.write_buffer(&self.grid_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.queue.write_buffer(&self.grid_i_buf, 0, bytemuck::cast_slice(&inds));
+        self.grid_inds = inds.len() as u32;
+    }
+
+    // outlines the targeted block, or the whole brush footprint one layer deep
+    pub fn update_cursor(&mut self, planet: &PlanetData, cursor: Option<(BlockId, BrushArea)>, color: [f32; 3]) {
+        if let Some((id, (us, vs))) = cursor {
             let res = planet.resolution;
-            let p = |u, v, l| CoordSystem::get_vertex_pos(id.face, id.u + u, id.v + v, id.layer + l, res);
+            let p = |u, v, l| CoordSystem::get_vertex_pos(id.face, u, v, id.layer + l, res);
+            let (u0, u1, v0, v1) = (us.start, us.end, vs.start, vs.end);
             
             let corners = [
-                p(0,0,0), p(1,0,0), p(0,1,0), p(1,1,0), 
-                p(0,0,1), p(1,0,1), p(0,1,1), p(1,1,1)  
-            ];
-
-            let edges = [
-                (0,1), (1,3), (3,2), (2,0), 
-                (4,5), (5,7), (7,6), (6,4), 
-                (0,4), (1,5), (2,6), (3,7)  
+                p(u0,v0,0), p(u1,v0,0), p(u0,v1,0), p(u1,v1,0), 
+                p(u0,v0,1), p(u1,v0,1), p(u0,v1,1), p(u1,v1,1)  
             ];
 
             let mut verts = Vec::new();
             let mut inds = Vec::new();
-            let thickness = 0.025; 
-            let color = [1.0, 1.0, 0.0]; 
-            let mut idx_base = 0;
-
-            for (start, end) in edges {
-                let a = corners[start];
-                let b = corners[end];
-                let dir = (b - a).normalize();
-                let ref_up = if dir.dot(Vec3::Y).abs() > 0.9 { Vec3::X } else { Vec3::Y };
-                let right = dir.cross(ref_up).normalize() * thickness;
-                let up = dir.cross(right).normalize() * thickness;
-                let offsets = [(-right - up), (right - up), (right + up), (-right + up)];
-                
-                for off in offsets {
-                    verts.push(Vertex { pos: (a + off).to_array(), color, normal: [0.0;3] });
-                    verts.push(Vertex { pos: (b + off).to_array(), color, normal: [0.0;3] });
-                }
-
-                let faces = [(0,1,3,2), (2,3,5,4), (4,5,7,6), (6,7,1,0)];
-                for (i0, i1, i2, i3) in faces {
-                    inds.push(idx_base + i0); inds.push(idx_base + i1); inds.push(idx_base + i2);
-                    inds.push(idx_base + i2); inds.push(idx_base + i3); inds.push(idx_base + i0);
-                }
-                idx_base += 8;
-            }
+            push_box_outline(&mut verts, &mut inds, corners, color);
 
             self.queue.write_buffer(&self.cursor_v_buf, 0, bytemuck::cast_slice(&verts));
             self.queue.write_buffer(&self.cursor_i_buf, 0, bytemuck::cast_slice(&inds));
@@ -971,12 +2554,203 @@ let size = window.inner_size();
         }
     }
 
+    // box around the blocks marked for /prefab save, and a ghost of the prefab being placed at `origin`.
+    // cells already taken by a block are tinted red since placing skips them
+    pub fn update_prefab(&mut self, planet: &PlanetData, selection: Option<(BlockId, BlockId)>, ghost: Option<(&Schematic, BlockId)>, color: [f32; 3]) {
+        let res = planet.resolution;
+        let mut verts = Vec::new();
+        let mut inds = Vec::new();
+
+        let mut outline = |face: u8, lo: [u32; 3], hi: [u32; 3]| {
+            let p = |u, v, l| CoordSystem::get_vertex_pos(face, u, v, l, res);
+            let corners = [
+                p(lo[0],lo[1],lo[2]), p(hi[0],lo[1],lo[2]), p(lo[0],hi[1],lo[2]), p(hi[0],hi[1],lo[2]),
+                p(lo[0],lo[1],hi[2]), p(hi[0],lo[1],hi[2]), p(lo[0],hi[1],hi[2]), p(hi[0],hi[1],hi[2])
+            ];
+            push_box_outline(&mut verts, &mut inds, corners, color);
+        };
+
+        if let Some((a, b)) = selection.filter(|(a, b)| a.face == b.face) {
+            let lo = [a.u.min(b.u), a.v.min(b.v), a.layer.min(b.layer)];
+            let hi = [a.u.max(b.u) + 1, a.v.max(b.v) + 1, a.layer.max(b.layer) + 1];
+            outline(a.face, lo, hi);
+        }
+        if let Some((schematic, origin)) = ghost {
+            let lo = [origin.u, origin.v, origin.layer];
+            let hi = [0, 1, 2].map(|i| (lo[i] + schematic.size[i]).min(res));
+            outline(origin.face, lo, hi);
+        }
+        self.queue.write_buffer(&self.select_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.queue.write_buffer(&self.select_i_buf, 0, bytemuck::cast_slice(&inds));
+        self.select_inds = inds.len() as u32;
+
+        let mut verts = Vec::new();
+        let mut inds = Vec::new();
+        if let Some((schematic, origin)) = ghost.filter(|(s, _)| s.solid.len() <= GHOST_MAX_BLOCKS) {
+            for (id, _) in schematic.blocks_at(origin, res) {
+                if planet.exists(id) {
+                    push_cube(&mut verts, &mut inds, id, res, [1.0, 0.3, 0.2], GHOST_GROW);
+                } else {
+                    push_cube(&mut verts, &mut inds, id, res, [0.4, 0.8, 1.0], 0.0);
+                }
+            }
+        }
+        self.queue.write_buffer(&self.ghost_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.queue.write_buffer(&self.ghost_i_buf, 0, bytemuck::cast_slice(&inds));
+        self.ghost_inds = inds.len() as u32;
+    }
+
+    // rebuilds the build guide after the blueprint compared itself with the world. missing blocks are
+    // drawn as blue ghosts, wrongly placed ones as red shells around the real block
+    pub fn update_blueprint(&mut self, planet: &PlanetData, blueprint: Option<&Blueprint>, color: [f32; 3]) {
+        let Some(bp) = blueprint else {
+            self.blueprint_inds = 0;
+            self.blueprint_label = None;
+            return;
+        };
+        let res = planet.resolution;
+        let mut verts = Vec::new();
+        let mut inds = Vec::new();
+
+        let lo = [bp.origin.u, bp.origin.v, bp.origin.layer];
+        let hi = [0, 1, 2].map(|i| (lo[i] + bp.schematic.size[i]).min(res));
+        let p = |u, v, l| CoordSystem::get_vertex_pos(bp.origin.face, u, v, l, res);
+        let corners = [
+            p(lo[0],lo[1],lo[2]), p(hi[0],lo[1],lo[2]), p(lo[0],hi[1],lo[2]), p(hi[0],hi[1],lo[2]),
+            p(lo[0],lo[1],hi[2]), p(hi[0],lo[1],hi[2]), p(lo[0],hi[1],hi[2]), p(hi[0],hi[1],hi[2])
+        ];
+        push_box_outline(&mut verts, &mut inds, corners, color);
+
+        // huge guides only show the first few thousand, missing blocks first
+        let missing = bp.missing.iter().map(|&id| (id, [0.4, 0.8, 1.0], 0.0));
+        let wrong = bp.wrong.iter().map(|&id| (id, [1.0, 0.3, 0.2], GHOST_GROW));
+        for (id, color, grow) in missing.chain(wrong).take(GHOST_MAX_BLOCKS) {
+            push_cube(&mut verts, &mut inds, id, res, color, grow);
+        }
+        self.queue.write_buffer(&self.blueprint_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.queue.write_buffer(&self.blueprint_i_buf, 0, bytemuck::cast_slice(&inds));
+        self.blueprint_inds = inds.len() as u32;
+
+        let (placed, total) = bp.progress();
+        self.blueprint_label = Some(tr!("hud.blueprint", bp.name, placed, total, bp.wrong.len()));
+    }
 
-pub fn render(&mut self, controller: &Controller, player: &Player, planet: &PlanetData, console: &Console) {
-        self.update_console_mesh(console.height_fraction);
+    // moves each kind's model to every mob, tinted with its color
+    pub fn update_mobs(&mut self, mobs: &Mobs) {
+        if mobs.entities.is_empty() && self.mob_meshes.iter().all(InstancedMesh::is_empty) { return; }
+        let mut instances: [Vec<Instance>; 6] = Default::default();
+        for (e, mob) in mobs.renderables.iter() {
+            let Some(transform) = mobs.transforms.get(e) else { continue };
+            let model = glam::Mat4::from_translation(transform.center()) * glam::Mat4::from_quat(transform.rotation);
+            let kind = match mob.kind { MobKind::Cube => 0, MobKind::Ball => 1, MobKind::Bird => 2, MobKind::Bike => 3, MobKind::Villager => 4, MobKind::Lurker => 5 };
+            instances[kind].push(Instance::new(model, Some(mob.color)));
+        }
+        for (mesh, instances) in self.mob_meshes.iter_mut().zip(&instances) {
+            mesh.upload(&self.queue, instances);
+        }
+    }
+
+    // sparks over the teleporters near `around`, livelier on the one being charged (`charge` 0..1),
+    // and the black the screen fades to while travelling
+    pub fn update_teleporters(&mut self, planet: &PlanetData, around: Vec3, charge: Option<(BlockId, f32)>, fade: f32) {
+        self.fade_drawn = fade > 0.0;
+        if self.fade_drawn {
+            let data = LocalUniform { model: glam::Mat4::IDENTITY.to_cols_array(), params: [fade, 0.0, 1.0, 0.0] };
+            self.queue.write_buffer(&self.fade_local_buf, 0, bytemuck::cast_slice(&[data]));
+        }
+
+        let res = planet.resolution;
+        let t = self.spark_clock.elapsed().as_secs_f32();
+        let mut verts = Vec::new();
+        let ends = planet.entities()
+            .filter(|(_, entity)| matches!(entity, BlockEntity::Teleporter { .. }))
+            .map(|(id, _)| (*id, teleporter::arrival(*id, res)))
+            .filter(|(_, top)| top.distance(around) < SPARK_RANGE)
+            .take(MAX_TELEPORTER_SPARKS);
+        for (id, top) in ends {
+            let up = top.normalize_or_zero();
+            let (a, b) = up.any_orthonormal_pair();
+            let level = charge.filter(|(charged, _)| *charged == id).map_or(0.0, |(_, c)| c);
+            let color = if teleporter::partner(planet, id).is_some() { SPARK_LINKED } else { SPARK_UNLINKED };
+            for i in 0..SPARKS {
+                let phase = (t * (0.6 + level * 2.0) + i as f32 * 0.618).fract();
+                let angle = i as f32 / SPARKS as f32 * std::f32::consts::TAU + t * (1.0 + level * 4.0);
+                let radius = 0.45 * (1.0 - phase * 0.5 * (1.0 + level));
+                let p = top + (a * angle.cos() + b * angle.sin()) * radius + up * phase * SPARK_RISE * (1.0 + level);
+                let color = color.map(|c| c * (1.0 - phase));
+                let normal = [0.0, 0.0, 1.0];
+                verts.push(Vertex { pos: p.to_array(), color, normal });
+                verts.push(Vertex { pos: (p + up * SPARK_LENGTH).to_array(), color, normal });
+            }
+        }
+        if verts.is_empty() && self.spark_inds == 0 { return; }
+        self.queue.write_buffer(&self.spark_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.spark_inds = verts.len() as u32;
+    }
+
+    // name of the prefab being placed for the hud, and the radial menu entries while it's open
+    pub fn update_prefab_hud(&mut self, label: Option<String>, radial: Vec<(String, bool)>) {
+        self.prefab_label = label;
+        self.radial_items = radial;
+    }
+
+    pub fn update_interact_hud(&mut self, label: Option<String>) {
+        self.interact_label = label;
+    }
+
+    pub fn update_hotbar(&mut self, inventory: &Inventory, slot: usize) {
+        self.hotbar = (inventory.slots, slot);
+    }
+
+    // left and top of hotbar slot `i`
+    fn slot_rect(&self, i: usize) -> (f32, f32) {
+        let width = HOTBAR_SLOTS as f32 * (SLOT_SIZE + SLOT_GAP) - SLOT_GAP;
+        let left = (self.config.width as f32 - width) * 0.5 + i as f32 * (SLOT_SIZE + SLOT_GAP);
+        (left, self.config.height as f32 - SLOT_SIZE - (HOTBAR_SPACE - SLOT_SIZE) * 0.5)
+    }
+
+    // each slot is a frame, white on the selected one, around a swatch of the block it holds
+    fn update_hotbar_mesh(&mut self, settings: &Settings) {
+        let (w, h) = (self.config.width as f32, self.config.height as f32);
+        let (slots, selected) = self.hotbar;
+        let mut verts = Vec::with_capacity(HOTBAR_SLOTS * 8);
+        for (i, stack) in slots.iter().enumerate() {
+            let (left, top) = self.slot_rect(i);
+            let frame = if i == selected { SLOT_SELECTED } else { BAR_TRACK };
+            let fill = stack.map_or([0.05, 0.05, 0.07], |s| s.block.color());
+            push_quad(&mut verts, (w, h), (left, top, SLOT_SIZE, SLOT_SIZE), settings.palette.apply(frame));
+            let inner = SLOT_SIZE - SLOT_BORDER * 2.0;
+            push_quad(&mut verts, (w, h), (left + SLOT_BORDER, top + SLOT_BORDER, inner, inner), settings.palette.apply(fill));
+        }
+        self.queue.write_buffer(&self.hotbar_v_buf, 0, bytemuck::cast_slice(&verts));
+        self.hotbar_inds = (verts.len() / 4 * 6) as u32;
+    }
+
+
+pub fn render(&mut self, controller: &Controller, player: &Player, planet: &PlanetData, console: &Console, settings: &Settings, saving: bool) {
+        while let Ok(timing) = self.profile_rx.try_recv() {
+            self.mesh_profile.record(timing);
+        }
+        self.update_console_mesh(console, settings.console_opacity);
+        if controller.show_help { self.update_help_mesh(); }
+        self.update_crosshair_mesh(settings);
+        self.update_touch_mesh(controller);
+        self.update_status_bars(player, settings);
+        self.update_hotbar_mesh(settings);
+        self.animator.set_reduce_motion(settings.reduce_motion);
+
+        self.rope_drawn = player.rope.is_some();
+        if let Some(rope) = &player.rope {
+            let hand = player.position + Physics::get_up_vector(player.position) * ROPE_HAND;
+            let normal = [0.0, 0.0, 1.0];
+            let color = settings.palette.apply(ROPE_COLOR);
+            let verts = [Vertex { pos: hand.to_array(), color, normal }, Vertex { pos: rope.anchor.to_array(), color, normal }];
+            self.queue.write_buffer(&self.rope_v_buf, 0, bytemuck::cast_slice(&verts));
+        }
 
 if controller.show_collisions {
-             let (v, i) = MeshGen::generate_collision_debug(player.position, planet);
+             let (mut v, i) = MeshGen::generate_collision_debug(player.position, planet);
+             for vert in &mut v { vert.color = settings.palette.apply(vert.color); }
              self.queue.write_buffer(&self.collision_v_buf, 0, bytemuck::cast_slice(&v));
              self.queue.write_buffer(&self.collision_i_buf, 0, bytemuck::cast_slice(&i));
              self.collision_inds = i.len() as u32;
@@ -990,7 +2764,7 @@ if controller.show_collisions {
         let view = out.texture.create_view(&wgpu::TextureViewDescriptor::default());
         
         // -- sun matrix --
-        let sun_dir = glam::Vec3::new(0.5, 0.8, 0.4).normalize();
+        let sun_dir = sun_dir();
         let shadow_dist = 200.0; // distance of light source from center
         let proj_size = 60.0;   // SIZE OF SHADOW AREA (Smaller = Sharper Shadows)
         
@@ -1005,10 +2779,10 @@ if controller.show_collisions {
         // texel Snapping
         // project the center position into light space, snap it to a pixel,
         // and then offset the view matrix by the difference.
-        let shadow_map_size = 4096.0;
+        let shadow_map_size = self.shadow_size as f32;
         let texel_size = (2.0 * proj_size) / shadow_map_size;
         
-        let mut shadow_origin = sun_view.transform_point3(center);
+        let shadow_origin = sun_view.transform_point3(center);
         let snapped_x = (shadow_origin.x / texel_size).round() * texel_size;
         let snapped_y = (shadow_origin.y / texel_size).round() * texel_size;
         
@@ -1029,49 +2803,71 @@ if controller.show_collisions {
         let light_view_proj = sun_proj * sun_view;
 
         // -- Camera Matrix --
-        let mvp = controller.get_matrix(player, self.config.width as f32, self.config.height as f32);
+        // in stereo each eye sees half the window, culling uses a camera between the two
+        let (width, height) = (self.config.width as f32, self.config.height as f32);
+        let stereo = settings.stereo;
+        let mvp = controller.get_matrix(player, if stereo { width / 2.0 } else { width }, height);
+        let eyes = if stereo { controller.eye_matrices(player, width, height, settings.ipd) } else { [mvp; 2] };
         
         // --- FRUSTUM CULLING LOGIC ---
         let current_frustum = crate::common::Frustum::from_matrix(mvp);
+        self.view_frustum = Some(current_frustum);
 
         // determine which frustum to use for culling
         // if freeze is on, we use the stored one. if freeze is off, update the stored one (or just use current).
         let cull_frustum = if controller.freeze_culling {
             if self.frozen_frustum.is_none() {
                 self.frozen_frustum = Some(crate::common::Frustum::from_matrix(mvp));
+                // the boxes stay where they were frozen, so they're written once
+                let normal = [0.0, 0.0, 1.0];
+                let verts: Vec<Vertex> = [(mvp, GIZMO_FRUSTUM_COLOR), (light_view_proj, GIZMO_SHADOW_COLOR)].iter()
+                    .flat_map(|(m, color)| {
+                        let color = settings.palette.apply(*color);
+                        frustum_corners(*m).map(|p| Vertex { pos: p.to_array(), color, normal })
+                    })
+                    .collect();
+                self.queue.write_buffer(&self.gizmo_v_buf, 0, bytemuck::cast_slice(&verts));
             }
-            self.frozen_frustum.as_ref().unwrap()
+            self.frozen_frustum.unwrap()
         } else {
             self.frozen_frustum = None;
-            &current_frustum
+            current_frustum
         };
 
-        // debug Stats
-        let mut rendered_lods = 0;
-        let mut rendered_chunks = 0;
-
 
 
 
 
         let cam_pos = controller.get_camera_pos(player);
+        let ground = CoordSystem::get_layer_radius(planet.resolution / 2, planet.resolution);
         let frustum = crate::common::Frustum::from_matrix(mvp);
 
         // 1. update main global uni
-        let global_data = GlobalUniform {
-            view_proj: mvp.to_cols_array(),
-            light_view_proj: light_view_proj.to_cols_array(),
-            cam_pos: [cam_pos.x, cam_pos.y, cam_pos.z, 1.0],
-            sun_dir: [sun_dir.x, sun_dir.y, sun_dir.z, 0.0],
-        };
-        self.queue.write_buffer(&self.global_buf, 0, bytemuck::cast_slice(&[global_data]));
+        for (eye, buf) in eyes.iter().zip([&self.global_buf, &self.global_buf_right]) {
+            let global_data = GlobalUniform {
+                view_proj: eye.to_cols_array(),
+                light_view_proj: light_view_proj.to_cols_array(),
+                cam_pos: [cam_pos.x, cam_pos.y, cam_pos.z, ground],
+                sun_dir: [sun_dir.x, sun_dir.y, sun_dir.z, self.render_mode as u32 as f32],
+                fog: self.fog,
+            };
+            self.queue.write_buffer(buf, 0, bytemuck::cast_slice(&[global_data]));
+        }
+        // the sky only needs which way each pixel looks, so its matrix leaves out where the camera is. inverted
+        // in f64, as the camera sits thousands of blocks from the origin
+        for (eye, buf) in eyes.iter().zip(&self.sky_local_bufs) {
+            let relative = eye.as_dmat4() * glam::DMat4::from_translation(cam_pos.as_dvec3());
+            let data = LocalUniform { model: relative.inverse().as_mat4().to_cols_array(), params: [0.0; 4] };
+            self.queue.write_buffer(buf, 0, bytemuck::cast_slice(&[data]));
+        }
 
         // 2. update shadow global uni (put Light Matrix in view_proj)
         let shadow_uniform_data = GlobalUniform {
             view_proj: light_view_proj.to_cols_array(), // Used by Shadow Pass Vertex Shader
             light_view_proj: light_view_proj.to_cols_array(),
-            cam_pos: [cam_pos.x, cam_pos.y, cam_pos.z, 1.0],
+            cam_pos: [cam_pos.x, cam_pos.y, cam_pos.z, ground],
             sun_dir: [sun_dir.x, sun_dir.y, sun_dir.z, 0.0],
+            fog: self.fog,
         };
         self.queue.write_buffer(&self.shadow_global_buf, 0, bytemuck::cast_slice(&[shadow_uniform_data]));
 
@@ -1089,7 +2885,7 @@ if controller.show_collisions {
             if let Some(state) = self.animator.dying_chunks.get(&key) {
                 let data = LocalUniform { 
                     model: glam::Mat4::IDENTITY.to_cols_array(), 
-                    params: [alpha, 1.0, 0.0, 0.0] 
+                    params: [alpha, 1.0, state.mesh.lod, state.mesh.reflectivity] 
                 };
                 self.queue.write_buffer(&state.mesh.uniform_buf, 0, bytemuck::cast_slice(&[data]));
             }
@@ -1097,113 +2893,281 @@ if controller.show_collisions {
 
         let queue = &self.queue;
         let animator = &mut self.animator;
+        let mut settled = Vec::new();
         
         let mut update_opacity = |key: AnyKey, mesh: &ChunkMesh| {
             let alpha = animator.get_opacity(key, now);
             if alpha < 1.0 {
                 let data = LocalUniform { 
                     model: glam::Mat4::IDENTITY.to_cols_array(), 
-                    params: [alpha, 0.0, 0.0, 0.0] 
+                    params: [alpha, 0.0, mesh.lod, mesh.reflectivity] 
                 };
                 queue.write_buffer(&mesh.uniform_buf, 0, bytemuck::cast_slice(&[data]));
             } else if animator.spawning_chunks.contains_key(&key) {
                 let data = LocalUniform { 
                     model: glam::Mat4::IDENTITY.to_cols_array(), 
-                    params: [1.0, 0.0, 0.0, 0.0] 
+                    params: [1.0, 0.0, mesh.lod, mesh.reflectivity] 
                 };
                 queue.write_buffer(&mesh.uniform_buf, 0, bytemuck::cast_slice(&[data]));
                 animator.spawning_chunks.remove(&key);
+                settled.push(key);
             }
         };
 
         for (key, mesh) in &self.lod_chunks { update_opacity(AnyKey::Lod(*key), mesh); }
         for (key, mesh) in &self.chunks { update_opacity(AnyKey::Voxel(*key), mesh); }
+        for key in settled {
+            if let AnyKey::Lod(key) = key { self.lod_batches.join(key); }
+        }
+        self.lod_batches.rebuild(&self.device, &self.queue, &self.local_layout, &self.lod_chunks);
 
+        // --- FPS CALCULATION ---
+        self.frame_count += 1;
+        let now = std::time::Instant::now();
+        if now.duration_since(self.last_fps_time).as_secs_f32() >= 1.0 {
+            self.current_fps = self.frame_count;
+            self.frame_count = 0;
+            self.last_fps_time = now;
+        }
+
+        // --- PASSES ---
+        let debug_out = self.debug_view.as_ref().and_then(|d| d.surface.get_current_texture().ok());
+        let frame = PassContext {
+            controller, player, console, settings, saving,
+            surface: &out.texture,
+            view: &view,
+            debug_target: debug_out.as_ref().map(|o| o.texture.create_view(&wgpu::TextureViewDescriptor::default())),
+            light_view_proj, sun_dir, cam_pos, ground, mvp, frustum, cull_frustum, stereo, width, height,
+            still_request: self.still_request.take(),
+            record: std::mem::take(&mut self.frame_request),
+        };
+        // passes only borrow pipelines, so whatever this frame can draw with gets built up front
+        let scene = if self.render_mode == RenderMode::Overdraw { OVERDRAW } else { mesh_key(controller.is_wireframe) };
+        for key in [SHADOW, FILL, mesh_key(controller.is_wireframe), scene, instanced_key(false), instanced_key(controller.is_wireframe), LINE, UI, SCREEN, SKY] {
+            self.pipelines.prepare(&self.device, key);
+        }
         let mut enc = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
-        
-        // --- PASS 1: SHADOW MAP GENERATION ---
-        {
-            let mut shadow_pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Shadow Pass"),
-                color_attachments: &[], 
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.shadow_view,
-                    depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Store }),
-                    stencil_ops: None,
-                }),
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
+        Self::frame_graph().execute(self, &mut enc, &frame);
 
-            shadow_pass.set_pipeline(&self.pipeline_shadow);
-            shadow_pass.set_bind_group(0, &self.shadow_global_bind, &[]);
+        self.queue.submit(std::iter::once(enc.finish()));
+        if frame.still_request.is_some() {
+            self.still_frame = self.still.as_ref().map(|s| s.readback.read(&self.device, self.config.format));
+        }
+        if frame.record {
+            self.recorded_frame = self.frame_readback.as_ref().map(|r| r.read(&self.device, self.config.format));
+        }
+        out.present();
+        if let Some(debug_out) = debug_out { debug_out.present(); }
+        self.text_atlas.trim();
+    }
 
-            for mesh in self.chunks.values() {
-                if frustum.intersects_sphere(mesh.center, mesh.radius) {
-                    shadow_pass.set_bind_group(1, &mesh.bind_group, &[]);
-                    shadow_pass.set_vertex_buffer(0, mesh.v_buf.slice(..));
-                    shadow_pass.set_index_buffer(mesh.i_buf.slice(..), wgpu::IndexFormat::Uint32);
-                    shadow_pass.draw_indexed(0..mesh.num_inds, 0, 0..1);
-                }
-            }
-            for mesh in self.lod_chunks.values() {
-                if frustum.intersects_sphere(mesh.center, mesh.radius) {
+    // the passes of a frame and what they touch, scheduled by the graph (see frame_graph.rs)
+    fn frame_graph<'f>() -> FrameGraph<Self, PassContext<'f>> {
+        use Resource::*;
+        FrameGraph::new()
+            .add("shadow", &[], &[ShadowMap], Self::shadow_pass)
+            .add("monitors", &[ShadowMap], &[MonitorTargets], Self::monitor_pass)
+            .add("still", &[ShadowMap], &[Still], Self::still_pass)
+            .add("probe", &[ShadowMap], &[ProbeCube], Self::probe_pass)
+            .add("main", &[ShadowMap, ProbeCube, MonitorTargets], &[Backbuffer], Self::main_pass)
+            .add("ui", &[Backbuffer], &[Backbuffer], Self::ui_pass)
+            .add("debug view", &[ShadowMap], &[DebugView], Self::debug_view_pass)
+            .add("text", &[Backbuffer], &[Backbuffer], Self::text_pass)
+            .add("capture", &[Backbuffer], &[FrameReadback], Self::capture_pass)
+    }
+
+    fn shadow_pass(&mut self, enc: &mut wgpu::CommandEncoder, frame: &PassContext) {
+        let mut shadow_pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadow Pass"),
+            color_attachments: &[], 
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.shadow_view,
+                depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Store }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        // the shaders were built without sampling it
+        if !frame.settings.shadows { return; }
+
+        shadow_pass.set_pipeline(self.pipelines.get(SHADOW));
+        shadow_pass.set_bind_group(0, &self.shadow_global_bind, &[]);
+
+        for mesh in self.world_meshes() {
+            if frame.frustum.intersects_obb(&mesh.bounds) {
                 shadow_pass.set_bind_group(1, &mesh.bind_group, &[]);
                 shadow_pass.set_vertex_buffer(0, mesh.v_buf.slice(..));
                 shadow_pass.set_index_buffer(mesh.i_buf.slice(..), wgpu::IndexFormat::Uint32);
                 shadow_pass.draw_indexed(0..mesh.num_inds, 0, 0..1);
-                }
             }
         }
+    }
 
-        // --- PASS 2: MAIN RENDER ---
-        {
-            let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
-
-            label: None, color_attachments: &[Some(wgpu::RenderPassColorAttachment { 
-                view: &view, 
-                resolve_target: None, 
-                ops: wgpu::Operations { 
-                    // Matches the atmospheric fog color in shader
+    // a few cameras per frame, so many cameras cost little and each still updates several times a second
+    fn monitor_pass(&mut self, enc: &mut wgpu::CommandEncoder, frame: &PassContext) {
+        let camera_count = self.camera_targets.len();
+        for i in 0..MONITOR_UPDATES_PER_FRAME.min(camera_count) {
+            let target = &self.camera_targets[(self.next_camera + i) % camera_count];
+            let camera_data = GlobalUniform {
+                view_proj: target.view_proj.to_cols_array(),
+                light_view_proj: frame.light_view_proj.to_cols_array(),
+                cam_pos: [target.eye.x, target.eye.y, target.eye.z, frame.ground],
+                sun_dir: [frame.sun_dir.x, frame.sun_dir.y, frame.sun_dir.z, 0.0],
+                fog: self.fog,
+            };
+            self.queue.write_buffer(&target.global_buf, 0, bytemuck::cast_slice(&[camera_data]));
+            let camera_frustum = crate::common::Frustum::from_matrix(target.view_proj);
 
-                    load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.02, g: 0.03, b: 0.05, a: 1.0 }),
-                    store: wgpu::StoreOp::Store 
-                } 
-            })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment { view: &self.depth, depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Store }), stencil_ops: None }),
+            let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Monitor Camera Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.02, g: 0.03, b: 0.05, a: 1.0 }), store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment { view: &target.depth, depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(FAR_DEPTH), store: wgpu::StoreOp::Store }), stencil_ops: None }),
                 timestamp_writes: None, occlusion_query_set: None,
             });
-            
-            if controller.is_wireframe { pass.set_pipeline(&self.pipeline_wire); } 
-            else { pass.set_pipeline(&self.pipeline_fill); }
-            
-            pass.set_bind_group(0, &self.global_bind, &[]);
-            
-            // DRAW LOD CHUNKS
-            for mesh in self.lod_chunks.values() {
-                if cull_frustum.intersects_sphere(mesh.center, mesh.radius) {
-                    rendered_lods += 1; // Count
-                    pass.set_bind_group(1, &mesh.bind_group, &[]); 
-                    pass.set_vertex_buffer(0, mesh.v_buf.slice(..));
-                    pass.set_index_buffer(mesh.i_buf.slice(..), wgpu::IndexFormat::Uint32);
-                    pass.draw_indexed(0..mesh.num_inds, 0, 0..1);
-                }
+            self.draw_scene(&mut pass, &target.global_bind, &camera_frustum);
+        }
+        if camera_count > 0 { self.next_camera = (self.next_camera + MONITOR_UPDATES_PER_FRAME) % camera_count; }
+    }
+
+    // timelapse and panorama shots, from their own camera
+    fn still_pass(&mut self, enc: &mut wgpu::CommandEncoder, frame: &PassContext) {
+        let still_request = frame.still_request;
+        if let Some((_, _, width, height)) = still_request {
+            if self.still.as_ref().is_none_or(|s| (s.readback.width, s.readback.height) != (width, height)) {
+                self.still = Some(self.mk_still_target(width, height));
             }
+        }
+        if let (Some((view_proj, eye, _, _)), Some(still)) = (still_request, &self.still) {
+            let still_data = GlobalUniform {
+                view_proj: view_proj.to_cols_array(),
+                light_view_proj: frame.light_view_proj.to_cols_array(),
+                cam_pos: [eye.x, eye.y, eye.z, frame.ground],
+                sun_dir: [frame.sun_dir.x, frame.sun_dir.y, frame.sun_dir.z, 0.0],
+                fog: self.fog,
+            };
+            self.queue.write_buffer(&still.global_buf, 0, bytemuck::cast_slice(&[still_data]));
+            {
+                let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Still Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &still.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.02, g: 0.03, b: 0.05, a: 1.0 }), store: wgpu::StoreOp::Store },
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment { view: &still.depth, depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(FAR_DEPTH), store: wgpu::StoreOp::Store }), stencil_ops: None }),
+                    timestamp_writes: None, occlusion_query_set: None,
+                });
+                self.draw_scene(&mut pass, &still.global_bind, &crate::common::Frustum::from_matrix(view_proj));
+            }
+            still.readback.copy_from(enc, &still.texture);
+        }
+    }
 
-            // DRAW VOXEL CHUNKS
-            for mesh in self.chunks.values() {
-                if cull_frustum.intersects_sphere(mesh.center, mesh.radius) {
-                    rendered_chunks += 1; // Count
-                    pass.set_bind_group(1, &mesh.bind_group, &[]);
-                    pass.set_vertex_buffer(0, mesh.v_buf.slice(..));
-                    pass.set_index_buffer(mesh.i_buf.slice(..), wgpu::IndexFormat::Uint32);
-                    pass.draw_indexed(0..mesh.num_inds, 0, 0..1);
-                }
+    // one face of the reflection cube a frame, the whole cube every PROBE_INTERVAL. nothing to capture while
+    // no loaded mesh mirrors it
+    fn probe_pass(&mut self, enc: &mut wgpu::CommandEncoder, frame: &PassContext) {
+        if self.probe.face >= PROBE_FACES.len() && !self.chunks.values().chain(self.lod_chunks.values()).any(|m| m.reflectivity > 0.0) {
+            return;
+        }
+        if self.probe.face >= PROBE_FACES.len() && self.probe.clock.elapsed().as_secs_f32() >= PROBE_INTERVAL {
+            self.probe.face = 0;
+            self.probe.center = frame.cam_pos;
+            self.probe.clock = std::time::Instant::now();
+        }
+        if self.probe.face < PROBE_FACES.len() {
+            let probe = &self.probe;
+            let view_proj = probe.face_view_proj(probe.face);
+            let probe_data = GlobalUniform {
+                view_proj: view_proj.to_cols_array(),
+                light_view_proj: frame.light_view_proj.to_cols_array(),
+                cam_pos: [probe.center.x, probe.center.y, probe.center.z, frame.ground],
+                sun_dir: [frame.sun_dir.x, frame.sun_dir.y, frame.sun_dir.z, 0.0],
+                fog: self.fog,
+            };
+            self.queue.write_buffer(&probe.global_buf, 0, bytemuck::cast_slice(&[probe_data]));
+            {
+                let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Probe Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &probe.face_views[probe.face],
+                        resolve_target: None,
+                        ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.02, g: 0.03, b: 0.05, a: 1.0 }), store: wgpu::StoreOp::Store },
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment { view: &probe.depth, depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(FAR_DEPTH), store: wgpu::StoreOp::Store }), stencil_ops: None }),
+                    timestamp_writes: None, occlusion_query_set: None,
+                });
+                self.draw_scene(&mut pass, &probe.global_bind, &crate::common::Frustum::from_matrix(view_proj));
+            }
+            self.probe.face += 1;
+            if self.probe.face == PROBE_FACES.len() {
+                enc.copy_texture_to_texture(self.probe.faces.as_image_copy(), self.probe.cube.as_image_copy(), self.probe.faces.size());
+            }
+        }
+    }
+
+    // the world, once per eye in stereo
+    fn main_pass(&mut self, enc: &mut wgpu::CommandEncoder, frame: &PassContext) {
+        let (controller, cull_frustum, frustum) = (frame.controller, &frame.cull_frustum, &frame.frustum);
+        let (stereo, width, height) = (frame.stereo, frame.width, frame.height);
+        // chunks and lods in view, nearest first so the depth test throws away what's behind before it's shaded.
+        // measured to the bounding sphere, a big lod around the camera is as near as it gets
+        let mut visible: Vec<&ChunkMesh> = self.world_meshes()
+            .filter(|mesh| cull_frustum.intersects_obb(&mesh.bounds))
+            .collect();
+        visible.sort_by(|a, b| (a.center.distance(frame.cam_pos) - a.radius).total_cmp(&(b.center.distance(frame.cam_pos) - b.radius)));
+        let rendered_lods = visible.iter().filter(|mesh| mesh.lod > 0.0).count();
+        let rendered_chunks = visible.len() - rendered_lods;
+        let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+
+        label: None, color_attachments: &[Some(wgpu::RenderPassColorAttachment { 
+            view: frame.view, 
+            resolve_target: None, 
+            ops: wgpu::Operations { 
+                // the sky covers it, overdraw counts up from black
+                load: wgpu::LoadOp::Clear(if self.render_mode == RenderMode::Overdraw { wgpu::Color::BLACK } else { wgpu::Color { r: 0.02, g: 0.03, b: 0.05, a: 1.0 } }),
+                store: wgpu::StoreOp::Store 
+            } 
+        })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment { view: &self.depth, depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(FAR_DEPTH), store: wgpu::StoreOp::Store }), stencil_ops: None }),
+            timestamp_writes: None, occlusion_query_set: None,
+        });
+
+        // the world and crosshair are drawn once per eye, the other overlays once for the whole window
+        let eye_count = if stereo { 2 } else { 1 };
+        for eye in 0..eye_count {
+            let global = if eye == 0 { &self.global_bind } else { &self.global_bind_right };
+            if stereo { pass.set_viewport(eye as f32 * width / 2.0, 0.0, width / 2.0, height, 0.0, 1.0); }
+
+            // SKY first, the world covers it where there's ground. overdraw counts only the world
+            if self.render_mode != RenderMode::Overdraw {
+                pass.set_pipeline(self.pipelines.get(SKY));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_bind_group(1, &self.sky_local_binds[eye], &[]);
+                pass.set_vertex_buffer(0, self.fade_v_buf.slice(..));
+                pass.set_index_buffer(self.help_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..6, 0, 0..1);
+            }
+        
+            pass.set_pipeline(self.pipelines.get(if self.render_mode == RenderMode::Overdraw { OVERDRAW } else { mesh_key(controller.is_wireframe) }));
+        
+            pass.set_bind_group(0, global, &[]);
+        
+            // DRAW CHUNKS AND LODS
+            for mesh in &visible {
+                pass.set_bind_group(1, &mesh.bind_group, &[]);
+                pass.set_vertex_buffer(0, mesh.v_buf.slice(..));
+                pass.set_index_buffer(mesh.i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..mesh.num_inds, 0, 0..1);
             }
 
             // DRAW DYING ANIMATIONS
             for state in self.animator.dying_chunks.values() {
-                if frustum.intersects_sphere(state.mesh.center, state.mesh.radius) {
+                if frustum.intersects_obb(&state.mesh.bounds) {
                     pass.set_bind_group(1, &state.mesh.bind_group, &[]);
                     pass.set_vertex_buffer(0, state.mesh.v_buf.slice(..));
                     pass.set_index_buffer(state.mesh.i_buf.slice(..), wgpu::IndexFormat::Uint32);
@@ -1211,18 +3175,97 @@ if controller.show_collisions {
                 }
             }
 
-            if !controller.first_person {
-                if controller.is_wireframe { pass.set_pipeline(&self.pipeline_wire); } 
-                else { pass.set_pipeline(&self.pipeline_fill); }
+            if !self.screens.is_empty() {
+                pass.set_pipeline(self.pipelines.get(SCREEN));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_vertex_buffer(0, self.screen_v_buf.slice(..));
+                pass.set_index_buffer(self.screen_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                for (i, camera) in self.screens.iter().enumerate() {
+                    let Some(target) = self.camera_targets.get(*camera) else { continue };
+                    pass.set_bind_group(1, &target.screen_bind, &[]);
+                    let first = i as u32 * 6;
+                    pass.draw_indexed(first..first + 6, 0, 0..1);
+                }
+            }
+
+            if !self.sign_order.is_empty() {
+                pass.set_pipeline(self.pipelines.get(SCREEN));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_vertex_buffer(0, self.sign_v_buf.slice(..));
+                pass.set_index_buffer(self.sign_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                for (i, id) in self.sign_order.iter().enumerate() {
+                    let Some((_, bind)) = self.sign_textures.get(id) else { continue };
+                    pass.set_bind_group(1, bind, &[]);
+                    let first = i as u32 * 6;
+                    pass.draw_indexed(first..first + 6, 0, 0..1);
+                }
+            }
+
+            if !controller.first_person || controller.chase.is_some() {
+                pass.set_pipeline(self.pipelines.get(mesh_key(controller.is_wireframe)));
                 pass.set_bind_group(1, &self.local_bind_player, &[]);
                 pass.set_vertex_buffer(0, self.player_v_buf.slice(..));
                 pass.set_index_buffer(self.player_i_buf.slice(..), wgpu::IndexFormat::Uint32);
                 pass.draw_indexed(0..self.player_inds, 0, 0..1);
             }
 
+            // other players and mobs, a draw call per model however many there are
+            pass.set_pipeline(self.pipelines.get(instanced_key(controller.is_wireframe)));
+            pass.set_bind_group(0, global, &[]);
+            pass.set_bind_group(1, &self.local_bind_identity, &[]);
+            self.remote_players.draw(&mut pass);
+            for mesh in &self.mob_meshes {
+                mesh.draw(&mut pass);
+            }
+
+            if self.rope_drawn {
+                pass.set_pipeline(self.pipelines.get(LINE));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_bind_group(1, &self.local_bind_identity, &[]);
+                pass.set_vertex_buffer(0, self.rope_v_buf.slice(..));
+                pass.set_index_buffer(self.rope_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..2, 0, 0..1);
+            }
+
+            if self.frozen_frustum.is_some() {
+                pass.set_pipeline(self.pipelines.get(LINE));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_bind_group(1, &self.local_bind_identity, &[]);
+                pass.set_vertex_buffer(0, self.gizmo_v_buf.slice(..));
+                pass.set_index_buffer(self.gizmo_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..(BOX_EDGES.len() * 4) as u32, 0, 0..1);
+            }
+
+            if self.spark_inds > 0 {
+                pass.set_pipeline(self.pipelines.get(LINE));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_bind_group(1, &self.local_bind_identity, &[]);
+                pass.set_vertex_buffer(0, self.spark_v_buf.slice(..));
+                pass.set_index_buffer(self.spark_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..self.spark_inds, 0, 0..1);
+            }
+
+            if self.beacon_inds > 0 {
+                pass.set_pipeline(self.pipelines.get(UI));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_bind_group(1, &self.local_bind_identity, &[]);
+                pass.set_vertex_buffer(0, self.beacon_v_buf.slice(..));
+                pass.set_index_buffer(self.beacon_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..self.beacon_inds, 0, 0..1);
+            }
+
+            if self.grid_inds > 0 {
+                pass.set_pipeline(self.pipelines.get(LINE));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_bind_group(1, &self.local_bind_identity, &[]);
+                pass.set_vertex_buffer(0, self.grid_v_buf.slice(..));
+                pass.set_index_buffer(self.grid_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..self.grid_inds, 0, 0..1);
+            }
+
             if self.collision_inds > 0 {
-                pass.set_pipeline(&self.pipeline_line); // Use line pipeline
-                pass.set_bind_group(0, &self.global_bind, &[]);
+                pass.set_pipeline(self.pipelines.get(LINE)); // Use line pipeline
+                pass.set_bind_group(0, global, &[]);
                 pass.set_bind_group(1, &self.local_bind_identity, &[]);
                 pass.set_vertex_buffer(0, self.collision_v_buf.slice(..));
                 pass.set_index_buffer(self.collision_i_buf.slice(..), wgpu::IndexFormat::Uint32);
@@ -1232,127 +3275,407 @@ if controller.show_collisions {
 
 
             if self.cursor_inds > 0 {
-                pass.set_pipeline(&self.pipeline_fill); 
-                pass.set_bind_group(0, &self.global_bind, &[]); 
+                pass.set_pipeline(self.pipelines.get(FILL)); 
+                pass.set_bind_group(0, global, &[]); 
                 pass.set_bind_group(1, &self.local_bind_identity, &[]); 
                 pass.set_vertex_buffer(0, self.cursor_v_buf.slice(..));
                 pass.set_index_buffer(self.cursor_i_buf.slice(..), wgpu::IndexFormat::Uint32);
                 pass.draw_indexed(0..self.cursor_inds, 0, 0..1);
             }
 
-            if controller.first_person {
-                pass.set_pipeline(&self.pipeline_line);
+            if self.ghost_inds > 0 {
+                pass.set_pipeline(self.pipelines.get(FILL));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_bind_group(1, &self.ghost_local_bind, &[]);
+                pass.set_vertex_buffer(0, self.ghost_v_buf.slice(..));
+                pass.set_index_buffer(self.ghost_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..self.ghost_inds, 0, 0..1);
+            }
+
+            if self.blueprint_inds > 0 {
+                pass.set_pipeline(self.pipelines.get(FILL));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_bind_group(1, &self.ghost_local_bind, &[]);
+                pass.set_vertex_buffer(0, self.blueprint_v_buf.slice(..));
+                pass.set_index_buffer(self.blueprint_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..self.blueprint_inds, 0, 0..1);
+            }
+
+            if self.select_inds > 0 {
+                pass.set_pipeline(self.pipelines.get(FILL));
+                pass.set_bind_group(0, global, &[]);
+                pass.set_bind_group(1, &self.local_bind_identity, &[]);
+                pass.set_vertex_buffer(0, self.select_v_buf.slice(..));
+                pass.set_index_buffer(self.select_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..self.select_inds, 0, 0..1);
+            }
+
+            if controller.first_person && self.cross_inds > 0 {
+                pass.set_pipeline(self.pipelines.get(UI));
                 pass.set_bind_group(0, &self.global_bind_identity, &[]);
                 pass.set_bind_group(1, &self.local_bind_identity, &[]); 
                 pass.set_vertex_buffer(0, self.cross_v_buf.slice(..));
                 pass.set_index_buffer(self.cross_i_buf.slice(..), wgpu::IndexFormat::Uint32);
                 pass.draw_indexed(0..self.cross_inds, 0, 0..1);
             }
+        }
+        drop(pass);
+        self.rendered = (rendered_chunks, rendered_lods);
+    }
 
-            if self.console_inds > 0 {
-                pass.set_pipeline(&self.pipeline_ui);
-                pass.set_bind_group(0, &self.global_bind_identity, &[]); 
-                pass.set_bind_group(1, &self.local_bind_identity, &[]); 
-                pass.set_vertex_buffer(0, self.console_v_buf.slice(..));
-                pass.set_index_buffer(self.console_i_buf.slice(..), wgpu::IndexFormat::Uint32);
-                pass.draw_indexed(0..self.console_inds, 0, 0..1);
-            }
+    // overlays over the whole window: bars, flashes, the touch stick, help and the console
+    fn ui_pass(&mut self, enc: &mut wgpu::CommandEncoder, frame: &PassContext) {
+        let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("UI Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: frame.view,
+                resolve_target: None,
+                ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: wgpu::StoreOp::Store },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment { view: &self.depth, depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Load, store: wgpu::StoreOp::Store }), stencil_ops: None }),
+            timestamp_writes: None, occlusion_query_set: None,
+        });
+        if self.hotbar_inds > 0 {
+            pass.set_pipeline(self.pipelines.get(UI));
+            pass.set_bind_group(0, &self.global_bind_identity, &[]);
+            pass.set_bind_group(1, &self.local_bind_identity, &[]);
+            pass.set_vertex_buffer(0, self.hotbar_v_buf.slice(..));
+            pass.set_index_buffer(self.hotbar_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..self.hotbar_inds, 0, 0..1);
         }
 
-        // --- FPS CALCULATION ---
-        self.frame_count += 1;
-        let now = std::time::Instant::now();
-        if now.duration_since(self.last_fps_time).as_secs_f32() >= 1.0 {
-            self.current_fps = self.frame_count;
-            self.frame_count = 0;
-            self.last_fps_time = now;
+        if self.bar_inds > 0 {
+            pass.set_pipeline(self.pipelines.get(UI));
+            pass.set_bind_group(0, &self.global_bind_identity, &[]);
+            pass.set_bind_group(1, &self.local_bind_identity, &[]);
+            pass.set_vertex_buffer(0, self.bar_v_buf.slice(..));
+            pass.set_index_buffer(self.bar_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..self.bar_inds, 0, 0..1);
         }
 
-        // --- PASS 3: TEXT RENDER ---
-        // run this pass every frame to show FPS
-        {
-            let mut text_buffers = Vec::new();
-            if console.height_fraction > 0.0 {
-                let console_pixel_height = (self.config.height as f32 / 2.0) * console.height_fraction;
-                let start_y = console_pixel_height - 40.0;
-                let line_height = 20.0;
-                
-                for (i, (line_text, color)) in console.history.iter().rev().enumerate() {
-                    let y = start_y - (i as f32 * line_height);
-                    if y < 0.0 { break; } 
-                    
-                    let mut buffer = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
-                    buffer.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
-                    buffer.set_text(&mut self.font_system, line_text, Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(
-                        (color[0] * 255.0) as u8, 
-                        (color[1] * 255.0) as u8, 
-                        (color[2] * 255.0) as u8
-                    )), Shaping::Advanced);
-                    text_buffers.push((buffer, y));
+        if self.flash_drawn {
+            pass.set_pipeline(self.pipelines.get(UI));
+            pass.set_bind_group(0, &self.global_bind_identity, &[]);
+            pass.set_bind_group(1, &self.flash_local_bind, &[]);
+            pass.set_vertex_buffer(0, self.flash_v_buf.slice(..));
+            pass.set_index_buffer(self.help_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..6, 0, 0..1);
+        }
+
+        if self.fade_drawn {
+            pass.set_pipeline(self.pipelines.get(UI));
+            pass.set_bind_group(0, &self.global_bind_identity, &[]);
+            pass.set_bind_group(1, &self.fade_local_bind, &[]);
+            pass.set_vertex_buffer(0, self.fade_v_buf.slice(..));
+            pass.set_index_buffer(self.help_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..6, 0, 0..1);
+        }
+
+        if self.touch_inds > 0 {
+            pass.set_pipeline(self.pipelines.get(UI));
+            pass.set_bind_group(0, &self.global_bind_identity, &[]);
+            pass.set_bind_group(1, &self.local_bind_identity, &[]);
+            pass.set_vertex_buffer(0, self.touch_v_buf.slice(..));
+            pass.set_index_buffer(self.touch_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..self.touch_inds, 0, 0..1);
+        }
+
+        if frame.controller.show_help {
+            pass.set_pipeline(self.pipelines.get(UI));
+            pass.set_bind_group(0, &self.global_bind_identity, &[]); 
+            pass.set_bind_group(1, &self.local_bind_identity, &[]); 
+            pass.set_vertex_buffer(0, self.help_v_buf.slice(..));
+            pass.set_index_buffer(self.help_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..6, 0, 0..1);
+        }
+
+        if self.menu.is_some() {
+            pass.set_pipeline(self.pipelines.get(UI));
+            pass.set_bind_group(0, &self.global_bind_identity, &[]);
+            pass.set_bind_group(1, &self.local_bind_identity, &[]);
+            pass.set_vertex_buffer(0, self.menu_v_buf.slice(..));
+            pass.set_index_buffer(self.help_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..6, 0, 0..1);
+        }
+
+        if self.console_inds > 0 {
+            pass.set_pipeline(self.pipelines.get(UI));
+            pass.set_bind_group(0, &self.global_bind_identity, &[]); 
+            pass.set_bind_group(1, &self.console_local_bind, &[]); 
+            pass.set_vertex_buffer(0, self.console_v_buf.slice(..));
+            pass.set_index_buffer(self.console_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..self.console_inds, 0, 0..1);
+        }
+    }
+
+    // fog still comes from the player's camera, so the debug view shows it the way they see it
+    fn debug_view_pass(&mut self, enc: &mut wgpu::CommandEncoder, frame: &PassContext) {
+        if let (Some(debug), Some(debug_target)) = (&self.debug_view, &frame.debug_target) {
+            let debug_data = GlobalUniform {
+                view_proj: debug.view_proj(frame.player, frame.light_view_proj).to_cols_array(),
+                light_view_proj: frame.light_view_proj.to_cols_array(),
+                cam_pos: [frame.cam_pos.x, frame.cam_pos.y, frame.cam_pos.z, frame.ground],
+                sun_dir: [frame.sun_dir.x, frame.sun_dir.y, frame.sun_dir.z, 0.0],
+                fog: self.fog,
+            };
+            self.queue.write_buffer(&debug.global_buf, 0, bytemuck::cast_slice(&[debug_data]));
+            let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Debug View Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: debug_target,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.02, g: 0.03, b: 0.05, a: 1.0 }), store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment { view: &debug.depth, depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(FAR_DEPTH), store: wgpu::StoreOp::Store }), stencil_ops: None }),
+                timestamp_writes: None, occlusion_query_set: None,
+            });
+
+            pass.set_pipeline(self.pipelines.get(mesh_key(frame.controller.is_wireframe)));
+            pass.set_bind_group(0, &debug.global_bind, &[]);
+
+            // same culling as the main view, so gaps around the player are what the main camera skipped
+            for mesh in self.world_meshes() {
+                if frame.cull_frustum.intersects_obb(&mesh.bounds) {
+                    pass.set_bind_group(1, &mesh.bind_group, &[]);
+                    pass.set_vertex_buffer(0, mesh.v_buf.slice(..));
+                    pass.set_index_buffer(mesh.i_buf.slice(..), wgpu::IndexFormat::Uint32);
+                    pass.draw_indexed(0..mesh.num_inds, 0, 0..1);
                 }
+            }
 
-                let input_y = console_pixel_height - 20.0;
-                let mut input_buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
-                input_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
-                let time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
-                let cursor = if (time / 500) % 2 == 0 { "_" } else { " " };
-                input_buf.set_text(&mut self.font_system, &format!("> {}{}", console.input_buffer, cursor), Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(255, 255, 0)), Shaping::Advanced);
-                text_buffers.push((input_buf, input_y));
+            // the player is always drawn here, it's the only way to tell where they are from above
+            pass.set_bind_group(1, &self.local_bind_player, &[]);
+            pass.set_vertex_buffer(0, self.player_v_buf.slice(..));
+            pass.set_index_buffer(self.player_i_buf.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..self.player_inds, 0, 0..1);
+        }
+    }
+
+    // console, hud text, name tags and help, over everything else. runs every frame to show the fps
+    fn text_pass(&mut self, enc: &mut wgpu::CommandEncoder, frame: &PassContext) {
+        let (controller, player, console, settings, saving, mvp) = (frame.controller, frame.player, frame.console, frame.settings, frame.saving, frame.mvp);
+        let (rendered_chunks, rendered_lods) = self.rendered;
+        let mut text_buffers = Vec::new();
+        if console.height_fraction > 0.0 {
+            let console_pixel_height = console.pixel_height(self.config.height as f32);
+            
+            for (i, (line_text, color)) in console.history.iter().enumerate().rev() {
+                let color = settings.palette.apply(*color);
+                let y = console.line_top(i, self.config.height as f32);
+                if y < 0.0 { break; } 
+                
+                let mut buffer = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+                buffer.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+                buffer.set_text(&mut self.font_system, line_text, Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(
+                    (color[0] * 255.0) as u8, 
+                    (color[1] * 255.0) as u8, 
+                    (color[2] * 255.0) as u8
+                )), Shaping::Advanced);
+                text_buffers.push((buffer, y));
             }
 
-            // 2. FPS Text
-            let mut fps_buffer = Buffer::new(&mut self.font_system, Metrics::new(20.0, 24.0));
-            fps_buffer.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
-            fps_buffer.set_text(
+            let input_y = console_pixel_height - CONSOLE_LINE_HEIGHT;
+            let mut input_buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+            input_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            let time = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+            let cursor = if settings.reduce_motion || (time / 500).is_multiple_of(2) { "_" } else { " " };
+            input_buf.set_text(&mut self.font_system, &format!("> {}{}", console.input_buffer, cursor), Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(255, 255, 0)), Shaping::Advanced);
+            text_buffers.push((input_buf, input_y));
+        }
+
+        // 2. FPS Text
+        let mut fps_buffer = Buffer::new(&mut self.font_system, Metrics::new(20.0, 24.0));
+        fps_buffer.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+        fps_buffer.set_text(
+            &mut self.font_system, 
+            &tr!("hud.fps", self.current_fps), 
+            Attrs::new().family(Family::Monospace).color(Self::text_color(settings.palette.apply([0.0, 1.0, 0.0]))), 
+            Shaping::Advanced
+        );
+
+
+      
+        let mut debug_buf = Buffer::new(&mut self.font_system, Metrics::new(14.0, 18.0));
+        
+        if player.debug_mode {
+            let status = if controller.freeze_culling { tr!("hud.culling_frozen") } else { tr!("hud.culling_active") };
+            let mut info = tr!(
+                "hud.debug",
+                status,
+                rendered_chunks, self.chunks.len(),
+                rendered_lods, self.lod_chunks.len(),
+                self.load_queue.len()
+            );
+            let slowest = self.mesh_profile.slowest();
+            if !slowest.is_empty() {
+                info.push_str(&tr!("hud.slowest_meshes", self.mesh_profile.meshed));
+                for timing in slowest {
+                    info.push('\n');
+                    info.push_str(&timing.describe());
+                }
+            }
+
+            debug_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            debug_buf.set_text(
                 &mut self.font_system, 
-                &format!("FPS: {}", self.current_fps), 
-                Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(0, 255, 0)), 
+                &info, 
+                Attrs::new().family(Family::Monospace).color(Self::text_color(settings.palette.apply([0.8, 0.8, 0.8]))), 
+                Shaping::Advanced
+            );
+        }
+       
+        // 3. Autosave indicator
+        let mut saving_buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+        if saving {
+            saving_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            saving_buf.set_text(
+                &mut self.font_system,
+                &tr!("hud.saving"),
+                Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(255, 255, 255)),
                 Shaping::Advanced
             );
+        }
+       
+        // brush size while editing in orbit mode
+        let mut brush_buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+        if !controller.first_person {
+            brush_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            brush_buf.set_text(
+                &mut self.font_system,
+                &tr!("hud.brush", controller.brush_size),
+                Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(255, 255, 255)),
+                Shaping::Advanced
+            );
+        }
 
+        // the block being placed over the hotbar, and how many of each are left in the corner of its slot
+        let (slots, selected) = self.hotbar;
+        let block_label = slots[selected].map_or_else(|| tr!("hud.block_empty"), |s| tr!("hud.block", s.block.name()));
+        let mut block_buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+        block_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+        block_buf.set_text(&mut self.font_system, &block_label, Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(255, 255, 255)), Shaping::Advanced);
+        let mut count_buffers = Vec::new();
+        for (i, stack) in slots.iter().enumerate() {
+            let Some(stack) = stack else { continue };
+            let (left, top) = self.slot_rect(i);
+            let count = stack.count.to_string();
+            let mut buf = Buffer::new(&mut self.font_system, Metrics::new(14.0, 16.0));
+            buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            buf.set_text(&mut self.font_system, &count, Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(255, 255, 255)), Shaping::Advanced);
+            // monospace glyphs are ~8px wide at this size
+            count_buffers.push((buf, left + SLOT_SIZE - SLOT_BORDER - 2.0 - count.len() as f32 * 8.0, top + SLOT_SIZE - SLOT_BORDER - 16.0));
+        }
+       
+        // temperature under the health bar
+        let mut temperature_buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+        let temperature = tr!("hud.temperature", format!("{:.0}", player.temperature.celsius));
+        let show_temperature = player.health.enabled && !player.spectator;
+        if show_temperature {
+            let color = if player.temperature.celsius < COLD_LIMIT { [0.4, 0.7, 1.0] } else if player.temperature.celsius > HEAT_LIMIT { [1.0, 0.5, 0.1] } else { [1.0; 3] };
+            temperature_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            temperature_buf.set_text(&mut self.font_system, &temperature, Attrs::new().family(Family::Monospace).color(Self::text_color(settings.palette.apply(color))), Shaping::Advanced);
+        }
 
-          
-            let mut debug_buf = Buffer::new(&mut self.font_system, Metrics::new(14.0, 18.0));
-            
-            if player.debug_mode {
-                let status = if controller.freeze_culling { "FROZEN" } else { "ACTIVE" };
-                let info = format!(
-                    "Culling: {}\nChunks: {} / {}\nLODs:   {} / {}\nQueue:  {}", 
-                    status,
-                    rendered_chunks, self.chunks.len(),
-                    rendered_lods, self.lod_chunks.len(),
-                    self.load_queue.len()
-                );
-
-                debug_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
-                debug_buf.set_text(
-                    &mut self.font_system, 
-                    &info, 
-                    Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(200, 200, 200)), 
-                    Shaping::Advanced
-                );
+        // prefab being placed, and the radial picker around the screen centre while Tab is held
+        let mut prefab_buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+        if let Some(name) = &self.prefab_label {
+            prefab_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            prefab_buf.set_text(
+                &mut self.font_system,
+                &tr!("hud.prefab", name),
+                Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(255, 255, 255)),
+                Shaping::Advanced
+            );
+        }
+        let mut interact_buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+        if let Some(text) = &self.interact_label {
+            interact_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            interact_buf.set_text(&mut self.font_system, text, Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(255, 255, 255)), Shaping::Advanced);
+        }
+        let mut blueprint_buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+        if let Some(text) = &self.blueprint_label {
+            blueprint_buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            blueprint_buf.set_text(&mut self.font_system, text, Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(255, 255, 255)), Shaping::Advanced);
+        }
+        let mut radial_buffers = Vec::new();
+        let (cx, cy) = (self.config.width as f32 / 2.0, self.config.height as f32 / 2.0);
+        for (i, (name, hovered)) in self.radial_items.iter().enumerate() {
+            let angle = i as f32 / self.radial_items.len() as f32 * std::f32::consts::TAU;
+            let color = if *hovered { Self::text_color(settings.palette.apply([1.0, 1.0, 0.0])) } else { glyphon::Color::rgb(220, 220, 220) };
+            let mut buf = Buffer::new(&mut self.font_system, Metrics::new(18.0, 22.0));
+            buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            buf.set_text(&mut self.font_system, name, Attrs::new().family(Family::Monospace).color(color), Shaping::Advanced);
+            // monospace glyphs are ~10px wide at this size
+            radial_buffers.push((buf, cx + angle.sin() * RADIAL_RADIUS - name.chars().count() as f32 * 5.0, cy - angle.cos() * RADIAL_RADIUS - 11.0));
+        }
+       
+        // 4. Name tags over other players, and marker labels
+        let mut tag_buffers = Vec::new();
+        let tags = self.remote_tags.iter().map(|(name, pos)| (name, pos, [1.0; 3])).chain(self.marker_tags.iter().map(|(text, pos, color)| (text, pos, *color)));
+        for (name, pos, color) in tags {
+            let clip = mvp * pos.extend(1.0);
+            if clip.w <= 0.0 { continue; }
+            let ndc = clip.truncate() / clip.w;
+            if ndc.x.abs() > 1.0 || ndc.y.abs() > 1.0 { continue; }
+
+            let x = (ndc.x * 0.5 + 0.5) * self.config.width as f32;
+            let y = (0.5 - ndc.y * 0.5) * self.config.height as f32;
+            let mut buf = Buffer::new(&mut self.font_system, Metrics::new(14.0, 18.0));
+            buf.set_size(&mut self.font_system, self.config.width as f32, self.config.height as f32);
+            buf.set_text(&mut self.font_system, name, Attrs::new().family(Family::Monospace).color(Self::text_color(settings.palette.apply(color))), Shaping::Advanced);
+            // roughly centred, monospace glyphs are ~8px wide at this size
+            tag_buffers.push((buf, x - name.len() as f32 * 4.0, y));
+        }
+       
+        // 5. F1 help overlay
+        let mut help_buf = Buffer::new(&mut self.font_system, Metrics::new(14.0, 18.0));
+        let help_rect = self.help_rect();
+        if controller.show_help {
+            help_buf.set_size(&mut self.font_system, help_rect.2, help_rect.3);
+            help_buf.set_text(
+                &mut self.font_system,
+                &Self::help_text(&controller.bindings),
+                Attrs::new().family(Family::Monospace).color(glyphon::Color::rgb(220, 220, 220)),
+                Shaping::Advanced
+            );
+        }
+       
+        // 6. settings or trade menu, the selected row highlighted
+        let mut menu_buffers = Vec::new();
+        if let Some(menu) = &self.menu {
+            let (left, top, pw, _) = self.menu_rect(menu.lines.len());
+            let blank = (String::new(), glyphon::Color::rgb(0, 0, 0));
+            let mut rows = vec![(menu.title.clone(), glyphon::Color::rgb(255, 255, 255)), blank.clone()];
+            rows.extend(menu.lines.iter().map(|(text, selected)| {
+                let color = if *selected { Self::text_color(settings.palette.apply([1.0, 1.0, 0.0])) } else { glyphon::Color::rgb(220, 220, 220) };
+                (format!("{} {}", if *selected { ">" } else { " " }, text), color)
+            }));
+            rows.extend([blank, (menu.hint.clone(), glyphon::Color::rgb(160, 160, 160))]);
+            for (i, (text, color)) in rows.into_iter().enumerate() {
+                let mut buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, MENU_LINE));
+                buf.set_size(&mut self.font_system, pw, MENU_LINE);
+                buf.set_text(&mut self.font_system, &text, Attrs::new().family(Family::Monospace).color(color), Shaping::Advanced);
+                menu_buffers.push((buf, left + 15.0, top + 15.0 + i as f32 * MENU_LINE));
             }
-           
-            // create text areas
-            let mut text_areas: Vec<TextArea> = text_buffers.iter().map(|(buf, y)| {
-                TextArea {
-                    buffer: buf,
-                    left: 10.0,
-                    top: *y,
-                    scale: 1.0,
-                    bounds: TextBounds {
-                        left: 0, top: 0,
-                        right: self.config.width as i32,
-                        bottom: self.config.height as i32,
-                    },
-                    default_color: glyphon::Color::rgb(255, 255, 255),
-                }
-            }).collect();
+        }
 
-            text_areas.push(TextArea {
-                buffer: &fps_buffer,
-                left: self.config.width as f32 - 120.0, 
-                top: 10.0,
+        // 7. toasts down from the top centre, fading in and out
+        let mut toast_buffers = Vec::new();
+        for (i, (text, color, alpha)) in self.toasts.iter().enumerate() {
+            let [r, g, b] = settings.palette.apply(*color).map(|c| (c * 255.0) as u8);
+            let mut buf = Buffer::new(&mut self.font_system, Metrics::new(16.0, 20.0));
+            buf.set_size(&mut self.font_system, self.config.width as f32, 20.0);
+            buf.set_text(&mut self.font_system, text, Attrs::new().family(Family::Monospace).color(glyphon::Color::rgba(r, g, b, (alpha * 255.0) as u8)), Shaping::Advanced);
+            // roughly centred, monospace glyphs are ~9px wide at this size
+            toast_buffers.push((buf, (self.config.width as f32 - text.chars().count() as f32 * 9.0) * 0.5, 12.0 + i as f32 * 22.0));
+        }
+
+        // create text areas
+        let mut text_areas: Vec<TextArea> = text_buffers.iter().map(|(buf, y)| {
+            TextArea {
+                buffer: buf,
+                left: 10.0,
+                top: *y,
                 scale: 1.0,
                 bounds: TextBounds {
                     left: 0, top: 0,
@@ -1360,49 +3683,245 @@ if controller.show_collisions {
                     bottom: self.config.height as i32,
                 },
                 default_color: glyphon::Color::rgb(255, 255, 255),
+            }
+        }).collect();
+
+        text_areas.push(TextArea {
+            buffer: &fps_buffer,
+            left: self.config.width as f32 - 120.0, 
+            top: 10.0,
+            scale: 1.0,
+            bounds: TextBounds {
+                left: 0, top: 0,
+                right: self.config.width as i32,
+                bottom: self.config.height as i32,
+            },
+            default_color: glyphon::Color::rgb(255, 255, 255),
+        });
+
+        if player.debug_mode {
+            text_areas.push(TextArea {
+                buffer: &debug_buf,
+                left: self.config.width as f32 - 180.0,
+                top: 40.0,
+                scale: 1.0,
+                bounds: TextBounds { left: 0, top: 0, right: self.config.width as i32, bottom: self.config.height as i32 },
+                default_color: glyphon::Color::rgb(255, 255, 255),
             });
+        }
 
-            if player.debug_mode {
-                text_areas.push(TextArea {
-                    buffer: &debug_buf,
-                    left: self.config.width as f32 - 180.0,
-                    top: 40.0,
-                    scale: 1.0,
-                    bounds: TextBounds { left: 0, top: 0, right: self.config.width as i32, bottom: self.config.height as i32 },
-                    default_color: glyphon::Color::rgb(255, 255, 255),
-                });
-            }
+        for (buf, x, y) in tag_buffers.iter().chain(&radial_buffers).chain(&count_buffers).chain(&menu_buffers).chain(&toast_buffers) {
+            text_areas.push(TextArea {
+                buffer: buf,
+                left: *x,
+                top: *y,
+                scale: 1.0,
+                bounds: TextBounds { left: 0, top: 0, right: self.config.width as i32, bottom: self.config.height as i32 },
+                default_color: glyphon::Color::rgb(255, 255, 255),
+            });
+        }
 
-            self.text_renderer.prepare(
-                &self.device,
-                &self.queue,
-                &mut self.font_system,
-                &mut self.text_atlas,
-                Resolution { width: self.config.width, height: self.config.height },
-                text_areas,
-                &mut self.swash_cache
-            ).unwrap();
+        if controller.show_help {
+            let (left, top, pw, ph) = help_rect;
+            text_areas.push(TextArea {
+                buffer: &help_buf,
+                left: left + 15.0,
+                top: top + 15.0,
+                scale: 1.0,
+                bounds: TextBounds { left: left as i32, top: top as i32, right: (left + pw) as i32, bottom: (top + ph) as i32 },
+                default_color: glyphon::Color::rgb(255, 255, 255),
+            });
+        }
 
-            let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Text Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Load, 
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None, 
-                timestamp_writes: None,
-                occlusion_query_set: None,
+        if saving {
+            text_areas.push(TextArea {
+                buffer: &saving_buf,
+                left: self.config.width as f32 - 120.0,
+                top: self.config.height as f32 - 30.0,
+                scale: 1.0,
+                bounds: TextBounds { left: 0, top: 0, right: self.config.width as i32, bottom: self.config.height as i32 },
+                default_color: glyphon::Color::rgb(255, 255, 255),
             });
-            
-            self.text_renderer.render(&self.text_atlas, &mut pass).unwrap();
         }
 
-        self.queue.submit(std::iter::once(enc.finish()));
-        out.present();
-        self.text_atlas.trim();
+        if show_temperature {
+            text_areas.push(TextArea {
+                buffer: &temperature_buf,
+                // roughly centred, monospace glyphs are ~9px wide at this size
+                left: (self.config.width as f32 - temperature.chars().count() as f32 * 9.0) * 0.5,
+                top: self.config.height as f32 - 46.0 - HOTBAR_SPACE,
+                scale: 1.0,
+                bounds: TextBounds { left: 0, top: 0, right: self.config.width as i32, bottom: self.config.height as i32 },
+                default_color: glyphon::Color::rgb(255, 255, 255),
+            });
+        }
+
+        text_areas.push(TextArea {
+            buffer: &block_buf,
+            // monospace glyphs are ~9px wide at this size
+            left: (self.config.width as f32 - block_label.chars().count() as f32 * 9.0) * 0.5,
+            top: self.slot_rect(0).1 - 22.0,
+            scale: 1.0,
+            bounds: TextBounds { left: 0, top: 0, right: self.config.width as i32, bottom: self.config.height as i32 },
+            default_color: glyphon::Color::rgb(255, 255, 255),
+        });
+
+        text_areas.push(TextArea {
+            buffer: &brush_buf,
+            left: 10.0,
+            top: self.config.height as f32 - 30.0,
+            scale: 1.0,
+            bounds: TextBounds { left: 0, top: 0, right: self.config.width as i32, bottom: self.config.height as i32 },
+            default_color: glyphon::Color::rgb(255, 255, 255),
+        });
+
+        if self.blueprint_label.is_some() {
+            text_areas.push(TextArea {
+                buffer: &blueprint_buf,
+                left: 10.0,
+                top: self.config.height as f32 - 80.0,
+                scale: 1.0,
+                bounds: TextBounds { left: 0, top: 0, right: self.config.width as i32, bottom: self.config.height as i32 },
+                default_color: glyphon::Color::rgb(255, 255, 255),
+            });
+        }
+
+        if let Some(text) = &self.interact_label {
+            // monospace glyphs are ~9px wide at this size
+            text_areas.push(TextArea {
+                buffer: &interact_buf,
+                left: self.config.width as f32 / 2.0 - text.chars().count() as f32 * 4.5,
+                top: self.config.height as f32 / 2.0 + 30.0,
+                scale: 1.0,
+                bounds: TextBounds { left: 0, top: 0, right: self.config.width as i32, bottom: self.config.height as i32 },
+                default_color: glyphon::Color::rgb(255, 255, 255),
+            });
+        }
+
+        if self.prefab_label.is_some() {
+            text_areas.push(TextArea {
+                buffer: &prefab_buf,
+                left: 10.0,
+                top: self.config.height as f32 - 55.0,
+                scale: 1.0,
+                bounds: TextBounds { left: 0, top: 0, right: self.config.width as i32, bottom: self.config.height as i32 },
+                default_color: glyphon::Color::rgb(255, 255, 255),
+            });
+        }
+
+        self.text_renderer.prepare(
+            &self.device,
+            &self.queue,
+            &mut self.font_system,
+            &mut self.text_atlas,
+            Resolution { width: self.config.width, height: self.config.height },
+            text_areas,
+            &mut self.swash_cache
+        ).unwrap();
+
+        let mut pass = enc.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Text Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: frame.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load, 
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None, 
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        
+        self.text_renderer.render(&self.text_atlas, &mut pass).unwrap();
+    }
+
+    // a copy of the finished frame for the recorder
+    fn capture_pass(&mut self, enc: &mut wgpu::CommandEncoder, frame: &PassContext) {
+        if !frame.record { return; }
+        let (w, h) = (frame.surface.width(), frame.surface.height());
+        if self.frame_readback.as_ref().is_none_or(|r| (r.width, r.height) != (w, h)) {
+            self.frame_readback = Some(Readback::new(&self.device, w, h));
+        }
+        if let Some(readback) = &self.frame_readback { readback.copy_from(enc, frame.surface); }
+    }
+}
+
+// edited blocks and arriving chunks queue a remesh of what they touch, see submit_remeshes
+impl Subscriber for Renderer<'_> {
+    fn on_event(&mut self, event: &GameEvent, _planet: &PlanetData) {
+        match event {
+            GameEvent::BlockMined { id, .. } | GameEvent::BlockPlaced { id, .. } => self.refresh_neighbors(*id),
+            GameEvent::ChunkLoaded(key) => self.refresh_chunk(*key),
+            GameEvent::Blasted { mined } => self.refresh_blocks(mined.iter().map(|(id, _)| *id)),
+            _ => {}
+        }
+    }
+}
+
+// a bar in pixels on a w x h screen, its track and the `fraction` of it that's filled, as two quads
+fn push_bar(verts: &mut Vec<Vertex>, size: (f32, f32), (left, top, width, height): (f32, f32, f32, f32), fraction: f32, color: [f32; 3]) {
+    push_quad(verts, size, (left, top, width, height), BAR_TRACK);
+    push_quad(verts, size, (left, top, width * fraction.clamp(0.0, 1.0), height), color);
+}
+
+// a screen rectangle in pixels as 4 ui vertices
+fn push_quad(verts: &mut Vec<Vertex>, (w, h): (f32, f32), (left, top, width, height): (f32, f32, f32, f32), color: [f32; 3]) {
+    let to_ndc = |x: f32, y: f32| [x / w * 2.0 - 1.0, 1.0 - y / h * 2.0, 0.0];
+    let normal = [0.0, 0.0, 1.0];
+    for (x, y) in [(left, top), (left + width, top), (left, top + height), (left + width, top + height)] {
+        verts.push(Vertex { pos: to_ndc(x, y), color, normal });
+    }
+}
+
+// a thin square tube along each of the 12 edges of a box. corners are ordered by bits: 1 = +u, 2 = +v, 4 = +layer
+fn push_box_outline(verts: &mut Vec<Vertex>, inds: &mut Vec<u32>, corners: [Vec3; 8], color: [f32; 3]) {
+    let edges = [
+        (0,1), (1,3), (3,2), (2,0), 
+        (4,5), (5,7), (7,6), (6,4), 
+        (0,4), (1,5), (2,6), (3,7)  
+    ];
+    let thickness = 0.025; 
+
+    for (start, end) in edges {
+        let idx_base = verts.len() as u32;
+        let a = corners[start];
+        let b = corners[end];
+        let dir = (b - a).normalize();
+        let ref_up = if dir.dot(Vec3::Y).abs() > 0.9 { Vec3::X } else { Vec3::Y };
+        let right = dir.cross(ref_up).normalize() * thickness;
+        let up = dir.cross(right).normalize() * thickness;
+        let offsets = [(-right - up), (right - up), (right + up), (-right + up)];
+        
+        for off in offsets {
+            verts.push(Vertex { pos: (a + off).to_array(), color, normal: [0.0;3] });
+            verts.push(Vertex { pos: (b + off).to_array(), color, normal: [0.0;3] });
+        }
+
+        let faces = [(0,1,3,2), (2,3,5,4), (4,5,7,6), (6,7,1,0)];
+        for (i0, i1, i2, i3) in faces {
+            inds.push(idx_base + i0); inds.push(idx_base + i1); inds.push(idx_base + i2);
+            inds.push(idx_base + i2); inds.push(idx_base + i3); inds.push(idx_base + i0);
+        }
+    }
+}
+
+// one solid block with flat shaded sides, for previews. `grow` scales it up a bit so it doesn't z-fight a real block
+fn push_cube(verts: &mut Vec<Vertex>, inds: &mut Vec<u32>, id: BlockId, res: u32, color: [f32; 3], grow: f32) {
+    let corner = |i: u32| CoordSystem::get_vertex_pos(id.face, id.u + (i & 1), id.v + (i >> 1 & 1), id.layer + (i >> 2 & 1), res);
+    let corners: [Vec3; 8] = std::array::from_fn(|i| corner(i as u32));
+    let center = corners.iter().copied().sum::<Vec3>() / 8.0;
+    let corners = corners.map(|c| center + (c - center) * (1.0 + grow));
+    let sides = [[0, 2, 6, 4], [1, 3, 7, 5], [0, 1, 5, 4], [2, 3, 7, 6], [0, 1, 3, 2], [4, 5, 7, 6]];
+
+    for side in sides {
+        let base = verts.len() as u32;
+        let quad = side.map(|i| corners[i]);
+        let normal = ((quad[0] + quad[1] + quad[2] + quad[3]) / 4.0 - center).normalize_or_zero().to_array();
+        for p in quad {
+            verts.push(Vertex { pos: p.to_array(), color, normal });
+        }
+        inds.extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
     }
 }
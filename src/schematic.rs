@@ -0,0 +1,98 @@
+//schematic.rs
+
+// a box of blocks cut out of one cube face, stored relative to its lowest corner.
+// cells are (u, v, layer) offsets with the block that's there; anything inside the box that isn't listed is air.
+
+use std::io;
+use crate::codec::*;
+use crate::common::{BlockId, BlockType, PlanetData};
+use crate::i18n::tr;
+
+// --- CONSTANTS ---
+// biggest box a schematic may cover, keeps captures and previews cheap
+pub const MAX_VOLUME: u32 = 64 * 64 * 64;
+// what cells of a schematic saved before it kept block types are, and blocks whose plugin is gone
+const FALLBACK_BLOCK: BlockType = BlockType::Stone;
+
+#[derive(Clone, Debug)]
+pub struct Schematic {
+    // extent along u, v and layer
+    pub size: [u32; 3],
+    pub solid: Vec<([u32; 3], BlockType)>,
+}
+
+impl Schematic {
+    // copies everything between two corners, inclusive. both have to be on the same face
+    pub fn capture(planet: &PlanetData, a: BlockId, b: BlockId) -> Result<Self, String> {
+        if a.face != b.face {
+            return Err(tr!("schematic.different_faces"));
+        }
+        let lo = [a.u.min(b.u), a.v.min(b.v), a.layer.min(b.layer)];
+        let hi = [a.u.max(b.u), a.v.max(b.v), a.layer.max(b.layer)];
+        let size = [hi[0] - lo[0] + 1, hi[1] - lo[1] + 1, hi[2] - lo[2] + 1];
+        if size.iter().map(|&s| s as u64).product::<u64>() > MAX_VOLUME as u64 {
+            return Err(tr!("schematic.too_big", MAX_VOLUME));
+        }
+
+        let mut solid = Vec::new();
+        for l in 0..size[2] {
+            for v in 0..size[1] {
+                for u in 0..size[0] {
+                    let id = BlockId { face: a.face, u: lo[0] + u, v: lo[1] + v, layer: lo[2] + l };
+                    if planet.exists(id) { solid.push(([u, v, l], planet.block_type(id))); }
+                }
+            }
+        }
+        Ok(Self { size, solid })
+    }
+
+    // turned a quarter turn at a time around the layer axis
+    pub fn rotated(&self, quarter_turns: u8) -> Self {
+        let mut out = self.clone();
+        for _ in 0..quarter_turns % 4 {
+            let [w, d, h] = out.size;
+            out.solid = out.solid.iter().map(|&([u, v, l], block)| ([d - 1 - v, u, l], block)).collect();
+            out.size = [d, w, h];
+        }
+        out
+    }
+
+    // world block for a cell with the schematic's lowest corner at `origin`, None past the face edge
+    pub fn cell_id(origin: BlockId, cell: [u32; 3], res: u32) -> Option<BlockId> {
+        let id = BlockId { face: origin.face, u: origin.u + cell[0], v: origin.v + cell[1], layer: origin.layer + cell[2] };
+        (id.u < res && id.v < res && id.layer < res).then_some(id)
+    }
+
+    // every solid cell placed at `origin`, with its block
+    pub fn blocks_at(&self, origin: BlockId, res: u32) -> Vec<(BlockId, BlockType)> {
+        self.solid.iter().filter_map(|&(cell, block)| Some((Self::cell_id(origin, cell, res)?, block))).collect()
+    }
+
+    // blocks by name, like the inventory, so custom ones survive plugins loading in another order
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        for s in self.size { put_u32(out, s); }
+        put_u32(out, self.solid.len() as u32);
+        for (cell, block) in &self.solid {
+            for c in cell { put_u32(out, *c); }
+            put_str(out, block.name());
+        }
+    }
+
+    // `typed` is false for schematics saved before cells had a block, which come back as FALLBACK_BLOCK
+    pub fn decode(r: &mut ByteReader, typed: bool) -> io::Result<Self> {
+        let size = [r.u32()?, r.u32()?, r.u32()?];
+        if size.contains(&0) || size.iter().map(|&s| s as u64).product::<u64>() > MAX_VOLUME as u64 {
+            return Err(invalid("schematic has a bad size"));
+        }
+        let count = r.u32()?;
+        if count > MAX_VOLUME { return Err(invalid("schematic has too many blocks")); }
+        let mut solid = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let cell = [r.u32()?, r.u32()?, r.u32()?];
+            if (0..3).any(|i| cell[i] >= size[i]) { return Err(invalid("schematic block is outside its box")); }
+            let block = if typed { BlockType::parse(&r.str()?) } else { None };
+            solid.push((cell, block.unwrap_or(FALLBACK_BLOCK)));
+        }
+        Ok(Self { size, solid })
+    }
+}
@@ -0,0 +1,28 @@
+// in-world monitor screens: a quad showing what an offscreen camera sees.
+// uses the normal vertex layout, with the texture coordinates in the color slot.
+#include "wgsl/global.wgsl"
+
+@group(1) @binding(0) var t_screen: texture_2d<f32>;
+@group(1) @binding(1) var s_screen: sampler;
+
+// slight dimming so a screen doesn't look brighter than the sunlit world around it
+const SCREEN_BRIGHTNESS = 0.9;
+
+struct VertexOut {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(in: VertexIn) -> VertexOut {
+    var out: VertexOut;
+    out.clip_pos = global.view_proj * vec4<f32>(in.pos, 1.0);
+    out.uv = in.color.xy;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    let color = textureSample(t_screen, s_screen, in.uv).rgb;
+    return vec4<f32>(color * SCREEN_BRIGHTNESS, 1.0);
+}
@@ -0,0 +1,534 @@
+//server.rs
+
+// dedicated server: runs the world without a window or GPU.
+// the sim ticks at a fixed rate, clients connect over TCP and stdin doubles as the admin console.
+
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+use std::path::Path;
+use std::sync::mpsc::channel;
+use std::time::{Duration, Instant};
+use glam::{Vec3, Quat};
+use crate::cmd::{Console, CommandContext, Permission};
+use crate::common::{BlockEntity, BlockId, ChunkKey, ChunkMods, PlanetData, CHUNK_SIZE};
+use crate::entity::Player;
+use crate::gen::CoordSystem;
+use crate::physics::Physics;
+use crate::net::{self, Message, Server, ServerEvent, DEFAULT_PORT, PROTOCOL_VERSION};
+use crate::rcon::{self, Rcon};
+use crate::sign;
+use crate::teleporter;
+use crate::marker::{Marker, Markers};
+use crate::world::{Autosave, World, WORLD_DIR};
+use crate::i18n::tr;
+
+// --- CONSTANTS ---
+pub const TICK_RATE: u32 = 20;
+pub const SERVER_CONFIG: &str = "server.cfg";
+
+// movement validation. sprint-flying is move_speed * 10 (50 with defaults) and a long fall gets close to 70,
+// so this only catches speed hacks, not players with a tweaked /move_speed
+const MAX_MOVE_SPEED: f32 = 80.0;
+// extra time allowed per move, packets bunch up on the way so elapsed time alone is too strict
+const MOVE_JITTER: f32 = 0.25;
+// no single update may move further than this, whatever the timing
+const MAX_TELEPORT: f32 = 32.0;
+// how far from where the server has them a player below Operator may edit blocks: the first person reach of the
+// client, from the eye rather than the feet, with room for a move that's still on its way
+const EDIT_REACH: f32 = 12.0;
+
+// --- CONFIG ---
+
+// server.cfg is plain `key = value` lines, # starts a comment:
+//   port = 27015
+//   rcon_port = 27016
+//   rcon_token = change-me
+//   op.alice = 3 s3cret (0 player, 1 moderator, 2 operator, 3 owner, then the secret alice joins with)
+// names aren't authenticated, the secret is: anyone can join as alice, only the one sending it gets her level.
+pub struct ServerConfig {
+    pub port: u16,
+    // remote console is only started when both are set
+    pub rcon_port: Option<u16>,
+    pub rcon_token: Option<String>,
+    // level and secret by name
+    pub ops: HashMap<String, (Permission, String)>,
+}
+
+impl ServerConfig {
+    pub fn load(path: &Path) -> Self {
+        let mut config = Self { port: DEFAULT_PORT, rcon_port: None, rcon_token: None, ops: HashMap::new() };
+        let Ok(text) = std::fs::read_to_string(path) else { return config };
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() { continue; }
+            let Some((key, value)) = line.split_once('=') else {
+                println!("{}:{}: expected key = value", path.display(), i + 1);
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            let ok = match key {
+                "port" => value.parse().map(|p| config.port = p).is_ok(),
+                "rcon_port" => value.parse().map(|p| config.rcon_port = Some(p)).is_ok(),
+                "rcon_token" => { config.rcon_token = Some(value.to_string()); true },
+                _ => match key.strip_prefix("op.") {
+                    Some(name) => match value.split_once(char::is_whitespace) {
+                        Some((level, secret)) if !secret.trim().is_empty() => level.parse::<u8>()
+                            .map(|l| { config.ops.insert(name.to_string(), (Permission::from_level(l), secret.trim().to_string())); })
+                            .is_ok(),
+                        // a level with no secret would go to whoever picks the name
+                        _ => false,
+                    },
+                    None => false,
+                },
+            };
+            if !ok {
+                println!("{}:{}: ignoring '{}'", path.display(), i + 1, line);
+            }
+        }
+        config
+    }
+
+    // an op's level only with their secret, compared in constant time like the rcon token
+    pub fn permission_of(&self, name: &str, secret: &str) -> Permission {
+        match self.ops.get(name) {
+            Some((level, expected)) if rcon::token_matches(secret, expected) => *level,
+            _ => Permission::Player,
+        }
+    }
+}
+
+struct ConnectedPlayer {
+    name: String,
+    permission: Permission,
+    position: Vec3,
+    rotation: Quat,
+    // false until the first PlayerMove, before that the position is meaningless
+    spawned: bool,
+    spectator: bool,
+    // when the last accepted move arrived, to bound how far the next one may go
+    last_move: Instant,
+
+    // what this client has been told about, so only changes in range go over the wire
+    visible_players: HashSet<u32>,
+    known_chunks: HashSet<ChunkKey>,
+}
+
+pub fn run_headless(config: ServerConfig) {
+    let mut console = Console::new();
+    let mut autosave = Autosave::new(WORLD_DIR);
+
+    // the server has no local player; this one only carries the saved spawn for level.dat
+    let mut admin = Player::new();
+    let (mut planet, loaded) = World::open(autosave.dir.as_path(), &mut admin);
+    match loaded {
+        Ok(true) => console.log(&tr!("world.loaded", planet.chunks.len()), [0.0, 1.0, 0.0]),
+        Ok(false) => console.log(&tr!("world.generated"), [0.0, 1.0, 0.0]),
+        Err(e) => console.log(&tr!("world.load_failed", e), [1.0, 0.0, 0.0]),
+    }
+    let mut markers = Markers::load(&autosave.dir).unwrap_or_else(|e| {
+        console.log(&tr!("marker.load_failed", e), [1.0, 0.0, 0.0]);
+        Markers::new()
+    });
+
+    let mut server = match Server::bind(config.port) {
+        Ok(server) => server,
+        Err(e) => {
+            console.log(&tr!("server.bind_failed", config.port, e), [1.0, 0.0, 0.0]);
+            return;
+        }
+    };
+    console.log(&tr!("server.listening", server.port, TICK_RATE), [0.0, 1.0, 0.0]);
+
+    // stdin blocks, so it gets its own thread and hands whole lines to the tick loop
+    let (line_tx, line_rx) = channel::<String>();
+    let stdin_tx = line_tx.clone();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if stdin_tx.send(line).is_err() { break; }
+        }
+    });
+
+    let mut rcon = match (config.rcon_port, config.rcon_token.clone()) {
+        (Some(port), Some(token)) if !token.is_empty() => match Rcon::start(port, token, line_tx) {
+            Ok(rcon) => {
+                console.log(&tr!("rcon.listening", rcon.port), [0.0, 1.0, 0.0]);
+                Some(rcon)
+            }
+            Err(e) => {
+                console.log(&tr!("rcon.start_failed", port, e), [1.0, 0.0, 0.0]);
+                None
+            }
+        },
+        (Some(_), _) => {
+            console.log(&tr!("rcon.no_token", crate::rcon::TOKEN_ENV), [1.0, 0.5, 0.0]);
+            None
+        }
+        _ => None,
+    };
+
+    let mut players: HashMap<u32, ConnectedPlayer> = HashMap::new();
+    let tick = Duration::from_secs_f32(1.0 / TICK_RATE as f32);
+    let dt = tick.as_secs_f32();
+    let mut next_tick = Instant::now();
+
+    'running: loop {
+        // NETWORK
+        for event in server.poll() {
+            let Some((id, text)) = handle_event(event, &mut server, &mut players, &mut planet, &mut markers, &mut console, &config) else { continue };
+            let Some(p) = players.get(&id) else { continue };
+
+            // run it with the player's level and send them everything it logs
+            console.log(&format!("[{}] {}", p.name, text), [0.8, 0.8, 0.8]);
+            let (tap, output) = channel();
+            console.add_tap(tap);
+            console.input_buffer = text;
+            let mut ctx = CommandContext::new(&mut admin, &mut planet, &mut autosave, p.permission);
+            console.submit(&mut ctx);
+            let (world_reloaded, quit) = (ctx.world_reloaded, ctx.quit);
+            for line in output.try_iter() {
+                server.send(id, &Message::Log { text: line });
+            }
+
+            if world_reloaded {
+                kick_all(&mut server, &mut players, &tr!("server.rolled_back"));
+            }
+            if quit { break 'running; }
+        }
+
+        update_interest(&server, &mut players, &planet);
+
+        // ADMIN CONSOLE
+        if let Some(rcon) = rcon.as_mut() {
+            rcon.poll(&mut console);
+        }
+        while let Ok(line) = line_rx.try_recv() {
+            console.input_buffer = line.trim().to_string();
+            let mut ctx = CommandContext::new(&mut admin, &mut planet, &mut autosave, Permission::Owner);
+            console.submit(&mut ctx);
+            let (world_reloaded, quit) = (ctx.world_reloaded, ctx.quit);
+
+            if world_reloaded {
+                // clients hold edits from the old world, make them rejoin
+                kick_all(&mut server, &mut players, &tr!("server.rolled_back"));
+            }
+            if quit { break 'running; }
+        }
+
+        // AUTOSAVE
+        match autosave.update(dt, &mut planet, &admin) {
+            Some(Ok(n)) => {
+                console.log(&tr!("world.autosaved", n), [0.6, 0.6, 0.6]);
+                if let Err(e) = markers.save(&autosave.dir) {
+                    console.log(&tr!("marker.save_failed", e), [1.0, 0.0, 0.0]);
+                }
+            },
+            Some(Err(e)) => console.log(&tr!("world.autosave_failed", e), [1.0, 0.0, 0.0]),
+            None => {}
+        }
+
+        // fixed rate, but never try to catch up on ticks lost to a stall
+        next_tick += tick;
+        let now = Instant::now();
+        if next_tick > now {
+            std::thread::sleep(next_tick - now);
+        } else {
+            next_tick = now;
+        }
+    }
+
+    kick_all(&mut server, &mut players, &tr!("server.stopped"));
+    match autosave.save_blocking(&mut planet, &admin) {
+        Ok(n) => console.log(&tr!("world.saved", n), [0.0, 1.0, 0.0]),
+        Err(e) => console.log(&tr!("world.save_failed", e), [1.0, 0.0, 0.0]),
+    }
+    if let Err(e) = markers.save(&autosave.dir) {
+        console.log(&tr!("marker.save_failed", e), [1.0, 0.0, 0.0]);
+    }
+}
+
+// returns a console command sent by a client, the caller runs it with that client's permission
+fn handle_event(event: ServerEvent, server: &mut Server, players: &mut HashMap<u32, ConnectedPlayer>, planet: &mut PlanetData, markers: &mut Markers, console: &mut Console, config: &ServerConfig) -> Option<(u32, String)> {
+    match event {
+        ServerEvent::Connected(id) => {
+            let addr = server.addr_of(id).unwrap_or("?").to_string();
+            console.log(&tr!("server.connection", id, addr, server.client_count()), [0.8, 0.8, 0.8]);
+        },
+        ServerEvent::Disconnected(id, reason) => {
+            if let Some(p) = players.remove(&id) {
+                console.log(&tr!("server.player_left", p.name, reason), [1.0, 1.0, 0.0]);
+                for (other_id, other) in players.iter_mut() {
+                    if other.visible_players.remove(&id) {
+                        server.send(*other_id, &Message::PlayerExit { client_id: id });
+                    }
+                }
+                server.broadcast(&Message::Log { text: tr!("server.left_game", p.name) }, None);
+            }
+        },
+        ServerEvent::Message(id, msg) => match msg {
+            Message::Hello { version, name, secret } => {
+                if version != PROTOCOL_VERSION {
+                    server.kick(id, &tr!("server.protocol_mismatch", PROTOCOL_VERSION, version));
+                    return None;
+                }
+                if players.contains_key(&id) { return None; }
+                if players.values().any(|p| p.name == name) {
+                    server.kick(id, &tr!("server.name_taken", name));
+                    return None;
+                }
+
+                // other players and edited chunks follow from update_interest once we know where they are
+                server.send(id, &Message::Welcome { client_id: id, resolution: planet.resolution, seed: planet.seed });
+                for marker in markers.visible_to(&name) {
+                    server.send(id, &Message::MarkerSet { marker: marker.clone() });
+                }
+
+                let permission = config.permission_of(&name, &secret);
+                console.log(&tr!("server.player_joined", name, id, format!("{:?}", permission)), [1.0, 1.0, 0.0]);
+                server.broadcast(&Message::Log { text: tr!("server.joined_game", name) }, None);
+                players.insert(id, ConnectedPlayer {
+                    name, permission,
+                    position: Vec3::ZERO, rotation: Quat::IDENTITY, spawned: false, spectator: false, last_move: Instant::now(),
+                    visible_players: HashSet::new(), known_chunks: HashSet::new(),
+                });
+            },
+            Message::PlayerMove { position, rotation } => {
+                let p = players.get_mut(&id)?;
+
+                // the first move has to start where Welcome put them
+                let from = if p.spawned { p.position } else { World::spawn_point(planet) };
+                if let Err(why) = validate_move(p, from, position, planet) {
+                    console.log(&tr!("server.corrected", p.name, why), [1.0, 0.5, 0.0]);
+                    server.send(id, &Message::Correction { position: from });
+                    if !p.spawned {
+                        p.position = from;
+                        p.spawned = true;
+                    }
+                    return None;
+                }
+
+                p.position = position;
+                p.rotation = rotation;
+                p.spawned = true;
+                p.last_move = Instant::now();
+
+                for (other_id, other) in players.iter() {
+                    if other.visible_players.contains(&id) {
+                        server.send(*other_id, &Message::PlayerState { client_id: id, position, rotation });
+                    }
+                }
+            },
+            Message::Teleport { from } => {
+                let p = players.get_mut(&id)?;
+                // standing on one end of a linked pair
+                if !p.spawned || p.spectator || !in_bounds(from, planet) { return None; }
+                if teleporter::arrival(from, planet.resolution).distance(p.position) > teleporter::TELEPORT_REACH { return None; }
+                let position = teleporter::arrival(teleporter::partner(planet, from)?, planet.resolution);
+                p.position = position;
+                p.last_move = Instant::now();
+                server.send(id, &Message::Correction { position });
+                let rotation = p.rotation;
+                for (other_id, other) in players.iter() {
+                    if other.visible_players.contains(&id) {
+                        server.send(*other_id, &Message::PlayerState { client_id: id, position, rotation });
+                    }
+                }
+            },
+            Message::BlockEdit { id: block, placed, block: material } => {
+                let p = players.get(&id)?;
+                if !in_bounds(block, planet) { return None; }
+                let center = CoordSystem::get_block_center(block.face, block.u, block.v, block.layer, planet.resolution);
+                let allowed = p.spawned && !p.spectator && (p.permission >= Permission::Operator || center.distance(p.position) <= EDIT_REACH);
+                if !allowed {
+                    // put the block back the way it is here, the client already changed it
+                    server.send(id, &Message::BlockEdit { id: block, placed: planet.exists(block), block: planet.block_type(block) });
+                    return None;
+                }
+                if placed { planet.add_block(block, material); } else { planet.remove_block(block); }
+                relay_edit(server, players, planet, id, block, &Message::BlockEdit { id: block, placed, block: material });
+            },
+            Message::SignEdit { id: block, text, facing } => {
+                if players.get(&id).is_none_or(|p| p.spectator) || !in_bounds(block, planet) || !planet.exists(block) { return None; }
+                let text: String = text.chars().take(sign::MAX_SIGN_TEXT).collect();
+                let entity = (!text.is_empty()).then(|| BlockEntity::Sign { text: text.clone(), facing });
+                planet.set_entity(block, entity);
+                relay_edit(server, players, planet, id, block, &Message::SignEdit { id: block, text, facing });
+            },
+            Message::TeleporterEdit { id: block, channel } => {
+                if players.get(&id).is_none_or(|p| p.spectator) || !in_bounds(block, planet) || !planet.exists(block) { return None; }
+                if channel.is_empty() {
+                    // only ever takes a teleporter off, never a sign
+                    teleporter::channel(planet, block)?;
+                } else if let Err(e) = teleporter::check_link(planet, block, &channel) {
+                    server.send(id, &Message::Log { text: e });
+                    return None;
+                }
+                let entity = (!channel.is_empty()).then(|| BlockEntity::Teleporter { channel: channel.clone() });
+                planet.set_entity(block, entity);
+                relay_edit(server, players, planet, id, block, &Message::TeleporterEdit { id: block, channel });
+            },
+            Message::MarkerSet { marker } => {
+                let p = players.get(&id)?;
+                let marker = Marker { owner: p.name.clone(), ..marker };
+                if let Err(e) = markers.check_set(&marker.owner, &marker.name) {
+                    server.send(id, &Message::Log { text: e });
+                    return None;
+                }
+                let old = markers.set(marker.clone());
+                // going private takes it away from everyone else
+                for (other_id, other) in players.iter() {
+                    if marker.visible_to(&other.name) {
+                        server.send(*other_id, &Message::MarkerSet { marker: marker.clone() });
+                    } else if old.as_ref().is_some_and(|old| old.visible_to(&other.name)) {
+                        server.send(*other_id, &Message::MarkerRemove { owner: marker.owner.clone(), name: marker.name.clone() });
+                    }
+                }
+            },
+            Message::MarkerRemove { owner, name } => {
+                let p = players.get(&id)?;
+                if owner != p.name && p.permission < Permission::Moderator {
+                    server.send(id, &Message::Log { text: tr!("marker.not_yours") });
+                    return None;
+                }
+                let removed = markers.remove(&owner, &name)?;
+                for (other_id, other) in players.iter() {
+                    if removed.visible_to(&other.name) {
+                        server.send(*other_id, &Message::MarkerRemove { owner: owner.clone(), name: name.clone() });
+                    }
+                }
+            },
+            Message::Spectator { enabled } => {
+                let p = players.get_mut(&id)?;
+                if enabled && p.permission < Permission::Moderator {
+                    server.send(id, &Message::Log { text: tr!("server.no_spectate") });
+                } else {
+                    p.spectator = enabled;
+                    console.log(&tr!("server.player_spectator", p.name, enabled), [0.8, 0.8, 0.8]);
+                }
+                // echo the real state back so a refused client drops out of spectator locally
+                server.send(id, &Message::Spectator { enabled: p.spectator });
+            },
+            Message::Command { text } if players.contains_key(&id) => return Some((id, text)),
+            Message::Disconnect { .. } => {
+                // the reader thread reports the socket closing, that's where cleanup happens
+            },
+            _ => {}
+        },
+    }
+    None
+}
+
+// sends enter/exit events for players and the edits of chunks that came into range since the last tick
+fn update_interest(server: &Server, players: &mut HashMap<u32, ConnectedPlayer>, planet: &PlanetData) {
+    let radius = net::interest_radius(planet.resolution);
+    let exit_radius = radius * net::INTEREST_EXIT_FACTOR;
+
+    // spectators are never shown to anyone
+    let subjects: Vec<(u32, String, Vec3, Quat)> = players.iter()
+        .filter(|(_, p)| p.spawned && !p.spectator)
+        .map(|(id, p)| (*id, p.name.clone(), p.position, p.rotation))
+        .collect();
+
+    for (&viewer_id, viewer) in players.iter_mut() {
+        if !viewer.spawned { continue; }
+
+        // whoever stopped being a subject (became a spectator) leaves view right away
+        let gone: Vec<u32> = viewer.visible_players.iter()
+            .filter(|id| !subjects.iter().any(|(other_id, ..)| other_id == *id))
+            .cloned()
+            .collect();
+        for other_id in gone {
+            viewer.visible_players.remove(&other_id);
+            server.send(viewer_id, &Message::PlayerExit { client_id: other_id });
+        }
+
+        for (other_id, name, position, rotation) in &subjects {
+            let other_id = *other_id;
+            if other_id == viewer_id { continue; }
+            let dist = viewer.position.distance(*position);
+            let visible = viewer.visible_players.contains(&other_id);
+
+            if !visible && dist < radius {
+                viewer.visible_players.insert(other_id);
+                server.send(viewer_id, &Message::PlayerEnter { client_id: other_id, name: name.clone(), position: *position, rotation: *rotation });
+            } else if visible && dist > exit_radius {
+                viewer.visible_players.remove(&other_id);
+                server.send(viewer_id, &Message::PlayerExit { client_id: other_id });
+            }
+        }
+
+        // everything that came into range this tick goes out as one compressed batch,
+        // on join that's the whole neighbourhood at once
+        let mut entering = Vec::new();
+        for (key, mods) in &planet.chunks {
+            let dist = viewer.position.distance(net::chunk_center(*key, planet.resolution));
+            let known = viewer.known_chunks.contains(key);
+
+            if !known && dist < radius {
+                viewer.known_chunks.insert(*key);
+                entering.push((*key, ChunkMods::clone(mods)));
+            } else if known && dist > exit_radius {
+                viewer.known_chunks.remove(key);
+            }
+        }
+        if !entering.is_empty() {
+            server.send(viewer_id, &Message::ChunkBatch { chunks: entering });
+        }
+    }
+}
+
+// re-checks a client's move against the same collision the client ran and a speed budget
+fn validate_move(p: &ConnectedPlayer, from: Vec3, to: Vec3, planet: &PlanetData) -> Result<(), String> {
+    if !to.is_finite() {
+        return Err(tr!("move.non_finite"));
+    }
+
+    let dist = from.distance(to);
+    if dist > MAX_TELEPORT {
+        return Err(tr!("move.teleport", format!("{:.1}", dist)));
+    }
+
+    let elapsed = if p.spawned { p.last_move.elapsed().as_secs_f32() } else { 0.0 };
+    let allowed = MAX_MOVE_SPEED * (elapsed + MOVE_JITTER);
+    if dist > allowed {
+        return Err(tr!("move.too_fast", format!("{:.1}", dist), format!("{:.2}", elapsed)));
+    }
+
+    // players already stuck in terrain (a block placed on them) may move while they climb out
+    if !p.spectator && Physics::check_collision(to, planet) && !Physics::check_collision(from, planet) {
+        return Err(tr!("move.into_terrain"));
+    }
+    Ok(())
+}
+
+// passes an edit by `sender` on to everyone in range of the block.
+// clients out of range forget the chunk and get all of it again when they come close
+fn relay_edit(server: &Server, players: &mut HashMap<u32, ConnectedPlayer>, planet: &PlanetData, sender: u32, block: BlockId, msg: &Message) {
+    let key = ChunkKey { face: block.face, u_idx: block.u / CHUNK_SIZE, v_idx: block.v / CHUNK_SIZE };
+    let center = net::chunk_center(key, planet.resolution);
+    let radius = net::interest_radius(planet.resolution);
+    for (other_id, other) in players.iter_mut() {
+        if *other_id == sender {
+            other.known_chunks.insert(key);
+        } else if other.spawned && other.position.distance(center) < radius {
+            server.send(*other_id, msg);
+            other.known_chunks.insert(key);
+        } else {
+            other.known_chunks.remove(&key);
+        }
+    }
+}
+
+fn in_bounds(id: BlockId, planet: &PlanetData) -> bool {
+    id.face < 6 && id.u < planet.resolution && id.v < planet.resolution && id.layer < planet.resolution
+}
+
+fn kick_all(server: &mut Server, players: &mut HashMap<u32, ConnectedPlayer>, reason: &str) {
+    let ids: Vec<u32> = players.keys().cloned().collect();
+    for id in ids {
+        server.kick(id, reason);
+    }
+    players.clear();
+}
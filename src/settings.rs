@@ -0,0 +1,396 @@
+//settings.rs
+
+// player preferences that survive restarts, kept next to the binary in client.cfg.
+// same `key = value` format as server.cfg, and every key can be changed live with /option.
+
+use std::path::Path;
+use crate::gen::ViewDistance;
+
+// --- CONSTANTS ---
+pub const CLIENT_CONFIG: &str = "client.cfg";
+
+// limits for the view distance, also used by the auto tuner
+pub const MIN_RENDER_DISTANCE: f32 = 2.0;
+pub const MAX_RENDER_DISTANCE: f32 = 48.0;
+pub const MIN_LOD_DETAIL: f32 = 0.25;
+pub const MAX_LOD_DETAIL: f32 = 4.0;
+pub const MIN_FOV: f32 = 50.0;
+pub const MAX_FOV: f32 = 110.0;
+pub const MAX_FOG_DENSITY: f32 = 0.05;
+pub const MIN_TICK_RATE: u32 = 10;
+pub const MAX_TICK_RATE: u32 = 240;
+
+// colors of the debug overlays and console text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Palette {
+    Default,
+    // red/green swapped for vermillion/sky blue, readable with red-green colorblindness
+    Colorblind,
+    // every channel pushed to full on or off, nothing dimmer than pure white
+    HighContrast,
+}
+
+impl Palette {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "default" => Some(Palette::Default),
+            "colorblind" => Some(Palette::Colorblind),
+            "high_contrast" => Some(Palette::HighContrast),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Palette::Default => "default",
+            Palette::Colorblind => "colorblind",
+            Palette::HighContrast => "high_contrast",
+        }
+    }
+
+    pub fn apply(self, c: [f32; 3]) -> [f32; 3] {
+        match self {
+            Palette::Default => c,
+            Palette::Colorblind => {
+                let [r, g, b] = c;
+                if r > 0.7 && g < 0.3 && b < 0.3 { [0.84, 0.37, 0.0] }
+                else if g > 0.7 && r < 0.3 && b < 0.3 { [0.34, 0.71, 0.91] }
+                else { c }
+            }
+            Palette::HighContrast => {
+                let snapped = c.map(|x| if x >= 0.5 { 1.0 } else { 0.0 });
+                if snapped == [0.0; 3] { [1.0; 3] } else { snapped }
+            }
+        }
+    }
+}
+
+// starting points for the graphics options, picked from the hardware on first run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphicsPreset {
+    Low,
+    Medium,
+    High,
+}
+
+impl GraphicsPreset {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "low" => Some(GraphicsPreset::Low),
+            "medium" => Some(GraphicsPreset::Medium),
+            "high" => Some(GraphicsPreset::High),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GraphicsPreset::Low => "low",
+            GraphicsPreset::Medium => "medium",
+            GraphicsPreset::High => "high",
+        }
+    }
+}
+
+pub struct Settings {
+    // crosshair size and line thickness are in pixels
+    pub crosshair_color: [f32; 3],
+    pub crosshair_size: f32,
+    pub crosshair_thickness: f32,
+    // outline around the targeted block
+    pub cursor_color: [f32; 3],
+    // 0 = invisible, 1 = solid
+    pub console_opacity: f32,
+    pub palette: Palette,
+    // cuts lod cross fades, ui slides, camera shake and view bobbing, for players who get motion sick
+    pub reduce_motion: bool,
+    // camera shake from landings and mining, 0 = off
+    pub shake_intensity: f32,
+    // head bob while walking and a dip on landing, first person only
+    pub view_bobbing: bool,
+
+    // multipliers on top of the base look speed
+    pub mouse_sensitivity_x: f32,
+    pub mouse_sensitivity_y: f32,
+    pub invert_y: bool,
+    // 0 = raw input, towards 1 = heavier smoothing
+    pub mouse_smoothing: f32,
+    // extra turn per count of mouse speed, 0 = linear
+    pub mouse_acceleration: f32,
+    // right stick look speed multiplier, and how far either stick moves before it counts (0..1 of its travel)
+    pub gamepad_sensitivity: f32,
+    pub gamepad_deadzone: f32,
+
+    // full-detail radius in chunks and lod reach multiplier, see ViewDistance
+    pub render_distance: f32,
+    pub lod_detail: f32,
+    // let the game pick both from fps and gpu memory instead
+    pub auto_render_distance: bool,
+    // vertical field of view in first person, in degrees
+    pub fov: f32,
+    // how quickly the air hides what's far off, thickest near the ground (0 = clear), and the colour it fades to
+    pub fog_density: f32,
+    pub fog_color: [f32; 3],
+
+    // last preset applied, None once nothing has been picked yet
+    pub graphics_preset: Option<GraphicsPreset>,
+    // side of the shadow map in texels
+    pub shadow_resolution: u32,
+    // off builds the shaders without shadow sampling and leaves the shadow map empty
+    pub shadows: bool,
+    pub vsync: bool,
+
+    // world updates a second, 0 = one per frame. see tick.rs
+    pub tick_rate: u32,
+    // frame rate caps, 0 = uncapped: always, while the settings menu is open, while the window is in the
+    // background and while the laptop is on battery. the lowest that applies wins
+    pub max_fps: u32,
+    pub menu_fps: u32,
+    pub background_fps: u32,
+    pub battery_fps: u32,
+    // resident memory the game tries to stay under by dropping meshes, in MB, 0 = no limit
+    pub memory_limit_mb: u32,
+
+    // experimental side by side stereo for headsets and 3d displays, one eye per half of the window.
+    // looking around turns the head before the body, see Controller::turn_head
+    pub stereo: bool,
+    // distance between the eyes in blocks, a block being about a metre
+    pub ipd: f32,
+
+    // background music. nothing plays yet, see music::AUDIO_OUTPUT
+    pub music: bool,
+    // 0 = silent, 1 = full
+    pub music_volume: f32,
+
+    // flocks of birds flying around the player
+    pub birds: bool,
+    // sprinting and jumping tire the player out, see stamina.rs
+    pub stamina: bool,
+    // hostile lurkers coming out of dark caves, see mob.rs
+    pub lurkers: bool,
+    // how long the marker left where the player died keeps what they carried, 0 = until it's picked up
+    pub death_marker_minutes: f32,
+
+    // every event on the bus printed to the console, see events.rs
+    pub log_events: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            crosshair_color: [1.0, 1.0, 1.0],
+            crosshair_size: 12.0,
+            crosshair_thickness: 2.0,
+            cursor_color: [1.0, 1.0, 0.0],
+            console_opacity: 1.0,
+            palette: Palette::Default,
+            reduce_motion: false,
+            shake_intensity: 1.0,
+            view_bobbing: true,
+            mouse_sensitivity_x: 1.0,
+            mouse_sensitivity_y: 1.0,
+            invert_y: false,
+            mouse_smoothing: 0.0,
+            mouse_acceleration: 0.0,
+            gamepad_sensitivity: 1.0,
+            gamepad_deadzone: 0.15,
+            render_distance: ViewDistance::default().chunks,
+            lod_detail: ViewDistance::default().lod_detail,
+            auto_render_distance: false,
+            fov: 80.0,
+            fog_density: 0.0015,
+            fog_color: [0.236, 0.352, 0.564],
+            graphics_preset: None,
+            shadow_resolution: 4096,
+            shadows: true,
+            vsync: false,
+            tick_rate: 0,
+            max_fps: 0,
+            menu_fps: 60,
+            background_fps: 10,
+            battery_fps: 30,
+            memory_limit_mb: 4096,
+            stereo: false,
+            ipd: 0.064,
+            music: true,
+            music_volume: 0.5,
+            birds: true,
+            stamina: false,
+            lurkers: true,
+            death_marker_minutes: 10.0,
+            log_events: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn apply_preset(&mut self, preset: GraphicsPreset) {
+        let (shadows, resolution, chunks, lod, vsync) = match preset {
+            GraphicsPreset::Low => (false, 1024, 8.0, 0.75, true),
+            GraphicsPreset::Medium => (true, 2048, 12.0, 1.0, false),
+            GraphicsPreset::High => (true, 4096, 16.0, 1.25, false),
+        };
+        self.graphics_preset = Some(preset);
+        self.shadows = shadows;
+        self.shadow_resolution = resolution;
+        self.render_distance = chunks;
+        self.lod_detail = lod;
+        self.vsync = vsync;
+    }
+
+    pub fn view_distance(&self) -> ViewDistance {
+        ViewDistance { chunks: self.render_distance, lod_detail: self.lod_detail }
+    }
+
+    // every key, in the order they're listed and saved.
+    // the preset comes first so the options it sets can still be overridden one by one further down the file
+    pub const KEYS: &'static [&'static str] = &[
+        "graphics_preset",
+        "crosshair_color", "crosshair_size", "crosshair_thickness", "cursor_color", "console_opacity", "palette", "reduce_motion", "shake_intensity", "view_bobbing",
+        "mouse_sensitivity_x", "mouse_sensitivity_y", "invert_y", "mouse_smoothing", "mouse_acceleration",
+        "gamepad_sensitivity", "gamepad_deadzone",
+        "render_distance", "lod_detail", "auto_render_distance", "fov", "fog_density", "fog_color",
+        "shadows", "shadow_resolution", "vsync", "tick_rate", "max_fps", "menu_fps", "background_fps", "battery_fps", "memory_limit_mb",
+        "stereo", "ipd",
+        "music", "music_volume",
+        "birds", "stamina", "lurkers", "death_marker_minutes",
+        "log_events",
+    ];
+
+    pub fn load(path: &Path) -> Self {
+        let mut settings = Self::default();
+        let Ok(text) = std::fs::read_to_string(path) else { return settings };
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() { continue; }
+            let ok = match line.split_once('=') {
+                Some((key, value)) => settings.set(key.trim(), value.trim()),
+                None => false,
+            };
+            if !ok {
+                println!("{}:{}: ignoring '{}'", path.display(), i + 1, line);
+            }
+        }
+        settings
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut text = String::new();
+        for key in Self::KEYS {
+            if let Some(value) = self.get(key) {
+                text.push_str(&format!("{} = {}\n", key, value));
+            }
+        }
+        std::fs::write(path, text)
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        Some(match key {
+            "crosshair_color" => color_to_hex(self.crosshair_color),
+            "crosshair_size" => self.crosshair_size.to_string(),
+            "crosshair_thickness" => self.crosshair_thickness.to_string(),
+            "cursor_color" => color_to_hex(self.cursor_color),
+            "console_opacity" => self.console_opacity.to_string(),
+            "palette" => self.palette.name().to_string(),
+            "reduce_motion" => self.reduce_motion.to_string(),
+            "shake_intensity" => self.shake_intensity.to_string(),
+            "view_bobbing" => self.view_bobbing.to_string(),
+            "mouse_sensitivity_x" => self.mouse_sensitivity_x.to_string(),
+            "mouse_sensitivity_y" => self.mouse_sensitivity_y.to_string(),
+            "invert_y" => self.invert_y.to_string(),
+            "mouse_smoothing" => self.mouse_smoothing.to_string(),
+            "mouse_acceleration" => self.mouse_acceleration.to_string(),
+            "gamepad_sensitivity" => self.gamepad_sensitivity.to_string(),
+            "gamepad_deadzone" => self.gamepad_deadzone.to_string(),
+            "render_distance" => self.render_distance.to_string(),
+            "lod_detail" => self.lod_detail.to_string(),
+            "auto_render_distance" => self.auto_render_distance.to_string(),
+            "fov" => self.fov.to_string(),
+            "fog_density" => self.fog_density.to_string(),
+            "fog_color" => color_to_hex(self.fog_color),
+            "graphics_preset" => self.graphics_preset.map_or("custom", |p| p.name()).to_string(),
+            "shadows" => self.shadows.to_string(),
+            "shadow_resolution" => self.shadow_resolution.to_string(),
+            "vsync" => self.vsync.to_string(),
+            "tick_rate" => self.tick_rate.to_string(),
+            "max_fps" => self.max_fps.to_string(),
+            "menu_fps" => self.menu_fps.to_string(),
+            "background_fps" => self.background_fps.to_string(),
+            "battery_fps" => self.battery_fps.to_string(),
+            "memory_limit_mb" => self.memory_limit_mb.to_string(),
+            "stereo" => self.stereo.to_string(),
+            "ipd" => self.ipd.to_string(),
+            "music" => self.music.to_string(),
+            "music_volume" => self.music_volume.to_string(),
+            "birds" => self.birds.to_string(),
+            "stamina" => self.stamina.to_string(),
+            "lurkers" => self.lurkers.to_string(),
+            "death_marker_minutes" => self.death_marker_minutes.to_string(),
+            "log_events" => self.log_events.to_string(),
+            _ => return None,
+        })
+    }
+
+    // false for an unknown key or a value that doesn't parse, the setting is left as it was
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "crosshair_color" => hex_to_color(value).map(|c| self.crosshair_color = c).is_some(),
+            "crosshair_size" => value.parse::<f32>().map(|v| self.crosshair_size = v.clamp(0.0, 200.0)).is_ok(),
+            "crosshair_thickness" => value.parse::<f32>().map(|v| self.crosshair_thickness = v.clamp(1.0, 50.0)).is_ok(),
+            "cursor_color" => hex_to_color(value).map(|c| self.cursor_color = c).is_some(),
+            "console_opacity" => value.parse::<f32>().map(|v| self.console_opacity = v.clamp(0.0, 1.0)).is_ok(),
+            "palette" => Palette::parse(value).map(|p| self.palette = p).is_some(),
+            "reduce_motion" => value.parse().map(|v| self.reduce_motion = v).is_ok(),
+            "shake_intensity" => value.parse::<f32>().map(|v| self.shake_intensity = v.clamp(0.0, 2.0)).is_ok(),
+            "view_bobbing" => value.parse().map(|v| self.view_bobbing = v).is_ok(),
+            "mouse_sensitivity_x" => value.parse::<f32>().map(|v| self.mouse_sensitivity_x = v.clamp(0.01, 20.0)).is_ok(),
+            "mouse_sensitivity_y" => value.parse::<f32>().map(|v| self.mouse_sensitivity_y = v.clamp(0.01, 20.0)).is_ok(),
+            "invert_y" => value.parse().map(|v| self.invert_y = v).is_ok(),
+            "mouse_smoothing" => value.parse::<f32>().map(|v| self.mouse_smoothing = v.clamp(0.0, 0.95)).is_ok(),
+            "mouse_acceleration" => value.parse::<f32>().map(|v| self.mouse_acceleration = v.clamp(0.0, 1.0)).is_ok(),
+            "gamepad_sensitivity" => value.parse::<f32>().map(|v| self.gamepad_sensitivity = v.clamp(0.01, 20.0)).is_ok(),
+            "gamepad_deadzone" => value.parse::<f32>().map(|v| self.gamepad_deadzone = v.clamp(0.0, 0.9)).is_ok(),
+            "render_distance" => value.parse::<f32>().map(|v| self.render_distance = v.clamp(MIN_RENDER_DISTANCE, MAX_RENDER_DISTANCE)).is_ok(),
+            "lod_detail" => value.parse::<f32>().map(|v| self.lod_detail = v.clamp(MIN_LOD_DETAIL, MAX_LOD_DETAIL)).is_ok(),
+            "auto_render_distance" => value.parse().map(|v| self.auto_render_distance = v).is_ok(),
+            "fov" => value.parse::<f32>().map(|v| self.fov = v.clamp(MIN_FOV, MAX_FOV)).is_ok(),
+            "fog_density" => value.parse::<f32>().map(|v| self.fog_density = v.clamp(0.0, MAX_FOG_DENSITY)).is_ok(),
+            "fog_color" => hex_to_color(value).map(|c| self.fog_color = c).is_some(),
+            "graphics_preset" => value == "custom" || GraphicsPreset::parse(value).map(|p| self.apply_preset(p)).is_some(),
+            "shadows" => value.parse().map(|v| self.shadows = v).is_ok(),
+            "shadow_resolution" => value.parse::<u32>().map(|v| self.shadow_resolution = v.clamp(512, 8192).next_power_of_two()).is_ok(),
+            "vsync" => value.parse().map(|v| self.vsync = v).is_ok(),
+            // 0, or a rate a world step can keep up with
+            "tick_rate" => value.parse::<u32>().map(|v| self.tick_rate = if v == 0 { 0 } else { v.clamp(MIN_TICK_RATE, MAX_TICK_RATE) }).is_ok(),
+            "max_fps" => value.parse::<u32>().map(|v| self.max_fps = v.min(1000)).is_ok(),
+            "menu_fps" => value.parse::<u32>().map(|v| self.menu_fps = v.min(1000)).is_ok(),
+            "background_fps" => value.parse::<u32>().map(|v| self.background_fps = v.min(1000)).is_ok(),
+            "battery_fps" => value.parse::<u32>().map(|v| self.battery_fps = v.min(1000)).is_ok(),
+            "memory_limit_mb" => value.parse::<u32>().map(|v| self.memory_limit_mb = v).is_ok(),
+            "stereo" => value.parse().map(|v| self.stereo = v).is_ok(),
+            "ipd" => value.parse::<f32>().map(|v| self.ipd = v.clamp(0.0, 0.2)).is_ok(),
+            "music" => value.parse().map(|v| self.music = v).is_ok(),
+            "music_volume" => value.parse::<f32>().map(|v| self.music_volume = v.clamp(0.0, 1.0)).is_ok(),
+            "birds" => value.parse().map(|v| self.birds = v).is_ok(),
+            "stamina" => value.parse().map(|v| self.stamina = v).is_ok(),
+            "lurkers" => value.parse().map(|v| self.lurkers = v).is_ok(),
+            "death_marker_minutes" => value.parse::<f32>().map(|v| self.death_marker_minutes = v.clamp(0.0, 1440.0)).is_ok(),
+            "log_events" => value.parse().map(|v| self.log_events = v).is_ok(),
+            _ => false,
+        }
+    }
+}
+
+// colors are written as rrggbb, without the # since that starts a comment in the file
+fn hex_to_color(s: &str) -> Option<[f32; 3]> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 { return None; }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok().map(|v| v as f32 / 255.0);
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn color_to_hex(c: [f32; 3]) -> String {
+    let [r, g, b] = c.map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("{:02x}{:02x}{:02x}", r, g, b)
+}
@@ -0,0 +1,94 @@
+//settings_menu.rs
+
+// the graphics options as a panel over the game, opened and closed with Esc. up/down pick a row and left/right
+// step its value. every step goes through Settings::set like /option, so the same limits apply and the frame
+// loop picks the change up on the next frame. client.cfg is written once, when the menu closes.
+
+use crate::i18n::tr;
+use crate::settings::Settings;
+
+// what one step of a row does to its value
+enum Step {
+    Toggle,
+    Add(f32),
+    // halved or doubled, for sizes in powers of two
+    Double,
+    Cycle(&'static [&'static str]),
+}
+
+// the option a row changes, the lang key of its label and its step
+struct Row {
+    key: &'static str,
+    label: &'static str,
+    step: Step,
+}
+
+const ROWS: &[Row] = &[
+    Row { key: "graphics_preset", label: "settings.graphics_preset", step: Step::Cycle(&["low", "medium", "high"]) },
+    Row { key: "vsync", label: "settings.vsync", step: Step::Toggle },
+    Row { key: "max_fps", label: "settings.max_fps", step: Step::Add(30.0) },
+    Row { key: "fov", label: "settings.fov", step: Step::Add(5.0) },
+    Row { key: "render_distance", label: "settings.render_distance", step: Step::Add(2.0) },
+    Row { key: "lod_detail", label: "settings.lod_detail", step: Step::Add(0.25) },
+    Row { key: "auto_render_distance", label: "settings.auto_render_distance", step: Step::Toggle },
+    Row { key: "shadows", label: "settings.shadows", step: Step::Toggle },
+    Row { key: "shadow_resolution", label: "settings.shadow_resolution", step: Step::Double },
+];
+
+#[derive(Default)]
+pub struct SettingsMenu {
+    pub open: bool,
+    selected: usize,
+    // something was stepped since it opened, so there's a file to write
+    changed: bool,
+}
+
+impl SettingsMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // opens the menu, or closes it and says whether the settings need saving
+    pub fn toggle(&mut self) -> bool {
+        self.open = !self.open;
+        !self.open && std::mem::take(&mut self.changed)
+    }
+
+    // next (1) or previous (-1) row, wrapping round
+    pub fn select(&mut self, dir: i32) {
+        self.selected = (self.selected as i32 + dir).rem_euclid(ROWS.len() as i32) as usize;
+    }
+
+    // one step up (1) or down (-1) of the selected row
+    pub fn adjust(&mut self, settings: &mut Settings, dir: i32) {
+        let row = &ROWS[self.selected];
+        let current = settings.get(row.key).unwrap_or_default();
+        let value = match row.step {
+            Step::Toggle => (current != "true").to_string(),
+            Step::Add(step) => (current.parse::<f32>().unwrap_or(0.0) + step * dir as f32).to_string(),
+            Step::Double => {
+                let size = current.parse::<u32>().unwrap_or(0);
+                (if dir > 0 { size * 2 } else { size / 2 }).to_string()
+            },
+            Step::Cycle(values) => {
+                // "custom" isn't in the list, so stepping from it lands on either end
+                let next = match values.iter().position(|v| *v == current) {
+                    Some(i) => (i as i32 + dir).rem_euclid(values.len() as i32) as usize,
+                    None if dir > 0 => 0,
+                    None => values.len() - 1,
+                };
+                values[next].to_string()
+            },
+        };
+        if settings.set(row.key, &value) {
+            self.changed = true;
+        }
+    }
+
+    // label and value of every row, and whether it's the selected one
+    pub fn lines(&self, settings: &Settings) -> Vec<(String, bool)> {
+        ROWS.iter().enumerate()
+            .map(|(i, row)| (tr!(row.label, settings.get(row.key).unwrap_or_default()), i == self.selected))
+            .collect()
+    }
+}
@@ -1,20 +1,19 @@
-
-
 // basic shading (IMPROVE THIS LATER)
-struct Global {
-    view_proj: mat4x4<f32>,
-    light_view_proj: mat4x4<f32>,
-    camera_pos: vec4<f32>,
-    sun_dir: vec4<f32>,
-}
-
-@group(0) @binding(0) var<uniform> global: Global;
-@group(0) @binding(1) var t_shadow: texture_depth_2d;
-@group(0) @binding(2) var s_shadow: sampler_comparison;
+// composed from the files in wgsl/, see pipelines.rs for #include and #ifdef
+#include "wgsl/global.wgsl"
+#include "wgsl/color.wgsl"
+#include "wgsl/fog.wgsl"
+#include "wgsl/debug.wgsl"
+#ifdef SHADOWS
+#include "wgsl/shadow.wgsl"
+#endif
+
+@group(0) @binding(3) var t_probe: texture_cube<f32>;
+@group(0) @binding(4) var s_probe: sampler;
 
 struct Local {
     model: mat4x4<f32>,
-    params: vec4<f32>, // x = opacity
+    params: vec4<f32>, // x = opacity, z = lod level, w = reflectivity
 }
 @group(1) @binding(0) var<uniform> local: Local;
 
@@ -27,12 +26,6 @@ const SHADOW_OPACITY  = 0.85;                        // Shadows are not pitch bl
 
 // --- VERTEX SHADER ---
 
-struct VertexIn {
-    @location(0) pos: vec3<f32>,
-    @location(1) color: vec3<f32>,
-    @location(2) normal: vec3<f32>,
-};
-
 struct VertexOut {
     @builtin(position) clip_pos: vec4<f32>,
     @location(0) color: vec3<f32>,
@@ -42,12 +35,31 @@ struct VertexOut {
     @location(4) shadow_pos: vec3<f32>,
 };
 
+// one copy of an instanced mesh, see instancing.rs
+struct InstanceIn {
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+    @location(7) tint: vec4<f32>, // rgb replaces the vertex color as far as a says
+};
+
 @vertex
 fn vs_main(in: VertexIn) -> VertexOut {
+    return transform(local.model, in, in.color);
+}
+
+@vertex
+fn vs_instanced(in: VertexIn, instance: InstanceIn) -> VertexOut {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    return transform(local.model * model, in, mix(in.color, instance.tint.rgb, instance.tint.a));
+}
+
+fn transform(model: mat4x4<f32>, in: VertexIn, color: vec3<f32>) -> VertexOut {
     var out: VertexOut;
     
     // World Position
-    let world_pos = local.model * vec4<f32>(in.pos, 1.0);
+    let world_pos = model * vec4<f32>(in.pos, 1.0);
     out.world_pos = world_pos.xyz;
     
     // Clip Position (Main Camera)
@@ -55,14 +67,14 @@ fn vs_main(in: VertexIn) -> VertexOut {
     
     // Normal Transformation
     let normal_mat = mat3x3<f32>(
-        local.model[0].xyz,
-        local.model[1].xyz,
-        local.model[2].xyz
+        model[0].xyz,
+        model[1].xyz,
+        model[2].xyz
     );
     out.world_normal = normalize(normal_mat * in.normal);
     
     // Color (Vertex Color + Baked AO)
-    out.color = in.color;
+    out.color = color;
     out.view_pos = global.camera_pos.xyz;
 
     // Shadow Calculation Space
@@ -81,87 +93,6 @@ fn vs_main(in: VertexIn) -> VertexOut {
     return out;
 }
 
-// --- SHADOW ENGINE (Gaussian PCF) ---
-
-fn fetch_shadow_accurate(shadow_pos: vec3<f32>, NdotL: f32) -> f32 {
-    // 1. Cull outside cascade
-    if (shadow_pos.z > 1.0 || shadow_pos.x < 0.0 || shadow_pos.x > 1.0 || shadow_pos.y < 0.0 || shadow_pos.y > 1.0) {
-        return 1.0;
-    }
-
-    // 2. Slope-Scaled Bias
-    // Steeper angles need more bias to prevent acne.
-    // Base bias matches the texel size of a 4096 map covering ~120 units.
-    let bias = max(0.0005 * (1.0 - NdotL), 0.0001);
-    let current_depth = shadow_pos.z - bias;
-
-    let tex_dim = vec2<f32>(textureDimensions(t_shadow));
-    let texel_size = 1.0 / tex_dim.x;
-
-    // 3. 5x5 Gaussian Weighted PCF
-    // We sample a grid, but center samples matter more.
-    var shadow_sum = 0.0;
-    var total_weight = 0.0;
-
-    // Gaussian weights for range -2 to +2
-    // [0.05, 0.25, 0.4, 0.25, 0.05] roughly
-    
-    for (var x = -1.0; x <= 1.0; x += 1.0) {
-        for (var y = -1.0; y <= 1.0; y += 1.0) {
-            // Calculate weight based on distance from center (Gaussian-ish)
-            let dist_sq = x*x + y*y;
-            let weight = exp(-dist_sq * 1.5); // Gaussian Falloff
-
-            let val = textureSampleCompare(
-                t_shadow, 
-                s_shadow, 
-                shadow_pos.xy + vec2<f32>(x, y) * texel_size, 
-                current_depth
-            );
-            
-            shadow_sum += val * weight;
-            total_weight += weight;
-        }
-    }
-
-    return shadow_sum / total_weight;
-}
-
-// --- UTILS ---
-
-fn dither_opacity(pos: vec4<f32>, alpha: f32) -> bool {
-    // 4x4 Ordered Dithering Matrix
-    let dither_threshold = dot(vec2<f32>(171.0, 231.0), pos.xy);
-    return fract(dither_threshold / 71.0) > alpha;
-}
-
-fn triplanar_detail(pos: vec3<f32>, normal: vec3<f32>) -> f32 {
-    // Adds subtle grain to voxels so they don't look like plastic
-    let p = pos * 2.0;
-    let n = abs(normal);
-    // Tight blend
-    let w = pow(n, vec3<f32>(16.0)); 
-    let weights = w / (w.x + w.y + w.z);
-    
-    // Fast hash noise
-    let hx = fract(sin(dot(p.yz, vec2<f32>(12.9898, 78.233))) * 43758.5453);
-    let hy = fract(sin(dot(p.zx, vec2<f32>(12.9898, 78.233))) * 43758.5453);
-    let hz = fract(sin(dot(p.xy, vec2<f32>(12.9898, 78.233))) * 43758.5453);
-
-    return (hx * weights.x + hy * weights.y + hz * weights.z) * 2.0 - 1.0;
-}
-
-// --- TONE MAPPING (ACES) ---
-// Industry standard for realistic color reproduction
-fn aces_approx(v: vec3<f32>) -> vec3<f32> {
-    let a = 2.51;
-    let b = 0.03;
-    let c = 2.43;
-    let d = 0.59;
-    let e = 0.14;
-    return clamp((v * (a * v + b)) / (v * (c * v + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
-}
-
 // --- FRAGMENT SHADER ---
 
 @fragment
@@ -187,7 +118,11 @@ fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
     let NdotL = max(dot(N, L), 0.0);
     
     // Shadow Map
+#ifdef SHADOWS
     let shadow_raw = fetch_shadow_accurate(in.shadow_pos, NdotL);
+#else
+    let shadow_raw = 1.0;
+#endif
     // Smooth transition shadow
     let shadow = mix(1.0 - SHADOW_OPACITY, 1.0, shadow_raw);
 
@@ -209,15 +144,23 @@ fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
     // Note: Ambient is multiplied by albedo (diffuse reflection)
     var final_color = albedo * (direct_light + ambient_light + rim_light);
 
+    let mode = i32(global.sun_dir.w);
+    if (mode != 0) {
+        let dist = distance(global.camera_pos.xyz, in.world_pos);
+        // Flat colors get a little sun shading so shapes stay readable
+        let relief = 0.6 + 0.4 * NdotL;
+        switch mode {
+            case MODE_NORMALS: { return vec4<f32>(N * 0.5 + 0.5, 1.0); }
+            case MODE_AO: { return vec4<f32>(in.color, 1.0); }
+            case MODE_LIGHT: { return vec4<f32>(pow(aces_approx(direct_light + ambient_light + rim_light), vec3<f32>(1.0 / 2.2)), 1.0); }
+            case MODE_LOD: { return vec4<f32>(lod_color(local.params.z) * relief, 1.0); }
+            case MODE_DEPTH: { return vec4<f32>(vec3<f32>(1.0 - log2(1.0 + dist) / log2(1.0 + DEPTH_RANGE)), 1.0); }
+            default: {}
+        }
+    }
+
     // 4. Fog (Atmospheric Scattering)
-    let dist = distance(global.camera_pos.xyz, in.world_pos);
-    // Fog density tuned for the scale defined in gen.rs
-    let fog_density = 0.0015; 
-    let fog_factor = 1.0 - exp(-(dist * fog_density) * (dist * fog_density * 0.5)); // Exp2 fog
-    
-    // Horizon Fog Color blends into Sky
-    let fog_col = mix(SKY_COLOR * 0.8, vec3<f32>(0.7, 0.8, 0.9), 0.2); 
-    final_color = mix(final_color, fog_col, clamp(fog_factor, 0.0, 1.0));
+    final_color = apply_fog(final_color, in.world_pos);
 
     // 5. Post Processing
     // Tone Mapping (HDR -> LDR)
@@ -226,5 +169,21 @@ fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
     // Gamma Correction (Linear -> sRGB)
     final_color = pow(final_color, vec3<f32>(1.0 / 2.2));
 
+    // 6. Reflections
+    // The probe holds finished colors, so it's blended in after tone mapping
+    if (local.params.w > 0.0) {
+        let R = reflect(-V, N);
+        let env = textureSampleLevel(t_probe, s_probe, R, 0.0).rgb;
+        let schlick = pow(1.0 - max(dot(N, V), 0.0), 5.0);
+        final_color = mix(final_color, env, local.params.w * mix(0.2, 1.0, schlick));
+    }
+
     return vec4<f32>(final_color, 1.0);
-}
\ No newline at end of file
+}
+
+// --- OVERDRAW ---
+// Drawn additively without a depth test, dithered-out fragments still cost a shader run so they count too
+@fragment
+fn fs_overdraw(in: VertexOut) -> @location(0) vec4<f32> {
+    return OVERDRAW_STEP;
+}
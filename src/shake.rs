@@ -0,0 +1,64 @@
+//shake.rs
+
+// trauma based camera shake: events add trauma, it drains over time,
+// and the shake is trauma squared so small bumps stay subtle while big hits kick hard.
+
+use glam::Quat;
+
+// --- CONSTANTS ---
+// trauma lost per second
+const DECAY: f32 = 1.5;
+// rotation at full trauma, in radians
+const MAX_YAW: f32 = 0.04;
+const MAX_PITCH: f32 = 0.04;
+const MAX_ROLL: f32 = 0.03;
+// how fast the shake wobbles
+const FREQUENCY: f32 = 25.0;
+
+// how much trauma each kind of event adds
+pub const MINE_TRAUMA: f32 = 0.15;
+// landings add this much per unit of fall speed above LANDING_MIN_SPEED
+pub const LANDING_TRAUMA: f32 = 0.04;
+pub const LANDING_MIN_SPEED: f32 = 6.0;
+
+pub struct CameraShake {
+    trauma: f32,
+    time: f32,
+    // user setting times reduce motion, 0 turns shaking off
+    intensity: f32,
+}
+
+impl CameraShake {
+    pub fn new() -> Self {
+        Self { trauma: 0.0, time: 0.0, intensity: 1.0 }
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    pub fn update(&mut self, dt: f32, intensity: f32) {
+        self.trauma = (self.trauma - DECAY * dt).max(0.0);
+        self.time += dt;
+        self.intensity = intensity;
+    }
+
+    // camera-space rotation to put in front of the view matrix
+    pub fn rotation(&self) -> Quat {
+        let shake = self.trauma * self.trauma * self.intensity;
+        if shake <= 0.0 { return Quat::IDENTITY; }
+
+        let t = self.time * FREQUENCY;
+        Quat::from_euler(
+            glam::EulerRot::YXZ,
+            MAX_YAW * shake * wobble(t, 0.0),
+            MAX_PITCH * shake * wobble(t, 1.7),
+            MAX_ROLL * shake * wobble(t, 4.1),
+        )
+    }
+}
+
+// smooth noise in -1..1, two sines at unrelated frequencies so it never visibly repeats
+fn wobble(t: f32, phase: f32) -> f32 {
+    (t + phase).sin() * 0.6 + (t * 2.31 + phase * 3.0).sin() * 0.4
+}
@@ -0,0 +1,67 @@
+//sign.rs
+
+// signs: text on one side of a block, written with /sign <text> while looking at the block.
+// the text lives in the block's BlockEntity, the renderer draws it into a texture and puts that on the block's side.
+
+use glam::Vec3;
+use crate::common::BlockId;
+use crate::gen::CoordSystem;
+
+// --- CONSTANTS ---
+pub const MAX_SIGN_TEXT: usize = 64;
+// lift off the block side so the sign doesn't z-fight it
+const SIGN_OFFSET: f32 = 0.02;
+
+// sides a sign can face: +u, -u, +v, -v
+const SIDES: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+// the side of `id` that faces `eye` the most, so a new sign reads from where its writer stands
+pub fn facing_towards(id: BlockId, res: u32, eye: Vec3) -> u8 {
+    let center = block_center(id, res);
+    let to_eye = eye - center;
+    (0..SIDES.len() as u8)
+        .max_by(|a, b| side_normal(id, res, *a).dot(to_eye).total_cmp(&side_normal(id, res, *b).dot(to_eye)))
+        .unwrap_or(0)
+}
+
+// corners of the sign on `facing` side, each with its texture coordinate. top left first, going clockwise
+pub fn quad(id: BlockId, res: u32, facing: u8) -> [(Vec3, [f32; 2]); 4] {
+    let corners = side_corners(id, res, facing);
+    let normal = side_normal(id, res, facing);
+    let center = corners.iter().copied().sum::<Vec3>() / 4.0 + normal * SIGN_OFFSET;
+    let up = ((corners[2] + corners[3]) - (corners[0] + corners[1])).normalize_or_zero();
+    // right as seen by someone standing in front of the sign, looking back along the normal
+    let right = up.cross(normal);
+    let half_w = (corners[1] - corners[0]).length() * 0.5;
+    let half_h = (corners[2] - corners[0]).length() * 0.5;
+    [
+        (center - right * half_w + up * half_h, [0.0, 0.0]),
+        (center + right * half_w + up * half_h, [1.0, 0.0]),
+        (center + right * half_w - up * half_h, [1.0, 1.0]),
+        (center - right * half_w - up * half_h, [0.0, 1.0]),
+    ]
+}
+
+fn block_center(id: BlockId, res: u32) -> Vec3 {
+    let corner = |i: u32| CoordSystem::get_vertex_pos(id.face, id.u + (i & 1), id.v + (i >> 1 & 1), id.layer + (i >> 2 & 1), res);
+    (0..8).map(corner).sum::<Vec3>() / 8.0
+}
+
+// bottom two then top two corners of one side
+fn side_corners(id: BlockId, res: u32, facing: u8) -> [Vec3; 4] {
+    let (du, dv) = SIDES[facing as usize % SIDES.len()];
+    // the side sits at u+1 (or v+1) when it faces the positive way
+    let (u0, u1) = if du != 0 { let u = id.u + (du > 0) as u32; (u, u) } else { (id.u, id.u + 1) };
+    let (v0, v1) = if dv != 0 { let v = id.v + (dv > 0) as u32; (v, v) } else { (id.v, id.v + 1) };
+    [
+        CoordSystem::get_vertex_pos(id.face, u0, v0, id.layer, res),
+        CoordSystem::get_vertex_pos(id.face, u1, v1, id.layer, res),
+        CoordSystem::get_vertex_pos(id.face, u0, v0, id.layer + 1, res),
+        CoordSystem::get_vertex_pos(id.face, u1, v1, id.layer + 1, res),
+    ]
+}
+
+fn side_normal(id: BlockId, res: u32, facing: u8) -> Vec3 {
+    let side = side_corners(id, res, facing).iter().copied().sum::<Vec3>() / 4.0;
+    (side - block_center(id, res)).normalize_or_zero()
+}
@@ -0,0 +1,99 @@
+// the sky behind everything: a full-screen quad drawn before the world, each pixel shaded by the direction it
+// looks in. blue from the horizon up by day, reddening as the sun nears the horizon and going dark with stars
+// at night, with the sun's disc where global.sun_dir points. "up" is away from the planet's centre, so how
+// high the sun stands depends on where on the planet the camera is.
+#include "wgsl/global.wgsl"
+#include "wgsl/color.wgsl"
+#include "wgsl/fog.wgsl"
+
+struct Local {
+    model: mat4x4<f32>, // clip space back to camera-relative world space
+    params: vec4<f32>,
+}
+@group(1) @binding(0) var<uniform> local: Local;
+
+// --- CONSTANTS ---
+const DAY_ZENITH      = vec3<f32>(0.18, 0.38, 0.78);
+const DAY_HORIZON     = vec3<f32>(0.62, 0.76, 0.92);
+const DUSK_HORIZON    = vec3<f32>(0.95, 0.48, 0.22);
+const NIGHT_ZENITH    = vec3<f32>(0.005, 0.008, 0.02);
+const NIGHT_HORIZON   = vec3<f32>(0.02, 0.03, 0.06);
+const SUN_DISC        = vec3<f32>(1.6, 1.5, 1.3);
+// cosine of the disc's angular radius, and of where its edge has faded out
+const SUN_SIZE        = 0.9996;
+const SUN_EDGE        = 0.9993;
+// cells per unit of direction the stars are scattered over, and the share of them that hold one
+const STAR_CELLS      = 400.0;
+const STAR_CHANCE     = 0.004;
+// the fog density the horizon haze is at its thickest from, settings.rs's default
+const DEFAULT_FOG_DENSITY = 0.0015;
+
+struct VertexOut {
+    @builtin(position) clip_pos: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+@vertex
+fn vs_main(in: VertexIn) -> VertexOut {
+    var out: VertexOut;
+    // at the far plane (reverse-z), so anything the world draws lands in front
+    out.clip_pos = vec4<f32>(in.pos.xy, 0.0, 1.0);
+    out.ndc = in.pos.xy;
+    return out;
+}
+
+fn hash(p: vec3<f32>) -> f32 {
+    return fract(sin(dot(p, vec3<f32>(12.9898, 78.233, 37.719))) * 43758.5453);
+}
+
+fn stars(dir: vec3<f32>) -> f32 {
+    let cell = floor(dir * STAR_CELLS);
+    if (hash(cell) > STAR_CHANCE) { return 0.0; }
+    // a point somewhere in the cell, fading out from it so stars are round rather than square
+    let centre = (cell + 0.5 + (vec3<f32>(hash(cell + 1.0), hash(cell + 2.0), hash(cell + 3.0)) - 0.5) * 0.6) / STAR_CELLS;
+    let falloff = 1.0 - smoothstep(0.0, 0.6 / STAR_CELLS, distance(dir, centre));
+    return falloff * (0.4 + 0.6 * hash(cell + 4.0));
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    // the ray through this pixel, from the near plane to the far one
+    let near = local.model * vec4<f32>(in.ndc, 1.0, 1.0);
+    let far = local.model * vec4<f32>(in.ndc, 0.0, 1.0);
+    let dir = normalize(far.xyz / far.w - near.xyz / near.w);
+
+    let up = normalize(global.camera_pos.xyz);
+    let sun = normalize(global.sun_dir.xyz);
+    let elevation = dot(up, sun);
+    let height = dot(dir, up);
+
+    // 1 in full day, 0 once the sun is well below the horizon, and how near it is to the horizon
+    let day = smoothstep(-0.15, 0.2, elevation);
+    let dusk = 1.0 - smoothstep(0.0, 0.35, abs(elevation));
+
+    // the horizon colour runs up into the zenith colour, faster by day. under the horizon it stays horizon
+    let t = pow(1.0 - clamp(height, 0.0, 1.0), 3.0);
+    let zenith = mix(NIGHT_ZENITH, DAY_ZENITH, day);
+    var horizon = mix(NIGHT_HORIZON, DAY_HORIZON, day);
+    // the sunset glow is strongest low down and on the sun's side
+    let toward_sun = max(dot(dir, sun), 0.0);
+    horizon = mix(horizon, DUSK_HORIZON, dusk * (0.35 + 0.65 * toward_sun * toward_sun));
+    var color = mix(zenith, horizon, t);
+
+    // a haze around the sun, then its disc
+    color += SUN_DISC * pow(toward_sun, 64.0) * 0.25 * (day + dusk);
+    color = mix(color, SUN_DISC, smoothstep(SUN_EDGE, SUN_SIZE, dot(dir, sun)));
+
+    // stars come out as the sky darkens, dimmed near the horizon
+    let night = 1.0 - smoothstep(-0.2, 0.05, elevation);
+    color += vec3<f32>(stars(dir) * night * smoothstep(-0.05, 0.2, height));
+
+    // the haze the world fades into lies along the horizon too, so far off land melts into the sky
+    if (global.fog.w > 0.0) {
+        let haze = 1.0 - smoothstep(-0.02, 0.25, height);
+        color = mix(color, fog_color(), haze * clamp(global.fog.w / DEFAULT_FOG_DENSITY, 0.0, 1.0));
+    }
+
+    // tone mapped like the world, so the horizon meets the fog
+    return vec4<f32>(pow(aces_approx(color), vec3<f32>(1.0 / 2.2)), 1.0);
+}
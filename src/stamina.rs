@@ -0,0 +1,46 @@
+//stamina.rs
+
+// optional, /option stamina: sprinting and jumping cost stamina and standing still brings it back. a player who
+// runs dry can't sprint or jump until it has come back past RECOVERED, so they can't hop along on a sliver of it.
+
+// --- CONSTANTS ---
+pub const MAX_STAMINA: f32 = 10.0;
+// per second of sprinting, and per jump
+const SPRINT_COST: f32 = 1.5;
+const JUMP_COST: f32 = 1.0;
+// per second standing still
+const REST_RATE: f32 = 2.5;
+const RECOVERED: f32 = 3.0;
+
+pub struct Stamina {
+    pub current: f32,
+    exhausted: bool,
+}
+
+impl Stamina {
+    pub fn new() -> Self {
+        Self { current: MAX_STAMINA, exhausted: false }
+    }
+
+    // sprinting and jumping are allowed
+    pub fn can_exert(&self) -> bool {
+        !self.exhausted
+    }
+
+    // what the player did over the last `dt` seconds
+    pub fn update(&mut self, dt: f32, sprinting: bool, jumped: bool, still: bool) {
+        let cost = if sprinting { SPRINT_COST * dt } else { 0.0 } + if jumped { JUMP_COST } else { 0.0 };
+        if cost > 0.0 {
+            self.current = (self.current - cost).max(0.0);
+            self.exhausted |= self.current <= 0.0;
+        } else if still {
+            self.current = (self.current + REST_RATE * dt).min(MAX_STAMINA);
+            self.exhausted &= self.current < RECOVERED;
+        }
+    }
+
+    // back to full, for when stamina is switched off
+    pub fn refill(&mut self) {
+        *self = Self::new();
+    }
+}
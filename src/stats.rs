@@ -0,0 +1,182 @@
+//stats.rs
+
+// what the player has done in this world: blocks mined and placed by type, distance walked and flown,
+// the deepest layer reached and time played. kept in stats.dat next to level.dat and saved along with it.
+// /stats me prints them, and crossing a milestone (1000 blocks mined, 10 km walked, ...) shows up in the console.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use glam::Vec3;
+use crate::codec::*;
+use crate::common::{BlockType, PlanetData};
+use crate::events::{GameEvent, Subscriber};
+use crate::gen::CoordSystem;
+use crate::i18n::tr;
+use crate::world::write_atomic;
+
+// --- CONSTANTS ---
+const STATS_FILE: &str = "stats.dat";
+const STATS_MAGIC: &[u8; 4] = b"VXNS";
+const STATS_VERSION: u32 = 1;
+// moving further than this in one frame is a teleport or respawn, not a walk
+const MAX_STEP: f32 = 20.0;
+const BLOCK_MILESTONES: [u32; 4] = [100, 1_000, 10_000, 100_000];
+// in blocks, which are about a metre
+const DISTANCE_MILESTONES: [f32; 4] = [1_000.0, 10_000.0, 100_000.0, 1_000_000.0];
+
+#[derive(Default)]
+pub struct Stats {
+    pub mined: BTreeMap<String, u32>,
+    pub placed: BTreeMap<String, u32>,
+    pub walked: f32,
+    pub flown: f32,
+    // lowest layer the player has stood in, None until they've been on the planet
+    pub deepest: Option<u32>,
+    // seconds
+    pub played: f32,
+    // milestone messages waiting for the caller to show
+    milestones: Vec<String>,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // a missing file is a world nobody has played yet, so that starts from zero
+    pub fn load(dir: &Path) -> io::Result<Self> {
+        match fs::read(dir.join(STATS_FILE)) {
+            Ok(bytes) => Self::decode(&bytes),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        write_atomic(&dir.join(STATS_FILE), &self.encode())
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(STATS_MAGIC);
+        put_u32(&mut out, STATS_VERSION);
+        for counts in [&self.mined, &self.placed] {
+            put_u32(&mut out, counts.len() as u32);
+            for (kind, n) in counts {
+                put_str(&mut out, kind);
+                put_u32(&mut out, *n);
+            }
+        }
+        put_f32(&mut out, self.walked);
+        put_f32(&mut out, self.flown);
+        put_u32(&mut out, self.deepest.unwrap_or(u32::MAX));
+        put_f32(&mut out, self.played);
+        out
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ByteReader::new(bytes);
+        if r.bytes(4)? != STATS_MAGIC { return Err(invalid("stats.dat has a bad header")); }
+        let version = r.u32()?;
+        if version != STATS_VERSION {
+            return Err(invalid(&format!("unsupported stats version {}", version)));
+        }
+
+        let mut stats = Self::new();
+        for counts in [&mut stats.mined, &mut stats.placed] {
+            for _ in 0..r.u32()? {
+                let kind = r.str()?;
+                counts.insert(kind, r.u32()?);
+            }
+        }
+        stats.walked = r.f32()?;
+        stats.flown = r.f32()?;
+        stats.deepest = Some(r.u32()?).filter(|layer| *layer != u32::MAX);
+        stats.played = r.f32()?;
+        Ok(stats)
+    }
+
+    pub fn total_mined(&self) -> u32 {
+        self.mined.values().sum()
+    }
+
+    pub fn total_placed(&self) -> u32 {
+        self.placed.values().sum()
+    }
+
+    // counts a block edit made by this player, `block` being what was placed or mined
+    fn record_edit(&mut self, block: BlockType, placed: bool) {
+        let kind = block.name().to_string();
+        if placed {
+            let before = self.total_placed();
+            *self.placed.entry(kind).or_insert(0) += 1;
+            self.block_milestone(before, "stats.milestone.placed");
+        } else {
+            let before = self.total_mined();
+            *self.mined.entry(kind).or_insert(0) += 1;
+            self.block_milestone(before, "stats.milestone.mined");
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.played += dt;
+    }
+
+    fn record_move(&mut self, from: Vec3, to: Vec3, flying: bool, planet: &PlanetData) {
+        let step = from.distance(to);
+        if step < MAX_STEP {
+            if flying {
+                let before = self.flown;
+                self.flown += step;
+                self.distance_milestone(before, self.flown, "stats.milestone.flown");
+            } else {
+                let before = self.walked;
+                self.walked += step;
+                self.distance_milestone(before, self.walked, "stats.milestone.walked");
+            }
+        }
+
+        if let Some(id) = CoordSystem::pos_to_id(to, planet.resolution) {
+            if self.deepest.is_none_or(|layer| id.layer < layer) {
+                self.deepest = Some(id.layer);
+            }
+        }
+    }
+
+    // milestones reached since the last call
+    pub fn take_milestones(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.milestones)
+    }
+
+    fn block_milestone(&mut self, before: u32, key: &str) {
+        if let Some(m) = BLOCK_MILESTONES.iter().find(|m| before < **m && before + 1 >= **m) {
+            self.milestones.push(tr!(key, m));
+        }
+    }
+
+    fn distance_milestone(&mut self, before: f32, after: f32, key: &str) {
+        if let Some(m) = DISTANCE_MILESTONES.iter().find(|m| before < **m && after >= **m) {
+            self.milestones.push(tr!(key, m / 1000.0));
+        }
+    }
+}
+
+impl Subscriber for Stats {
+    fn on_event(&mut self, event: &GameEvent, planet: &PlanetData) {
+        match *event {
+            GameEvent::BlockMined { block, remote: false, .. } => self.record_edit(block, false),
+            GameEvent::BlockPlaced { block, remote: false, .. } => self.record_edit(block, true),
+            GameEvent::PlayerMoved { from, to, flying } => self.record_move(from, to, flying, planet),
+            _ => {}
+        }
+    }
+}
+
+// 3725 seconds -> 1:02:05
+pub fn format_duration(seconds: f32) -> String {
+    let s = seconds as u64;
+    format!("{}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60)
+}
@@ -1,4 +1,18 @@
-use sysinfo::System;
+use sysinfo::{Pid, System};
+use crate::gen::ViewDistance;
+use crate::settings::{GraphicsPreset, MAX_LOD_DETAIL, MAX_RENDER_DISTANCE, MIN_LOD_DETAIL, MIN_RENDER_DISTANCE};
+
+// --- CONSTANTS ---
+// seconds between auto render distance adjustments, long enough for the new chunks to load and fps to settle
+const TUNE_INTERVAL: f32 = 2.0;
+// seconds between battery checks, reading the power supply isn't free
+const POWER_POLL_INTERVAL: f32 = 10.0;
+// seconds between memory checks
+const MEMORY_POLL_INTERVAL: f32 = 2.0;
+
+// fps band the auto tuner tries to stay inside. the top sits just under 60 so a vsynced display can still grow
+const TUNE_MIN_FPS: u32 = 45;
+const TUNE_MAX_FPS: u32 = 58;
 
 pub struct SystemDiagnostics;
 
@@ -33,6 +47,48 @@ impl SystemDiagnostics {
         println!("==========================================\n");
     }
 
+    // how much gpu memory chunk meshes may use. wgpu can't report vram, so this guesses from the device type:
+    // integrated gpus share system ram, discrete ones get a fixed budget that fits any card from the last decade
+    pub fn mesh_memory_budget(info: &wgpu::AdapterInfo) -> u64 {
+        const MB: u64 = 1024 * 1024;
+        match info.device_type {
+            wgpu::DeviceType::DiscreteGpu => 1536 * MB,
+            wgpu::DeviceType::IntegratedGpu => {
+                let mut sys = System::new();
+                sys.refresh_memory();
+                (sys.total_memory() / 8).clamp(256 * MB, 1024 * MB)
+            }
+            _ => 256 * MB,
+        }
+    }
+
+    // first-run graphics preset: the gpu sets the ceiling, then little ram, few cores,
+    // a software renderer or the gl fallback backend each pull it down
+    pub fn detect_preset(info: &wgpu::AdapterInfo) -> GraphicsPreset {
+        let mut sys = System::new();
+        sys.refresh_memory();
+        sys.refresh_cpu();
+        let ram_gb = sys.total_memory() as f32 / 1024.0 / 1024.0 / 1024.0;
+        let cores = sys.cpus().len();
+        let name = info.name.to_lowercase();
+
+        let mut tier = match info.device_type {
+            wgpu::DeviceType::DiscreteGpu => 2,
+            wgpu::DeviceType::IntegratedGpu => 1,
+            _ => 0,
+        };
+        if ["llvmpipe", "swiftshader", "software", "basic render"].iter().any(|s| name.contains(s)) { tier = 0; }
+        if info.backend == wgpu::Backend::Gl { tier = tier.min(1); }
+        if ram_gb < 8.0 || cores < 4 { tier = tier.min(1); }
+        if ram_gb < 4.0 || cores < 2 { tier = 0; }
+
+        match tier {
+            0 => GraphicsPreset::Low,
+            1 => GraphicsPreset::Medium,
+            _ => GraphicsPreset::High,
+        }
+    }
+
     pub fn log_gpu(info: &wgpu::AdapterInfo) {
         println!("--- GPU INFO ---");
         println!("Name     : {}", info.name);
@@ -41,4 +97,87 @@ impl SystemDiagnostics {
         println!("Vendor   : {:?}", info.vendor);
         println!("----------------\n");
     }
-}
\ No newline at end of file
+}
+// auto render distance: shrinks the view when fps drops or meshes outgrow the memory budget, grows it back when there's headroom
+pub struct AutoTune {
+    timer: f32,
+}
+
+impl AutoTune {
+    pub fn new() -> Self {
+        Self { timer: 0.0 }
+    }
+
+    pub fn update(&mut self, dt: f32, fps: u32, mesh_bytes: u64, budget: u64, view: &mut ViewDistance) {
+        self.timer += dt;
+        if self.timer < TUNE_INTERVAL { return; }
+        self.timer = 0.0;
+
+        let memory = mesh_bytes as f32 / budget.max(1) as f32;
+        if fps < TUNE_MIN_FPS || memory > 0.9 {
+            view.chunks = (view.chunks - 1.0).max(MIN_RENDER_DISTANCE);
+            view.lod_detail = (view.lod_detail * 0.9).max(MIN_LOD_DETAIL);
+        } else if fps > TUNE_MAX_FPS && memory < 0.6 {
+            view.chunks = (view.chunks + 1.0).min(MAX_RENDER_DISTANCE);
+            view.lod_detail = (view.lod_detail * 1.1).min(MAX_LOD_DETAIL);
+        }
+    }
+}
+
+// watches whether the machine runs off its battery. sysinfo has no battery api, so this reads
+// /sys/class/power_supply on linux and reports mains power everywhere else
+pub struct PowerMonitor {
+    timer: f32,
+    pub on_battery: bool,
+}
+
+impl PowerMonitor {
+    pub fn new() -> Self {
+        Self { timer: 0.0, on_battery: Self::read_on_battery() }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.timer += dt;
+        if self.timer < POWER_POLL_INTERVAL { return; }
+        self.timer = 0.0;
+        self.on_battery = Self::read_on_battery();
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read_on_battery() -> bool {
+        let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else { return false };
+        let read = |path: &std::path::Path, file: &str| std::fs::read_to_string(path.join(file)).unwrap_or_default();
+        supplies.flatten().map(|e| e.path()).any(|path| {
+            read(&path, "type").trim() == "Battery" && read(&path, "status").trim() == "Discharging"
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn read_on_battery() -> bool {
+        false
+    }
+}
+
+// samples the resident memory of this process
+pub struct MemoryMonitor {
+    sys: System,
+    pid: Option<Pid>,
+    timer: f32,
+}
+
+impl MemoryMonitor {
+    pub fn new() -> Self {
+        Self { sys: System::new(), pid: sysinfo::get_current_pid().ok(), timer: 0.0 }
+    }
+
+    // resident set size in bytes, whenever a new sample was taken
+    pub fn update(&mut self, dt: f32) -> Option<u64> {
+        self.timer += dt;
+        if self.timer < MEMORY_POLL_INTERVAL { return None; }
+        self.timer = 0.0;
+
+        let pid = self.pid?;
+        self.sys.refresh_process(pid);
+        self.sys.process(pid).map(|p| p.memory())
+    }
+}
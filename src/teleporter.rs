@@ -0,0 +1,167 @@
+//teleporter.rs
+
+// teleporters: a block linked to one other block through a shared channel name, set with /teleporter link <channel>
+// while looking at it. the channel lives in the block's BlockEntity. standing on one end charges it up, once it's
+// full the screen fades out, the player is put on top of the other end and the screen fades back in. online the
+// server does the moving (Message::Teleport), the client only asks once the screen is black.
+
+use std::collections::BTreeMap;
+use glam::Vec3;
+use crate::common::{BlockEntity, BlockId, PlanetData};
+use crate::entity::Player;
+use crate::gen::CoordSystem;
+use crate::i18n::tr;
+use crate::physics::Physics;
+
+// --- CONSTANTS ---
+pub const MAX_CHANNEL: usize = 32;
+// seconds standing on a teleporter before it fires
+pub const CHARGE_TIME: f32 = 2.0;
+// seconds for the screen to go black, and again to come back
+const FADE_TIME: f32 = 0.35;
+// how far from the top of a teleporter the server still lets someone go through it: standing on it, or pressing
+// E on it from interact.rs's reach
+pub const TELEPORT_REACH: f32 = 6.0;
+
+pub fn channel(planet: &PlanetData, id: BlockId) -> Option<&str> {
+    match planet.entity(id)? {
+        BlockEntity::Teleporter { channel } => Some(channel),
+        _ => None,
+    }
+}
+
+// the other end of the teleporter at `id`, None while it's waiting for one
+pub fn partner(planet: &PlanetData, id: BlockId) -> Option<BlockId> {
+    let channel = channel(planet, id)?;
+    planet.entities()
+        .find(|(other, entity)| **other != id && matches!(entity, BlockEntity::Teleporter { channel: c } if c == channel))
+        .map(|(other, _)| *other)
+}
+
+// every channel in the world and the blocks on it
+pub fn channels(planet: &PlanetData) -> BTreeMap<String, Vec<BlockId>> {
+    let mut channels: BTreeMap<String, Vec<BlockId>> = BTreeMap::new();
+    for (id, entity) in planet.entities() {
+        if let BlockEntity::Teleporter { channel } = entity {
+            channels.entry(channel.clone()).or_default().push(*id);
+        }
+    }
+    channels
+}
+
+// whether `id` may become an end of `channel`. a channel links exactly two blocks
+pub fn check_link(planet: &PlanetData, id: BlockId, channel: &str) -> Result<(), String> {
+    let valid = !channel.is_empty() && channel.len() <= MAX_CHANNEL
+        && channel.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(tr!("teleporter.bad_channel", MAX_CHANNEL));
+    }
+    let ends = channels(planet).get(channel).map_or(0, |ends| ends.iter().filter(|end| **end != id).count());
+    if ends >= 2 {
+        return Err(tr!("teleporter.channel_full", channel));
+    }
+    Ok(())
+}
+
+// middle of the top of `id`, where someone arriving on it stands
+pub fn arrival(id: BlockId, res: u32) -> Vec3 {
+    let center = CoordSystem::get_block_center(id.face, id.u, id.v, id.layer, res);
+    center.normalize_or_zero() * CoordSystem::get_layer_radius(id.layer + 1, res)
+}
+
+// the teleporter the player stands on, if any
+fn standing_on(player: &Player, planet: &PlanetData) -> Option<BlockId> {
+    if !player.grounded { return None; }
+    let below = player.position - Physics::get_up_vector(player.position) * 0.1;
+    CoordSystem::pos_to_id(below, planet.resolution).filter(|id| channel(planet, *id).is_some())
+}
+
+#[derive(Default)]
+enum Phase {
+    #[default]
+    Idle,
+    // screen going black, then the player goes from the first to the second
+    FadeOut(BlockId, BlockId),
+    FadeIn,
+}
+
+#[derive(Default)]
+pub struct Travel {
+    // teleporter being charged and for how long
+    charging: Option<(BlockId, f32)>,
+    phase: Phase,
+    // 0 clear .. 1 black
+    fade: f32,
+    // the end the player arrived on, it won't send them back until they've stepped off
+    arrived_on: Option<BlockId>,
+}
+
+impl Travel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // returns the teleporters the player just went from and to. `online` leaves the player where they are for
+    // the caller to send Message::Teleport, the server's Correction moves them
+    pub fn update(&mut self, dt: f32, player: &mut Player, planet: &PlanetData, online: bool) -> Option<(BlockId, BlockId)> {
+        let step = dt / FADE_TIME;
+        match self.phase {
+            Phase::FadeOut(from, dest) => {
+                self.fade = (self.fade + step).min(1.0);
+                if self.fade < 1.0 { return None; }
+                self.phase = Phase::FadeIn;
+                // taken down while the screen was going black
+                channel(planet, dest)?;
+                if !online {
+                    player.position = arrival(dest, planet.resolution);
+                    player.velocity = Vec3::ZERO;
+                }
+                player.rope = None;
+                player.gliding = false;
+                self.arrived_on = Some(dest);
+                Some((from, dest))
+            },
+            Phase::FadeIn => {
+                self.fade = (self.fade - step).max(0.0);
+                if self.fade <= 0.0 { self.phase = Phase::Idle; }
+                None
+            },
+            Phase::Idle => {
+                let under = standing_on(player, planet);
+                if under != self.arrived_on { self.arrived_on = None; }
+                let link = under.filter(|id| Some(*id) != self.arrived_on).and_then(|id| Some((id, partner(planet, id)?)));
+                self.charging = match link {
+                    Some((id, dest)) => {
+                        let charge = self.charging.filter(|(charged, _)| *charged == id).map_or(0.0, |(_, t)| t) + dt;
+                        if charge >= CHARGE_TIME {
+                            self.phase = Phase::FadeOut(id, dest);
+                            None
+                        } else {
+                            Some((id, charge))
+                        }
+                    },
+                    None => None,
+                };
+                None
+            },
+        }
+    }
+
+    // sends the player to the other end of `id` without waiting for it to charge. false if it has no other end
+    pub fn activate(&mut self, id: BlockId, planet: &PlanetData) -> bool {
+        if !matches!(self.phase, Phase::Idle) { return false; }
+        let Some(dest) = partner(planet, id) else { return false };
+        self.charging = None;
+        self.phase = Phase::FadeOut(id, dest);
+        true
+    }
+
+    pub fn fade(&self) -> f32 {
+        self.fade
+    }
+
+    // teleporter being charged and how full it is, 0..1
+    pub fn charge(&self) -> Option<(BlockId, f32)> {
+        self.charging.map(|(id, t)| (id, t / CHARGE_TIME))
+    }
+}
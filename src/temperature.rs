@@ -0,0 +1,68 @@
+//temperature.rs
+
+// the air gets colder the higher the player stands and warmer the deeper they dig, by LAPSE_RATE per layer
+// from the planet's base radius. on top of that the day side is up to SUN_SWING warmer and the night side as
+// much colder, by how high the sun stands where the player is (the sun is fixed, so that's the time of day, as
+// for the music). only sunlight that reaches the player counts, from LightEngine, so a cave or a roof evens it
+// out. nothing gives off light of its own yet, so there's no warming near blocks. outside the comfortable range
+// the player takes a point of damage every EXPOSURE_TIME seconds, through Health::hurt like any other damage.
+
+use glam::Vec3;
+use crate::common::{sun_dir, PlanetData};
+use crate::gen::CoordSystem;
+use crate::lighting::LightEngine;
+
+// --- CONSTANTS ---
+// degrees at the base radius, and lost per layer above it
+const BASE_TEMPERATURE: f32 = 25.0;
+const LAPSE_RATE: f32 = 1.5;
+// degrees added under the noon sun and taken off at midnight, in full light
+const SUN_SWING: f32 = 12.0;
+// outside these it starts to hurt
+pub const COLD_LIMIT: f32 = -10.0;
+pub const HEAT_LIMIT: f32 = 45.0;
+const EXPOSURE_TIME: f32 = 3.0;
+pub const EXPOSURE_DAMAGE: f32 = 1.0;
+
+pub struct Temperature {
+    // degrees celsius where the player is
+    pub celsius: f32,
+    exposure: f32,
+}
+
+impl Temperature {
+    pub fn new() -> Self {
+        Self { celsius: BASE_TEMPERATURE, exposure: 0.0 }
+    }
+
+    // true when the cold or heat has got to the player again
+    pub fn update(&mut self, dt: f32, pos: Vec3, planet: &PlanetData) -> bool {
+        self.celsius = ambient(pos, planet);
+        if (COLD_LIMIT..=HEAT_LIMIT).contains(&self.celsius) {
+            self.exposure = 0.0;
+            return false;
+        }
+        self.exposure += dt;
+        if self.exposure < EXPOSURE_TIME { return false; }
+        self.exposure -= EXPOSURE_TIME;
+        true
+    }
+
+    pub fn is_cold(&self) -> bool {
+        self.celsius < COLD_LIMIT
+    }
+}
+
+// temperature of the air at `pos`
+pub fn ambient(pos: Vec3, planet: &PlanetData) -> f32 {
+    let res = planet.resolution;
+    let layers = CoordSystem::get_layer_at(pos.length(), res) - res as f32 / 2.0;
+    // past the top layer there's nothing to shade the player, under the bottom one there's no sun at all
+    let light = match CoordSystem::pos_to_id(pos, res) {
+        Some(id) => LightEngine::calculate_light(id, planet),
+        None if layers > 0.0 => LightEngine::MAX_LIGHT,
+        None => 0,
+    };
+    let sun = pos.normalize_or_zero().dot(sun_dir()) * light as f32 / LightEngine::MAX_LIGHT as f32;
+    BASE_TEMPERATURE - layers * LAPSE_RATE + sun * SUN_SWING
+}
@@ -0,0 +1,60 @@
+//tick.rs
+
+// the simulation's clock, apart from the frame rate. at a tick rate of 0 the world steps once for every pass
+// of the event loop with however long it's been, as it always has. at a fixed rate it steps in ticks of
+// 1 / rate seconds, as many as are due, and frames drawn between two ticks show the player part way from
+// where the one before left them to where the last one did, so a 30 Hz world still looks smooth at 144 fps.
+// the view turns with the player, so it trails the mouse by up to a tick. only the simulation steps in ticks: the
+// network, the event bus, the view and the hud run once a pass, and the last two see the player where they're drawn.
+
+use glam::{Quat, Vec3};
+
+// --- CONSTANTS ---
+// most ticks run to catch up at once. past this a slow machine runs the world slower instead of spending
+// every frame catching up and falling further behind
+const MAX_CATCH_UP: u32 = 5;
+// further than this in one tick is a teleport or a respawn, shown as a jump rather than a streak
+const SNAP_DISTANCE: f32 = 16.0;
+
+#[derive(Default)]
+pub struct SimClock {
+    // seconds since the last tick, short of the next
+    behind: f32,
+    // where the player was before the last tick, for interpolating up to where it left them
+    from: Option<(Vec3, Quat)>,
+}
+
+impl SimClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // how many ticks are due after `dt` more seconds at `rate` a second, and how long each is
+    pub fn advance(&mut self, dt: f32, rate: u32) -> (u32, f32) {
+        if rate == 0 {
+            self.behind = 0.0;
+            return (1, dt);
+        }
+        let step = 1.0 / rate as f32;
+        self.behind += dt;
+        let ticks = (self.behind / step) as u32;
+        self.behind -= ticks as f32 * step;
+        if ticks > MAX_CATCH_UP {
+            self.behind = 0.0;
+        }
+        (ticks.min(MAX_CATCH_UP), step)
+    }
+
+    // a tick is about to move the player on from here
+    pub fn start_tick(&mut self, position: Vec3, rotation: Quat) {
+        self.from = Some((position, rotation));
+    }
+
+    // where to draw a player the last tick left at `position` and `rotation`
+    pub fn interpolate(&self, rate: u32, position: Vec3, rotation: Quat) -> (Vec3, Quat) {
+        let Some((from, from_rotation)) = self.from.filter(|_| rate > 0) else { return (position, rotation) };
+        if from.distance(position) > SNAP_DISTANCE { return (position, rotation); }
+        let t = (self.behind * rate as f32).clamp(0.0, 1.0);
+        (from.lerp(position, t), from_rotation.slerp(rotation, t))
+    }
+}
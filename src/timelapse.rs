@@ -0,0 +1,103 @@
+//timelapse.rs
+
+// the time-lapse recorder: a still from a fixed camera, or one circling a point, every few minutes of game
+// time, numbered in a directory under timelapse/ so any video tool can turn them into a film of a build.
+// there's no day cycle, so game time is just how long the world has been running.
+
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use glam::{Mat4, Quat, Vec3};
+use crate::common::reverse_z;
+use crate::capture::{self, Frame};
+use crate::entity::Player;
+use crate::physics::Physics;
+
+// --- CONSTANTS ---
+pub const TIMELAPSE_DIR: &str = "timelapse";
+// size of every still, whatever the window is
+pub const LAPSE_WIDTH: u32 = 1280;
+pub const LAPSE_HEIGHT: u32 = 720;
+const LAPSE_FOV: f32 = 60.0;
+const LAPSE_FAR: f32 = 4000.0;
+// degrees the orbit camera moves on between two stills
+const ORBIT_STEP: f32 = 3.0;
+
+pub enum LapseCamera {
+    Fixed { eye: Vec3, rotation: Quat },
+    // looks at `center` from `offset` away, turning around it
+    Orbit { center: Vec3, offset: Vec3 },
+}
+
+impl LapseCamera {
+    // where the player's eyes are, looking where they look, like a monitor camera
+    pub fn fixed(player: &Player) -> Self {
+        let up = Physics::get_up_vector(player.position);
+        let rotation = player.rotation * Quat::from_axis_angle(Vec3::X, player.cam_pitch);
+        LapseCamera::Fixed { eye: player.position + up * Physics::EYE_HEIGHT, rotation }
+    }
+
+    // circling `center` at the distance and height of the player's eyes
+    pub fn orbit(player: &Player, center: Vec3) -> Self {
+        let eye = player.position + Physics::get_up_vector(player.position) * Physics::EYE_HEIGHT;
+        LapseCamera::Orbit { center, offset: eye - center }
+    }
+
+    pub fn eye(&self) -> Vec3 {
+        match self {
+            LapseCamera::Fixed { eye, .. } => *eye,
+            LapseCamera::Orbit { center, offset } => *center + *offset,
+        }
+    }
+
+    pub fn view_proj(&self) -> Mat4 {
+        let eye = self.eye();
+        let up = Physics::get_up_vector(eye);
+        let target = match self {
+            LapseCamera::Fixed { rotation, .. } => eye + *rotation * Vec3::NEG_Z,
+            LapseCamera::Orbit { center, .. } => *center,
+        };
+        let proj = reverse_z(Mat4::perspective_rh(LAPSE_FOV.to_radians(), LAPSE_WIDTH as f32 / LAPSE_HEIGHT as f32, 0.1, LAPSE_FAR));
+        proj * Mat4::look_at_rh(eye, target, up)
+    }
+}
+
+pub struct TimeLapse {
+    pub dir: PathBuf,
+    // seconds of game time between stills
+    pub interval: f32,
+    timer: f32,
+    pub frames: u32,
+    pub camera: LapseCamera,
+    errors: (Sender<String>, Receiver<String>),
+}
+
+impl TimeLapse {
+    pub fn start(minutes: f32, camera: LapseCamera) -> std::io::Result<Self> {
+        let dir = capture::new_session_dir(TIMELAPSE_DIR)?;
+        // the first still is taken right away
+        let interval = minutes * 60.0;
+        Ok(Self { dir, interval, timer: interval, frames: 0, camera, errors: channel() })
+    }
+
+    // whether a still is due this frame
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.timer += dt;
+        if self.timer < self.interval { return false; }
+        self.timer = 0.0;
+        true
+    }
+
+    pub fn save(&mut self, frame: Frame) {
+        let path = self.dir.join(format!("frame_{:05}.png", self.frames));
+        capture::save_png(path, frame, self.errors.0.clone());
+        self.frames += 1;
+        if let LapseCamera::Orbit { center, offset } = &mut self.camera {
+            *offset = Quat::from_axis_angle(Physics::get_up_vector(*center), ORBIT_STEP.to_radians()) * *offset;
+        }
+    }
+
+    // stills that couldn't be written since the last call
+    pub fn errors(&self) -> Vec<String> {
+        self.errors.1.try_iter().collect()
+    }
+}
@@ -0,0 +1,51 @@
+//toast.rs
+
+// short notices at the top of the screen for things worth a glance but not the console, like an autosave or a
+// finished panorama. each fades in, stays a few seconds and fades out. the newest is on top and only the last few
+// are kept, so a burst of them doesn't cover the view.
+
+// --- CONSTANTS ---
+const SHOWN_FOR: f32 = 3.0;
+const FADE: f32 = 0.4;
+const MAX_TOASTS: usize = 4;
+
+struct Toast {
+    text: String,
+    color: [f32; 3],
+    age: f32,
+}
+
+#[derive(Default)]
+pub struct Toasts {
+    list: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, text: String, color: [f32; 3]) {
+        // the same notice again restarts the one already up instead of stacking
+        self.list.retain(|t| t.text != text);
+        self.list.insert(0, Toast { text, color, age: 0.0 });
+        self.list.truncate(MAX_TOASTS);
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for toast in &mut self.list {
+            toast.age += dt;
+        }
+        self.list.retain(|t| t.age < SHOWN_FOR);
+    }
+
+    // text, colour and opacity of each toast up, newest first
+    pub fn visible(&self) -> Vec<(String, [f32; 3], f32)> {
+        self.list.iter()
+            .map(|t| {
+                let alpha = (t.age / FADE).min((SHOWN_FOR - t.age) / FADE).clamp(0.0, 1.0);
+                (t.text.clone(), t.color, alpha)
+            })
+            .collect()
+    }
+}
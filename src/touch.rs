@@ -0,0 +1,106 @@
+//touch.rs
+
+// touchscreen controls: a finger on the left half of the screen is a floating joystick, one on the right half
+// drags to look. a quick tap on the right half mines, holding it still places.
+
+use std::time::Instant;
+use glam::Vec2;
+use winit::event::{Touch, TouchPhase};
+
+// --- CONSTANTS ---
+// joystick travel in pixels for full speed
+pub const STICK_RADIUS: f32 = 70.0;
+const STICK_DEADZONE: f32 = 0.2;
+// how far a finger may drift and still count as a tap or long press, in pixels
+const TAP_SLOP: f32 = 12.0;
+// seconds a finger has to stay down for a long press
+const LONG_PRESS: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug)]
+pub enum TouchAction {
+    Mine(Vec2),
+    Place(Vec2),
+}
+
+struct Stick {
+    id: u64,
+    origin: Vec2,
+    pos: Vec2,
+}
+
+struct Look {
+    id: u64,
+    start: Vec2,
+    last: Vec2,
+    since: Instant,
+    // hasn't left TAP_SLOP yet, so it may still turn into a tap or long press
+    still: bool,
+    // the long press already fired
+    pressed: bool,
+}
+
+pub struct TouchControls {
+    stick: Option<Stick>,
+    look: Option<Look>,
+}
+
+impl TouchControls {
+    pub fn new() -> Self {
+        Self { stick: None, look: None }
+    }
+
+    // returns a look delta to use like raw mouse motion, and the action of a finished tap
+    pub fn handle(&mut self, touch: &Touch, width: f32) -> (Option<(f64, f64)>, Option<TouchAction>) {
+        let pos = Vec2::new(touch.location.x as f32, touch.location.y as f32);
+
+        match touch.phase {
+            TouchPhase::Started => {
+                if pos.x < width / 2.0 && self.stick.is_none() {
+                    self.stick = Some(Stick { id: touch.id, origin: pos, pos });
+                } else if self.look.is_none() {
+                    self.look = Some(Look { id: touch.id, start: pos, last: pos, since: Instant::now(), still: true, pressed: false });
+                }
+            },
+            TouchPhase::Moved => {
+                if let Some(stick) = self.stick.as_mut().filter(|s| s.id == touch.id) {
+                    stick.pos = pos;
+                } else if let Some(look) = self.look.as_mut().filter(|l| l.id == touch.id) {
+                    let d = pos - look.last;
+                    look.last = pos;
+                    if look.start.distance(pos) > TAP_SLOP { look.still = false; }
+                    if !look.still { return (Some((d.x as f64, d.y as f64)), None); }
+                }
+            },
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if self.stick.as_ref().is_some_and(|s| s.id == touch.id) {
+                    self.stick = None;
+                }
+                if let Some(look) = self.look.take_if(|l| l.id == touch.id) {
+                    let tap = touch.phase == TouchPhase::Ended && look.still && !look.pressed;
+                    if tap { return (None, Some(TouchAction::Mine(look.start))); }
+                }
+            },
+        }
+        (None, None)
+    }
+
+    // long presses fire while the finger is still down, so call this every frame
+    pub fn update(&mut self) -> Option<TouchAction> {
+        let look = self.look.as_mut()?;
+        if !look.still || look.pressed || look.since.elapsed().as_secs_f32() < LONG_PRESS { return None; }
+        look.pressed = true;
+        Some(TouchAction::Place(look.start))
+    }
+
+    // joystick as movement input: x strafes right, y walks backwards, same as the wasd axes
+    pub fn stick_input(&self) -> Vec2 {
+        let Some(stick) = &self.stick else { return Vec2::ZERO };
+        let v = ((stick.pos - stick.origin) / STICK_RADIUS).clamp_length_max(1.0);
+        if v.length() < STICK_DEADZONE { Vec2::ZERO } else { v }
+    }
+
+    // where the joystick is held: its centre and the knob, for drawing
+    pub fn stick(&self) -> Option<(Vec2, Vec2)> {
+        self.stick.as_ref().map(|s| (s.origin, s.origin + (s.pos - s.origin).clamp_length_max(STICK_RADIUS)))
+    }
+}
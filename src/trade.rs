@@ -0,0 +1,120 @@
+//trade.rs
+
+// trading with villagers. every villager gets a few offers out of OFFERS when it's added (see Mobs::add) and
+// keeps them for as long as it's around. E on a villager opens its offers as a panel like the settings menu:
+// up/down pick one, Enter hands the blocks over and E or Esc walks away. a trade goes through
+// Inventory::exchange, so it happens whole or not at all.
+
+use glam::Vec3;
+use crate::common::BlockType;
+use crate::ecs::Entity;
+use crate::i18n::tr;
+use crate::inventory::{Inventory, Stack};
+use crate::mob::{Mobs, Rng};
+
+// --- CONSTANTS ---
+// how many offers each villager has
+const OFFERS_PER_VILLAGER: usize = 3;
+// the panel closes once the player is further than this from the villager
+const TRADE_REACH: f32 = 6.0;
+
+// every deal a villager may offer: what the player hands over, and what they get for it
+const OFFERS: &[(BlockType, u32, BlockType, u32)] = &[
+    (BlockType::Dirt, 16, BlockType::Wood, 4),
+    (BlockType::Rock, 16, BlockType::Stone, 8),
+    (BlockType::Stone, 12, BlockType::Brick, 4),
+    (BlockType::Sand, 8, BlockType::Glass, 4),
+    (BlockType::Wood, 8, BlockType::Brick, 2),
+    (BlockType::Dirt, 8, BlockType::Sand, 8),
+    (BlockType::Grass, 4, BlockType::Wood, 2),
+];
+
+#[derive(Clone, Copy)]
+pub struct Offer {
+    pub give: Stack,
+    pub get: Stack,
+}
+
+// a villager's offers
+pub struct Trades(pub Vec<Offer>);
+
+impl Trades {
+    // OFFERS_PER_VILLAGER different offers, picked at random
+    pub fn roll(rng: &mut Rng) -> Self {
+        let mut pool: Vec<usize> = (0..OFFERS.len()).collect();
+        let mut offers = Vec::with_capacity(OFFERS_PER_VILLAGER);
+        while offers.len() < OFFERS_PER_VILLAGER && !pool.is_empty() {
+            let i = pool.swap_remove((rng.next() * pool.len() as f32) as usize);
+            let (give, give_count, get, get_count) = OFFERS[i];
+            offers.push(Offer { give: Stack { block: give, count: give_count }, get: Stack { block: get, count: get_count } });
+        }
+        Self(offers)
+    }
+}
+
+#[derive(Default)]
+pub struct TradeMenu {
+    // the villager being traded with, while the panel is open
+    villager: Option<Entity>,
+    selected: usize,
+}
+
+impl TradeMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(&mut self, villager: Entity) {
+        *self = Self { villager: Some(villager), selected: 0 };
+    }
+
+    pub fn close(&mut self) {
+        self.villager = None;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.villager.is_some()
+    }
+
+    // closes the panel once the villager is gone or the player has walked off
+    pub fn update(&mut self, mobs: &Mobs, player: Vec3) {
+        let Some(e) = self.villager else { return };
+        if !mobs.transforms.get(e).is_some_and(|t| t.center().distance(player) <= TRADE_REACH) {
+            self.close();
+        }
+    }
+
+    fn offers<'a>(&self, mobs: &'a Mobs) -> &'a [Offer] {
+        self.villager.and_then(|e| mobs.traders.get(e)).map_or(&[], |t| &t.0)
+    }
+
+    // next (1) or previous (-1) offer, wrapping round
+    pub fn select(&mut self, mobs: &Mobs, dir: i32) {
+        let len = self.offers(mobs).len().max(1) as i32;
+        self.selected = (self.selected as i32 + dir).rem_euclid(len) as usize;
+    }
+
+    // makes the selected trade, or says why it can't
+    pub fn accept(&self, mobs: &Mobs, inventory: &mut Inventory) -> Result<Offer, String> {
+        let Some(offer) = self.offers(mobs).get(self.selected).copied() else { return Err(tr!("trade.nothing")) };
+        let (give, get) = (offer.give, offer.get);
+        if inventory.count(give.block) < give.count {
+            return Err(tr!("trade.short", give.count, give.block.name()));
+        }
+        if !inventory.exchange(give, get) {
+            return Err(tr!("trade.no_room", get.count, get.block.name()));
+        }
+        Ok(offer)
+    }
+
+    // every offer with how much of what it asks for the player has, and whether it's the selected one.
+    // None while the panel is closed
+    pub fn lines(&self, mobs: &Mobs, inventory: &Inventory) -> Option<Vec<(String, bool)>> {
+        self.is_open().then(|| {
+            self.offers(mobs).iter().enumerate().map(|(i, o)| {
+                let line = tr!("trade.offer", o.give.count, o.give.block.name(), o.get.count, o.get.block.name(), inventory.count(o.give.block));
+                (line, i == self.selected)
+            }).collect()
+        })
+    }
+}
@@ -0,0 +1,155 @@
+//vehicle.rs
+
+// the hover bike: a mob the player can ride, E to get on and off. it floats over the blocks under it on a
+// spring, keeps its momentum and drifts wide in fast turns. while someone rides it, Controller::chase
+// follows it from behind and the rider is just carried along.
+
+use glam::{Quat, Vec3};
+use crate::common::{BlockId, PlanetData};
+use crate::entity::Player;
+use crate::gen::CoordSystem;
+use crate::ecs::Entity;
+use crate::mob::{Body, Mobs, Transform};
+use crate::physics::Physics;
+
+// --- CONSTANTS ---
+// how close the player has to stand to get on
+pub const MOUNT_RANGE: f32 = 3.0;
+// a rider sits this high over the bike's lowest point
+const SEAT_HEIGHT: f32 = 0.5;
+// where the rider is put down, off to the bike's right
+const DISMOUNT_OFFSET: f32 = 1.5;
+// resting height over the ground, and how far above that the spring still pulls the bike down
+const HOVER_HEIGHT: f32 = 1.2;
+const SPRING_REACH: f32 = 3.0;
+const SPRING: f32 = 30.0;
+const SPRING_DAMPING: f32 = 6.0;
+const THRUST: f32 = 18.0;
+const BOOST: f32 = 1.8;
+const MAX_SPEED: f32 = 30.0;
+// share of forward speed lost per second, and how fast sideways sliding dies out
+const DRAG: f32 = 0.4;
+const GRIP: f32 = 3.0;
+// radians per second at full steer, reached once the bike does STEER_SPEED
+const TURN_RATE: f32 = 2.2;
+const STEER_SPEED: f32 = 8.0;
+const HOP: f32 = 7.0;
+// ground rising more than this in one step is a wall, the bike bounces off it keeping this share of its speed
+const MAX_CLIMB: f32 = 2.5;
+const WALL_BOUNCE: f32 = 0.3;
+// layers searched under the bike for the ground
+const GROUND_SCAN: u32 = 8;
+
+#[derive(Clone, Copy, Default)]
+pub struct DriveInput {
+    // -1 full reverse .. 1 full ahead
+    pub throttle: f32,
+    // -1 left .. 1 right
+    pub steer: f32,
+    pub boost: bool,
+    pub hop: bool,
+}
+
+// radius of the top of the highest block in the column under `pos`, looking a few layers up for hills
+fn ground_radius(pos: Vec3, planet: &PlanetData) -> Option<f32> {
+    let res = planet.resolution;
+    let id = CoordSystem::pos_to_id(pos, res)?;
+    let top = (id.layer + MAX_CLIMB as u32 + 1).min(res - 1);
+    let bottom = id.layer.saturating_sub(GROUND_SCAN);
+    (bottom..=top).rev()
+        .find(|layer| planet.exists(BlockId { layer: *layer, ..id }))
+        .map(|layer| CoordSystem::get_layer_radius(layer + 1, res))
+}
+
+pub fn drive(transform: &mut Transform, body: &mut Body, input: DriveInput, dt: f32, planet: &PlanetData) {
+    let up = Physics::get_up_vector(transform.position);
+    let forward = transform.rotation * Vec3::NEG_Z;
+
+    // steering needs speed, and flips in reverse like a real vehicle
+    let speed = body.velocity.dot(forward);
+    let turn = -input.steer * TURN_RATE * (speed.abs() / STEER_SPEED).min(1.0) * speed.signum();
+    transform.rotation = Quat::from_axis_angle(up, turn * dt) * transform.rotation;
+    let forward = transform.rotation * Vec3::NEG_Z;
+
+    let boost = if input.boost { BOOST } else { 1.0 };
+    let vel = body.velocity + forward * input.throttle.clamp(-1.0, 1.0) * THRUST * boost * dt;
+    let horz = vel - up * vel.dot(up);
+    let along = forward * horz.dot(forward);
+    let side = horz - along;
+    let horz = (along * (1.0 - DRAG * dt).max(0.0) + side * (-GRIP * dt).exp()).clamp_length_max(MAX_SPEED * boost);
+
+    let mut vert = vel.dot(up) - Physics::GRAVITY * dt;
+    let height = ground_radius(transform.position, planet).map(|ground| transform.position.length() - ground);
+    body.grounded = false;
+    if let Some(h) = height.filter(|h| *h < HOVER_HEIGHT + SPRING_REACH) {
+        vert += ((HOVER_HEIGHT - h) * SPRING - vert * SPRING_DAMPING) * dt;
+        body.grounded = true;
+        if input.hop && h < HOVER_HEIGHT + 0.5 {
+            vert = HOP;
+        }
+    }
+
+    let mut vel = horz + up * vert;
+    let mut next = transform.position + vel * dt;
+    if let Some(ground) = ground_radius(next, planet) {
+        if ground - next.length() > MAX_CLIMB {
+            vel = -horz * WALL_BOUNCE + up * vert;
+            next = transform.position + up * vert * dt;
+        }
+    }
+    transform.position = next;
+    body.velocity = vel;
+    transform.rotation = Physics::align_to_planet(transform.rotation, Physics::get_up_vector(next));
+}
+
+impl Mobs {
+    // the bike being ridden, if any
+    pub fn ridden(&self) -> Option<(&Transform, &Body)> {
+        let (e, _) = self.vehicles.iter().find(|(_, v)| v.ridden)?;
+        Some((self.transforms.get(e)?, self.bodies.get(e)?))
+    }
+
+    // gets on `e` if it's a bike, returns whether it was
+    pub fn ride(&mut self, e: Entity) -> bool {
+        match self.vehicles.get_mut(e) {
+            Some(vehicle) => {
+                vehicle.ridden = true;
+                true
+            },
+            None => false,
+        }
+    }
+
+    // gets on the nearest bike in reach, returns whether there was one
+    pub fn mount(&mut self, player: &Player) -> bool {
+        let distance = |e| self.transforms.get(e).map_or(f32::INFINITY, |t: &Transform| t.center().distance(player.position));
+        let nearest = self.vehicles.iter()
+            .map(|(e, _)| (e, distance(e)))
+            .filter(|(_, d)| *d < MOUNT_RANGE)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        nearest.is_some_and(|(e, _)| self.ride(e))
+    }
+
+    // puts the rider down next to the bike, clear of any terrain
+    pub fn dismount(&mut self, player: &mut Player, planet: &PlanetData) {
+        let Some((e, vehicle)) = self.vehicles.iter_mut().find(|(_, v)| v.ridden) else { return };
+        vehicle.ridden = false;
+        let (Some(bike), Some(body)) = (self.transforms.get(e), self.bodies.get(e)) else { return };
+        let mut position = bike.position + bike.rotation * Vec3::X * DISMOUNT_OFFSET;
+        for _ in 0..16 {
+            if !Physics::check_collision(position, planet) { break; }
+            position += Physics::get_up_vector(position);
+        }
+        player.position = position;
+        player.velocity = body.velocity * 0.5;
+        player.grounded = false;
+    }
+}
+
+// carries the rider along on the seat
+pub fn seat(player: &mut Player, (bike, body): (&Transform, &Body)) {
+    player.position = bike.position + Physics::get_up_vector(bike.position) * SEAT_HEIGHT;
+    player.velocity = body.velocity;
+    player.rotation = bike.rotation;
+    player.grounded = true;
+}
@@ -0,0 +1,110 @@
+//village.rs
+
+// small villages: a ring of huts stamped into a freshly generated world, one village on every face but the
+// spawn's, and villagers wandering between the huts while the player is near (see Mobs::update_villages).
+// the sites come from the height map alone, so a seed always has its villages in the same places whatever
+// has been built or dug there since.
+
+use glam::Vec3;
+use crate::common::{BlockId, BlockType, PlanetData};
+use crate::gen::CoordSystem;
+use crate::schematic::Schematic;
+
+// --- CONSTANTS ---
+// huts stand on a square this far out from the middle of the village, in blocks
+const HUT_SPACING: i32 = 5;
+// half the side of the square that has to be flat enough for a village
+const SITE_RADIUS: i32 = 9;
+// most the natural ground may rise and fall across a site
+const SITE_MAX_RELIEF: u32 = 3;
+// how far from the middle of a face a site is looked for, as a share of the resolution
+const SITE_SEARCH: f32 = 0.3;
+// hut footprint and height: floor, two layers of wall with a doorway, roof
+const HUT_SIZE: [u32; 3] = [5, 5, 4];
+// villagers stay this close to the middle of their village
+pub const VILLAGE_RADIUS: f32 = 9.0;
+
+// where the villages of this planet are: the block at the middle of each, on the natural ground
+pub fn sites(planet: &PlanetData) -> Vec<BlockId> {
+    (1..6).filter_map(|face| site(planet, face)).collect()
+}
+
+// the flat enough spot closest to the middle of `face`
+fn site(planet: &PlanetData, face: u8) -> Option<BlockId> {
+    let res = planet.resolution as i32;
+    let center = res / 2;
+    let reach = (res as f32 * SITE_SEARCH) as i32;
+    for ring in 0..=reach {
+        for dv in -ring..=ring {
+            for du in -ring..=ring {
+                if du.abs().max(dv.abs()) != ring { continue; }
+                let (u, v) = (center + du, center + dv);
+                if u - SITE_RADIUS < 0 || v - SITE_RADIUS < 0 || u + SITE_RADIUS >= res || v + SITE_RADIUS >= res { continue; }
+                let (lo, hi) = relief(planet, face, u as u32, v as u32, SITE_RADIUS as u32);
+                if hi - lo <= SITE_MAX_RELIEF {
+                    return Some(BlockId { face, u: u as u32, v: v as u32, layer: planet.terrain.get_height(face, u as u32, v as u32) });
+                }
+            }
+        }
+    }
+    None
+}
+
+// lowest and highest natural ground in the square of half side `radius` around (u, v)
+fn relief(planet: &PlanetData, face: u8, u: u32, v: u32, radius: u32) -> (u32, u32) {
+    let mut range = (u32::MAX, 0);
+    for v in v - radius..=v + radius {
+        for u in u - radius..=u + radius {
+            let h = planet.terrain.get_height(face, u, v);
+            range = (range.0.min(h), range.1.max(h));
+        }
+    }
+    range
+}
+
+// a hut with its doorway in the middle of the v = 0 wall
+fn hut() -> Schematic {
+    let [w, d, h] = HUT_SIZE;
+    let mut solid = Vec::new();
+    for l in 0..h {
+        for v in 0..d {
+            for u in 0..w {
+                let wall = u == 0 || v == 0 || u == w - 1 || v == d - 1;
+                let doorway = v == 0 && u == w / 2 && (1..h - 1).contains(&l);
+                if l == 0 || l == h - 1 || (wall && !doorway) {
+                    solid.push(([u, v, l], BlockType::Wood));
+                }
+            }
+        }
+    }
+    Schematic { size: HUT_SIZE, solid }
+}
+
+// stamps the huts of every village into a new world, wooden, each on a stone foundation down to the ground
+pub fn build(planet: &mut PlanetData) {
+    let res = planet.resolution;
+    let half = HUT_SIZE[0] as i32 / 2;
+    for site in sites(planet) {
+        // four huts round the middle, turned so their doorways face it
+        for (turns, (du, dv)) in [(2u8, (0, -1)), (3, (1, 0)), (0, (0, 1)), (1, (-1, 0))] {
+            let schematic = hut().rotated(turns);
+            let (u, v) = (site.u as i32 + du * HUT_SPACING - half, site.v as i32 + dv * HUT_SPACING - half);
+            let (u, v) = (u as u32, v as u32);
+            let (_, floor) = relief(planet, site.face, u + half as u32, v + half as u32, half as u32);
+            let origin = BlockId { face: site.face, u, v, layer: floor };
+            let mut blocks = schematic.blocks_at(origin, res);
+            for (fu, fv) in (0..HUT_SIZE[0]).flat_map(|fu| (0..HUT_SIZE[1]).map(move |fv| (fu, fv))) {
+                let ground = planet.terrain.get_height(site.face, u + fu, v + fv);
+                blocks.extend((ground + 1..floor).map(|layer| (BlockId { face: site.face, u: u + fu, v: v + fv, layer }, BlockType::Stone)));
+            }
+            for (id, block) in blocks {
+                if !planet.exists(id) { planet.add_block(id, block); }
+            }
+        }
+    }
+}
+
+// on top of the ground at the middle of a village, where villagers appear
+pub fn center(site: BlockId, res: u32) -> Vec3 {
+    CoordSystem::get_block_center(site.face, site.u, site.v, site.layer + 1, res)
+}
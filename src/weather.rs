@@ -0,0 +1,93 @@
+//weather.rs
+
+// weather, kept out of the engine as a plugin (see plugin.rs): /weather snow lets snow settle around the
+// player, one block at a time on top of whatever is highest there, until /weather clear. it adds its own
+// snow block, and stays clear while connected since the server wouldn't hear of the snow.
+
+use crate::cmd::{CommandContext, Console};
+use crate::common::{BlockDef, BlockId, BlockType, PlanetData};
+use crate::events::GameEvent;
+use crate::gen::CoordSystem;
+use crate::i18n::tr;
+use crate::mob::Rng;
+use crate::plugin::{Plugin, TickContext};
+
+// --- CONSTANTS ---
+// seconds between flakes settling
+const FLAKE_INTERVAL: f32 = 0.25;
+// how far from the player snow settles, in blocks
+const SNOW_RADIUS: f32 = 12.0;
+// how far above the natural ground snow looks for the top of a build
+const MAX_PILE: u32 = 32;
+
+pub struct Weather {
+    snow: Option<BlockType>,
+    snowing: bool,
+    timer: f32,
+    rng: Rng,
+}
+
+impl Weather {
+    pub fn new() -> Self {
+        Self { snow: None, snowing: false, timer: 0.0, rng: Rng::new(0x5eed) }
+    }
+
+    // a flake somewhere around the player, on the highest block of its column. it doesn't pile up: a column
+    // already topped with snow, or dug out, stays as it is
+    fn settle(&mut self, snow: BlockType, ctx: &mut TickContext) {
+        let res = ctx.planet.resolution;
+        let Some(below) = CoordSystem::pos_to_id(ctx.player.position, res) else { return };
+        let u = below.u as i32 + self.rng.range((-SNOW_RADIUS, SNOW_RADIUS)).round() as i32;
+        let v = below.v as i32 + self.rng.range((-SNOW_RADIUS, SNOW_RADIUS)).round() as i32;
+        if u < 0 || v < 0 || u >= res as i32 || v >= res as i32 { return; }
+        let at = |layer| BlockId { face: below.face, u: u as u32, v: v as u32, layer };
+        let ground = ctx.planet.terrain.get_height(below.face, u as u32, v as u32);
+        let Some(top) = (ground..ground + MAX_PILE).find(|&l| !ctx.planet.exists(at(l + 1))) else { return };
+        if !ctx.planet.exists(at(top)) || ctx.planet.block_type(at(top)) == snow { return; }
+        let id = at(top + 1);
+        ctx.planet.add_block(id, snow);
+        // the sky's doing, not the player's: no stats, nothing sent
+        ctx.events.emit(GameEvent::BlockPlaced { id, block: snow, remote: true });
+    }
+}
+
+impl Plugin for Weather {
+    fn name(&self) -> &'static str {
+        "weather"
+    }
+
+    fn init(&mut self, _planet: &PlanetData, _console: &mut Console) {
+        self.snow = BlockType::parse("snow");
+    }
+
+    fn on_tick(&mut self, dt: f32, ctx: &mut TickContext) {
+        let Some(snow) = self.snow.filter(|_| self.snowing && !ctx.online) else { return };
+        self.timer -= dt;
+        while self.timer <= 0.0 {
+            self.timer += FLAKE_INTERVAL;
+            self.settle(snow, ctx);
+        }
+    }
+
+    fn register_commands(&self) -> Vec<(&'static str, &'static str)> {
+        vec![("/weather [clear|snow]", "cmd.summary.weather")]
+    }
+
+    fn run_command(&mut self, parts: &[&str], _ctx: &mut CommandContext, console: &mut Console) {
+        self.snowing = match parts.get(1) {
+            Some(&"snow") => true,
+            Some(&"clear") => false,
+            None => self.snowing,
+            Some(_) => {
+                console.log(&tr!("weather.usage"), [1.0, 0.5, 0.0]);
+                return;
+            },
+        };
+        let key = if self.snowing { "weather.snow" } else { "weather.clear" };
+        console.log(&tr!(key), [0.0, 1.0, 0.0]);
+    }
+
+    fn register_blocks(&self) -> Vec<BlockDef> {
+        vec![BlockDef { name: "snow", color: [0.92, 0.94, 0.98], see_through: false }]
+    }
+}
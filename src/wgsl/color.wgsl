@@ -0,0 +1,34 @@
+// --- UTILS ---
+
+fn dither_opacity(pos: vec4<f32>, alpha: f32) -> bool {
+    // 4x4 Ordered Dithering Matrix
+    let dither_threshold = dot(vec2<f32>(171.0, 231.0), pos.xy);
+    return fract(dither_threshold / 71.0) > alpha;
+}
+
+fn triplanar_detail(pos: vec3<f32>, normal: vec3<f32>) -> f32 {
+    // Adds subtle grain to voxels so they don't look like plastic
+    let p = pos * 2.0;
+    let n = abs(normal);
+    // Tight blend
+    let w = pow(n, vec3<f32>(16.0)); 
+    let weights = w / (w.x + w.y + w.z);
+    
+    // Fast hash noise
+    let hx = fract(sin(dot(p.yz, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+    let hy = fract(sin(dot(p.zx, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+    let hz = fract(sin(dot(p.xy, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+
+    return (hx * weights.x + hy * weights.y + hz * weights.z) * 2.0 - 1.0;
+}
+
+// --- TONE MAPPING (ACES) ---
+// Industry standard for realistic color reproduction
+fn aces_approx(v: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((v * (a * v + b)) / (v * (c * v + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
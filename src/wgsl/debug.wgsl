@@ -0,0 +1,17 @@
+// --- DEBUG VIEWS ---
+// Render modes picked with /rendermode, 0 is the normal image
+const MODE_NORMALS = 1;
+const MODE_AO      = 2;
+const MODE_LIGHT   = 3;
+const MODE_LOD     = 4;
+const MODE_DEPTH   = 5;
+const DEPTH_RANGE  = 4000.0;
+
+// What each terrain fragment adds in the overdraw mode, white takes about ten layers
+const OVERDRAW_STEP = vec4<f32>(0.1, 0.05, 0.025, 1.0);
+
+// A distinct hue per lod level
+fn lod_color(level: f32) -> vec3<f32> {
+    let h = fract(level * 0.17);
+    return clamp(abs(fract(h + vec3<f32>(0.0, 2.0 / 3.0, 1.0 / 3.0)) * 6.0 - 3.0) - 1.0, vec3<f32>(0.0), vec3<f32>(1.0));
+}
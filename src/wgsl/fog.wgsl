@@ -0,0 +1,34 @@
+// --- FOG (Atmospheric Scattering) ---
+// Needs global.camera_pos.w = radius of the planet's base surface, where the air is thickest, and the
+// colour and density from /fog in global.fog
+
+// height over which the air thins by e, in blocks
+const FOG_SCALE_HEIGHT: f32 = 120.0;
+const FOG_STEPS: i32 = 8;
+
+// the distance from `start` to `end` measured in air as thick as at the surface: the air thins with
+// height above the planet, so a line looking down from a mountain crosses less of it than one skimming
+// the ground toward the horizon, however long both are. a few midpoint samples are plenty at this scale
+fn fog_depth(start: vec3<f32>, end: vec3<f32>, ground: f32) -> f32 {
+    let step = (end - start) / f32(FOG_STEPS);
+    var density = 0.0;
+    for (var i = 0; i < FOG_STEPS; i++) {
+        let p = start + step * (f32(i) + 0.5);
+        density += exp(-max(length(p) - ground, 0.0) / FOG_SCALE_HEIGHT);
+    }
+    return density * length(step);
+}
+
+// the colour from /fog, dimmed on the night side of the planet as the sky is so the haze doesn't glow
+fn fog_color() -> vec3<f32> {
+    let elevation = dot(normalize(global.camera_pos.xyz), normalize(global.sun_dir.xyz));
+    return global.fog.rgb * mix(0.05, 1.0, smoothstep(-0.15, 0.2, elevation));
+}
+
+fn apply_fog(color: vec3<f32>, world_pos: vec3<f32>) -> vec3<f32> {
+    let fog_density = global.fog.w;
+    if (fog_density <= 0.0) { return color; }
+    let dist = fog_depth(global.camera_pos.xyz, world_pos, global.camera_pos.w);
+    let fog_factor = 1.0 - exp(-(dist * fog_density) * (dist * fog_density * 0.5)); // Exp2 fog
+    return mix(color, fog_color(), clamp(fog_factor, 0.0, 1.0));
+}
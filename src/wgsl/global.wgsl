@@ -0,0 +1,16 @@
+// what every world-space shader starts from: the camera and sun, and the vertex layout (common.rs Vertex)
+struct Global {
+    view_proj: mat4x4<f32>,
+    light_view_proj: mat4x4<f32>,
+    camera_pos: vec4<f32>, // w = radius of the base surface (fog)
+    sun_dir: vec4<f32>, // w = render mode (/rendermode)
+    fog: vec4<f32>, // rgb = colour, w = density (/fog)
+}
+
+@group(0) @binding(0) var<uniform> global: Global;
+
+struct VertexIn {
+    @location(0) pos: vec3<f32>,
+    @location(1) color: vec3<f32>,
+    @location(2) normal: vec3<f32>,
+};
@@ -0,0 +1,48 @@
+// --- SHADOW ENGINE (Gaussian PCF) ---
+
+@group(0) @binding(1) var t_shadow: texture_depth_2d;
+@group(0) @binding(2) var s_shadow: sampler_comparison;
+
+fn fetch_shadow_accurate(shadow_pos: vec3<f32>, NdotL: f32) -> f32 {
+    // 1. Cull outside cascade
+    if (shadow_pos.z > 1.0 || shadow_pos.x < 0.0 || shadow_pos.x > 1.0 || shadow_pos.y < 0.0 || shadow_pos.y > 1.0) {
+        return 1.0;
+    }
+
+    // 2. Slope-Scaled Bias
+    // Steeper angles need more bias to prevent acne.
+    // Base bias matches the texel size of a 4096 map covering ~120 units.
+    let bias = max(0.0005 * (1.0 - NdotL), 0.0001);
+    let current_depth = shadow_pos.z - bias;
+
+    let tex_dim = vec2<f32>(textureDimensions(t_shadow));
+    let texel_size = 1.0 / tex_dim.x;
+
+    // 3. 5x5 Gaussian Weighted PCF
+    // We sample a grid, but center samples matter more.
+    var shadow_sum = 0.0;
+    var total_weight = 0.0;
+
+    // Gaussian weights for range -2 to +2
+    // [0.05, 0.25, 0.4, 0.25, 0.05] roughly
+    
+    for (var x = -1.0; x <= 1.0; x += 1.0) {
+        for (var y = -1.0; y <= 1.0; y += 1.0) {
+            // Calculate weight based on distance from center (Gaussian-ish)
+            let dist_sq = x*x + y*y;
+            let weight = exp(-dist_sq * 1.5); // Gaussian Falloff
+
+            let val = textureSampleCompare(
+                t_shadow, 
+                s_shadow, 
+                shadow_pos.xy + vec2<f32>(x, y) * texel_size, 
+                current_depth
+            );
+            
+            shadow_sum += val * weight;
+            total_weight += weight;
+        }
+    }
+
+    return shadow_sum / total_weight;
+}
@@ -0,0 +1,534 @@
+//world.rs
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use glam::{Vec3, Quat};
+use crate::codec::*;
+use crate::common::*;
+use crate::entity::Player;
+use crate::gen::CoordSystem;
+use crate::i18n::tr;
+use crate::physics::Physics;
+use crate::village;
+
+// --- CONSTANTS ---
+pub const WORLD_DIR: &str = "saves/world";
+
+const LEVEL_MAGIC: &[u8; 4] = b"VXNW";
+const CHUNK_MAGIC: &[u8; 4] = b"VXNC";
+const BACKUP_MAGIC: &[u8; 4] = b"VXNB";
+const FORMAT_VERSION: u32 = 1;
+
+// spawn search: how far from the middle of face 0 it looks, in blocks, how much higher than a column's
+// generated ground a placed block may sit, and the most a neighbour's ground may differ for the spot to be flat
+const SPAWN_SEARCH_RADIUS: i32 = 48;
+const SPAWN_HEADROOM: u32 = 32;
+const SPAWN_MAX_STEP: u32 = 1;
+// feet this far above the ground so the first collision test doesn't catch the floor
+const SPAWN_LIFT: f32 = 0.1;
+
+// --- PLAYER STATE ---
+
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerState {
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub cam_pitch: f32,
+}
+
+impl PlayerState {
+    pub fn capture(player: &Player) -> Self {
+        Self { position: player.position, rotation: player.rotation, cam_pitch: player.cam_pitch }
+    }
+
+    pub fn apply(&self, player: &mut Player) {
+        player.spawn(self.position);
+        player.rotation = self.rotation;
+        player.cam_pitch = self.cam_pitch;
+    }
+}
+
+// --- SNAPSHOT ---
+
+// everything a save needs, detached from the live planet so it can be written on another thread
+pub struct WorldSnapshot {
+    pub resolution: u32,
+    pub seed: u32,
+    pub has_core: bool,
+    pub player: PlayerState,
+    // None means the chunk has no edits left and its file should be removed
+    pub chunks: Vec<(ChunkKey, Option<Arc<ChunkMods>>)>,
+    // wipe every chunk file before writing (resolution changed)
+    pub full: bool,
+}
+
+impl WorldSnapshot {
+    // takes the dirty set out of the planet; the keys are put back if the write fails
+    pub fn take_dirty(planet: &mut PlanetData, player: &Player) -> Self {
+        let full = planet.needs_full_save;
+        planet.needs_full_save = false;
+
+        let keys: Vec<ChunkKey> = if full {
+            planet.dirty_chunks.clear();
+            planet.chunks.keys().cloned().collect()
+        } else {
+            planet.dirty_chunks.drain().collect()
+        };
+
+        let chunks = keys.into_iter().map(|key| {
+            let mods = planet.chunks.get(&key)
+                .filter(|m| !m.is_empty())
+                .cloned();
+            (key, mods)
+        }).collect();
+
+        Self {
+            resolution: planet.resolution,
+            seed: planet.seed,
+            has_core: planet.has_core,
+            player: PlayerState::capture(player),
+            chunks,
+            full,
+        }
+    }
+
+    // copies the whole world without touching the dirty set
+    pub fn full(planet: &PlanetData, player: &Player) -> Self {
+        Self {
+            resolution: planet.resolution,
+            seed: planet.seed,
+            has_core: planet.has_core,
+            player: PlayerState::capture(player),
+            chunks: planet.chunks.iter()
+                .filter(|(_, m)| !m.is_empty())
+                .map(|(k, m)| (*k, Some(m.clone())))
+                .collect(),
+            full: true,
+        }
+    }
+
+    fn keys(&self) -> WorldSnapshotKeys {
+        WorldSnapshotKeys {
+            resolution: self.resolution,
+            keys: self.chunks.iter().map(|(k, _)| *k).collect(),
+            full: self.full,
+        }
+    }
+
+    // returns the number of chunk files touched
+    pub fn write(&self, dir: &Path) -> io::Result<usize> {
+        let chunk_dir = dir.join("chunks");
+        if self.full && chunk_dir.exists() {
+            fs::remove_dir_all(&chunk_dir)?;
+        }
+        fs::create_dir_all(&chunk_dir)?;
+
+        for (key, mods) in &self.chunks {
+            let path = chunk_dir.join(chunk_file_name(*key));
+            match mods {
+                Some(mods) => write_atomic(&path, &encode_chunk(*key, mods))?,
+                None => {
+                    if path.exists() { fs::remove_file(&path)?; }
+                }
+            }
+        }
+
+        write_atomic(&dir.join("level.dat"), &self.encode_level())?;
+
+        Ok(self.chunks.len())
+    }
+
+    fn encode_level(&self) -> Vec<u8> {
+        let mut level = Vec::new();
+        level.extend_from_slice(LEVEL_MAGIC);
+        put_u32(&mut level, FORMAT_VERSION);
+        put_u32(&mut level, self.resolution);
+        put_u32(&mut level, self.seed);
+        level.push(self.has_core as u8);
+        put_vec3(&mut level, self.player.position);
+        put_quat(&mut level, self.player.rotation);
+        put_f32(&mut level, self.player.cam_pitch);
+        level
+    }
+}
+
+// header of level.dat, shared by saves and backups
+struct LevelInfo {
+    resolution: u32,
+    seed: u32,
+    has_core: bool,
+    player: PlayerState,
+}
+
+impl LevelInfo {
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ByteReader::new(bytes);
+
+        if r.bytes(4)? != LEVEL_MAGIC { return Err(invalid("level.dat has a bad header")); }
+        let version = r.u32()?;
+        if version != FORMAT_VERSION {
+            return Err(invalid(&format!("unsupported save version {}", version)));
+        }
+
+        let resolution = r.u32()?;
+        let seed = r.u32()?;
+        let has_core = r.u8()? != 0;
+        let position = r.vec3()?;
+        let rotation = r.quat()?;
+        let cam_pitch = r.f32()?;
+
+        Ok(Self { resolution, seed, has_core, player: PlayerState { position, rotation, cam_pitch } })
+    }
+
+    fn into_planet(self) -> (PlanetData, PlayerState) {
+        let mut planet = PlanetData::with_seed(self.resolution, self.seed);
+        planet.has_core = self.has_core;
+        (planet, self.player)
+    }
+}
+
+// --- LOADING ---
+
+pub struct World;
+
+impl World {
+    pub fn exists(dir: &Path) -> bool {
+        dir.join("level.dat").exists()
+    }
+
+    // where /save and /load keep a named copy, next to the live world. None for names that could reach
+    // anywhere else
+    pub fn named_dir(world_dir: &Path, name: &str) -> Option<PathBuf> {
+        let plain = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        plain.then(|| world_dir.parent().unwrap_or(Path::new(".")).join(name))
+    }
+
+    pub fn load(dir: &Path) -> io::Result<(PlanetData, PlayerState)> {
+        let level = fs::read(dir.join("level.dat"))?;
+        let (mut planet, player) = LevelInfo::decode(&level)?.into_planet();
+
+        let chunk_dir = dir.join("chunks");
+        if chunk_dir.exists() {
+            for entry in fs::read_dir(&chunk_dir)? {
+                let path = entry?.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("chunk") { continue; }
+
+                let mut bytes = Vec::new();
+                fs::File::open(&path)?.read_to_end(&mut bytes)?;
+                match decode_chunk(&bytes) {
+                    Ok((key, mods)) => { planet.chunks.insert(key, Arc::new(mods)); },
+                    Err(e) => println!("{}", tr!("world.chunk_corrupt", path.display(), e)),
+                }
+            }
+        }
+
+        Ok((planet, player))
+    }
+
+    // resumes the saved world if there is one, otherwise generates a fresh planet and spawns the player on it.
+    // Ok(true) means a save was loaded; on Err the fresh planet is still returned so the caller can log and carry on.
+    pub fn open(dir: &Path, player: &mut Player) -> (PlanetData, io::Result<bool>) {
+        if Self::exists(dir) {
+            match Self::load(dir) {
+                Ok((planet, state)) => {
+                    state.apply(player);
+                    return (planet, Ok(true));
+                }
+                Err(e) => {
+                    let planet = Self::generate(player);
+                    return (planet, Err(e));
+                }
+            }
+        }
+        (Self::generate(player), Ok(false))
+    }
+
+    fn generate(player: &mut Player) -> PlanetData {
+        let mut planet = PlanetData::new(DEFAULT_RESOLUTION); // Keep high resolution
+        // the huts are edits like any other, so they're saved with the world and sent to clients
+        village::build(&mut planet);
+        player.spawn(Self::spawn_point(&planet));
+        planet
+    }
+
+    // the nearest flat, solid and unobstructed column to the middle of face 0 (roughly the "North Pole"),
+    // looking ring by ring outwards. edits count, so a mined pit or a tower built there is stepped around
+    pub fn spawn_point(planet: &PlanetData) -> Vec3 {
+        let res = planet.resolution;
+        let center = (res / 2) as i32;
+        for ring in 0..=SPAWN_SEARCH_RADIUS {
+            for dv in -ring..=ring {
+                for du in -ring..=ring {
+                    if du.abs().max(dv.abs()) != ring { continue; }
+                    let (u, v) = (center + du, center + dv);
+                    if u < 1 || v < 1 || u >= res as i32 - 1 || v >= res as i32 - 1 { continue; }
+                    if let Some(pos) = Self::safe_column(planet, u as u32, v as u32) {
+                        return pos;
+                    }
+                }
+            }
+        }
+        // nowhere safe nearby: drop in from above the middle like before
+        let ground_level = planet.terrain.get_height(0, center as u32, center as u32);
+        let spawn_h = CoordSystem::get_layer_radius(ground_level, res) + 10.0;
+        Vec3::new(0.0, spawn_h, 0.0)
+    }
+
+    // feet position on top of the column at (u, v) on face 0, if the player can stand there
+    fn safe_column(planet: &PlanetData, u: u32, v: u32) -> Option<Vec3> {
+        let top = Self::ground(planet, u, v)?;
+        for (nu, nv) in [(u - 1, v), (u + 1, v), (u, v - 1), (u, v + 1)] {
+            if Self::ground(planet, nu, nv)?.abs_diff(top) > SPAWN_MAX_STEP { return None; }
+        }
+        let up = CoordSystem::get_block_center(0, u, v, top, planet.resolution).normalize();
+        let pos = up * (CoordSystem::get_layer_radius(top + 1, planet.resolution) + SPAWN_LIFT);
+        (!Physics::check_collision(pos, planet)).then_some(pos)
+    }
+
+    // highest solid layer of a column on face 0
+    fn ground(planet: &PlanetData, u: u32, v: u32) -> Option<u32> {
+        let highest = (planet.terrain.get_height(0, u, v) + SPAWN_HEADROOM).min(planet.resolution - 1);
+        (0..=highest).rev().find(|&layer| planet.exists(BlockId { face: 0, u, v, layer }))
+    }
+}
+
+// --- BACKUPS ---
+
+// a backup is one gzip file holding level.dat plus every edited chunk, named by its unix timestamp
+pub struct Backups;
+
+impl Backups {
+    pub fn dir(world_dir: &Path) -> PathBuf {
+        world_dir.join("backups")
+    }
+
+    pub fn create(world_dir: &Path, planet: &PlanetData, player: &Player) -> io::Result<u64> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let dir = Self::dir(world_dir);
+        fs::create_dir_all(&dir)?;
+
+        let path = dir.join(format!("{}.vxb", timestamp));
+        if path.exists() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "a backup was already taken this second"));
+        }
+
+        let snapshot = WorldSnapshot::full(planet, player);
+        let mut raw = Vec::new();
+        raw.extend_from_slice(BACKUP_MAGIC);
+        let level = snapshot.encode_level();
+        put_u32(&mut raw, level.len() as u32);
+        raw.extend_from_slice(&level);
+        put_u32(&mut raw, snapshot.chunks.len() as u32);
+        for (key, mods) in &snapshot.chunks {
+            if let Some(mods) = mods {
+                let chunk = encode_chunk(*key, mods);
+                put_u32(&mut raw, chunk.len() as u32);
+                raw.extend_from_slice(&chunk);
+            }
+        }
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&raw)?;
+        write_atomic(&path, &encoder.finish()?)?;
+        Ok(timestamp)
+    }
+
+    // timestamps of every backup on disk, oldest first
+    pub fn list(world_dir: &Path) -> Vec<u64> {
+        let mut stamps: Vec<u64> = fs::read_dir(Self::dir(world_dir))
+            .map(|entries| entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| {
+                    let path = e.path();
+                    if path.extension().and_then(|x| x.to_str()) != Some("vxb") { return None; }
+                    path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse().ok())
+                })
+                .collect())
+            .unwrap_or_default();
+        stamps.sort();
+        stamps
+    }
+
+    pub fn restore(world_dir: &Path, timestamp: u64) -> io::Result<(PlanetData, PlayerState)> {
+        let file = fs::File::open(Self::dir(world_dir).join(format!("{}.vxb", timestamp)))?;
+        let mut raw = Vec::new();
+        GzDecoder::new(file).read_to_end(&mut raw)?;
+
+        let mut r = ByteReader::new(&raw);
+        if r.bytes(4)? != BACKUP_MAGIC { return Err(invalid("bad backup header")); }
+        let level_len = r.u32()? as usize;
+        let (mut planet, player) = LevelInfo::decode(r.bytes(level_len)?)?.into_planet();
+
+        let count = r.u32()?;
+        for _ in 0..count {
+            let len = r.u32()? as usize;
+            let (key, mods) = decode_chunk(r.bytes(len)?)?;
+            planet.chunks.insert(key, Arc::new(mods));
+        }
+
+        // the world on disk no longer matches, rewrite all of it on the next save
+        planet.needs_full_save = true;
+        Ok((planet, player))
+    }
+}
+
+// --- AUTOSAVE ---
+
+pub struct Autosave {
+    pub dir: PathBuf,
+    pub interval: f32, // seconds between saves, 0 disables
+    timer: f32,
+    in_flight: Option<(Receiver<io::Result<usize>>, WorldSnapshotKeys)>,
+    indicator_timer: f32,
+}
+
+// the part of a snapshot we keep on the main thread so a failed write can be retried
+struct WorldSnapshotKeys {
+    resolution: u32,
+    keys: Vec<ChunkKey>,
+    full: bool,
+}
+
+impl Autosave {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            interval: 120.0,
+            timer: 0.0,
+            in_flight: None,
+            indicator_timer: 0.0,
+        }
+    }
+
+    // true while a save is running (plus a short linger so the HUD hint is readable)
+    pub fn is_saving(&self) -> bool {
+        self.in_flight.is_some() || self.indicator_timer > 0.0
+    }
+
+    // ticks the timer and collects finished background saves.
+    // returns the result of a save that completed this frame, if any.
+    pub fn update(&mut self, dt: f32, planet: &mut PlanetData, player: &Player) -> Option<io::Result<usize>> {
+        self.indicator_timer = (self.indicator_timer - dt).max(0.0);
+
+        let mut finished = None;
+        if let Some((rx, _)) = &self.in_flight {
+            if let Ok(result) = rx.try_recv() {
+                let (_, keys) = self.in_flight.take().unwrap();
+                if result.is_err() { keys.restore(planet); }
+                finished = Some(result);
+            }
+        }
+
+        if self.interval > 0.0 && self.in_flight.is_none() {
+            self.timer += dt;
+            if self.timer >= self.interval {
+                self.timer = 0.0;
+                self.start(planet, player);
+            }
+        }
+
+        finished
+    }
+
+    // kicks off a background save of the dirty chunks and player state
+    pub fn start(&mut self, planet: &mut PlanetData, player: &Player) {
+        if self.in_flight.is_some() { return; }
+
+        let snapshot = WorldSnapshot::take_dirty(planet, player);
+        let keys = snapshot.keys();
+
+        let (tx, rx) = channel();
+        let dir = self.dir.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(snapshot.write(&dir));
+        });
+
+        self.in_flight = Some((rx, keys));
+        self.indicator_timer = 1.0;
+    }
+
+    // waits for any running save, then writes everything that is still dirty. used on exit.
+    pub fn save_blocking(&mut self, planet: &mut PlanetData, player: &Player) -> io::Result<usize> {
+        if let Some((rx, keys)) = self.in_flight.take() {
+            if let Ok(Err(_)) | Err(_) = rx.recv() { keys.restore(planet); }
+        }
+
+        let snapshot = WorldSnapshot::take_dirty(planet, player);
+        let result = snapshot.write(&self.dir);
+        if result.is_err() { snapshot.keys().restore(planet); }
+        self.timer = 0.0;
+        result
+    }
+}
+
+impl WorldSnapshotKeys {
+    fn restore(&self, planet: &mut PlanetData) {
+        if self.resolution != planet.resolution { return; }
+        planet.needs_full_save |= self.full;
+        planet.dirty_chunks.extend(self.keys.iter().cloned());
+    }
+}
+
+// --- ENCODING HELPERS ---
+
+fn chunk_file_name(key: ChunkKey) -> String {
+    format!("{}_{}_{}.chunk", key.face, key.u_idx, key.v_idx)
+}
+
+pub fn encode_chunk(key: ChunkKey, mods: &ChunkMods) -> Vec<u8> {
+    let mut out = Vec::with_capacity(16 + (mods.mined.len() + mods.placed.len()) * 13);
+    out.extend_from_slice(CHUNK_MAGIC);
+    out.push(key.face);
+    put_u32(&mut out, key.u_idx);
+    put_u32(&mut out, key.v_idx);
+
+    for set in [&mods.mined, &mods.placed] {
+        put_u32(&mut out, set.len() as u32);
+        for id in set {
+            put_block_id(&mut out, *id);
+        }
+    }
+    put_block_entities(&mut out, &mods.entities);
+    put_block_types(&mut out, &mods.types);
+    out
+}
+
+pub fn decode_chunk(bytes: &[u8]) -> io::Result<(ChunkKey, ChunkMods)> {
+    let mut r = ByteReader::new(bytes);
+    if r.bytes(4)? != CHUNK_MAGIC { return Err(invalid("bad chunk header")); }
+    let key = ChunkKey { face: r.u8()?, u_idx: r.u32()?, v_idx: r.u32()? };
+
+    let mut mods = ChunkMods::new();
+    for set in [&mut mods.mined, &mut mods.placed] {
+        let count = r.u32()?;
+        for _ in 0..count {
+            set.insert(r.block_id()?);
+        }
+    }
+    // chunk files from before block entities end here, and from before block types after them
+    if !r.is_empty() {
+        mods.entities = r.block_entities()?;
+    }
+    if !r.is_empty() {
+        mods.types = r.block_types()?;
+    }
+    Ok((key, mods))
+}
+
+// write to a temp file first so a crash mid-save never leaves a half-written file behind
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    {
+        let mut f = fs::File::create(&tmp)?;
+        f.write_all(bytes)?;
+        f.sync_all()?;
+    }
+    fs::rename(tmp, path)
+}